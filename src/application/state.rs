@@ -1,16 +1,21 @@
 use mongodb::Database;
 use std::sync::Arc;
 
-use crate::core::JwtService;
+use crate::core::{JwtService, SecretBox};
+use tokio::sync::RwLock;
+
+use crate::domain::fhir::{ProfileRegistry, Terminology};
+use crate::infrastructure::factories::ConnectorFactory;
 use crate::infrastructure::repositories::{
     CompanyRepository, UserRepository, DatabaseConfigurationRepository,
     DatabaseColumnRepository, DatabaseTableRepository, DatabaseViewRepository,
     DatabaseViewMappingRepository, DatabaseTransformationRepository, SyncJobRepository,
     MetricsSummaryRepository, DatabaseModelRepository, DatabaseModelValueRepository,
-    TargetIntegrationRepository, IntegrationControlRepository
+    TargetIntegrationRepository, IntegrationControlRepository, FileDatasetRepository,
+    SchemaDriftRepository, TerminologyRepository, DeliveredResourceRepository,
 };
 use crate::application::usecases::MetricsUseCase;
-use crate::sync::SyncManager;
+use crate::sync::{MongoSyncStore, SyncManager};
 
 #[derive(Clone)]
 pub struct AppState {
@@ -33,34 +38,62 @@ pub struct AppState {
     pub metrics_use_case: Arc<MetricsUseCase>,
     pub database_model_repository: Arc<DatabaseModelRepository>,
     pub database_model_value_repository: Arc<DatabaseModelValueRepository>,
+    pub file_dataset_repository: Arc<FileDatasetRepository>,
+    pub schema_drift_repository: Arc<SchemaDriftRepository>,
+    pub profile_registry: Arc<ProfileRegistry>,
+    pub terminology_repository: Arc<TerminologyRepository>,
+    /// CodeSystems and ValueSets from FHIR packages and uploads
+    pub terminology: Arc<RwLock<Terminology>>,
+    /// Last delivered version of each resource, served by the FHIR facade
+    pub delivered_resource_repository: Arc<DeliveredResourceRepository>,
+    /// Opens the source connector of a database configuration
+    pub connectors: ConnectorFactory,
 }
 
 impl AppState {
-    pub fn new(db: Database, jwt_secret: String, token_exp: u64, max_concurrent_jobs: usize) -> Self {
+    pub fn new(
+        db: Database,
+        jwt_secret: String,
+        token_exp: u64,
+        max_concurrent_jobs: usize,
+        secrets: Arc<SecretBox>,
+        connectors: ConnectorFactory,
+    ) -> Self {
         let jwt_service = Arc::new(JwtService::new(jwt_secret));
         let company_repository = CompanyRepository::arc(db.clone());
         let user_repository = UserRepository::arc(db.clone());
-        let database_configuration_repository = DatabaseConfigurationRepository::arc(db.clone());
+        let database_configuration_repository = Arc::new(DatabaseConfigurationRepository::new(db.clone()).with_secrets(secrets.clone()));
         let database_column_repository = DatabaseColumnRepository::arc(db.clone());
         let database_table_repository = DatabaseTableRepository::arc(db.clone());
         let database_view_repository = DatabaseViewRepository::arc(db.clone());
         let database_view_mapping_repository = DatabaseViewMappingRepository::arc(db.clone());
-        let target_integration_repository = TargetIntegrationRepository::arc(db.clone());
+        let target_integration_repository = Arc::new(TargetIntegrationRepository::new(db.clone()).with_secrets(secrets));
         let integration_control_repository = IntegrationControlRepository::arc(db.clone());
         let database_transformation_repository = DatabaseTransformationRepository::arc(db.clone());
         let sync_job_repository = SyncJobRepository::arc(db.clone());
         let metrics_summary_repository = MetricsSummaryRepository::arc(db.clone());
+        let file_dataset_repository = FileDatasetRepository::arc(db.clone());
+        let connectors = connectors.with_file_datasets(file_dataset_repository.clone());
+        let delivered_resource_repository = DeliveredResourceRepository::arc(db.clone());
 
         // Create SyncManager with configurable parallel workers from .env
-        let sync_manager = Arc::new(SyncManager::new(
-            max_concurrent_jobs,
+        let sync_store = MongoSyncStore::new(
             sync_job_repository.clone(),
             database_configuration_repository.clone(),
             database_view_repository.clone(),
             database_view_mapping_repository.clone(),
             database_transformation_repository.clone(),
-            database_table_repository.clone(),
-        ));
+            company_repository.clone(),
+            target_integration_repository.clone(),
+        )
+        .with_delivered_resources(delivered_resource_repository.clone());
+        let sync_manager = Arc::new(SyncManager::new(
+            max_concurrent_jobs,
+            sync_store,
+            sync_job_repository.clone(),
+            database_configuration_repository.clone(),
+            database_view_repository.clone(),
+        ).with_connectors(connectors.clone()));
         
         // Get sync_status from SyncManager to create MetricsUseCase
         let sync_status = sync_manager.status.clone();
@@ -75,6 +108,8 @@ impl AppState {
         ));
         let database_model_repository = DatabaseModelRepository::arc(db.clone());
         let database_model_value_repository = DatabaseModelValueRepository::arc(db.clone());
+        let schema_drift_repository = SchemaDriftRepository::arc(db.clone());
+        let terminology_repository = TerminologyRepository::arc(db.clone());
 
         Self {
             db,
@@ -96,8 +131,26 @@ impl AppState {
             metrics_use_case,
             database_model_repository,
             database_model_value_repository,
+            file_dataset_repository,
+            schema_drift_repository,
+            profile_registry: Arc::new(ProfileRegistry::default()),
+            terminology_repository,
+            terminology: Arc::new(RwLock::new(Terminology::default())),
+            delivered_resource_repository,
+            connectors,
         }
     }
+
+    /// Validate previews against the StructureDefinitions of the loaded FHIR packages
+    pub fn with_profile_registry(mut self, profile_registry: Arc<ProfileRegistry>) -> Self {
+        self.profile_registry = profile_registry;
+        self
+    }
+
+    pub fn with_terminology(mut self, terminology: Terminology) -> Self {
+        self.terminology = Arc::new(RwLock::new(terminology));
+        self
+    }
     
     // Helper to get database reference for health checks
     pub fn database(&self) -> &Database {
@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use crate::domain::dtos::{CompanyEntity, CreateCompanyDto, UpdateCompanyDto};
 use crate::infrastructure::repositories::CompanyRepository;
-use crate::utils::{AppError, AppResult};
+use crate::utils::{date_format, AppError, AppResult};
 
 pub struct CompanyUseCase {
     repository: Arc<CompanyRepository>,
@@ -15,6 +15,7 @@ impl CompanyUseCase {
 
     fn map_company_to_entity(&self, company: crate::domain::entities::Company) -> AppResult<CompanyEntity> {
         let company_id = company.id.ok_or_else(|| AppError::InternalServerError)?.to_hex();
+        let timezone = company.timezone().name().to_string();
 
         Ok(CompanyEntity {
             id: company_id,
@@ -30,12 +31,40 @@ impl CompanyUseCase {
             zipcode: company.zipcode,
             country: company.country,
             status: company.status,
+            timezone,
+            hl7_facility: company.hl7_facility,
             created_at: company.created_at.to_rfc3339(),
             updated_at: company.updated_at.to_rfc3339(),
         })
     }
 
+    /// Refuse names that are not IANA timezones, stored as given otherwise
+    fn check_timezone(timezone: Option<&str>) -> AppResult<()> {
+        match timezone {
+            Some(timezone) => date_format::parse_timezone(timezone).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+
+    /// One company per HL7 receiving facility, otherwise inbound messages could not be routed
+    async fn check_hl7_facility(&self, facility: Option<&str>, company_id: Option<&str>) -> AppResult<()> {
+        let Some(facility) = facility else {
+            return Ok(());
+        };
+
+        match self.repository.find_by_hl7_facility(facility).await? {
+            Some(company) if company.id.map(|id| id.to_hex()).as_deref() != company_id => Err(AppError::Conflict(format!(
+                "Facility HL7 {} já pertence à empresa {}",
+                facility, company.name
+            ))),
+            _ => Ok(()),
+        }
+    }
+
     pub async fn create_company(&self, data: CreateCompanyDto) -> AppResult<CompanyEntity> {
+        Self::check_timezone(data.timezone.as_deref())?;
+        self.check_hl7_facility(data.hl7_facility.as_deref(), None).await?;
+
         let company = self.repository.create(crate::infrastructure::repositories::CreateCompanyDto {
             code: data.code,
             name: data.name,
@@ -48,6 +77,8 @@ impl CompanyUseCase {
             state: data.state,
             zipcode: data.zipcode,
             country: data.country,
+            timezone: data.timezone,
+            hl7_facility: data.hl7_facility,
         }).await?;
 
         self.map_company_to_entity(company)
@@ -89,6 +120,9 @@ impl CompanyUseCase {
     }
 
     pub async fn update_company(&self, id: &str, data: UpdateCompanyDto) -> AppResult<CompanyEntity> {
+        Self::check_timezone(data.timezone.as_deref())?;
+        self.check_hl7_facility(data.hl7_facility.as_deref(), Some(id)).await?;
+
         let company = self.repository.update(id, crate::infrastructure::repositories::UpdateCompanyDto {
             code: data.code,
             name: data.name,
@@ -102,6 +136,8 @@ impl CompanyUseCase {
             zipcode: data.zipcode,
             country: data.country,
             status: data.status,
+            timezone: data.timezone,
+            hl7_facility: data.hl7_facility,
         }).await?;
 
         self.map_company_to_entity(company)
@@ -1,33 +1,37 @@
+use std::collections::BTreeSet;
 use std::sync::Arc;
+use std::time::Instant;
 
 use crate::domain::dtos::{CreateDatabaseConfigurationDto, UpdateDatabaseConfigurationDto, DatabaseConfigurationEntity};
-use crate::infrastructure::repositories::DatabaseConfigurationRepository;
-use crate::infrastructure::factories::{ConnectorFactory, DatabaseConfig};
-use crate::utils::{AppError, AppResult, PaginationResponse};
+use crate::domain::entities::{ConnectionDiagnostics, DatabaseConfiguration, DiagnosticCheck, SourceDiagnostics};
+use crate::infrastructure::repositories::{DatabaseConfigurationRepository, DatabaseViewRepository};
+use crate::infrastructure::factories::ConnectorFactory;
+use crate::utils::{redact, AppError, AppResult, PaginationResponse};
 
 pub struct DatabaseConfigurationUseCase {
     repository: Arc<DatabaseConfigurationRepository>,
+    view_repository: Option<Arc<DatabaseViewRepository>>,
+    connectors: ConnectorFactory,
 }
 
 impl DatabaseConfigurationUseCase {
     pub fn new(repository: Arc<DatabaseConfigurationRepository>) -> Self {
-        Self { repository }
+        Self { repository, view_repository: None, connectors: ConnectorFactory::default() }
+    }
+
+    /// Open source connectors with the application's connector factory
+    pub fn with_connectors(mut self, connectors: ConnectorFactory) -> Self {
+        self.connectors = connectors;
+        self
+    }
+
+    pub fn with_view_repository(mut self, view_repository: Arc<DatabaseViewRepository>) -> Self {
+        self.view_repository = Some(view_repository);
+        self
     }
 
     pub async fn create_database_configuration(&self, data: CreateDatabaseConfigurationDto) -> AppResult<DatabaseConfigurationEntity> {
-        let config = self.repository.create(
-            data.name,
-            data.db_type,
-            data.version,
-            data.host,
-            data.port,
-            data.database,
-            data.username,
-            data.password,
-            data.auth_type,
-            data.credentials,
-            data.company_id.unwrap_or_default()
-        ).await?;
+        let config = self.repository.create(data).await?;
 
         Ok(DatabaseConfigurationEntity {
             id: config.id.unwrap().to_hex(),
@@ -38,9 +42,12 @@ impl DatabaseConfigurationUseCase {
             port: config.port,
             database: config.database,
             username: config.username,
-            password: config.password,
+            password: redact::secret(&config.password),
             auth_type: config.auth_type,
-            credentials: config.credentials,
+            credentials: redact::secret(&config.credentials),
+            api_source: config.api_source,
+            oracle: config.oracle,
+            sqlserver: config.sqlserver,
             company_id: Some(config.company_id),
             created_at: config.created_at.to_rfc3339(),
             updated_at: config.updated_at.to_rfc3339(),
@@ -63,9 +70,12 @@ impl DatabaseConfigurationUseCase {
                 port: config.port,
                 database: config.database,
                 username: config.username,
-                password: config.password,
+                password: redact::secret(&config.password),
                 auth_type: config.auth_type,
-                credentials: config.credentials,
+                credentials: redact::secret(&config.credentials),
+            api_source: config.api_source,
+                oracle: config.oracle,
+                sqlserver: config.sqlserver,
                 company_id: Some(config.company_id),
                 created_at: config.created_at.to_rfc3339(),
                 updated_at: config.updated_at.to_rfc3339(),
@@ -94,9 +104,12 @@ impl DatabaseConfigurationUseCase {
             port: config.port,
             database: config.database,
             username: config.username,
-            password: config.password,
+            password: redact::secret(&config.password),
             auth_type: config.auth_type,
-            credentials: config.credentials,
+            credentials: redact::secret(&config.credentials),
+            api_source: config.api_source,
+            oracle: config.oracle,
+            sqlserver: config.sqlserver,
             company_id: Some(config.company_id),
             created_at: config.created_at.to_rfc3339(),
             updated_at: config.updated_at.to_rfc3339(),
@@ -104,21 +117,8 @@ impl DatabaseConfigurationUseCase {
     }
 
 
-    pub async fn update_database_configuration(&self, id: &str, _data: UpdateDatabaseConfigurationDto) -> AppResult<DatabaseConfigurationEntity> {
-        let updated = self.repository.update(
-            id,
-            _data.name,
-            _data.db_type,
-            _data.version,
-            _data.host,
-            _data.port,
-            _data.database,
-            _data.username,
-            _data.password,
-            _data.auth_type,
-            _data.credentials,
-            _data.company_id
-        ).await?;
+    pub async fn update_database_configuration(&self, id: &str, data: UpdateDatabaseConfigurationDto) -> AppResult<DatabaseConfigurationEntity> {
+        let updated = self.repository.update(id, data).await?;
 
         Ok(DatabaseConfigurationEntity {
             id: updated.id.unwrap().to_hex(),
@@ -129,9 +129,12 @@ impl DatabaseConfigurationUseCase {
             port: updated.port,
             database: updated.database,
             username: updated.username,
-            password: updated.password,
+            password: redact::secret(&updated.password),
             auth_type: updated.auth_type,
-            credentials: updated.credentials,
+            credentials: redact::secret(&updated.credentials),
+            api_source: updated.api_source,
+            oracle: updated.oracle,
+            sqlserver: updated.sqlserver,
             company_id: Some(updated.company_id),
             created_at: updated.created_at.to_rfc3339(),
             updated_at: updated.updated_at.to_rfc3339(),
@@ -142,87 +145,201 @@ impl DatabaseConfigurationUseCase {
         self.repository.delete(id).await
     }
 
-    pub async fn test_connection(&self, data: CreateDatabaseConfigurationDto) -> AppResult<serde_json::Value> {
+    /// Run the test-connection checklist
+    ///
+    /// Besides connectivity, every `*_INTERHEALTH` object read by the company's views is checked for
+    /// SELECT privilege and row visibility (only the views of `configuration_id` when given)
+    pub async fn test_connection(
+        &self,
+        data: CreateDatabaseConfigurationDto,
+        company_id: Option<&str>,
+        configuration_id: Option<&str>,
+    ) -> AppResult<ConnectionDiagnostics> {
+        let objects = self.dependent_objects(company_id, configuration_id).await?;
+        let data = self.with_stored_secrets(data, company_id, configuration_id).await?;
+
         // Check if this is an API connection
         if data.db_type.to_uppercase() == "API" {
-            // Test API connection
             use crate::infrastructure::adapters::ApiConnector;
-            
-            match ApiConnector::new(&data.host, data.auth_type.clone(), data.credentials.clone()).await {
-                Ok(connector) => {
-                    match connector.test_connection().await {
-                        Ok(is_connected) => {
-                            Ok(serde_json::json!({
-                                "success": true,
-                                "message": "Conexão API estabelecida com sucesso",
-                                "connected": is_connected,
-                                "type": "API",
-                                "host": data.host,
-                                "authType": data.auth_type
-                            }))
-                        }
-                        Err(e) => {
-                            Ok(serde_json::json!({
-                                "success": false,
-                                "message": format!("Falha ao testar conexão API: {}", e),
-                                "type": "API",
-                                "host": data.host,
-                                "authType": data.auth_type
-                            }))
-                        }
-                    }
-                }
-                Err(e) => {
-                    Ok(serde_json::json!({
-                        "success": false,
-                        "message": format!("Falha ao criar conector API: {}", e),
-                        "type": "API",
-                        "host": data.host,
-                        "authType": data.auth_type
-                    }))
-                }
-            }
-        } else {
-            // Test database connection
-            let connection_string = ConnectorFactory::build_connection_string(&data)?;
-
-            let config = DatabaseConfig {
-                connection_string,
-                database_name: data.database.clone(),
-                max_connections: Some(10),
+
+            let started = Instant::now();
+            let check = match ApiConnector::new(&data.host, data.auth_type.clone(), data.credentials.clone()).await {
+                Ok(connector) => match connector.test_connection().await {
+                    Ok(_) => DiagnosticCheck::passed("connectivity", "Conexão API estabelecida com sucesso")
+                        .with_latency(started.elapsed().as_millis() as u64),
+                    Err(e) => DiagnosticCheck::failed("connectivity", format!("Falha ao testar conexão API: {}", e)),
+                },
+                Err(e) => DiagnosticCheck::failed("connectivity", format!("Falha ao criar conector API: {}", e)),
             };
 
-            match ConnectorFactory::create_connection(&data.db_type, config).await {
-                Ok(connection) => {
-                    let is_connected = connection.is_connected();
-                    let db_type = format!("{:?}", connection.get_type());
-                    let host = data.host.clone();
-                    let port = data.port;
-                    let database = data.database.clone();
-                    
-                    drop(connection);
-                    
-                    Ok(serde_json::json!({
-                        "success": true,
-                        "message": "Conexão estabelecida com sucesso",
-                        "connected": is_connected,
-                        "type": db_type,
-                        "host": host,
-                        "port": port,
-                        "database": database
-                    }))
-                }
-                Err(e) => {
-                    Ok(serde_json::json!({
-                        "success": false,
-                        "message": format!("Falha ao realizar a conexão: {}", e),
-                        "type": data.db_type,
-                        "host": data.host,
-                        "port": data.port,
-                        "database": data.database
-                    }))
-                }
+            let diagnostics = SourceDiagnostics {
+                latency_ms: check.latency_ms,
+                checks: vec![check],
+                ..Default::default()
+            };
+            return Ok(ConnectionDiagnostics::new("API".to_string(), data.host, None, None, diagnostics));
+        }
+
+        let diagnostics = match self.connectors.create_source_from_dto(&data).await {
+            Ok(mut connector) => {
+                let diagnostics = connector.diagnose(&objects).await;
+                let _ = connector.close().await;
+
+                diagnostics.unwrap_or_else(|e| SourceDiagnostics {
+                    checks: vec![DiagnosticCheck::failed("connectivity", format!("Falha ao realizar a conexão: {}", e))],
+                    ..Default::default()
+                })
             }
+            Err(e) => SourceDiagnostics {
+                checks: vec![DiagnosticCheck::failed("connectivity", format!("Falha ao realizar a conexão: {}", e))],
+                ..Default::default()
+            },
+        };
+
+        Ok(ConnectionDiagnostics::new(data.db_type, data.host, data.port, data.database, diagnostics))
+    }
+
+    /// Clients only ever see redacted secrets; testing a saved configuration of the company uses the stored ones
+    async fn with_stored_secrets(
+        &self,
+        data: CreateDatabaseConfigurationDto,
+        company_id: Option<&str>,
+        configuration_id: Option<&str>,
+    ) -> AppResult<CreateDatabaseConfigurationDto> {
+        let redacted = |value: &Option<String>| value.as_deref().is_some_and(redact::is_redacted);
+        if !redacted(&data.password) && !redacted(&data.credentials) {
+            return Ok(data);
+        }
+
+        let stored = match configuration_id {
+            Some(id) => self.repository.find_by_id(id).await?,
+            None => None,
+        };
+        Self::reuse_stored_secrets(data, stored, company_id)
+    }
+
+    /// Fill redacted secrets from `stored` when it belongs to `company_id` and points at the same
+    /// host, port, database and username; anything else must come with its own secret
+    fn reuse_stored_secrets(
+        mut data: CreateDatabaseConfigurationDto,
+        stored: Option<DatabaseConfiguration>,
+        company_id: Option<&str>,
+    ) -> AppResult<CreateDatabaseConfigurationDto> {
+        let stored = stored
+            .filter(|stored| company_id == Some(stored.company_id.as_str()))
+            .ok_or_else(|| AppError::BadRequest("Informe a senha ou o configurationId da configuração salva".to_string()))?;
+
+        let same_target = stored.host == data.host
+            && stored.port == data.port
+            && stored.database == data.database
+            && stored.username == data.username;
+        if !same_target {
+            return Err(AppError::BadRequest(
+                "Host, porta, banco ou usuário diferem da configuração salva; informe a senha".to_string(),
+            ));
+        }
+
+        let redacted = |value: &Option<String>| value.as_deref().is_some_and(redact::is_redacted);
+        if redacted(&data.password) {
+            data.password = stored.password;
+        }
+        if redacted(&data.credentials) {
+            data.credentials = stored.credentials;
+        }
+        Ok(data)
+    }
+
+    /// Source objects read by the company's views (e.g. PATIENT_INTERHEALTH)
+    async fn dependent_objects(&self, company_id: Option<&str>, configuration_id: Option<&str>) -> AppResult<Vec<String>> {
+        let (Some(view_repository), Some(company_id)) = (self.view_repository.as_ref(), company_id) else {
+            return Ok(Vec::new());
+        };
+
+        let objects: BTreeSet<String> = view_repository
+            .find_by_company_id(company_id, configuration_id)
+            .await?
+            .iter()
+            .map(|view| view.source_table_name())
+            .collect();
+
+        Ok(objects.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn stored(company_id: &str) -> DatabaseConfiguration {
+        DatabaseConfiguration {
+            id: None,
+            name: "MV".to_string(),
+            db_type: "MV".to_string(),
+            version: None,
+            host: "db.hospital".to_string(),
+            port: Some(1521),
+            database: Some("PROD".to_string()),
+            username: Some("interhealth".to_string()),
+            password: Some("s3cret".to_string()),
+            auth_type: None,
+            credentials: None,
+            api_source: None,
+            oracle: None,
+            sqlserver: None,
+            company_id: company_id.to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
         }
     }
+
+    fn redacted_request(host: &str) -> CreateDatabaseConfigurationDto {
+        CreateDatabaseConfigurationDto {
+            name: "MV".to_string(),
+            db_type: "MV".to_string(),
+            version: None,
+            host: host.to_string(),
+            port: Some(1521),
+            database: Some("PROD".to_string()),
+            username: Some("interhealth".to_string()),
+            password: redact::secret(&Some("s3cret".to_string())),
+            auth_type: None,
+            credentials: None,
+            api_source: None,
+            oracle: None,
+            sqlserver: None,
+            company_id: None,
+        }
+    }
+
+    #[test]
+    fn test_reuses_stored_secrets_for_the_same_company_and_target() {
+        let data = DatabaseConfigurationUseCase::reuse_stored_secrets(
+            redacted_request("db.hospital"),
+            Some(stored("company-a")),
+            Some("company-a"),
+        )
+        .unwrap();
+        assert_eq!(data.password.as_deref(), Some("s3cret"));
+    }
+
+    #[test]
+    fn test_refuses_stored_secrets_of_another_company() {
+        let result = DatabaseConfigurationUseCase::reuse_stored_secrets(
+            redacted_request("db.hospital"),
+            Some(stored("company-a")),
+            Some("company-b"),
+        );
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn test_refuses_stored_secrets_for_a_changed_host() {
+        let result = DatabaseConfigurationUseCase::reuse_stored_secrets(
+            redacted_request("attacker.example"),
+            Some(stored("company-a")),
+            Some("company-a"),
+        );
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+    }
 }
@@ -2,18 +2,19 @@ use std::sync::Arc;
 
 use crate::domain::dtos::{CreateDatabaseTableDto, UpdateDatabaseTableDto, DatabaseTableEntity, DatabaseColumnEntity, DatabaseTableReference};
 use crate::infrastructure::repositories::{DatabaseTableRepository, DatabaseColumnRepository, DatabaseConfigurationRepository};
-use crate::infrastructure::adapters::OracleConnector;
+use crate::infrastructure::factories::ConnectorFactory;
 use crate::utils::{AppError, AppResult, PaginationResponse};
 
 pub struct DatabaseTableUseCase {
     repository: Arc<DatabaseTableRepository>,
     column_repository: Arc<DatabaseColumnRepository>,
     config_repository: Option<Arc<DatabaseConfigurationRepository>>,
+    connectors: ConnectorFactory,
 }
 
 impl DatabaseTableUseCase {
     pub fn new(repository: Arc<DatabaseTableRepository>, column_repository: Arc<DatabaseColumnRepository>) -> Self {
-        Self { repository, column_repository, config_repository: None }
+        Self { repository, column_repository, config_repository: None, connectors: ConnectorFactory::default() }
     }
 
     pub fn with_config_repository(mut self, config_repository: Arc<DatabaseConfigurationRepository>) -> Self {
@@ -21,6 +22,12 @@ impl DatabaseTableUseCase {
         self
     }
 
+    /// Open source connectors with the application's connector factory
+    pub fn with_connectors(mut self, connectors: ConnectorFactory) -> Self {
+        self.connectors = connectors;
+        self
+    }
+
     pub async fn create_database_table(&self, data: CreateDatabaseTableDto, company_id: String) -> AppResult<DatabaseTableEntity> {
         let table = self.repository.create(
             data.name.clone(),
@@ -206,50 +213,26 @@ impl DatabaseTableUseCase {
         let db_config = config_repo.find_by_id(connection_id).await?
             .ok_or_else(|| AppError::NotFound("Database configuration not found".to_string()))?;
 
-        let username = db_config.username.as_ref().ok_or_else(|| AppError::BadRequest("Database username is required".to_string()))?;
-        let password = db_config.password.as_ref().ok_or_else(|| AppError::BadRequest("Database password is required".to_string()))?;
-        let port = db_config.port.ok_or_else(|| AppError::BadRequest("Database port is required".to_string()))?;
-        let database = db_config.database.as_ref().ok_or_else(|| AppError::BadRequest("Database name is required".to_string()))?;
-
-        let connection_string = format!(
-            "oracle://{}:{}@{}:{}/{}",
-            username,
-            password,
-            db_config.host,
-            port,
-            database
-        );
-
-        let connector = OracleConnector::new(&connection_string).await?;
-        let columns = connector.get_table_columns(table_name).await?;
+        let mut connector = self.connectors.create_source(&db_config).await?;
+        let columns = connector.introspect(table_name).await?;
 
-        drop(connector);
-
-        // Map Oracle data types to simplified types
+        // Map source data types to simplified types
         let mapped_columns: Vec<serde_json::Value> = columns.iter().map(|col| {
             let data_type = col.get("dataType")
                 .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_uppercase();
-            
-            let simplified_type = match data_type.as_str() {
-                "NUMBER" | "INTEGER" | "SMALLINT" | "NUMERIC" => "integer",
-                "VARCHAR" | "VARCHAR2" | "CHAR" | "NVARCHAR2" | "NCHAR" | "CLOB" | "NCLOB" => "varchar",
-                "DATE" => "date",
-                "TIMESTAMP" | "TIMESTAMP(6)" => "timestamp",
-                "BOOLEAN" => "boolean",
-                _ => "varchar"
-            };
+                .unwrap_or("");
 
             serde_json::json!({
                 "id": col.get("id").and_then(|v| v.as_str()).unwrap_or(""),
                 "name": col.get("name").and_then(|v| v.as_str()).unwrap_or("").to_lowercase(),
-                "dataType": simplified_type,
+                "dataType": connector.simplify_data_type(data_type),
                 "isNullable": col.get("isNullable").and_then(|v| v.as_bool()).unwrap_or(true)
             })
         }).collect();
 
-        // Build the response structure with MongoDB table metadata + Oracle columns
+        connector.close().await?;
+
+        // Build the response structure with MongoDB table metadata + source columns
         let response = serde_json::json!({
             "id": table.id.map(|id| id.to_hex()).unwrap_or_default(),
             "name": table.name,
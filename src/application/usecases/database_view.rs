@@ -3,6 +3,7 @@ use std::sync::Arc;
 use crate::domain::dtos::{CreateDatabaseViewDto, UpdateDatabaseViewDto, DatabaseViewEntity, ResourceItemDto};
 use crate::domain::entities::ResourceItem;
 use crate::infrastructure::repositories::{DatabaseViewRepository, DatabaseConfigurationRepository, DatabaseViewMappingRepository};
+use crate::hl7::normalize_message_type;
 use crate::utils::{AppError, AppResult, PaginationResponse};
 
 pub struct DatabaseViewUseCase {
@@ -63,6 +64,27 @@ impl DatabaseViewUseCase {
                 .await?;
         }
 
+        if let Some(message_types) = data.message_types.clone() {
+            self
+                .repository
+                .set_message_types(&view.id.as_ref().unwrap().to_hex(), Some(normalize_message_types(message_types)))
+                .await?;
+        }
+
+        if let Some(delivery_mode) = data.delivery_mode {
+            self
+                .repository
+                .set_delivery_mode(&view.id.as_ref().unwrap().to_hex(), delivery_mode)
+                .await?;
+        }
+
+        if let Some(reference_strategy) = data.reference_strategy {
+            self
+                .repository
+                .set_reference_strategy(&view.id.as_ref().unwrap().to_hex(), reference_strategy)
+                .await?;
+        }
+
         let refreshed = self
             .repository
             .find_by_id(&view.id.as_ref().unwrap().to_hex())
@@ -80,6 +102,9 @@ impl DatabaseViewUseCase {
             is_interhealth_destination: refreshed.is_interhealth_destination.unwrap_or(false),
             database_configuration_id: data.database_configuration_id.clone(),
             target_integration_id: refreshed.target_integration_id.clone(),
+            message_types: refreshed.message_types.clone(),
+            delivery_mode: refreshed.delivery_mode.unwrap_or_default(),
+            reference_strategy: refreshed.reference_strategy.unwrap_or_default(),
             company_id: Some(company_id),
             status: refreshed.status,
             job_id: refreshed.job_id,
@@ -110,6 +135,9 @@ impl DatabaseViewUseCase {
                 is_interhealth_destination: view.is_interhealth_destination.unwrap_or(false),
                 database_configuration_id: view.database_configuration_id.clone(),
                 target_integration_id: view.target_integration_id.clone(),
+                message_types: view.message_types.clone(),
+                delivery_mode: view.delivery_mode.unwrap_or_default(),
+                reference_strategy: view.reference_strategy.unwrap_or_default(),
                 company_id: Some(view.company_id),
                 status: view.status,
                 job_id: view.job_id,
@@ -145,6 +173,9 @@ impl DatabaseViewUseCase {
             is_interhealth_destination: view.is_interhealth_destination.unwrap_or(false),
             database_configuration_id: view.database_configuration_id.clone(),
             target_integration_id: view.target_integration_id.clone(),
+            message_types: view.message_types.clone(),
+            delivery_mode: view.delivery_mode.unwrap_or_default(),
+            reference_strategy: view.reference_strategy.unwrap_or_default(),
             company_id: Some(view.company_id),
             status: view.status,
             job_id: view.job_id,
@@ -180,6 +211,27 @@ impl DatabaseViewUseCase {
                 .await?;
         }
 
+        if let Some(message_types) = data.message_types.clone() {
+            self
+                .repository
+                .set_message_types(id, Some(normalize_message_types(message_types)))
+                .await?;
+        }
+
+        if let Some(delivery_mode) = data.delivery_mode {
+            self
+                .repository
+                .set_delivery_mode(id, delivery_mode)
+                .await?;
+        }
+
+        if let Some(reference_strategy) = data.reference_strategy {
+            self
+                .repository
+                .set_reference_strategy(id, reference_strategy)
+                .await?;
+        }
+
         let refreshed = self
             .repository
             .find_by_id(id)
@@ -197,6 +249,9 @@ impl DatabaseViewUseCase {
             is_interhealth_destination: refreshed.is_interhealth_destination.unwrap_or(false),
             database_configuration_id: refreshed.database_configuration_id.clone(),
             target_integration_id: refreshed.target_integration_id.clone(),
+            message_types: refreshed.message_types.clone(),
+            delivery_mode: refreshed.delivery_mode.unwrap_or_default(),
+            reference_strategy: refreshed.reference_strategy.unwrap_or_default(),
             company_id: Some(refreshed.company_id),
             status: refreshed.status,
             job_id: refreshed.job_id,
@@ -227,6 +282,9 @@ impl DatabaseViewUseCase {
             is_interhealth_destination: view.is_interhealth_destination.unwrap_or(false),
             database_configuration_id: view.database_configuration_id.clone(),
             target_integration_id: view.target_integration_id.clone(),
+            message_types: view.message_types.clone(),
+            delivery_mode: view.delivery_mode.unwrap_or_default(),
+            reference_strategy: view.reference_strategy.unwrap_or_default(),
             company_id: Some(view.company_id),
             status: view.status,
             job_id: view.job_id,
@@ -252,6 +310,9 @@ impl DatabaseViewUseCase {
             is_interhealth_destination: view.is_interhealth_destination.unwrap_or(false),
             database_configuration_id: view.database_configuration_id.clone(),
             target_integration_id: view.target_integration_id.clone(),
+            message_types: view.message_types.clone(),
+            delivery_mode: view.delivery_mode.unwrap_or_default(),
+            reference_strategy: view.reference_strategy.unwrap_or_default(),
             company_id: Some(view.company_id),
             status: view.status,
             job_id: view.job_id,
@@ -263,3 +324,12 @@ impl DatabaseViewUseCase {
         })
     }
 }
+
+/// Store message types in the MSH-9 form matched by the MLLP listener (ADT^A01 -> ADT_A01)
+fn normalize_message_types(message_types: Vec<String>) -> Vec<String> {
+    message_types
+        .iter()
+        .map(|message_type| normalize_message_type(message_type))
+        .filter(|message_type| !message_type.is_empty())
+        .collect()
+}
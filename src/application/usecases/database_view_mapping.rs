@@ -1,11 +1,16 @@
 use std::sync::Arc;
+use tokio::sync::RwLock;
 use serde::{Deserialize, Serialize};
+use chrono_tz::Tz;
 use serde_json::{json, Value};
 
-use crate::domain::entities::{FieldMapping, DatabaseTransformation, DatabaseModelValue};
-use crate::infrastructure::repositories::{DatabaseViewMappingRepository, DatabaseViewRepository, DatabaseConfigurationRepository, DatabaseTableRepository, DatabaseTransformationRepository, DatabaseModelValueRepository};
-use crate::infrastructure::adapters::oracledb::OracleConnector;
-use crate::utils::{AppError, AppResult, PaginationResponse, Replacer, Validator};
+use crate::domain::entities::{FieldMapping, DatabaseTransformation, DatabaseModelValue, DatabaseView};
+use crate::domain::fhir::{r4, FhirVersion, ProfileRegistry, Terminology};
+use crate::infrastructure::repositories::{CompanyRepository, DatabaseViewMappingRepository, DatabaseViewRepository, DatabaseConfigurationRepository, DatabaseTableRepository, DatabaseTransformationRepository, DatabaseModelValueRepository, TargetIntegrationRepository};
+use crate::infrastructure::factories::{record_to_string_map, ConnectorFactory, SourceConnector};
+use crate::utils::{date_format, AppError, AppResult, PaginationResponse, Replacer, ValidationRecommendation, Validator};
+use crate::utils::date_format::DatePrecision;
+use crate::utils::expression::Expression;
 use super::fhir::FhirGenerator;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -73,6 +78,19 @@ pub struct DatabaseViewMappingEntity {
     pub updated_at: String,
 }
 
+/// How preview resources are generated and checked
+#[derive(Clone, Copy)]
+pub struct PreviewOptions<'a> {
+    /// Release of the view's target; mappings are translated from R4 paths to it
+    pub version: FhirVersion,
+    /// Timezone sample dates without an offset are read in
+    pub timezone: Tz,
+    /// Profiles and terminology the preview is validated against, when FHIR packages are loaded
+    pub conformance: Option<(&'a ProfileRegistry, &'a Terminology)>,
+    /// Key column and value of the source row to preview instead of each table's first row
+    pub sample_key: Option<(&'a str, &'a str)>,
+}
+
 pub struct DatabaseViewMappingUseCase {
     repository: Arc<DatabaseViewMappingRepository>,
     view_repository: Option<Arc<DatabaseViewRepository>>,
@@ -80,6 +98,10 @@ pub struct DatabaseViewMappingUseCase {
     table_repository: Option<Arc<DatabaseTableRepository>>,
     transformation_repository: Option<Arc<DatabaseTransformationRepository>>,
     model_value_repository: Option<Arc<DatabaseModelValueRepository>>,
+    target_repository: Option<Arc<TargetIntegrationRepository>>,
+    company_repository: Option<Arc<CompanyRepository>>,
+    conformance: Option<(Arc<ProfileRegistry>, Arc<RwLock<Terminology>>)>,
+    connectors: ConnectorFactory,
 }
 
 impl DatabaseViewMappingUseCase {
@@ -91,6 +113,10 @@ impl DatabaseViewMappingUseCase {
             table_repository: None,
             transformation_repository: None,
             model_value_repository: None,
+            target_repository: None,
+            company_repository: None,
+            conformance: None,
+            connectors: ConnectorFactory::default(),
         }
     }
 
@@ -109,9 +135,47 @@ impl DatabaseViewMappingUseCase {
             table_repository: Some(table_repository),
             transformation_repository: Some(transformation_repository),
             model_value_repository: Some(model_value_repository),
+            target_repository: None,
+            company_repository: None,
+            conformance: None,
+            connectors: ConnectorFactory::default(),
         }
     }
 
+    /// Open source connectors with the application's connector factory
+    pub fn with_connectors(mut self, connectors: ConnectorFactory) -> Self {
+        self.connectors = connectors;
+        self
+    }
+
+    /// Resolve the FHIR version of the view's target integration for previews
+    pub fn with_target_repository(mut self, target_repository: Arc<TargetIntegrationRepository>) -> Self {
+        self.target_repository = Some(target_repository);
+        self
+    }
+
+    /// Read preview dates in the company's timezone (America/Sao_Paulo without it)
+    pub fn with_company_repository(mut self, company_repository: Arc<CompanyRepository>) -> Self {
+        self.company_repository = Some(company_repository);
+        self
+    }
+
+    /// Validate previews against loaded StructureDefinitions and terminology instead of the built-in checks
+    pub fn with_conformance(mut self, profiles: Arc<ProfileRegistry>, terminology: Arc<RwLock<Terminology>>) -> Self {
+        self.conformance = Some((profiles, terminology));
+        self
+    }
+
+    /// FHIR version of the view's target integration; R4 when it has none
+    async fn target_fhir_version(&self, view: &DatabaseView) -> AppResult<FhirVersion> {
+        let (Some(target_repo), Some(target_id)) = (&self.target_repository, &view.target_integration_id) else {
+            return Ok(FhirVersion::default());
+        };
+
+        let target = target_repo.find_by_id(target_id).await?;
+        Ok(FhirVersion::for_target(target.as_ref().and_then(|t| t.version.as_deref())))
+    }
+
     // Helper method to convert resource type references to UUID references for bundles
     fn convert_references_to_uuid(resource: &mut Value, uuid_map: &std::collections::HashMap<String, String>) {
         if let Some(resource_obj) = resource.get_mut("resource") {
@@ -125,9 +189,9 @@ impl DatabaseViewMappingUseCase {
                 // Check if this object has a "reference" field
                 if let Some(reference) = map.get_mut("reference") {
                     if let Some(ref_str) = reference.as_str() {
-                        // Check if it's a resource type reference (e.g., "Patient/123")
-                        if let Some(slash_pos) = ref_str.find('/') {
-                            let resource_type = &ref_str[..slash_pos];
+                        // Check if it's a resource type reference (e.g., "Patient/123" or "Patient?identifier=...")
+                        if let Some(type_end) = ref_str.find(['/', '?']) {
+                            let resource_type = &ref_str[..type_end];
                             // If we have a UUID mapping for this resource type, replace it
                             if let Some(uuid) = uuid_map.get(resource_type) {
                                 *reference = json!(format!("urn:uuid:{}", uuid));
@@ -151,7 +215,63 @@ impl DatabaseViewMappingUseCase {
         }
     }
 
+    /// Check `fieldDestiny` and `referenceDestiny` paths against the typed R4 model of the mapped resource,
+    /// so a typo is refused when the mapping is saved instead of producing invalid resources.
+    /// A `timeOrigin` needs a date, dateTime or instant destination
+    fn check_field_paths(entity_type: &str, field_mappings: &[FieldMapping]) -> AppResult<()> {
+        let resource_type = FhirGenerator::map_entity_type_to_fhir_resource(entity_type);
+
+        let mut errors: Vec<String> = Vec::new();
+        for field_mapping in field_mappings {
+            if field_mapping.time_origin.is_some() {
+                if let Ok(Some(fhir_type)) = r4::primitive_at(&resource_type, &field_mapping.field_destiny) {
+                    if DatePrecision::from_fhir_type(fhir_type).is_none() {
+                        errors.push(format!(
+                            "'{}': timeOrigin exige um elemento date, dateTime ou instant, não {}",
+                            field_mapping.field_destiny, fhir_type
+                        ));
+                    }
+                }
+            }
+
+            let references = field_mapping.reference_destiny.iter().flat_map(|references| references.keys());
+            for path in std::iter::once(&field_mapping.field_destiny).chain(references) {
+                if path.is_empty() {
+                    continue;
+                }
+                if let Err(error) = r4::check_path(&resource_type, path) {
+                    let error = format!("'{}': {}", path, error);
+                    if !errors.contains(&error) {
+                        errors.push(error);
+                    }
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::Validation(format!("Caminhos FHIR inválidos no mapeamento: {}", errors.join("; "))))
+        }
+    }
+
+    /// Parse each `expression`, so a syntax error or unknown function is refused when the mapping is saved
+    fn check_expressions(field_mappings: &[FieldMapping]) -> AppResult<()> {
+        for field_mapping in field_mappings {
+            if let Some(expression) = field_mapping.expression() {
+                Expression::parse(expression).map_err(|error| match error {
+                    AppError::Validation(message) => AppError::Validation(format!("{}: {}", field_mapping.field_destiny, message)),
+                    other => other,
+                })?;
+            }
+        }
+        Ok(())
+    }
+
     pub async fn create_database_view_mapping(&self, data: CreateDatabaseViewMappingDto, _company_id: String) -> AppResult<DatabaseViewMappingEntity> {
+        Self::check_field_paths(&data.entity_type, &data.field_mappings)?;
+        Self::check_expressions(&data.field_mappings)?;
+
         let mapping = self.repository.create(
             data.name,
             data.description,
@@ -334,6 +454,15 @@ impl DatabaseViewMappingUseCase {
     }
 
     pub async fn update_database_view_mapping(&self, id: &str, data: UpdateDatabaseViewMappingDto) -> AppResult<DatabaseViewMappingEntity> {
+        if data.entity_type.is_some() || data.field_mappings.is_some() {
+            let current = self.repository.find_by_id(id).await?
+                .ok_or_else(|| AppError::NotFound("Database view mapping not found".to_string()))?;
+            let entity_type = data.entity_type.as_deref().unwrap_or(&current.entity_type);
+            let field_mappings = data.field_mappings.as_deref().unwrap_or(&current.field_mappings);
+            Self::check_field_paths(entity_type, field_mappings)?;
+            Self::check_expressions(field_mappings)?;
+        }
+
         let updated = self.repository.update(
             id,
             data.name,
@@ -367,10 +496,14 @@ impl DatabaseViewMappingUseCase {
         self.repository.delete(id).await
     }
 
+    /// Preview the view's resources in `version`, or in its target integration's version when None
+    /// `sample_key` picks the source row by key column and value instead of each table's first row
     pub async fn generate_fhir_preview(
         &self,
         view_id: &str,
-        company_id: &str
+        company_id: &str,
+        version: Option<FhirVersion>,
+        sample_key: Option<(&str, &str)>,
     ) -> AppResult<Value> {
 
         // Fetch database view and mappings together at the start
@@ -397,6 +530,11 @@ impl DatabaseViewMappingUseCase {
             return Ok(json!({ "entries": [] }));
         }
 
+        let version = match version {
+            Some(version) => version,
+            None => self.target_fhir_version(&db_view).await?,
+        };
+
         // Collect all transformation IDs (which are database_model owner_ids) from field mappings
         let mut owner_ids: Vec<String> = Vec::new();
         for mapping in &mappings {
@@ -423,15 +561,106 @@ impl DatabaseViewMappingUseCase {
              }
          }
 
+        // Resolve the source table read by each mapping
+        let mut origin_tables: std::collections::HashMap<String, String> = std::collections::HashMap::new();
+        if let Some(table_repo) = &self.table_repository {
+            for mapping in &mappings {
+                if origin_tables.contains_key(&mapping.database_table_origin_id) {
+                    continue;
+                }
+                if let Ok(Some(origin_table)) = table_repo.find_by_id(&mapping.database_table_origin_id).await {
+                    origin_tables.insert(
+                        mapping.database_table_origin_id.clone(),
+                        format!("{}_INTERHEALTH", origin_table.entity_type),
+                    );
+                }
+            }
+        }
+
+        // Try to fetch real data from client database and replace placeholders
+        let mut source = if self.table_repository.is_some() {
+            match self.connectors.create_source(&db_config).await {
+                Ok(connector) => Some(connector),
+                Err(_) => {
+                    println!("Failed to connect to client database");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let terminology = match &self.conformance {
+            Some((_, terminology)) => Some(terminology.read().await),
+            None => None,
+        };
+        let conformance = self
+            .conformance
+            .as_ref()
+            .zip(terminology.as_deref())
+            .map(|((profiles, _), terminology)| (profiles.as_ref(), terminology));
+
+        let timezone = match &self.company_repository {
+            Some(company_repo) => company_repo.find_by_id(company_id).await?.map(|company| company.timezone()),
+            None => None,
+        };
+
+        let preview = Self::build_fhir_preview(
+            &db_view,
+            &mappings,
+            &origin_tables,
+            &model_values,
+            source.as_deref(),
+            company_id,
+            PreviewOptions {
+                version,
+                timezone: timezone.unwrap_or(date_format::DEFAULT_TIMEZONE),
+                conformance,
+                sample_key,
+            },
+        ).await;
+
+        if let Some(connector) = source.as_mut() {
+            let _ = connector.close().await;
+        }
+
+        Ok(preview)
+    }
+
+    /// Build the FHIR preview for already loaded mappings
+    /// `origin_tables` maps each mapping's origin table id to the source table name;
+    /// without a `source` the preview keeps the template placeholders.
+    /// Mappings are written in R4 paths and translated to `options.version` here; sample dates are read in `options.timezone`
+    pub async fn build_fhir_preview(
+        db_view: &DatabaseView,
+        mappings: &[DatabaseViewMappingEntity],
+        origin_tables: &std::collections::HashMap<String, String>,
+        model_values: &std::collections::HashMap<String, DatabaseModelValue>,
+        source: Option<&dyn SourceConnector>,
+        company_id: &str,
+        options: PreviewOptions<'_>,
+    ) -> Value {
+        let PreviewOptions { version, timezone, conformance, sample_key } = options;
         // Check if we should create a bundle based on db_view entity_type
         let should_create_bundle = db_view.entity_type == "BUNDLE";
+
+        let mappings: Vec<DatabaseViewMappingEntity> = mappings
+            .iter()
+            .map(|mapping| {
+                let resource_type = FhirGenerator::map_entity_type_to_fhir_resource(&mapping.entity_type);
+                DatabaseViewMappingEntity {
+                    field_mappings: version.translate_field_mappings(&resource_type, &mapping.field_mappings),
+                    ..mapping.clone()
+                }
+            })
+            .collect();
         
         // Generate resources for all mappings with database_model_values
         let mut generated_resources: Vec<Value> = Vec::new();
         
         for mapping in &mappings {
             // Generate resource with model values
-            let resource = FhirGenerator::generate_resource_with_model_values(mapping, &model_values, company_id);
+            let resource = FhirGenerator::generate_resource_with_model_values(mapping, model_values, company_id, version, db_view.delivery_mode());
             generated_resources.push(resource);
         }
         
@@ -454,61 +683,69 @@ impl DatabaseViewMappingUseCase {
             std::collections::HashMap::new()
         };
 
-        // Try to fetch real data from client database and replace placeholders
-        if let Some(table_repo) = &self.table_repository {
-            let username = db_config.username.as_ref().ok_or_else(|| AppError::BadRequest("Database username is required".to_string()))?;
-            let password = db_config.password.as_ref().ok_or_else(|| AppError::BadRequest("Database password is required".to_string()))?;
-            let port = db_config.port.ok_or_else(|| AppError::BadRequest("Database port is required".to_string()))?;
-            let database = db_config.database.as_ref().ok_or_else(|| AppError::BadRequest("Database name is required".to_string()))?;
-            
-            // Build Oracle connection string
-            let connection_string = format!(
-                "oracle://{}:{}@{}:{}/{}",
-                username,
-                password,
-                db_config.host,
-                port,
-                database
-            );
-
-            // Connect to client database
-            match OracleConnector::new(&connection_string).await {
-                Ok(connector) => {
-                    // Apply data replacement for each generated resource
-                    for (idx, resource) in generated_resources.iter_mut().enumerate() {
-                        if idx < mappings.len() {
-                            let mapping = &mappings[idx];
-                            
-                            // Get the origin table for this mapping
-                            if let Ok(Some(origin_table)) = table_repo.find_by_id(&mapping.database_table_origin_id).await {
-                                let table_name = format!("{}_INTERHEALTH", origin_table.entity_type);
-                                
-                                // Fetch data for this specific resource
-                                match connector.fetch_first_row(&table_name).await {
-                                    Ok(data) => {
-                                        // Replace placeholders with real data and apply database_model_value transformations
-                                        Replacer::replace_in_entry_with_model_values(
-                                            resource,
-                                            &data,
-                                            &mapping.field_mappings,
-                                            &model_values,
-                                            company_id
-                                        );
-                                    }
-                                    Err(_) => {
-                                        println!("Failed to fetch data from table: {}", table_name);
-                                    }
-                                }
-                            }
+        // Replace placeholders with a sample row from each mapping's origin table
+        let mut replaced = vec![false; generated_resources.len()];
+        let mut structure_errors = Vec::new();
+        if let Some(connector) = source {
+            for ((resource, mapping), replaced) in generated_resources.iter_mut().zip(&mappings).zip(replaced.iter_mut()) {
+                let Some(table_name) = origin_tables.get(&mapping.database_table_origin_id) else {
+                    continue;
+                };
+
+                // Fetch data for this specific resource
+                let row = match sample_key {
+                    Some((key_column, key_value)) => connector
+                        .fetch_by_key(table_name, key_column, key_value)
+                        .await
+                        .and_then(|record| {
+                            record.as_ref().map(record_to_string_map).ok_or_else(|| {
+                                AppError::NotFound(format!("No row with {} = {} in table {}", key_column, key_value, table_name))
+                            })
+                        }),
+                    None => connector.fetch_first_row(table_name).await,
+                };
+                match row {
+                    Ok(data) => {
+                        // Replace placeholders with real data and apply database_model_value transformations
+                        match Replacer::replace_in_entry_with_model_values(
+                            resource,
+                            &data,
+                            &mapping.field_mappings,
+                            model_values,
+                            company_id,
+                            timezone,
+                        ) {
+                            Ok(()) => *replaced = true,
+                            // An expression that fails on the sample row
+                            Err(error) => structure_errors.push(r4::StructureError {
+                                path: FhirGenerator::map_entity_type_to_fhir_resource(&mapping.entity_type),
+                                message: match error {
+                                    AppError::Validation(message) => message,
+                                    other => other.to_string(),
+                                },
+                            }),
                         }
                     }
-                }
-                Err(_) => {
-                    println!("Failed to connect to client database");
+                    Err(_) => {
+                        println!("Failed to fetch data from table: {}", table_name);
+                    }
                 }
             }
         }
         
+        // Resources filled with a sample row are shown as sent; what does not fit the FHIR model is reported
+        for (resource, replaced) in generated_resources.iter_mut().zip(replaced) {
+            if let Some(resource_obj) = resource.get_mut("resource") {
+                version.adapt_values(resource_obj);
+                if replaced {
+                    match version.conform(resource_obj) {
+                        Ok(conformed) => *resource_obj = conformed,
+                        Err(error) => structure_errors.push(error),
+                    }
+                }
+            }
+        }
+
         // Convert references to UUIDs AFTER data replacement (for bundles)
         if should_create_bundle && !resource_uuids.is_empty() {
             for resource in &mut generated_resources {
@@ -518,27 +755,25 @@ impl DatabaseViewMappingUseCase {
         
         // Create final result - bundle or single resource
         let mut result = if should_create_bundle && generated_resources.len() > 1 {
-            // Create a bundle with all generated resources
-            json!({
-                "resourceType": "Bundle",
-                "type": "transaction",
-                "entry": generated_resources.into_iter().map(|resource| {
-                    json!({
-                        "fullUrl": resource.get("fullUrl").cloned().unwrap_or_else(|| json!(format!("urn:uuid:{}", uuid::Uuid::new_v4()))),
-                        "resource": resource.get("resource").unwrap_or(&resource).clone(),
-                        "request": resource.get("request").cloned().unwrap_or_else(|| {
-                            let resource_type = resource.get("resource")
-                                .and_then(|r| r.get("resourceType"))
-                                .and_then(|rt| rt.as_str())
-                                .unwrap_or("Resource");
-                            json!({
-                                "method": "POST",
-                                "url": resource_type
-                            })
+            // Create a bundle of the target's FHIR version with all generated resources
+            let mut bundle = version.bundle_template();
+            bundle["entry"] = Value::Array(generated_resources.into_iter().map(|resource| {
+                json!({
+                    "fullUrl": resource.get("fullUrl").cloned().unwrap_or_else(|| json!(format!("urn:uuid:{}", uuid::Uuid::new_v4()))),
+                    "resource": resource.get("resource").unwrap_or(&resource).clone(),
+                    "request": resource.get("request").cloned().unwrap_or_else(|| {
+                        let resource_type = resource.get("resource")
+                            .and_then(|r| r.get("resourceType"))
+                            .and_then(|rt| rt.as_str())
+                            .unwrap_or("Resource");
+                        json!({
+                            "method": "POST",
+                            "url": resource_type
                         })
                     })
-                }).collect::<Vec<Value>>()
-            })
+                })
+            }).collect());
+            bundle
         } else if !generated_resources.is_empty() {
             // Single resource
             generated_resources.into_iter().next().unwrap()
@@ -547,15 +782,336 @@ impl DatabaseViewMappingUseCase {
         };
 
         // Validate the FHIR resource and add recommendations
-        let validation = Validator::validate(&result);
+        let mut validation = match conformance {
+            Some((profiles, terminology)) => Validator::validate_with_profiles(&result, profiles, terminology),
+            None => Validator::validate(&result),
+        };
+        for error in structure_errors {
+            validation.is_valid = false;
+            validation.recommendations.push(ValidationRecommendation {
+                severity: "error".to_string(),
+                field: error.path,
+                message: error.message,
+                recommendation: "Corrija o caminho do mapeamento ou o formato do valor na origem".to_string(),
+            });
+        }
         
         // Return the resource with validation recommendations
-        Ok(json!({
+        json!({
             "resource": result,
             "validation": {
                 "isValid": validation.is_valid,
                 "recommendations": validation.recommendations
             }
-        }))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use chrono::Utc;
+    use crate::infrastructure::adapters::FixtureConnector;
+
+    fn field(origin: &str, destiny: &str) -> FieldMapping {
+        FieldMapping {
+            field_origin: origin.to_string(),
+            field_destiny: destiny.to_string(),
+            description: None,
+            reference_destiny: None,
+            relationship_destiny: None,
+            reference_identifier_system: None,
+            data_type: "string".to_string(),
+            is_nullable: true,
+            min_length: 0,
+            max_length: 0,
+            is_enumerable: false,
+            transformation_id: None,
+            reference: None,
+            expression: None,
+            time_origin: None,
+        }
+    }
+
+    fn patient_view() -> DatabaseView {
+        DatabaseView {
+            id: None,
+            name: "Patients".to_string(),
+            description: String::new(),
+            resource: None,
+            entity_type: "PATIENT".to_string(),
+            main_resource: None,
+            is_fhir_destination: Some(true),
+            is_interhealth_destination: None,
+            database_configuration_id: "config-1".to_string(),
+            company_id: "company".to_string(),
+            target_integration_id: None,
+            message_types: None,
+            delivery_mode: None,
+            reference_strategy: None,
+            status: "pending".to_string(),
+            job_id: None,
+            resources: None,
+            started_at: None,
+            cancelled_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn patient_mapping() -> DatabaseViewMappingEntity {
+        DatabaseViewMappingEntity {
+            id: "mapping-1".to_string(),
+            name: "Patient".to_string(),
+            description: String::new(),
+            entity_type: "PATIENT".to_string(),
+            resource: None,
+            database_table_origin_id: "table-1".to_string(),
+            database_table_destiny_id: "table-2".to_string(),
+            data_view_id: "view-1".to_string(),
+            field_mappings: vec![
+                field("patient_gender", "gender"),
+                field("patient_birth_date", "birthDate"),
+            ],
+            status: "active".to_string(),
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_preview_replaces_placeholders_with_fixture_row() {
+        let mut tables = HashMap::new();
+        tables.insert(
+            "PATIENT_INTERHEALTH".to_string(),
+            vec![json!({ "PATIENT_GENDER": "female", "PATIENT_BIRTH_DATE": "1990-04-12" })],
+        );
+        let source = FixtureConnector::from_tables("preview", tables);
+
+        let origin_tables = HashMap::from([("table-1".to_string(), "PATIENT_INTERHEALTH".to_string())]);
+
+        let preview = DatabaseViewMappingUseCase::build_fhir_preview(
+            &patient_view(),
+            &[patient_mapping()],
+            &origin_tables,
+            &HashMap::new(),
+            Some(&source),
+            "company",
+            PreviewOptions { version: FhirVersion::R4, timezone: date_format::DEFAULT_TIMEZONE, conformance: None, sample_key: None },
+        ).await;
+
+        let resource = &preview["resource"]["resource"];
+        assert_eq!(resource["resourceType"], "Patient");
+        assert_eq!(resource["gender"], "female");
+        assert_eq!(resource["birthDate"], "1990-04-12");
+        assert!(preview["validation"]["isValid"].is_boolean());
+    }
+
+    #[tokio::test]
+    async fn test_preview_reads_the_row_matching_sample_key() {
+        let mut tables = HashMap::new();
+        tables.insert(
+            "PATIENT_INTERHEALTH".to_string(),
+            vec![
+                json!({ "PATIENT_CODE": 1, "PATIENT_GENDER": "female", "PATIENT_BIRTH_DATE": "1990-04-12" }),
+                json!({ "PATIENT_CODE": 2, "PATIENT_GENDER": "male", "PATIENT_BIRTH_DATE": "1985-11-03" }),
+            ],
+        );
+        let source = FixtureConnector::from_tables("preview", tables);
+        let origin_tables = HashMap::from([("table-1".to_string(), "PATIENT_INTERHEALTH".to_string())]);
+
+        let preview = DatabaseViewMappingUseCase::build_fhir_preview(
+            &patient_view(),
+            &[patient_mapping()],
+            &origin_tables,
+            &HashMap::new(),
+            Some(&source),
+            "company",
+            PreviewOptions {
+                version: FhirVersion::R4,
+                timezone: date_format::DEFAULT_TIMEZONE,
+                conformance: None,
+                sample_key: Some(("PATIENT_CODE", "2")),
+            },
+        ).await;
+
+        let resource = &preview["resource"]["resource"];
+        assert_eq!(resource["gender"], "male");
+        assert_eq!(resource["birthDate"], "1985-11-03");
+    }
+
+    #[tokio::test]
+    async fn test_preview_without_source_keeps_placeholders() {
+        let preview = DatabaseViewMappingUseCase::build_fhir_preview(
+            &patient_view(),
+            &[patient_mapping()],
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            "company",
+            PreviewOptions { version: FhirVersion::R4, timezone: date_format::DEFAULT_TIMEZONE, conformance: None, sample_key: None },
+        ).await;
+
+        assert_eq!(preview["resource"]["resource"]["gender"], "patient_gender");
+    }
+
+    #[tokio::test]
+    async fn test_preview_translates_mapping_to_target_version() {
+        let mapping = DatabaseViewMappingEntity {
+            entity_type: "ENCOUNTER".to_string(),
+            field_mappings: vec![field("encounter_class", "class.code"), field("encounter_status", "status")],
+            ..patient_mapping()
+        };
+        let mut tables = HashMap::new();
+        tables.insert(
+            "ENCOUNTER_INTERHEALTH".to_string(),
+            vec![json!({ "ENCOUNTER_CLASS": "IMP", "ENCOUNTER_STATUS": "finished" })],
+        );
+        let source = FixtureConnector::from_tables("preview", tables);
+        let origin_tables = HashMap::from([("table-1".to_string(), "ENCOUNTER_INTERHEALTH".to_string())]);
+
+        let mut previews = Vec::new();
+        for version in [FhirVersion::R4, FhirVersion::R5] {
+            previews.push(DatabaseViewMappingUseCase::build_fhir_preview(
+                &patient_view(),
+                std::slice::from_ref(&mapping),
+                &origin_tables,
+                &HashMap::new(),
+                Some(&source),
+                "company",
+                PreviewOptions { version, timezone: date_format::DEFAULT_TIMEZONE, conformance: None, sample_key: None },
+            ).await);
+        }
+
+        let r4 = &previews[0]["resource"]["resource"];
+        assert_eq!(r4["class"]["code"], "IMP");
+        assert_eq!(r4["status"], "finished");
+
+        let r5 = &previews[1]["resource"]["resource"];
+        assert_eq!(r5["class"][0]["coding"][0]["code"], "IMP");
+        assert_eq!(r5["status"], "completed");
+    }
+
+    #[test]
+    fn test_check_field_paths_refuses_unknown_elements() {
+        let mut coded = field("patient_marital_status", "maritalStatus.coding[0].code");
+        coded.reference_destiny = Some(HashMap::from([(
+            "maritalStatus.coding[0].system".to_string(),
+            "http://terminology.hl7.org/CodeSystem/v3-MaritalStatus".to_string(),
+        )]));
+        assert!(DatabaseViewMappingUseCase::check_field_paths("PATIENT", &[coded.clone(), field("patient_name", "name[0].text")]).is_ok());
+
+        coded.reference_destiny = Some(HashMap::from([("maritalStatus.coding[0].sytem".to_string(), "x".to_string())]));
+        let error = DatabaseViewMappingUseCase::check_field_paths("PATIENT", &[coded, field("patient_birth_date", "birthdate")])
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("sytem"));
+        assert!(error.contains("birthdate"));
+    }
+
+    #[tokio::test]
+    async fn test_preview_reports_values_outside_fhir_formats() {
+        let mut tables = HashMap::new();
+        tables.insert(
+            "PATIENT_INTERHEALTH".to_string(),
+            vec![json!({ "PATIENT_GENDER": "female", "PATIENT_BIRTH_DATE": "12/04/1990" })],
+        );
+        let source = FixtureConnector::from_tables("preview", tables);
+        let origin_tables = HashMap::from([("table-1".to_string(), "PATIENT_INTERHEALTH".to_string())]);
+
+        let preview = DatabaseViewMappingUseCase::build_fhir_preview(
+            &patient_view(),
+            &[patient_mapping()],
+            &origin_tables,
+            &HashMap::new(),
+            Some(&source),
+            "company",
+            PreviewOptions { version: FhirVersion::R4, timezone: date_format::DEFAULT_TIMEZONE, conformance: None, sample_key: None },
+        ).await;
+
+        assert_eq!(preview["validation"]["isValid"], false);
+        let recommendations = preview["validation"]["recommendations"].as_array().unwrap();
+        assert!(recommendations.iter().any(|r| r["field"] == "Patient.birthDate"));
+    }
+
+    #[tokio::test]
+    async fn test_preview_evaluates_field_expressions() {
+        let mut tables = HashMap::new();
+        tables.insert(
+            "PATIENT_INTERHEALTH".to_string(),
+            vec![json!({ "PATIENT_GENDER": "F", "PATIENT_BIRTH_DATE": "12/04/1990", "PATIENT_CODE": "4521" })],
+        );
+        let source = FixtureConnector::from_tables("preview", tables);
+        let origin_tables = HashMap::from([("table-1".to_string(), "PATIENT_INTERHEALTH".to_string())]);
+
+        let mut mapping = patient_mapping();
+        mapping.field_mappings[0].expression = Some("if(patient_gender = 'F', 'female', 'male')".to_string());
+        mapping.field_mappings[1].expression = Some("parse_date(patient_birth_date, '%d/%m/%Y')".to_string());
+        let mut code = field("", "identifier[0].value");
+        code.expression = Some("lpad(patient_code, 8, '0')".to_string());
+        mapping.field_mappings.push(code);
+
+        let preview = DatabaseViewMappingUseCase::build_fhir_preview(
+            &patient_view(),
+            &[mapping.clone()],
+            &origin_tables,
+            &HashMap::new(),
+            Some(&source),
+            "company",
+            PreviewOptions { version: FhirVersion::R4, timezone: date_format::DEFAULT_TIMEZONE, conformance: None, sample_key: None },
+        ).await;
+
+        assert_eq!(preview["validation"]["isValid"], true);
+        let entry = &preview["resource"];
+        assert_eq!(entry["resource"]["gender"], "female");
+        assert_eq!(entry["resource"]["birthDate"], "1990-04-12");
+        assert_eq!(entry["resource"]["identifier"][0]["value"], "00004521");
+        assert!(entry["request"]["ifNoneExist"].as_str().unwrap().ends_with("|00004521"));
+
+        mapping.field_mappings[2].expression = Some("lpad(patient_code, 8, '0'".to_string());
+        let error = DatabaseViewMappingUseCase::check_expressions(&mapping.field_mappings).unwrap_err().to_string();
+        assert!(error.contains("identifier[0].value"));
+    }
+
+    #[tokio::test]
+    async fn test_preview_merges_date_and_time_columns_in_company_timezone() {
+        let mut tables = HashMap::new();
+        tables.insert(
+            "PATIENT_INTERHEALTH".to_string(),
+            vec![json!({
+                "PATIENT_BIRTH_DATE": "12/04/1990 00:00:00",
+                "PATIENT_DEATH_DATE": "2024-03-05 00:00:00",
+                "PATIENT_DEATH_TIME": "14:30",
+            })],
+        );
+        let source = FixtureConnector::from_tables("preview", tables);
+        let origin_tables = HashMap::from([("table-1".to_string(), "PATIENT_INTERHEALTH".to_string())]);
+
+        let mut mapping = patient_mapping();
+        mapping.field_mappings[1].data_type = "datetime".to_string();
+        let mut death = field("patient_death_date", "deceasedDateTime");
+        death.data_type = "date".to_string();
+        death.time_origin = Some("patient_death_time".to_string());
+        mapping.field_mappings.push(death);
+
+        let preview = DatabaseViewMappingUseCase::build_fhir_preview(
+            &patient_view(),
+            &[mapping.clone()],
+            &origin_tables,
+            &HashMap::new(),
+            Some(&source),
+            "company",
+            PreviewOptions { version: FhirVersion::R4, timezone: date_format::parse_timezone("America/Manaus").unwrap(), conformance: None, sample_key: None },
+        ).await;
+
+        assert_eq!(preview["validation"]["isValid"], true);
+        let resource = &preview["resource"]["resource"];
+        assert_eq!(resource["birthDate"], "1990-04-12");
+        assert_eq!(resource["deceasedDateTime"], "2024-03-05T14:30:00-04:00");
+
+        mapping.field_mappings[0].time_origin = Some("patient_death_time".to_string());
+        let error = DatabaseViewMappingUseCase::check_field_paths("PATIENT", &mapping.field_mappings).unwrap_err().to_string();
+        assert!(error.contains("timeOrigin"));
     }
 }
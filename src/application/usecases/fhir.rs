@@ -1,7 +1,7 @@
 use serde_json::{json, Value};
 use super::database_view_mapping::DatabaseViewMappingEntity;
-use crate::domain::fhir::r4::{bundle, resource};
-use crate::domain::entities::{DatabaseTransformation, DatabaseModelValue};
+use crate::domain::fhir::{ConditionalReference, FhirVersion, DEFAULT_IDENTIFIER_SYSTEM};
+use crate::domain::entities::{DatabaseTransformation, DatabaseModelValue, DeliveryMode};
 use std::collections::HashMap;
 use uuid::Uuid;
 use bson::oid::ObjectId;
@@ -9,58 +9,13 @@ use bson::oid::ObjectId;
 pub struct FhirGenerator;
 
 impl FhirGenerator {
-    pub fn generate_bundle(mappings: &[DatabaseViewMappingEntity], company_id: &str) -> Value {
-        Self::generate_bundle_with_model_values(mappings, &HashMap::new(), company_id)
-    }
-
-    pub fn generate_bundle_with_transformations(
-        mappings: &[DatabaseViewMappingEntity],
-        transformations: &HashMap<String, DatabaseTransformation>
-    ) -> Value {
-        let mut bundle = bundle::get_template();
-        let mut entries = Vec::new();
-
-        for mapping in mappings {
-            let resource_entry = Self::generate_resource_with_transformations(mapping, transformations);
-            entries.push(resource_entry);
-        }
-
-        if let Some(bundle_obj) = bundle.as_object_mut() {
-            bundle_obj.insert("entry".to_string(), json!(entries));
-        }
-
-        bundle
-    }
-
-    pub fn generate_bundle_with_model_values(
-        mappings: &[DatabaseViewMappingEntity],
-        model_values: &HashMap<String, DatabaseModelValue>,
-        company_id: &str
-    ) -> Value {
-        let mut bundle = bundle::get_template();
-        let mut entries = Vec::new();
-
-        for mapping in mappings {
-            let resource_entry = Self::generate_resource_with_model_values(mapping, model_values, company_id);
-            entries.push(resource_entry);
-        }
-
-        if let Some(bundle_obj) = bundle.as_object_mut() {
-            bundle_obj.insert("entry".to_string(), json!(entries));
-        }
-
-        bundle
-    }
-
-    pub fn generate_resource(mapping: &DatabaseViewMappingEntity, company_id: &str) -> Value {
-        Self::generate_resource_with_model_values(mapping, &HashMap::new(), company_id)
-    }
-
     pub fn generate_resource_with_transformations(
         mapping: &DatabaseViewMappingEntity,
-        transformations: &HashMap<String, DatabaseTransformation>
+        transformations: &HashMap<String, DatabaseTransformation>,
+        version: FhirVersion,
+        mode: DeliveryMode,
     ) -> Value {
-        Self::build_resource_template(mapping, |field_mapping, origin_value| {
+        Self::build_resource_template(mapping, version, mode, |field_mapping, origin_value| {
             // Apply transformation if exists
             if let Some(transformation_id) = &field_mapping.transformation_id {
                 if let Some(transformation) = transformations.get(transformation_id) {
@@ -76,24 +31,30 @@ impl FhirGenerator {
     pub fn generate_resource_with_model_values(
         mapping: &DatabaseViewMappingEntity,
         model_values: &HashMap<String, DatabaseModelValue>,
-        company_id: &str
+        company_id: &str,
+        version: FhirVersion,
+        mode: DeliveryMode,
     ) -> Value {
         // Model values transformation happens during data replacement, not template generation
         // So we just pass through the placeholder values
-        Self::build_resource_template(mapping, |_field_mapping, origin_value| {
+        Self::build_resource_template(mapping, version, mode, |_field_mapping, origin_value| {
             (origin_value.to_string(), None)
         })
     }
 
     /// Shared helper to build FHIR resource template with custom transformation logic
+    /// The mapping's field paths must already be in `version` (see FhirVersion::translate_field_mappings)
+    /// and the entry's `request` follows the view's delivery `mode`
     fn build_resource_template<F>(
         mapping: &DatabaseViewMappingEntity,
+        version: FhirVersion,
+        mode: DeliveryMode,
         transform_fn: F
     ) -> Value 
     where
         F: Fn(&crate::domain::entities::FieldMapping, &str) -> (String, Option<String>)
     {
-        let mut resource_template = resource::get_template();
+        let mut resource_template = version.entry_template();
         
         if let Some(resource_obj) = resource_template.as_object_mut() {
             let resource_type = Self::map_entity_type_to_fhir_resource(&mapping.entity_type);
@@ -119,12 +80,8 @@ impl FhirGenerator {
             }
             
             // Set request metadata
-            let if_none_exist = Self::build_if_none_exist_from_resource(resource_obj);
-            if let Some(request) = resource_obj.get_mut("request").and_then(|r| r.as_object_mut()) {
-                request.insert("method".to_string(), json!("POST"));
-                request.insert("url".to_string(), json!(&resource_type));
-                request.insert("ifNoneExist".to_string(), json!(if_none_exist));
-            }
+            let identifier_query = Self::build_if_none_exist_from_resource(resource_obj);
+            resource_obj.insert("request".to_string(), mode.request(&resource_type, &identifier_query));
         }
         
         resource_template
@@ -159,17 +116,18 @@ impl FhirGenerator {
         
         if let Some(data_obj) = resource_data.as_object_mut() {
             for field_mapping in field_mappings {
-                if field_mapping.field_origin.is_empty() {
+                let placeholder = field_mapping.placeholder();
+                if placeholder.is_empty() {
                     continue;
                 }
                 
-                let origin_value = &field_mapping.field_origin;
-                let (mut transformed_value, display_value) = transform_fn(field_mapping, origin_value);
+                let (mut transformed_value, display_value) = transform_fn(field_mapping, &placeholder);
                 
-                // Apply relationship prefix if needed
+                // References carry the source code as an identifier, not as the logical id on the target
                 if let Some(relationship) = &field_mapping.relationship_destiny {
                     if field_mapping.field_destiny.ends_with(".reference") {
-                        transformed_value = format!("{}/{}", relationship, transformed_value);
+                        let system = field_mapping.reference_identifier_system.as_deref().unwrap_or(DEFAULT_IDENTIFIER_SYSTEM);
+                        transformed_value = ConditionalReference::new(relationship, system, &transformed_value).to_string();
                     }
                 }
                 
@@ -199,7 +157,8 @@ impl FhirGenerator {
         resource_data
     }
 
-    fn map_entity_type_to_fhir_resource(entity_type: &str) -> String {
+    /// Resource type generated for a mapping entity type (PATIENT -> Patient)
+    pub fn map_entity_type_to_fhir_resource(entity_type: &str) -> String {
         let lower = entity_type.to_lowercase();
         let mut chars = lower.chars();
         match chars.next() {
@@ -434,67 +393,4 @@ impl FhirGenerator {
             }
         }
     }
-
-    fn set_reference_metadata(obj: &mut serde_json::Map<String, Value>, path: &str, reference: &std::collections::HashMap<String, String>) {
-        // Extract the parent path (everything before the last field)
-        // For example: "telecom[0].value" -> "telecom[0]"
-        let parts: Vec<&str> = path.split('.').collect();
-        if parts.is_empty() {
-            return;
-        }
-        
-        // Get parent path (all parts except the last one)
-        let parent_path = if parts.len() > 1 {
-            parts[..parts.len() - 1].join(".")
-        } else {
-            // If there's no parent (single field), we can't add reference metadata
-            return;
-        };
-        
-        // Navigate to the parent object and add reference fields
-        let parent_parts: Vec<&str> = parent_path.split('.').collect();
-        Self::navigate_and_set_reference(obj, &parent_parts, reference);
-    }
-
-    fn navigate_and_set_reference(obj: &mut serde_json::Map<String, Value>, parts: &[&str], reference: &std::collections::HashMap<String, String>) {
-        if parts.is_empty() {
-            // We're at the target, add reference fields
-            for (key, value) in reference {
-                obj.insert(key.clone(), json!(value));
-            }
-            return;
-        }
-        
-        let first = parts[0];
-        let rest = &parts[1..];
-        
-        if first.contains('[') && first.contains(']') {
-            let array_path = Self::parse_array_path(first);
-            if let Some((field, indices)) = array_path {
-                if !obj.contains_key(&field) {
-                    obj.insert(field.clone(), json!([]));
-                }
-                
-                if let Some(array) = obj.get_mut(&field).and_then(|v| v.as_array_mut()) {
-                    for &idx in &indices {
-                        while array.len() <= idx {
-                            array.push(json!({}));
-                        }
-                        
-                        if let Some(nested_obj) = array[idx].as_object_mut() {
-                            Self::navigate_and_set_reference(nested_obj, rest, reference);
-                        }
-                    }
-                }
-            }
-        } else {
-            if !obj.contains_key(first) {
-                obj.insert(first.to_string(), json!({}));
-            }
-            
-            if let Some(nested_obj) = obj.get_mut(first).and_then(|v| v.as_object_mut()) {
-                Self::navigate_and_set_reference(nested_obj, rest, reference);
-            }
-        }
-    }
-}
\ No newline at end of file
+}
@@ -0,0 +1,262 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Months, NaiveDate, TimeZone, Utc};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde_json::{json, Value};
+
+use crate::infrastructure::repositories::{DeliveredResourceQuery, DeliveredResourceRepository};
+use crate::utils::{AppError, AppResult};
+
+const DEFAULT_COUNT: i64 = 50;
+const MAX_COUNT: i64 = 500;
+
+/// Characters left as is in the query string of Bundle links
+const QUERY_VALUE: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~').remove(b'|').remove(b':').remove(b'/');
+
+/// Search parameters accepted by the facade
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FacadeSearch {
+    pub query: DeliveredResourceQuery,
+    /// Patient ids or `Patient/{id}` references from `patient`
+    pub patients: Vec<String>,
+    pub count: i64,
+    pub offset: u64,
+}
+
+impl FacadeSearch {
+    /// Parse `identifier`, `_lastUpdated`, `patient`, `_count` and `_offset`; other parameters are refused
+    /// so a typo does not return every resource
+    pub fn parse(resource_type: &str, params: &[(String, String)]) -> AppResult<Self> {
+        let mut search = FacadeSearch { count: DEFAULT_COUNT, ..Default::default() };
+
+        for (name, value) in params {
+            match name.as_str() {
+                "identifier" => {
+                    for token in value.split(',').filter(|token| !token.is_empty()) {
+                        if token.contains('|') {
+                            search.query.identifiers.push(token.to_string());
+                        } else {
+                            search.query.identifier_values.push(token.to_string());
+                        }
+                    }
+                }
+                "_lastUpdated" => search.query.last_updated.extend(parse_date_bounds(value)?),
+                "patient" if resource_type != "Patient" => {
+                    search.patients.extend(value.split(',').filter(|id| !id.is_empty()).map(|id| {
+                        id.strip_prefix("Patient/").unwrap_or(id).to_string()
+                    }));
+                }
+                "_count" => {
+                    let count: i64 = value.parse().map_err(|_| AppError::BadRequest(format!("_count inválido: {}", value)))?;
+                    search.count = count.clamp(1, MAX_COUNT);
+                }
+                "_offset" => {
+                    search.offset = value.parse().map_err(|_| AppError::BadRequest(format!("_offset inválido: {}", value)))?;
+                }
+                "_format" => {}
+                _ => {
+                    return Err(AppError::BadRequest(format!(
+                        "Parâmetro de busca não suportado para {}: {}",
+                        resource_type, name
+                    )))
+                }
+            }
+        }
+
+        Ok(search)
+    }
+}
+
+/// Mongo bounds of a FHIR date search value (`ge2024-01-01`, `lt2024-03-01T10:00:00Z`, `2024-05`)
+/// A value covers its whole precision: `2024-05` is every instant of May 2024
+fn parse_date_bounds(value: &str) -> AppResult<Vec<(&'static str, DateTime<Utc>)>> {
+    let invalid = || AppError::BadRequest(format!("_lastUpdated inválido: {}", value));
+
+    let (prefix, date) = match value.get(..2) {
+        Some(prefix @ ("eq" | "gt" | "ge" | "lt" | "le" | "sa" | "eb")) => (prefix, &value[2..]),
+        _ => ("eq", value),
+    };
+
+    let (start, end) = if let Ok(instant) = DateTime::parse_from_rfc3339(date) {
+        let start = instant.with_timezone(&Utc);
+        (start, start + Duration::seconds(1))
+    } else {
+        let (day, months) = match date.len() {
+            4 => (format!("{}-01-01", date), 12),
+            7 => (format!("{}-01", date), 1),
+            10 => (date.to_string(), 0),
+            _ => return Err(invalid()),
+        };
+        let day = NaiveDate::parse_from_str(&day, "%Y-%m-%d").map_err(|_| invalid())?;
+        let end = if months == 0 { day.succ_opt() } else { day.checked_add_months(Months::new(months)) }.ok_or_else(invalid)?;
+        let midnight = |date: NaiveDate| Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).unwrap_or_default());
+        (midnight(day), midnight(end))
+    };
+
+    Ok(match prefix {
+        "gt" | "sa" => vec![("$gte", end)],
+        "ge" => vec![("$gte", start)],
+        "lt" | "eb" => vec![("$lt", start)],
+        "le" => vec![("$lt", end)],
+        _ => vec![("$gte", start), ("$lt", end)],
+    })
+}
+
+/// Read-only FHIR REST facade over the last delivered version of each resource, per company
+pub struct FhirFacadeUseCase {
+    repository: Arc<DeliveredResourceRepository>,
+}
+
+impl FhirFacadeUseCase {
+    pub fn new(repository: Arc<DeliveredResourceRepository>) -> Self {
+        Self { repository }
+    }
+
+    /// `GET /fhir/{type}/{id}`
+    pub async fn read(&self, company_id: &str, resource_type: &str, id: &str) -> AppResult<Value> {
+        self.repository
+            .find_by_id(company_id, resource_type, id)
+            .await?
+            .map(|delivered| delivered.to_fhir())
+            .ok_or_else(|| AppError::NotFound(format!("{}/{} não encontrado", resource_type, id)))
+    }
+
+    /// `GET /fhir/{type}?...` as a searchset Bundle; `base_url` is the facade root (`https://host/fhir`)
+    pub async fn search(&self, company_id: &str, resource_type: &str, params: &[(String, String)], base_url: &str) -> AppResult<Value> {
+        let mut search = FacadeSearch::parse(resource_type, params)?;
+
+        // Resources reference the patient by id or, as delivered, by its identifiers
+        for patient_id in &search.patients {
+            search.query.patient_references.push(format!("Patient/{}", patient_id));
+            if let Some(patient) = self.repository.find_by_id(company_id, "Patient", patient_id).await? {
+                search.query.patient_references.extend(
+                    patient.identifiers.iter().map(|identifier| format!("Patient?identifier={}", identifier)),
+                );
+            }
+        }
+
+        let (resources, total) = self
+            .repository
+            .search(company_id, resource_type, &search.query, search.offset, search.count)
+            .await?;
+
+        let page_url = |offset: u64| {
+            let mut query: Vec<String> = params
+                .iter()
+                .filter(|(name, _)| name != "_offset" && name != "_count")
+                .map(|(name, value)| format!("{}={}", name, utf8_percent_encode(value, QUERY_VALUE)))
+                .collect();
+            query.push(format!("_count={}", search.count));
+            query.push(format!("_offset={}", offset));
+            format!("{}/{}?{}", base_url, resource_type, query.join("&"))
+        };
+
+        let mut links = vec![json!({ "relation": "self", "url": page_url(search.offset) })];
+        let next_offset = search.offset + resources.len() as u64;
+        if next_offset < total {
+            links.push(json!({ "relation": "next", "url": page_url(next_offset) }));
+        }
+
+        let entries: Vec<Value> = resources
+            .iter()
+            .map(|delivered| json!({
+                "fullUrl": format!("{}/{}/{}", base_url, delivered.resource_type, delivered.resource_id),
+                "resource": delivered.to_fhir(),
+                "search": { "mode": "match" },
+            }))
+            .collect();
+
+        Ok(json!({
+            "resourceType": "Bundle",
+            "type": "searchset",
+            "total": total,
+            "link": links,
+            "entry": entries,
+        }))
+    }
+
+    /// `GET /fhir/metadata`: the resource types delivered for the company with read and search
+    ///
+    /// `fhirVersion` is the release the company's resources were generated for (the newest one
+    /// when its views deliver to targets of different releases), R4 before any delivery.
+    pub async fn capability_statement(&self, company_id: &str, base_url: &str) -> AppResult<Value> {
+        let fhir_version = self.repository.fhir_versions(company_id).await?.last().copied().unwrap_or_default();
+        let resources: Vec<Value> = self
+            .repository
+            .resource_types(company_id)
+            .await?
+            .iter()
+            .map(|resource_type| {
+                let mut search_params = vec![
+                    json!({ "name": "identifier", "type": "token" }),
+                    json!({ "name": "_lastUpdated", "type": "date" }),
+                ];
+                if resource_type != "Patient" {
+                    search_params.push(json!({ "name": "patient", "type": "reference" }));
+                }
+                json!({
+                    "type": resource_type,
+                    "interaction": [{ "code": "read" }, { "code": "search-type" }],
+                    "versioning": "versioned",
+                    "readHistory": false,
+                    "searchParam": search_params,
+                })
+            })
+            .collect();
+
+        Ok(json!({
+            "resourceType": "CapabilityStatement",
+            "status": "active",
+            "date": Utc::now().to_rfc3339(),
+            "kind": "instance",
+            "software": { "name": "Interhealth" },
+            "implementation": {
+                "description": "Última versão entregue de cada recurso (somente leitura)",
+                "url": base_url,
+            },
+            "fhirVersion": fhir_version.number(),
+            "format": ["json"],
+            "rest": [{ "mode": "server", "resource": resources }],
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn test_parses_search_parameters() {
+        let search = FacadeSearch::parse(
+            "Encounter",
+            &params(&[("identifier", "sys|e1,e2"), ("patient", "Patient/p1"), ("_count", "1000")]),
+        )
+        .unwrap();
+
+        assert_eq!(search.query.identifiers, vec!["sys|e1"]);
+        assert_eq!(search.query.identifier_values, vec!["e2"]);
+        assert_eq!(search.patients, vec!["p1"]);
+        assert_eq!(search.count, MAX_COUNT);
+
+        assert!(FacadeSearch::parse("Encounter", &params(&[("status", "finished")])).is_err());
+        assert!(FacadeSearch::parse("Patient", &params(&[("patient", "p1")])).is_err());
+    }
+
+    #[test]
+    fn test_last_updated_covers_its_precision() {
+        let day = |text: &str| Utc.from_utc_datetime(&NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap().and_hms_opt(0, 0, 0).unwrap());
+
+        assert_eq!(parse_date_bounds("2024-05").unwrap(), vec![("$gte", day("2024-05-01")), ("$lt", day("2024-06-01"))]);
+        assert_eq!(parse_date_bounds("ge2024").unwrap(), vec![("$gte", day("2024-01-01"))]);
+        assert_eq!(parse_date_bounds("le2024-02-28").unwrap(), vec![("$lt", day("2024-02-29"))]);
+        assert_eq!(
+            parse_date_bounds("gt2024-01-01T10:00:00Z").unwrap(),
+            vec![("$gte", day("2024-01-01") + Duration::hours(10) + Duration::seconds(1))]
+        );
+        assert!(parse_date_bounds("ontem").is_err());
+    }
+}
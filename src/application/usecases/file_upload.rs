@@ -0,0 +1,161 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use chrono::Utc;
+use serde_json::{json, Value};
+
+use crate::domain::entities::{DatabaseConfiguration, FileDataset};
+use crate::infrastructure::adapters::{parse_spreadsheet, FileConnector};
+use crate::infrastructure::factories::ClientAlias;
+use crate::infrastructure::repositories::{
+    DatabaseColumnRepository, DatabaseConfigurationRepository, DatabaseTableRepository, FileDatasetRepository,
+};
+use crate::utils::{AppError, AppResult};
+
+pub struct FileUploadUseCase {
+    repository: Arc<FileDatasetRepository>,
+    config_repository: Arc<DatabaseConfigurationRepository>,
+    table_repository: Arc<DatabaseTableRepository>,
+    column_repository: Arc<DatabaseColumnRepository>,
+}
+
+impl FileUploadUseCase {
+    pub fn new(
+        repository: Arc<FileDatasetRepository>,
+        config_repository: Arc<DatabaseConfigurationRepository>,
+        table_repository: Arc<DatabaseTableRepository>,
+        column_repository: Arc<DatabaseColumnRepository>,
+    ) -> Self {
+        Self { repository, config_repository, table_repository, column_repository }
+    }
+
+    /// Import a CSV/XLSX file as the source table of an entity (e.g. PATIENT -> PATIENT_INTERHEALTH)
+    /// Re-uploading for the same entity replaces the rows and keeps the existing table and its mappings
+    pub async fn upload(
+        &self,
+        configuration_id: &str,
+        entity_type: &str,
+        file_name: &str,
+        content: &[u8],
+        company_id: String,
+    ) -> AppResult<Value> {
+        let configuration = self.file_configuration(configuration_id, &company_id).await?;
+
+        let entity_type = entity_type.trim().to_uppercase();
+        if entity_type.is_empty() {
+            return Err(AppError::BadRequest("O campo entityType é obrigatório".to_string()));
+        }
+
+        let parsed = parse_spreadsheet(file_name, content)?;
+        let table_name = format!("{}_INTERHEALTH", entity_type);
+
+        // Reuse the table created by a previous upload so view mappings keep pointing at it
+        let previous = self.repository.find_by_table(configuration_id, &table_name).await?;
+        let existing_table = match previous.as_ref().and_then(|d| d.database_table_id.clone()) {
+            Some(table_id) => self.table_repository.find_by_id(&table_id).await?,
+            None => None,
+        };
+
+        let table = match existing_table {
+            Some(table) => table,
+            None => {
+                self.table_repository.create(
+                    file_name.to_string(),
+                    format!("Arquivo {} importado no conector {}", file_name, configuration.name),
+                    Some(configuration_id.to_string()),
+                    Some("FILE".to_string()),
+                    entity_type.clone(),
+                    Some(entity_type.clone()),
+                    company_id.clone(),
+                ).await?
+            }
+        };
+        let table_id = table.id.map(|id| id.to_hex()).unwrap_or_default();
+
+        // Only columns not seen in earlier uploads are added
+        let known: HashSet<String> = self.column_repository
+            .find_by_table_id(&table_id)
+            .await?
+            .into_iter()
+            .map(|column| column.name.to_lowercase())
+            .collect();
+
+        let mut created_columns = Vec::new();
+        for column in parsed.columns.iter().filter(|c| !known.contains(&c.name)) {
+            let created = self.column_repository.create(
+                column.name.clone(),
+                None,
+                FileConnector::simplify_data_type(&column.data_type).to_string(),
+                column.is_nullable,
+                false,
+                false,
+                format!("Coluna {} detectada no arquivo", column.name),
+                column.max_length,
+                None,
+                table_id.clone(),
+                company_id.clone(),
+            ).await?;
+            created_columns.push(created);
+        }
+
+        let rows = parsed.rows
+            .into_iter()
+            .map(|(row_number, data)| {
+                bson::to_document(&data)
+                    .map(|document| (row_number, document))
+                    .map_err(|e| AppError::BadRequest(format!("Falha ao converter a linha {}: {}", row_number, e)))
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        let now = Utc::now();
+        let dataset = FileDataset {
+            id: None,
+            database_configuration_id: configuration_id.to_string(),
+            database_table_id: Some(table_id),
+            table_name: table_name.clone(),
+            file_name: file_name.to_string(),
+            format: parsed.format,
+            columns: parsed.columns,
+            total_rows: parsed.total_rows,
+            imported_rows: rows.len() as u64,
+            row_errors: parsed.row_errors,
+            company_id,
+            created_at: now,
+            updated_at: now,
+        };
+        let dataset = self.repository.replace(dataset, rows).await?;
+
+        Ok(json!({
+            "datasetId": dataset.id.map(|id| id.to_hex()),
+            "tableName": dataset.table_name,
+            "totalRows": dataset.total_rows,
+            "importedRows": dataset.imported_rows,
+            "rowErrors": dataset.row_errors,
+            "table": table,
+            "createdColumns": created_columns,
+        }))
+    }
+
+    pub async fn get_datasets(&self, configuration_id: &str, company_id: &str) -> AppResult<Vec<FileDataset>> {
+        self.file_configuration(configuration_id, company_id).await?;
+        self.repository.find_by_configuration_id(configuration_id).await
+    }
+
+    /// The FILE configuration `configuration_id`, when it belongs to `company_id`
+    async fn file_configuration(&self, configuration_id: &str, company_id: &str) -> AppResult<DatabaseConfiguration> {
+        let configuration = self.config_repository
+            .find_by_id(configuration_id)
+            .await?
+            .filter(|configuration| configuration.company_id == company_id)
+            .ok_or_else(|| AppError::NotFound("Conector não encontrado".to_string()))?;
+
+        if ClientAlias::from_str(&configuration.db_type) != ClientAlias::File {
+            return Err(AppError::BadRequest(format!(
+                "O conector {} não é do tipo FILE",
+                configuration.name
+            )));
+        }
+
+        Ok(configuration)
+    }
+}
@@ -13,7 +13,13 @@ pub mod fhir;
 pub mod sync;
 pub mod metrics;
 pub mod target_integration;
+pub mod target_compatibility;
 pub mod integration_control;
+pub mod file_upload;
+pub mod schema_discovery;
+pub mod schema_drift;
+pub mod terminology;
+pub mod fhir_facade;
 
 pub use auth::AuthUseCase;
 pub use user::UserUseCase;
@@ -35,4 +41,10 @@ pub use fhir::FhirGenerator;
 pub use sync::SyncUseCase;
 pub use metrics::MetricsUseCase;
 pub use target_integration::TargetIntegrationUseCase;
+pub use target_compatibility::TargetCompatibilityUseCase;
 pub use integration_control::IntegrationControlUseCase;
+pub use file_upload::FileUploadUseCase;
+pub use schema_discovery::SchemaDiscoveryUseCase;
+pub use schema_drift::SchemaDriftUseCase;
+pub use terminology::TerminologyUseCase;
+pub use fhir_facade::FhirFacadeUseCase;
@@ -0,0 +1,456 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::domain::dtos::DiscoverSchemaDto;
+use crate::domain::entities::{
+    ColumnChange, DatabaseColumn, DatabaseTable, DiscoveredColumn, DiscoveredTable, SchemaChangeAction,
+    SchemaDiscoveryPlan, SourceObjectKind, TableChange,
+};
+use crate::infrastructure::factories::ConnectorFactory;
+use crate::infrastructure::repositories::{
+    DatabaseColumnRepository, DatabaseConfigurationRepository, DatabaseTableRepository,
+};
+use crate::utils::{AppError, AppResult};
+
+/// Imports the tables, views and keys of a source connector as DatabaseTable/DatabaseColumn documents
+///
+/// `preview` only computes the plan; `apply` recomputes it against the live catalog and writes it.
+/// Columns that disappeared from the source are left untouched because view mappings may still use them.
+pub struct SchemaDiscoveryUseCase {
+    config_repository: Arc<DatabaseConfigurationRepository>,
+    table_repository: Arc<DatabaseTableRepository>,
+    column_repository: Arc<DatabaseColumnRepository>,
+    connectors: ConnectorFactory,
+}
+
+impl SchemaDiscoveryUseCase {
+    pub fn new(
+        config_repository: Arc<DatabaseConfigurationRepository>,
+        table_repository: Arc<DatabaseTableRepository>,
+        column_repository: Arc<DatabaseColumnRepository>,
+        connectors: ConnectorFactory,
+    ) -> Self {
+        Self { config_repository, table_repository, column_repository, connectors }
+    }
+
+    pub async fn preview(
+        &self,
+        configuration_id: &str,
+        options: DiscoverSchemaDto,
+        company_id: &str,
+    ) -> AppResult<SchemaDiscoveryPlan> {
+        let tables = self.plan(configuration_id, &options, company_id).await?;
+        Ok(SchemaDiscoveryPlan::new(configuration_id.to_string(), tables))
+    }
+
+    pub async fn apply(
+        &self,
+        configuration_id: &str,
+        options: DiscoverSchemaDto,
+        company_id: String,
+    ) -> AppResult<SchemaDiscoveryPlan> {
+        let mut tables = self.plan(configuration_id, &options, &company_id).await?;
+
+        for table in tables.iter_mut() {
+            let table_id = match (table.action, table.database_table_id.clone()) {
+                (SchemaChangeAction::Create, _) | (_, None) => {
+                    let created = self.table_repository.create(
+                        table.name.clone(),
+                        format!("{} {} descoberta no conector", table.kind.as_str(), qualified_name(table)),
+                        Some(configuration_id.to_string()),
+                        Some(table.kind.as_str().to_string()),
+                        table.entity_type.clone(),
+                        None,
+                        company_id.clone(),
+                    ).await?;
+                    created.id.map(|id| id.to_hex()).unwrap_or_default()
+                }
+                (SchemaChangeAction::Update, Some(table_id)) => {
+                    self.table_repository.update(
+                        &table_id,
+                        None,
+                        None,
+                        None,
+                        Some(table.kind.as_str().to_string()),
+                        None,
+                        None,
+                    ).await?;
+                    table_id
+                }
+                (SchemaChangeAction::Unchanged, Some(table_id)) => table_id,
+            };
+            table.database_table_id = Some(table_id.clone());
+
+            for column in table.columns.iter_mut() {
+                match (column.action, column.database_column_id.clone()) {
+                    (SchemaChangeAction::Create, _) | (_, None) => {
+                        let created = self.column_repository.create(
+                            column.name.clone(),
+                            None,
+                            column.data_type.clone(),
+                            column.is_nullable,
+                            column.is_primary_key,
+                            column.is_foreign_key,
+                            column_description(&table.name, column),
+                            column.max_length,
+                            None,
+                            table_id.clone(),
+                            company_id.clone(),
+                        ).await?;
+                        column.database_column_id = created.id.map(|id| id.to_hex());
+                    }
+                    (SchemaChangeAction::Update, Some(column_id)) => {
+                        self.column_repository.update(
+                            &column_id,
+                            None,
+                            None,
+                            Some(column.data_type.clone()),
+                            Some(column.is_nullable),
+                            Some(column.is_primary_key),
+                            Some(column.is_foreign_key),
+                            None,
+                            column.max_length,
+                            None,
+                        ).await?;
+                    }
+                    (SchemaChangeAction::Unchanged, Some(_)) => {}
+                }
+            }
+        }
+
+        let mut plan = SchemaDiscoveryPlan::new(configuration_id.to_string(), tables);
+        plan.applied = true;
+        Ok(plan)
+    }
+
+    /// Read the source catalog and compare it with the tables already registered for the connector
+    /// The connector must belong to `company_id`
+    async fn plan(&self, configuration_id: &str, options: &DiscoverSchemaDto, company_id: &str) -> AppResult<Vec<TableChange>> {
+        let configuration = self.config_repository.find_by_id(configuration_id).await?
+            .filter(|configuration| configuration.company_id == company_id)
+            .ok_or_else(|| AppError::NotFound("Database configuration not found".to_string()))?;
+
+        let mut connector = self.connectors.create_source(&configuration).await?;
+        let discovered = connector.discover_schema(options.owner.as_deref()).await;
+        let simplified: HashMap<String, &'static str> = discovered
+            .iter()
+            .flatten()
+            .flat_map(|table| table.columns.iter())
+            .map(|column| (column.source_type.clone(), connector.simplify_data_type(&column.source_type)))
+            .collect();
+        connector.close().await?;
+
+        let discovered = filter_tables(discovered?, options);
+
+        let mut existing = Vec::new();
+        for table in self.table_repository.find_by_table_reference(configuration_id).await? {
+            let table_id = table.id.map(|id| id.to_hex()).unwrap_or_default();
+            let columns = self.column_repository.find_by_table_id(&table_id).await?;
+            existing.push((table, columns));
+        }
+
+        Ok(plan_table_changes(&discovered, &existing, |source_type| {
+            simplified.get(source_type).copied().unwrap_or("string")
+        }))
+    }
+}
+
+fn filter_tables(tables: Vec<DiscoveredTable>, options: &DiscoverSchemaDto) -> Vec<DiscoveredTable> {
+    let include_views = options.include_views.unwrap_or(true);
+    let wanted: Option<Vec<String>> = options
+        .tables
+        .as_ref()
+        .map(|names| names.iter().map(|name| name.trim().to_uppercase()).collect());
+
+    tables
+        .into_iter()
+        .filter(|table| include_views || table.kind != SourceObjectKind::View)
+        .filter(|table| {
+            wanted
+                .as_ref()
+                .is_none_or(|names| names.contains(&table.name.to_uppercase()))
+        })
+        .collect()
+}
+
+/// Compare discovered tables with the stored ones (matched by name, case-insensitive)
+pub fn plan_table_changes(
+    discovered: &[DiscoveredTable],
+    existing: &[(DatabaseTable, Vec<DatabaseColumn>)],
+    simplify: impl Fn(&str) -> &'static str,
+) -> Vec<TableChange> {
+    discovered
+        .iter()
+        .map(|table| {
+            let stored = existing
+                .iter()
+                .find(|(stored, _)| stored.name.eq_ignore_ascii_case(&table.name));
+
+            let columns: Vec<ColumnChange> = table
+                .columns
+                .iter()
+                .map(|column| {
+                    let stored_column = stored.and_then(|(_, columns)| {
+                        columns.iter().find(|c| c.name.eq_ignore_ascii_case(&column.name))
+                    });
+                    plan_column_change(column, stored_column, simplify(&column.source_type))
+                })
+                .collect();
+
+            let action = match stored {
+                None => SchemaChangeAction::Create,
+                Some((stored, _))
+                    if stored.table_type.as_deref() != Some(table.kind.as_str())
+                        || columns.iter().any(|c| c.action != SchemaChangeAction::Unchanged) =>
+                {
+                    SchemaChangeAction::Update
+                }
+                Some(_) => SchemaChangeAction::Unchanged,
+            };
+
+            TableChange {
+                owner: table.owner.clone(),
+                name: table.name.clone(),
+                kind: table.kind,
+                entity_type: stored
+                    .map(|(stored, _)| stored.entity_type.clone())
+                    .unwrap_or_else(|| entity_type_for(&table.name)),
+                action,
+                database_table_id: stored.and_then(|(stored, _)| stored.id.map(|id| id.to_hex())),
+                columns,
+            }
+        })
+        .collect()
+}
+
+/// `data_type` is the connector's simplified type; it is planned and compared in the seed vocabulary
+fn plan_column_change(column: &DiscoveredColumn, stored: Option<&DatabaseColumn>, data_type: &str) -> ColumnChange {
+    let is_foreign_key = column.foreign_key.is_some();
+    let data_type = DatabaseColumn::canonical_data_type(data_type).unwrap_or(data_type);
+
+    let changed_fields: Vec<String> = match stored {
+        None => Vec::new(),
+        Some(stored) => [
+            ("dataType", !DatabaseColumn::same_data_type(&stored.data_type, data_type)),
+            ("isNullable", stored.is_nullable != column.is_nullable),
+            ("isPrimaryKey", stored.is_primary_key != column.is_primary_key),
+            ("isForeignKey", stored.is_foreign_key != is_foreign_key),
+            ("maxLength", column.max_length.is_some() && stored.max_length != column.max_length),
+        ]
+        .into_iter()
+        .filter(|(_, changed)| *changed)
+        .map(|(field, _)| field.to_string())
+        .collect(),
+    };
+
+    let action = match stored {
+        None => SchemaChangeAction::Create,
+        Some(_) if !changed_fields.is_empty() => SchemaChangeAction::Update,
+        Some(_) => SchemaChangeAction::Unchanged,
+    };
+
+    ColumnChange {
+        name: stored.map(|c| c.name.clone()).unwrap_or_else(|| column.name.to_lowercase()),
+        action,
+        database_column_id: stored.and_then(|c| c.id.map(|id| id.to_hex())),
+        source_type: column.source_type.clone(),
+        data_type: data_type.to_string(),
+        is_nullable: column.is_nullable,
+        is_primary_key: column.is_primary_key,
+        is_foreign_key,
+        foreign_key: column.foreign_key.clone(),
+        max_length: column.max_length,
+        changed_fields,
+    }
+}
+
+/// PATIENT_INTERHEALTH -> PATIENT; any other name is used as is
+fn entity_type_for(table_name: &str) -> String {
+    let upper = table_name.to_uppercase();
+    upper
+        .strip_suffix("_INTERHEALTH")
+        .map(str::to_string)
+        .unwrap_or(upper)
+}
+
+fn qualified_name(table: &TableChange) -> String {
+    match &table.owner {
+        Some(owner) => format!("{}.{}", owner, table.name),
+        None => table.name.clone(),
+    }
+}
+
+fn column_description(table_name: &str, column: &ColumnChange) -> String {
+    match &column.foreign_key {
+        Some(target) => format!(
+            "Coluna {} descoberta em {} (FK para {}.{})",
+            column.name, table_name, target.table, target.column
+        ),
+        None => format!("Coluna {} descoberta em {}", column.name, table_name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bson::oid::ObjectId;
+    use chrono::Utc;
+    use crate::domain::entities::{CatalogColumn, CatalogKey, ForeignKeyTarget};
+
+    fn catalog_column(table: &str, column: &str, source_type: &str, kind: SourceObjectKind) -> CatalogColumn {
+        CatalogColumn {
+            owner: Some("HIS".to_string()),
+            table: table.to_string(),
+            kind,
+            column: column.to_string(),
+            source_type: source_type.to_string(),
+            max_length: if source_type == "VARCHAR2" { Some(100) } else { None },
+            is_nullable: column != "ID",
+        }
+    }
+
+    fn simplify(source_type: &str) -> &'static str {
+        match source_type {
+            "NUMBER" => "integer",
+            "DATE" => "date",
+            _ => "varchar",
+        }
+    }
+
+    #[test]
+    fn test_plan_creates_updates_and_keeps_tables() {
+        let discovered = DiscoveredTable::from_catalog(
+            vec![
+                catalog_column("PATIENT_INTERHEALTH", "ID", "NUMBER", SourceObjectKind::Table),
+                catalog_column("PATIENT_INTERHEALTH", "NAME", "VARCHAR2", SourceObjectKind::Table),
+                catalog_column("ENCOUNTER_INTERHEALTH", "ID", "NUMBER", SourceObjectKind::Table),
+                catalog_column("ENCOUNTER_INTERHEALTH", "PATIENT_ID", "NUMBER", SourceObjectKind::Table),
+                catalog_column("V_LAB", "CODE", "VARCHAR2", SourceObjectKind::View),
+            ],
+            vec![
+                CatalogKey {
+                    owner: Some("HIS".to_string()),
+                    table: "PATIENT_INTERHEALTH".to_string(),
+                    column: "ID".to_string(),
+                    references: None,
+                },
+                CatalogKey {
+                    owner: Some("HIS".to_string()),
+                    table: "ENCOUNTER_INTERHEALTH".to_string(),
+                    column: "PATIENT_ID".to_string(),
+                    references: Some(ForeignKeyTarget {
+                        table: "PATIENT_INTERHEALTH".to_string(),
+                        column: "ID".to_string(),
+                    }),
+                },
+            ],
+        );
+
+        let now = Utc::now();
+        let table_id = ObjectId::new();
+        let stored_table = DatabaseTable {
+            id: Some(table_id),
+            name: "PATIENT_INTERHEALTH".to_string(),
+            description: String::new(),
+            table_reference: Some("cfg".to_string()),
+            table_type: Some("TABLE".to_string()),
+            entity_type: "PATIENT".to_string(),
+            resource: None,
+            company_id: "company".to_string(),
+            created_at: now,
+            updated_at: now,
+        };
+        let stored_column = |name: &str, data_type: &str, is_nullable: bool, is_primary_key: bool| DatabaseColumn {
+            id: Some(ObjectId::new()),
+            name: name.to_string(),
+            reference: None,
+            data_type: data_type.to_string(),
+            is_nullable,
+            is_primary_key,
+            is_foreign_key: false,
+            description: String::new(),
+            max_length: Some(100),
+            min_length: None,
+            database_table_id: table_id.to_hex(),
+            company_id: "company".to_string(),
+            created_at: now,
+            updated_at: now,
+        };
+        let existing = vec![(
+            stored_table,
+            vec![stored_column("id", "integer", false, true), stored_column("name", "string", false, false)],
+        )];
+
+        let changes = plan_table_changes(&discovered, &existing, simplify);
+        let by_name = |name: &str| changes.iter().find(|t| t.name == name).unwrap();
+
+        let encounter = by_name("ENCOUNTER_INTERHEALTH");
+        assert_eq!(encounter.action, SchemaChangeAction::Create);
+        assert_eq!(encounter.entity_type, "ENCOUNTER");
+        assert_eq!(encounter.columns[1].name, "patient_id");
+        assert!(encounter.columns[1].is_foreign_key);
+        assert_eq!(encounter.columns[1].foreign_key.as_ref().unwrap().table, "PATIENT_INTERHEALTH");
+
+        let patient = by_name("PATIENT_INTERHEALTH");
+        assert_eq!(patient.action, SchemaChangeAction::Update);
+        assert_eq!(patient.database_table_id, Some(table_id.to_hex()));
+        assert_eq!(patient.columns[0].action, SchemaChangeAction::Unchanged);
+        assert_eq!(patient.columns[1].action, SchemaChangeAction::Update);
+        assert_eq!(patient.columns[1].changed_fields, vec!["isNullable".to_string()]);
+        assert_eq!(patient.columns[1].data_type, "string");
+
+        let view = by_name("V_LAB");
+        assert_eq!(view.kind, SourceObjectKind::View);
+        assert_eq!(view.entity_type, "V_LAB");
+
+        let plan = SchemaDiscoveryPlan::new("cfg".to_string(), changes);
+        assert_eq!(plan.summary.tables_created, 2);
+        assert_eq!(plan.summary.tables_updated, 1);
+        assert_eq!(plan.summary.columns_created, 3);
+        assert_eq!(plan.summary.columns_updated, 1);
+
+        let without_views = filter_tables(
+            discovered,
+            &DiscoverSchemaDto { include_views: Some(false), ..Default::default() },
+        );
+        assert_eq!(without_views.len(), 2);
+    }
+
+    #[test]
+    fn test_plan_compares_seed_types_with_simplified_source_types() {
+        let now = Utc::now();
+        let stored = |data_type: &str| DatabaseColumn {
+            id: Some(ObjectId::new()),
+            name: "value".to_string(),
+            reference: None,
+            data_type: data_type.to_string(),
+            is_nullable: true,
+            is_primary_key: false,
+            is_foreign_key: false,
+            description: String::new(),
+            max_length: None,
+            min_length: None,
+            database_table_id: "table".to_string(),
+            company_id: "company".to_string(),
+            created_at: now,
+            updated_at: now,
+        };
+        let discovered = DiscoveredColumn {
+            name: "VALUE".to_string(),
+            source_type: "SOURCE".to_string(),
+            is_nullable: true,
+            max_length: None,
+            is_primary_key: false,
+            foreign_key: None,
+        };
+
+        for (seed_type, simplified) in [("string", "varchar"), ("datetime", "timestamp"), ("integer", "integer"), ("reference", "integer")] {
+            let change = plan_column_change(&discovered, Some(&stored(seed_type)), simplified);
+            assert_eq!(change.action, SchemaChangeAction::Unchanged, "{} vs {}", seed_type, simplified);
+        }
+
+        let change = plan_column_change(&discovered, Some(&stored("string")), "timestamp");
+        assert_eq!(change.changed_fields, vec!["dataType".to_string()]);
+        assert_eq!(change.data_type, "datetime");
+    }
+}
@@ -0,0 +1,144 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use tracing::{info, warn};
+
+use crate::domain::entities::{DatabaseView, SchemaDriftReport};
+use crate::infrastructure::factories::ConnectorFactory;
+use crate::infrastructure::repositories::{
+    DatabaseColumnRepository, DatabaseConfigurationRepository, DatabaseViewMappingRepository,
+    DatabaseViewRepository, SchemaDriftRepository,
+};
+use crate::utils::{AppError, AppResult};
+
+/// Views are scanned in pages of this size by the scheduled check
+const VIEW_PAGE_SIZE: i64 = 100;
+
+/// Compares the columns registered for a view (and the columns its mappings read) with the live source table
+pub struct SchemaDriftUseCase {
+    repository: Arc<SchemaDriftRepository>,
+    view_repository: Arc<DatabaseViewRepository>,
+    config_repository: Arc<DatabaseConfigurationRepository>,
+    mapping_repository: Arc<DatabaseViewMappingRepository>,
+    column_repository: Arc<DatabaseColumnRepository>,
+    connectors: ConnectorFactory,
+}
+
+impl SchemaDriftUseCase {
+    pub fn new(
+        repository: Arc<SchemaDriftRepository>,
+        view_repository: Arc<DatabaseViewRepository>,
+        config_repository: Arc<DatabaseConfigurationRepository>,
+        mapping_repository: Arc<DatabaseViewMappingRepository>,
+        column_repository: Arc<DatabaseColumnRepository>,
+        connectors: ConnectorFactory,
+    ) -> Self {
+        Self { repository, view_repository, config_repository, mapping_repository, column_repository, connectors }
+    }
+
+    /// Run the check for one view now and store the result
+    pub async fn check_view(&self, view_id: &str) -> AppResult<SchemaDriftReport> {
+        let view = self.view_repository.find_by_id(view_id).await?
+            .ok_or_else(|| AppError::NotFound("Database view not found".to_string()))?;
+
+        let report = self.compare_view(view_id, &view).await?;
+        self.repository.save(&report).await
+    }
+
+    /// Latest stored report of a view
+    pub async fn get_report(&self, view_id: &str) -> AppResult<SchemaDriftReport> {
+        self.repository.find_by_view_id(view_id).await?
+            .ok_or_else(|| AppError::NotFound("Nenhuma verificação de schema encontrada para a view".to_string()))
+    }
+
+    /// Reports of the company's views currently drifting
+    pub async fn get_drifting(&self, company_id: &str) -> AppResult<Vec<SchemaDriftReport>> {
+        let view_ids: Vec<String> = self.view_repository.find_by_company_id(company_id, None).await?
+            .into_iter()
+            .filter_map(|view| view.id.map(|id| id.to_hex()))
+            .collect();
+
+        self.repository.find_with_drift(&view_ids).await
+    }
+
+    /// Scheduled check: every view is compared; unreachable sources are recorded instead of aborting the run
+    pub async fn check_all(&self) -> AppResult<Vec<SchemaDriftReport>> {
+        let mut reports = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let (views, total) = self.view_repository.find_all(page, VIEW_PAGE_SIZE, None, None).await?;
+            let fetched = views.len() as i64;
+
+            for view in views {
+                let Some(view_id) = view.id.map(|id| id.to_hex()) else { continue };
+
+                let report = match self.compare_view(&view_id, &view).await {
+                    Ok(report) => report,
+                    Err(e) => {
+                        warn!("Schema drift check for view {} failed: {}", view.name, e);
+                        SchemaDriftReport::failed(
+                            &view_id,
+                            &view.database_configuration_id,
+                            &view.source_table_name(),
+                            e.to_string(),
+                        )
+                    }
+                };
+                reports.push(self.repository.save(&report).await?);
+            }
+
+            if fetched < VIEW_PAGE_SIZE || page * VIEW_PAGE_SIZE >= total {
+                break;
+            }
+            page += 1;
+        }
+
+        let drifting = reports.iter().filter(|r| r.has_drift).count();
+        info!("🔎 Schema drift check: {} views, {} with drift", reports.len(), drifting);
+
+        Ok(reports)
+    }
+
+    async fn compare_view(&self, view_id: &str, view: &DatabaseView) -> AppResult<SchemaDriftReport> {
+        let configuration = self.config_repository.find_by_id(&view.database_configuration_id).await?
+            .ok_or_else(|| AppError::NotFound("Database configuration not found".to_string()))?;
+
+        let mappings = self.mapping_repository.find_by_data_view_id(view_id).await?;
+
+        // Columns registered for the tables the mappings read from
+        let table_ids: HashSet<&str> = mappings
+            .iter()
+            .map(|mapping| mapping.database_table_origin_id.as_str())
+            .filter(|id| !id.is_empty())
+            .collect();
+        let mut stored = Vec::new();
+        for table_id in table_ids {
+            stored.extend(self.column_repository.find_by_table_id(table_id).await?);
+        }
+
+        let table_name = view.source_table_name();
+        let mut connector = self.connectors.create_source(&configuration).await?;
+        let columns = connector.introspect(&table_name).await;
+        let live: Vec<(String, String)> = columns
+            .iter()
+            .flatten()
+            .map(|column| {
+                let name = column.get("name").and_then(|v| v.as_str()).unwrap_or("").to_lowercase();
+                let data_type = column.get("dataType").and_then(|v| v.as_str()).unwrap_or("");
+                (name, connector.simplify_data_type(data_type).to_string())
+            })
+            .collect();
+        connector.close().await?;
+        columns?;
+
+        Ok(SchemaDriftReport::compare(
+            view_id,
+            &view.database_configuration_id,
+            &table_name,
+            &stored,
+            &live,
+            &mappings,
+        ))
+    }
+}
@@ -1,9 +1,11 @@
 // Sync use case - orchestrates FHIR transformation for synchronization
 use std::collections::HashMap;
 use std::sync::Arc;
+use chrono_tz::Tz;
 use serde_json::Value;
 
-use crate::domain::entities::{DatabaseViewMapping, DatabaseTransformation};
+use crate::domain::entities::{DatabaseViewMapping, DatabaseTransformation, DeliveryMode};
+use crate::domain::fhir::FhirVersion;
 use crate::infrastructure::repositories::{
     DatabaseViewMappingRepository,
     DatabaseTransformationRepository,
@@ -30,12 +32,16 @@ impl SyncUseCase {
         }
     }
 
-    /// Transform a page of Oracle records to FHIR resources
-    /// Returns a list of FHIR resources ready to be sent
+    /// Transform a page of Oracle records to FHIR entries of the target's `version`
+    /// Dates without an offset are read in the company's `timezone`
+    /// Returns bundle entries (`resource` + the `request` of the view's delivery `mode`) ready to be sent
     pub async fn transform_records_to_fhir(
         &self,
         view_id: &str,
         records: Vec<HashMap<String, String>>,
+        version: FhirVersion,
+        timezone: Tz,
+        mode: DeliveryMode,
     ) -> AppResult<Vec<Value>> {
         
         // Fetch mappings for this view
@@ -50,27 +56,65 @@ impl SyncUseCase {
         // Fetch transformations
         let transformations = self.fetch_transformations(&mappings).await?;
 
-        // Generate FHIR resources for each record
-        let mut fhir_resources = Vec::new();
+        // Mappings are written against R4 paths
+        let mappings: Vec<DatabaseViewMapping> = mappings
+            .into_iter()
+            .map(|mapping| Self::for_version(mapping, version))
+            .collect();
+
+        // Generate FHIR entries for each record
+        let mut fhir_entries = Vec::new();
 
         for record in records {
-            // For each mapping, generate a FHIR resource
-            for mapping in &mappings {
-                let resource = self.generate_fhir_resource(
-                    mapping,
-                    &transformations,
-                    &record,
-                ).await?;
-                
-                fhir_resources.push(resource);
-            }
+            fhir_entries.extend(Self::transform_record_entries(&mappings, &transformations, &record, version, timezone, mode)?);
         }
 
-        Ok(fhir_resources)
+        Ok(fhir_entries)
+    }
+
+    /// FHIR resources of one record, one per mapping (already translated with `for_version`)
+    /// Fails when a resource does not fit the FHIR model, e.g. a date column in another format
+    pub fn transform_record(
+        mappings: &[DatabaseViewMapping],
+        transformations: &HashMap<String, DatabaseTransformation>,
+        record: &HashMap<String, String>,
+        version: FhirVersion,
+        timezone: Tz,
+    ) -> AppResult<Vec<Value>> {
+        // Only the resources are kept, so the request (and its delivery mode) is irrelevant
+        let entries = Self::transform_record_entries(mappings, transformations, record, version, timezone, DeliveryMode::default())?;
+        Ok(entries.into_iter().map(Self::entry_resource).collect())
+    }
+
+    /// Bundle entries of one record, one per mapping, with the `request` of the delivery `mode`
+    pub fn transform_record_entries(
+        mappings: &[DatabaseViewMapping],
+        transformations: &HashMap<String, DatabaseTransformation>,
+        record: &HashMap<String, String>,
+        version: FhirVersion,
+        timezone: Tz,
+        mode: DeliveryMode,
+    ) -> AppResult<Vec<Value>> {
+        mappings
+            .iter()
+            .map(|mapping| Self::generate_fhir_entry(mapping, transformations, record, version, timezone, mode))
+            .collect()
+    }
+
+    /// Resource carried by a bundle entry
+    pub fn entry_resource(mut entry: Value) -> Value {
+        entry.get_mut("resource").map(Value::take).unwrap_or(entry)
+    }
+
+    /// Mapping with its field paths translated to `version`
+    pub fn for_version(mut mapping: DatabaseViewMapping, version: FhirVersion) -> DatabaseViewMapping {
+        let resource_type = FhirGenerator::map_entity_type_to_fhir_resource(&mapping.entity_type);
+        mapping.field_mappings = version.translate_field_mappings(&resource_type, &mapping.field_mappings);
+        mapping
     }
 
     /// Convert DatabaseViewMapping (domain entity) to DatabaseViewMappingEntity (DTO)
-    fn to_entity(&self, mapping: &DatabaseViewMapping) -> DatabaseViewMappingEntity {
+    fn to_entity(mapping: &DatabaseViewMapping) -> DatabaseViewMappingEntity {
         DatabaseViewMappingEntity {
             id: mapping.id.as_ref().map(|id| id.to_hex()).unwrap_or_default(),
             name: mapping.name.clone(),
@@ -87,16 +131,18 @@ impl SyncUseCase {
         }
     }
 
-    /// Generate a single FHIR resource from Oracle data
-    async fn generate_fhir_resource(
-        &self,
+    /// Generate a single FHIR entry from Oracle data
+    fn generate_fhir_entry(
         mapping: &DatabaseViewMapping,
         transformations: &HashMap<String, DatabaseTransformation>,
         record: &HashMap<String, String>,
+        version: FhirVersion,
+        timezone: Tz,
+        mode: DeliveryMode,
     ) -> AppResult<Value> {
         
         // Convert to entity type (DTO)
-        let mapping_entity = self.to_entity(mapping);
+        let mapping_entity = Self::to_entity(mapping);
         
         // Filter transformations for this mapping
         let mapping_transformations: HashMap<String, DatabaseTransformation> = 
@@ -110,25 +156,28 @@ impl SyncUseCase {
                 .collect();
         
         // Generate FHIR template with placeholders
-        let mut resource = FhirGenerator::generate_resource_with_transformations(
+        let mut entry = FhirGenerator::generate_resource_with_transformations(
             &mapping_entity,
             &mapping_transformations,
+            version,
+            mode,
         );
 
-        // Replace placeholders with actual data and apply transformations
+        // Replace placeholders with actual data and apply transformations (request URLs included)
         Replacer::replace_in_entry_with_transformations(
-            &mut resource,
+            &mut entry,
             record,
             &mapping.field_mappings,
             &mapping_transformations,
-        );
-
-        // Extract the resource if it's wrapped in an entry
-        if let Some(extracted_resource) = resource.get("resource") {
-            Ok(extracted_resource.clone())
-        } else {
-            Ok(resource)
-        }
+            timezone,
+        )?;
+
+        let Some(resource) = entry.get_mut("resource") else {
+            return Err(AppError::Validation(format!("Mapeamento {} não gerou recurso", mapping.name)));
+        };
+        version.adapt_values(resource);
+        *resource = version.conform(resource)?;
+        Ok(entry)
     }
 
     /// Fetch all transformations needed for the mappings
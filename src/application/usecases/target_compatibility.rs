@@ -0,0 +1,124 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use crate::application::usecases::fhir::FhirGenerator;
+use crate::domain::dtos::TargetDiscoveryResult;
+use crate::domain::entities::{CompatibilityReport, DatabaseView, TargetCapabilities, TargetIntegration};
+use crate::infrastructure::adapters::ApiConnector;
+use crate::infrastructure::repositories::{
+    DatabaseViewMappingRepository, DatabaseViewRepository, TargetIntegrationRepository,
+};
+use crate::utils::{AppError, AppResult};
+
+/// Discovers what a target FHIR server supports and checks the views delivering to it against that
+pub struct TargetCompatibilityUseCase {
+    target_repository: Arc<TargetIntegrationRepository>,
+    view_repository: Arc<DatabaseViewRepository>,
+    mapping_repository: Arc<DatabaseViewMappingRepository>,
+}
+
+impl TargetCompatibilityUseCase {
+    pub fn new(
+        target_repository: Arc<TargetIntegrationRepository>,
+        view_repository: Arc<DatabaseViewRepository>,
+        mapping_repository: Arc<DatabaseViewMappingRepository>,
+    ) -> Self {
+        Self { target_repository, view_repository, mapping_repository }
+    }
+
+    /// Fetch the CapabilityStatement of a target's server
+    pub async fn fetch_capabilities(target: &TargetIntegration) -> AppResult<TargetCapabilities> {
+        let connector = ApiConnector::for_target(target).await?;
+        let statement = connector.capability_statement().await?;
+        TargetCapabilities::from_capability_statement(&statement)
+    }
+
+    /// Fetch and store the CapabilityStatement, then check every view delivering to the target
+    pub async fn discover(&self, target_id: &str) -> AppResult<TargetDiscoveryResult> {
+        let mut target = self.find_target(target_id).await?;
+
+        let capabilities = Self::fetch_capabilities(&target).await?;
+        self.target_repository.set_capabilities(target_id, &capabilities).await?;
+        target.capabilities = Some(capabilities.clone());
+
+        let mut views = Vec::new();
+        for view in self.view_repository.find_by_target_integration_id(target_id).await? {
+            views.push(self.check(&view, &target).await?);
+        }
+
+        Ok(TargetDiscoveryResult { capabilities, views })
+    }
+
+    /// Check a view against the stored capabilities of its target integration
+    pub async fn check_view(&self, view_id: &str) -> AppResult<CompatibilityReport> {
+        let view = self.view_repository.find_by_id(view_id).await?
+            .ok_or_else(|| AppError::NotFound("Database view not found".to_string()))?;
+
+        let target_id = view.target_integration_id.as_deref()
+            .ok_or_else(|| AppError::NotFound("Target integration not found for this database view".to_string()))?;
+        let target = self.find_target(target_id).await?;
+
+        self.check(&view, &target).await
+    }
+
+    /// Refuse to integrate a view whose target is known not to support what it produces;
+    /// views without a target integration are not checked
+    pub async fn ensure_view_compatible(&self, view_id: &str) -> AppResult<()> {
+        let view = self.view_repository.find_by_id(view_id).await?
+            .ok_or_else(|| AppError::NotFound("Database view not found".to_string()))?;
+
+        let Some(target_id) = view.target_integration_id.as_deref() else {
+            return Ok(());
+        };
+        let target = self.find_target(target_id).await?;
+
+        let report = self.check(&view, &target).await?;
+        if report.compatible {
+            return Ok(());
+        }
+
+        Err(AppError::Validation(format!(
+            "Destino {} incompatível com a integração {}: {}",
+            target.name,
+            view.name,
+            report.errors().join("; ")
+        )))
+    }
+
+    async fn check(&self, view: &DatabaseView, target: &TargetIntegration) -> AppResult<CompatibilityReport> {
+        let view_id = view.id.map(|id| id.to_hex()).unwrap_or_default();
+        let target_id = target.id.map(|id| id.to_hex()).unwrap_or_default();
+
+        Ok(CompatibilityReport::check(
+            &view_id,
+            &target_id,
+            target.version.as_deref(),
+            view.delivery_mode(),
+            target.capabilities.as_ref(),
+            self.resource_types(&view_id, view).await?,
+        ))
+    }
+
+    /// Resource types the view's mappings generate, or the view's own entity type without mappings
+    async fn resource_types(&self, view_id: &str, view: &DatabaseView) -> AppResult<Vec<String>> {
+        let mappings = self.mapping_repository.find_by_data_view_id(view_id).await?;
+
+        let entity_types: BTreeSet<String> = if mappings.is_empty() {
+            BTreeSet::from([FhirGenerator::map_entity_type_to_fhir_resource(&view.entity_type)])
+        } else {
+            mappings
+                .iter()
+                .map(|mapping| FhirGenerator::map_entity_type_to_fhir_resource(&mapping.entity_type))
+                .collect()
+        };
+
+        Ok(entity_types.into_iter().filter(|resource_type| !resource_type.is_empty()).collect())
+    }
+
+    async fn find_target(&self, id: &str) -> AppResult<TargetIntegration> {
+        self.target_repository
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Target integration not found".to_string()))
+    }
+}
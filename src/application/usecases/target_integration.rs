@@ -1,8 +1,21 @@
 use std::sync::Arc;
+use std::time::Instant;
 
-use crate::domain::dtos::{CreateTargetIntegrationDto, TargetIntegrationEntity, UpdateTargetIntegrationDto};
+use chrono::{DateTime, Utc};
+use tracing::warn;
+
+use super::target_compatibility::TargetCompatibilityUseCase;
+use crate::domain::dtos::{
+    ClientCertificateSummary, ClientCertificateUpload, CreateTargetIntegrationDto, TargetIntegrationEntity,
+    UpdateTargetIntegrationDto,
+};
+use crate::domain::entities::{
+    ClientCertificate, ConnectionDiagnostics, DiagnosticCheck, SourceDiagnostics, TargetIntegration,
+    CERTIFICATE_EXPIRY_WARNING_DAYS,
+};
+use crate::infrastructure::adapters::{tls, ApiConnector, TokenProvider};
 use crate::infrastructure::repositories::{DatabaseViewRepository, TargetIntegrationRepository};
-use crate::utils::{AppError, AppResult};
+use crate::utils::{redact, AppError, AppResult};
 
 pub struct TargetIntegrationUseCase {
     repository: Arc<TargetIntegrationRepository>,
@@ -22,7 +35,9 @@ impl TargetIntegrationUseCase {
         data: CreateTargetIntegrationDto,
         company_id: String,
     ) -> AppResult<TargetIntegrationEntity> {
-        let created = self
+        Self::validate_auth(data.auth_type.as_deref(), data.credentials.as_deref())?;
+
+        let mut created = self
             .repository
             .create(
                 data.name,
@@ -34,6 +49,16 @@ impl TargetIntegrationUseCase {
             )
             .await?;
 
+        // Best effort: the server may not be reachable yet (e.g. waiting for its client certificate)
+        match TargetCompatibilityUseCase::fetch_capabilities(&created).await {
+            Ok(capabilities) => {
+                let created_id = created.id.map(|id| id.to_hex()).unwrap_or_default();
+                self.repository.set_capabilities(&created_id, &capabilities).await?;
+                created.capabilities = Some(capabilities);
+            }
+            Err(e) => warn!("CapabilityStatement of target {} not discovered: {}", created.host, e),
+        }
+
         let created_id = created
             .id
             .as_ref()
@@ -46,7 +71,9 @@ impl TargetIntegrationUseCase {
             version: created.version,
             host: created.host,
             auth_type: created.auth_type,
-            credentials: created.credentials,
+            credentials: redact::secret(&created.credentials),
+            client_certificate: certificate_summary(&created.client_certificate),
+            capabilities: created.capabilities,
             company_id: Some(created.company_id),
             created_at: created.created_at.to_rfc3339(),
             updated_at: created.updated_at.to_rfc3339(),
@@ -66,7 +93,9 @@ impl TargetIntegrationUseCase {
             version: target.version,
             host: target.host,
             auth_type: target.auth_type,
-            credentials: target.credentials,
+            credentials: redact::secret(&target.credentials),
+            client_certificate: certificate_summary(&target.client_certificate),
+            capabilities: target.capabilities,
             company_id: Some(target.company_id),
             created_at: target.created_at.to_rfc3339(),
             updated_at: target.updated_at.to_rfc3339(),
@@ -84,7 +113,9 @@ impl TargetIntegrationUseCase {
                 version: target.version,
                 host: target.host,
                 auth_type: target.auth_type,
-                credentials: target.credentials,
+                credentials: redact::secret(&target.credentials),
+                client_certificate: certificate_summary(&target.client_certificate),
+                capabilities: target.capabilities,
                 company_id: Some(target.company_id),
                 created_at: target.created_at.to_rfc3339(),
                 updated_at: target.updated_at.to_rfc3339(),
@@ -118,7 +149,9 @@ impl TargetIntegrationUseCase {
             version: target.version,
             host: target.host,
             auth_type: target.auth_type,
-            credentials: target.credentials,
+            credentials: redact::secret(&target.credentials),
+            client_certificate: certificate_summary(&target.client_certificate),
+            capabilities: target.capabilities,
             company_id: Some(target.company_id),
             created_at: target.created_at.to_rfc3339(),
             updated_at: target.updated_at.to_rfc3339(),
@@ -130,6 +163,21 @@ impl TargetIntegrationUseCase {
         id: &str,
         data: UpdateTargetIntegrationDto,
     ) -> AppResult<TargetIntegrationEntity> {
+        if data.auth_type.is_some() || data.credentials.is_some() {
+            let current = self
+                .repository
+                .find_by_id(id)
+                .await?
+                .ok_or_else(|| AppError::NotFound("Target integration not found".to_string()))?;
+
+            let credentials = data
+                .credentials
+                .clone()
+                .filter(|v| !redact::is_redacted(v))
+                .or(current.credentials);
+            Self::validate_auth(data.auth_type.as_deref().or(current.auth_type.as_deref()), credentials.as_deref())?;
+        }
+
         let updated = self
             .repository
             .update(id, data.name, data.version, data.host, data.auth_type, data.credentials)
@@ -141,14 +189,145 @@ impl TargetIntegrationUseCase {
             version: updated.version,
             host: updated.host,
             auth_type: updated.auth_type,
-            credentials: updated.credentials,
+            credentials: redact::secret(&updated.credentials),
+            client_certificate: certificate_summary(&updated.client_certificate),
+            capabilities: updated.capabilities,
             company_id: Some(updated.company_id),
             created_at: updated.created_at.to_rfc3339(),
             updated_at: updated.updated_at.to_rfc3339(),
         })
     }
 
+    /// OAuth2 auth types keep their settings as JSON in `credentials`; reject them before saving
+    fn validate_auth(auth_type: Option<&str>, credentials: Option<&str>) -> AppResult<()> {
+        match auth_type {
+            Some(auth_type) if TokenProvider::handles(auth_type) => {
+                let credentials = credentials
+                    .ok_or_else(|| AppError::BadRequest(format!("Credenciais são obrigatórias para {}", auth_type)))?;
+                TokenProvider::new(auth_type, credentials).map(|_| ())
+            }
+            _ => Ok(()),
+        }
+    }
+
     pub async fn delete_target_integration(&self, id: &str) -> AppResult<bool> {
         self.repository.delete(id).await
     }
+
+    /// Validate and attach a client certificate, replacing the current one
+    pub async fn upload_client_certificate(
+        &self,
+        id: &str,
+        upload: ClientCertificateUpload,
+    ) -> AppResult<TargetIntegrationEntity> {
+        self.find_target(id).await?;
+        let certificate = tls::read_client_certificate(upload)?;
+
+        let updated = self.repository.set_client_certificate(id, Some(certificate)).await?;
+        Ok(Self::to_entity(updated))
+    }
+
+    pub async fn remove_client_certificate(&self, id: &str) -> AppResult<TargetIntegrationEntity> {
+        let updated = self.repository.set_client_certificate(id, None).await?;
+        Ok(Self::to_entity(updated))
+    }
+
+    /// Targets whose client certificate expires within `days` (CERTIFICATE_EXPIRY_WARNING_DAYS by default)
+    /// or has already expired, soonest first
+    pub async fn get_expiring_certificates(&self, days: Option<i64>) -> AppResult<Vec<TargetIntegrationEntity>> {
+        let days = days.unwrap_or(CERTIFICATE_EXPIRY_WARNING_DAYS);
+        let now = Utc::now();
+
+        let mut targets: Vec<TargetIntegration> = self
+            .repository
+            .find_all()
+            .await?
+            .into_iter()
+            .filter(|target| {
+                target
+                    .client_certificate
+                    .as_ref()
+                    .is_some_and(|certificate| certificate.days_until_expiry(now) < days)
+            })
+            .collect();
+        targets.sort_by_key(|target| target.client_certificate.as_ref().map(|c| c.not_after));
+
+        Ok(targets.into_iter().map(Self::to_entity).collect())
+    }
+
+    /// Check the client certificate, then reach the target with the same client used for delivery
+    pub async fn test_connection(&self, id: &str) -> AppResult<ConnectionDiagnostics> {
+        let target = self.find_target(id).await?;
+        let mut checks = Vec::new();
+
+        if let Some(certificate) = &target.client_certificate {
+            checks.push(certificate_check(certificate, Utc::now()));
+        }
+
+        let started = Instant::now();
+        let connectivity = match ApiConnector::for_target(&target).await {
+            Ok(connector) => match connector.test_connection().await {
+                Ok(true) => DiagnosticCheck::passed("connectivity", "Conexão com o destino estabelecida com sucesso")
+                    .with_latency(started.elapsed().as_millis() as u64),
+                Ok(false) => DiagnosticCheck::failed("connectivity", "O destino respondeu com erro"),
+                Err(e) => DiagnosticCheck::failed("connectivity", format!("Falha ao conectar ao destino: {}", e)),
+            },
+            Err(e) => DiagnosticCheck::failed("connectivity", format!("Falha ao criar conector API: {}", e)),
+        };
+
+        let diagnostics = SourceDiagnostics {
+            latency_ms: connectivity.latency_ms,
+            checks: checks.into_iter().chain(std::iter::once(connectivity)).collect(),
+            ..Default::default()
+        };
+        Ok(ConnectionDiagnostics::new("API".to_string(), target.host, None, None, diagnostics))
+    }
+
+    async fn find_target(&self, id: &str) -> AppResult<TargetIntegration> {
+        self.repository
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Target integration not found".to_string()))
+    }
+
+    fn to_entity(target: TargetIntegration) -> TargetIntegrationEntity {
+        TargetIntegrationEntity {
+            id: target.id.map(|id| id.to_hex()).unwrap_or_default(),
+            name: target.name,
+            version: target.version,
+            host: target.host,
+            auth_type: target.auth_type,
+            credentials: redact::secret(&target.credentials),
+            client_certificate: certificate_summary(&target.client_certificate),
+            capabilities: target.capabilities,
+            company_id: Some(target.company_id),
+            created_at: target.created_at.to_rfc3339(),
+            updated_at: target.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+fn certificate_summary(certificate: &Option<ClientCertificate>) -> Option<ClientCertificateSummary> {
+    certificate
+        .as_ref()
+        .map(|certificate| ClientCertificateSummary::from_certificate(certificate, Utc::now()))
+}
+
+/// Failed once expired, warning within CERTIFICATE_EXPIRY_WARNING_DAYS of `notAfter`
+fn certificate_check(certificate: &ClientCertificate, now: DateTime<Utc>) -> DiagnosticCheck {
+    let not_after = certificate.not_after.format("%d/%m/%Y");
+    let days = certificate.days_until_expiry(now);
+
+    if certificate.is_expired(now) {
+        DiagnosticCheck::failed("clientCertificate", format!("Certificado de cliente expirou em {}", not_after))
+    } else if days < CERTIFICATE_EXPIRY_WARNING_DAYS {
+        DiagnosticCheck::warning(
+            "clientCertificate",
+            format!("Certificado de cliente expira em {} dia(s), em {}", days, not_after),
+        )
+    } else {
+        DiagnosticCheck::passed("clientCertificate", format!("Certificado de cliente válido até {}", not_after))
+    }
+    .for_object(&certificate.subject)
 }
+
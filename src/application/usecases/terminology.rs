@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use serde_json::Value;
+use tokio::sync::RwLock;
+
+use crate::domain::dtos::{ModelTerminologyCheck, ModelValueCheck, ValidateCodeDto};
+use crate::domain::entities::TerminologyResource;
+use crate::domain::fhir::terminology::TerminologySummary;
+use crate::domain::fhir::{CodeCheck, Terminology};
+use crate::infrastructure::repositories::{DatabaseModelRepository, DatabaseModelValueRepository, TerminologyRepository};
+use crate::utils::{AppError, AppResult};
+
+/// Manages uploaded CodeSystems and ValueSets and checks codes against the loaded terminology
+pub struct TerminologyUseCase {
+    repository: Arc<TerminologyRepository>,
+    terminology: Arc<RwLock<Terminology>>,
+    model_repositories: Option<(Arc<DatabaseModelRepository>, Arc<DatabaseModelValueRepository>)>,
+}
+
+impl TerminologyUseCase {
+    pub fn new(repository: Arc<TerminologyRepository>, terminology: Arc<RwLock<Terminology>>) -> Self {
+        Self { repository, terminology, model_repositories: None }
+    }
+
+    /// Check the values of terminology models (DatabaseModel)
+    pub fn with_model_repositories(
+        mut self,
+        model_repository: Arc<DatabaseModelRepository>,
+        model_value_repository: Arc<DatabaseModelValueRepository>,
+    ) -> Self {
+        self.model_repositories = Some((model_repository, model_value_repository));
+        self
+    }
+
+    /// Load the uploaded resources over the ones from FHIR packages
+    pub async fn load_uploaded(repository: &TerminologyRepository, terminology: &mut Terminology) -> AppResult<usize> {
+        let resources: Vec<Value> = repository.find_all().await?.into_iter().map(|stored| stored.resource).collect();
+        terminology.extend(&resources);
+        Ok(resources.len())
+    }
+
+    pub async fn list(&self) -> Vec<TerminologySummary> {
+        self.terminology.read().await.summaries()
+    }
+
+    /// Load a CodeSystem or ValueSet and keep it for the next startups
+    pub async fn upload(&self, resource: Value) -> AppResult<TerminologySummary> {
+        let url = self.terminology.write().await.add(&resource)?;
+        let resource_type = resource.get("resourceType").and_then(Value::as_str).unwrap_or_default().to_string();
+
+        self.repository
+            .save(&TerminologyResource {
+                id: None,
+                url: url.clone(),
+                resource_type: resource_type.clone(),
+                resource,
+                updated_at: Utc::now(),
+            })
+            .await?;
+
+        self.list()
+            .await
+            .into_iter()
+            .find(|summary| summary.url == url && summary.resource_type == resource_type)
+            .ok_or_else(|| AppError::NotFound("Recurso de terminologia não encontrado após o envio".to_string()))
+    }
+
+    /// Remove an uploaded resource; resources from FHIR packages come back on the next startup
+    pub async fn remove(&self, url: &str) -> AppResult<()> {
+        let stored = self.repository.delete_by_url(url).await?;
+        let loaded = self.terminology.write().await.remove(url);
+
+        if !stored && !loaded {
+            return Err(AppError::NotFound("Recurso de terminologia não encontrado".to_string()));
+        }
+        Ok(())
+    }
+
+    pub async fn validate_code(&self, data: ValidateCodeDto) -> AppResult<CodeCheck> {
+        if data.value_set.trim().is_empty() || data.code.trim().is_empty() {
+            return Err(AppError::BadRequest("'valueSet' e 'code' são obrigatórios".to_string()));
+        }
+
+        let terminology = self.terminology.read().await;
+        if !terminology.has_value_set(&data.value_set) {
+            return Err(AppError::NotFound(format!("ValueSet {} não carregado ou não expansível localmente", data.value_set)));
+        }
+        Ok(terminology.validate_code(&data.value_set, data.system.as_deref(), &data.code))
+    }
+
+    /// Check the codes of a terminology model against a ValueSet; without `value_set`, the model's
+    /// `reference` is used when it is a canonical url
+    pub async fn check_model(&self, model_id: &str, value_set: Option<String>) -> AppResult<ModelTerminologyCheck> {
+        let (model_repository, model_value_repository) = self
+            .model_repositories
+            .as_ref()
+            .ok_or_else(|| AppError::Database("Required repositories not available".to_string()))?;
+
+        let model = model_repository
+            .find_by_id(model_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Modelo de terminologia não encontrado".to_string()))?;
+
+        let value_set = value_set
+            .or_else(|| model.reference.clone().filter(|reference| reference.starts_with("http")))
+            .ok_or_else(|| AppError::BadRequest("Informe o 'valueSet' para verificar o modelo".to_string()))?;
+
+        let mut codes: Vec<(String, String)> = model.values.into_iter().map(|value| (value.code, value.description)).collect();
+        for value in model_value_repository.find_by_owner_ids(&[model_id.to_string()]).await? {
+            if !codes.iter().any(|(code, _)| *code == value.code) {
+                codes.push((value.code, value.description));
+            }
+        }
+
+        let terminology = self.terminology.read().await;
+        if !terminology.has_value_set(&value_set) {
+            return Err(AppError::NotFound(format!("ValueSet {} não carregado ou não expansível localmente", value_set)));
+        }
+
+        Ok(check_codes(&terminology, model_id, &value_set, codes))
+    }
+}
+
+fn check_codes(terminology: &Terminology, model_id: &str, value_set: &str, codes: Vec<(String, String)>) -> ModelTerminologyCheck {
+    let values: Vec<ModelValueCheck> = codes
+        .into_iter()
+        .map(|(code, description)| ModelValueCheck {
+            result: terminology.validate_code(value_set, None, &code),
+            code,
+            description,
+        })
+        .collect();
+
+    ModelTerminologyCheck {
+        model_id: model_id.to_string(),
+        value_set: value_set.to_string(),
+        invalid_count: values.iter().filter(|value| value.result == CodeCheck::Invalid).count(),
+        values,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::fhir::terminology::tests::encounter_status;
+
+    #[test]
+    fn test_checks_model_codes_against_value_set() {
+        let terminology = Terminology::from_resources(&encounter_status());
+        let check = check_codes(
+            &terminology,
+            "model",
+            "http://hl7.org/fhir/ValueSet/encounter-status",
+            vec![("finished".to_string(), "Alta".to_string()), ("alta".to_string(), "Alta".to_string())],
+        );
+
+        assert_eq!(check.invalid_count, 1);
+        assert_eq!(check.values[0].result, CodeCheck::Valid { display: Some("Finished".to_string()) });
+        assert_eq!(check.values[1].result, CodeCheck::Invalid);
+    }
+}
@@ -1,14 +1,15 @@
 use axum::{
-    extract::{Path, Query, State},
+    extract::{Multipart, Path, Query, State},
     http::StatusCode,
     Json,
 };
 use serde::Deserialize;
 
-use crate::application::{AppState, DatabaseConfigurationUseCase};
+use crate::application::{AppState, DatabaseConfigurationUseCase, FileUploadUseCase, SchemaDiscoveryUseCase};
 use crate::core::AuthUser;
-use crate::domain::dtos::{CreateDatabaseConfigurationDto, UpdateDatabaseConfigurationDto, DatabaseConfigurationEntity};
-use crate::utils::{ApiResponse, AppResult, PaginationResponse, PaginationQuery};
+use crate::domain::dtos::{CreateDatabaseConfigurationDto, UpdateDatabaseConfigurationDto, DatabaseConfigurationEntity, DiscoverSchemaDto};
+use crate::domain::entities::{ConnectionDiagnostics, FileDataset, SchemaDiscoveryPlan};
+use crate::utils::{ApiResponse, AppError, AppResult, PaginationResponse, PaginationQuery};
 
 pub async fn create_database_configuration(
     State(state): State<AppState>,
@@ -52,12 +53,25 @@ pub async fn get_database_configuration_by_id(
     Ok(Json(ApiResponse::success("Conector encontrado", config)))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct TestConnectionQuery {
+    /// Restrict the object checks to the views of this configuration
+    #[serde(rename = "configurationId")]
+    pub configuration_id: Option<String>,
+}
+
 pub async fn test_connection(
     State(state): State<AppState>,
+    auth: AuthUser,
+    Query(query): Query<TestConnectionQuery>,
     Json(payload): Json<CreateDatabaseConfigurationDto>,
-) -> AppResult<Json<serde_json::Value>> {
-    let use_case = DatabaseConfigurationUseCase::new(state.database_configuration_repository.clone());
-    let result = use_case.test_connection(payload).await?;
+) -> AppResult<Json<ConnectionDiagnostics>> {
+    let use_case = DatabaseConfigurationUseCase::new(state.database_configuration_repository.clone())
+        .with_view_repository(state.database_view_repository.clone())
+        .with_connectors(state.connectors.clone());
+    let result = use_case
+        .test_connection(payload, Some(&auth.company_id), query.configuration_id.as_deref())
+        .await?;
 
     Ok(Json(result))
 }
@@ -85,3 +99,114 @@ pub async fn delete_database_configuration(
 
     Ok(Json(ApiResponse::success("Conector excluído com sucesso", "Deleted".to_string())))
 }
+
+/// Multipart upload of a CSV/XLSX file to a FILE connector (fields: `file`, `entityType`)
+pub async fn upload_file(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+    mut multipart: Multipart,
+) -> AppResult<(StatusCode, Json<ApiResponse<serde_json::Value>>)> {
+    let mut file: Option<(String, Vec<u8>)> = None;
+    let mut entity_type: Option<String> = None;
+
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| AppError::BadRequest(format!("Upload inválido: {}", e)))?
+    {
+        match field.name() {
+            Some("file") => {
+                let file_name = field.file_name().unwrap_or_default().to_string();
+                let content = field
+                    .bytes()
+                    .await
+                    .map_err(|e| AppError::BadRequest(format!("Falha ao ler o arquivo: {}", e)))?;
+                file = Some((file_name, content.to_vec()));
+            }
+            Some("entityType") => {
+                entity_type = Some(
+                    field
+                        .text()
+                        .await
+                        .map_err(|e| AppError::BadRequest(format!("Campo entityType inválido: {}", e)))?,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    let (file_name, content) = file.ok_or_else(|| AppError::BadRequest("O campo file é obrigatório".to_string()))?;
+    let entity_type = entity_type.unwrap_or_default();
+
+    let use_case = FileUploadUseCase::new(
+        state.file_dataset_repository.clone(),
+        state.database_configuration_repository.clone(),
+        state.database_table_repository.clone(),
+        state.database_column_repository.clone(),
+    );
+    let result = use_case
+        .upload(&id, &entity_type, &file_name, &content, auth.company_id)
+        .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse::success("Arquivo importado com sucesso", result)),
+    ))
+}
+
+pub async fn get_file_datasets(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<ApiResponse<Vec<FileDataset>>>> {
+    let use_case = FileUploadUseCase::new(
+        state.file_dataset_repository.clone(),
+        state.database_configuration_repository.clone(),
+        state.database_table_repository.clone(),
+        state.database_column_repository.clone(),
+    );
+    let datasets = use_case.get_datasets(&id, &auth.company_id).await?;
+
+    Ok(Json(ApiResponse::success("Arquivos encontrados", datasets)))
+}
+
+/// Preview the tables/columns a schema discovery would create or update (nothing is written)
+pub async fn preview_schema_discovery(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+    payload: Option<Json<DiscoverSchemaDto>>,
+) -> AppResult<Json<ApiResponse<SchemaDiscoveryPlan>>> {
+    let use_case = SchemaDiscoveryUseCase::new(
+        state.database_configuration_repository.clone(),
+        state.database_table_repository.clone(),
+        state.database_column_repository.clone(),
+        state.connectors.clone(),
+    );
+    let plan = use_case
+        .preview(&id, payload.map(|Json(p)| p).unwrap_or_default(), &auth.company_id)
+        .await?;
+
+    Ok(Json(ApiResponse::success("Prévia da descoberta de schema", plan)))
+}
+
+/// Apply a schema discovery, creating or updating the DatabaseTable/DatabaseColumn documents
+pub async fn apply_schema_discovery(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+    payload: Option<Json<DiscoverSchemaDto>>,
+) -> AppResult<Json<ApiResponse<SchemaDiscoveryPlan>>> {
+    let use_case = SchemaDiscoveryUseCase::new(
+        state.database_configuration_repository.clone(),
+        state.database_table_repository.clone(),
+        state.database_column_repository.clone(),
+        state.connectors.clone(),
+    );
+    let plan = use_case
+        .apply(&id, payload.map(|Json(p)| p).unwrap_or_default(), auth.company_id)
+        .await?;
+
+    Ok(Json(ApiResponse::success("Schema importado com sucesso", plan)))
+}
@@ -139,7 +139,8 @@ pub async fn get_database_tables_by_connection_id(
         state.database_table_repository.clone(),
         state.database_column_repository.clone(),
     )
-    .with_config_repository(state.database_configuration_repository.clone());
+    .with_config_repository(state.database_configuration_repository.clone())
+    .with_connectors(state.connectors.clone());
 
     // Parse comma-separated values into vectors
     let table_types = query.table_types.map(|s| s.split(',').map(|v| v.trim().to_string()).collect());
@@ -5,9 +5,11 @@ use axum::{
 };
 use serde::Deserialize;
 
-use crate::application::{AppState, DatabaseViewUseCase};
+use crate::application::{AppState, DatabaseViewUseCase, SchemaDriftUseCase};
+use crate::controllers::target_integration::target_compatibility_use_case;
 use crate::core::AuthUser;
 use crate::domain::dtos::{DatabaseViewEntity, CreateDatabaseViewDto, UpdateDatabaseViewDto};
+use crate::domain::entities::{CompatibilityReport, SchemaDriftReport};
 use crate::utils::{ApiResponse, AppResult, PaginationResponse, PaginationQuery};
 
 #[derive(Debug, Deserialize)]
@@ -109,6 +111,8 @@ pub async fn start_integration(
     State(state): State<AppState>,
     Path(id): Path<String>,
 ) -> AppResult<(StatusCode, Json<ApiResponse<DatabaseViewEntity>>)> {
+    target_compatibility_use_case(&state).ensure_view_compatible(&id).await?;
+
     let use_case = DatabaseViewUseCase::new(
         state.database_view_repository.clone(),
         state.database_configuration_repository.clone(),
@@ -130,3 +134,54 @@ pub async fn cancel_integration(
     let view = use_case.cancel_integration(&id).await?;
     Ok((StatusCode::OK, Json(ApiResponse::success("Integração cancelada com sucesso", view))))
 }
+
+fn schema_drift_use_case(state: &AppState) -> SchemaDriftUseCase {
+    SchemaDriftUseCase::new(
+        state.schema_drift_repository.clone(),
+        state.database_view_repository.clone(),
+        state.database_configuration_repository.clone(),
+        state.database_view_mapping_repository.clone(),
+        state.database_column_repository.clone(),
+        state.connectors.clone(),
+    )
+}
+
+/// Run the schema drift check of an integration now
+pub async fn check_schema_drift(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> AppResult<Json<ApiResponse<SchemaDriftReport>>> {
+    let report = schema_drift_use_case(&state).check_view(&id).await?;
+
+    Ok(Json(ApiResponse::success("Verificação de schema concluída", report)))
+}
+
+/// Latest schema drift report of an integration (on demand or scheduled)
+pub async fn get_schema_drift(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> AppResult<Json<ApiResponse<SchemaDriftReport>>> {
+    let report = schema_drift_use_case(&state).get_report(&id).await?;
+
+    Ok(Json(ApiResponse::success("Verificação de schema encontrada", report)))
+}
+
+/// Integrations whose latest check found drift
+pub async fn get_drifting_views(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> AppResult<Json<ApiResponse<Vec<SchemaDriftReport>>>> {
+    let reports = schema_drift_use_case(&state).get_drifting(&auth.company_id).await?;
+
+    Ok(Json(ApiResponse::success("Integrações com divergência de schema", reports)))
+}
+
+/// Resource types of an integration against the capabilities of its target
+pub async fn get_target_compatibility(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> AppResult<Json<ApiResponse<CompatibilityReport>>> {
+    let report = target_compatibility_use_case(&state).check_view(&id).await?;
+
+    Ok(Json(ApiResponse::success("Verificação de compatibilidade concluída", report)))
+}
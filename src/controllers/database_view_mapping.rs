@@ -11,7 +11,8 @@ use crate::application::{
     CreateDatabaseViewMappingDto, UpdateDatabaseViewMappingDto,
 };
 use crate::core::AuthUser;
-use crate::utils::{ApiResponse, AppResult, PaginationResponse, PaginationQuery};
+use crate::domain::fhir::FhirVersion;
+use crate::utils::{ApiResponse, AppError, AppResult, PaginationResponse, PaginationQuery};
 
 #[derive(Debug, Deserialize)]
 pub struct DatabaseViewMappingQuery {
@@ -114,11 +115,38 @@ pub async fn get_database_view_mappings_by_view_id(
     Ok(Json(ApiResponse::success("Mapeamentos encontrados", mappings)))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct FhirPreviewQuery {
+    /// Preview in another release than the target integration's (R4, R4B, R5)
+    #[serde(rename = "fhirVersion")]
+    pub fhir_version: Option<String>,
+    /// Preview the source row whose `keyColumn` holds `keyValue` instead of the first row
+    #[serde(rename = "keyColumn")]
+    pub key_column: Option<String>,
+    #[serde(rename = "keyValue")]
+    pub key_value: Option<String>,
+}
+
 pub async fn get_database_view_mappings_preview(
     State(state): State<AppState>,
     auth: AuthUser,
     Path(view_id): Path<String>,
+    Query(query): Query<FhirPreviewQuery>,
 ) -> AppResult<Json<ApiResponse<Value>>> {
+    let version = query
+        .fhir_version
+        .as_deref()
+        .map(|version| {
+            FhirVersion::parse(version)
+                .ok_or_else(|| AppError::BadRequest(format!("Versão FHIR não suportada: {}", version)))
+        })
+        .transpose()?;
+    let sample_key = match (query.key_column.as_deref(), query.key_value.as_deref()) {
+        (Some(key_column), Some(key_value)) => Some((key_column, key_value)),
+        (None, None) => None,
+        _ => return Err(AppError::BadRequest("keyColumn e keyValue devem ser informados juntos".to_string())),
+    };
+
     let use_case = DatabaseViewMappingUseCase::with_repositories(
         state.database_view_mapping_repository.clone(),
         state.database_view_repository.clone(),
@@ -126,8 +154,12 @@ pub async fn get_database_view_mappings_preview(
         state.database_table_repository.clone(),
         state.database_transformation_repository.clone(),
         state.database_model_value_repository.clone(),
-    );
-    let result = use_case.generate_fhir_preview(&view_id, &auth.company_id).await?;
+    )
+    .with_target_repository(state.target_integration_repository.clone())
+    .with_company_repository(state.company_repository.clone())
+    .with_conformance(state.profile_registry.clone(), state.terminology.clone())
+    .with_connectors(state.connectors.clone());
+    let result = use_case.generate_fhir_preview(&view_id, &auth.company_id, version, sample_key).await?;
 
     Ok(Json(ApiResponse::success(
         "Prévia FHIR gerada com sucesso",
@@ -0,0 +1,70 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::{json, Value};
+
+use crate::application::{AppState, FhirFacadeUseCase};
+use crate::core::AuthUser;
+use crate::utils::{AppError, AppResult};
+
+fn use_case(state: &AppState) -> FhirFacadeUseCase {
+    FhirFacadeUseCase::new(state.delivered_resource_repository.clone())
+}
+
+/// Scheme and host the request was sent to (`https://api.example.com`), honouring X-Forwarded-Proto
+pub(crate) fn request_base_url(headers: &HeaderMap) -> String {
+    let host = headers.get(header::HOST).and_then(|value| value.to_str().ok()).unwrap_or("localhost");
+    let scheme = headers.get("x-forwarded-proto").and_then(|value| value.to_str().ok()).unwrap_or("http");
+    format!("{}://{}", scheme, host)
+}
+
+/// FHIR JSON on success, an OperationOutcome on failure
+fn fhir_response(result: AppResult<Value>) -> Response {
+    let (status, body) = match result {
+        Ok(resource) => (StatusCode::OK, resource),
+        Err(error) => {
+            let (status, code) = match &error {
+                AppError::NotFound(_) => (StatusCode::NOT_FOUND, "not-found"),
+                AppError::BadRequest(_) | AppError::Validation(_) => (StatusCode::BAD_REQUEST, "invalid"),
+                _ => (StatusCode::INTERNAL_SERVER_ERROR, "exception"),
+            };
+            let outcome = json!({
+                "resourceType": "OperationOutcome",
+                "issue": [{ "severity": "error", "code": code, "diagnostics": error.to_string() }],
+            });
+            (status, outcome)
+        }
+    };
+
+    (status, [(header::CONTENT_TYPE, "application/fhir+json")], Json(body)).into_response()
+}
+
+/// GET /fhir/metadata
+pub async fn capability_statement(State(state): State<AppState>, auth: AuthUser, headers: HeaderMap) -> Response {
+    let base_url = format!("{}/fhir", request_base_url(&headers));
+    fhir_response(use_case(&state).capability_statement(&auth.company_id, &base_url).await)
+}
+
+/// GET /fhir/:resource_type?identifier=&_lastUpdated=&patient=
+pub async fn search(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    headers: HeaderMap,
+    Path(resource_type): Path<String>,
+    Query(params): Query<Vec<(String, String)>>,
+) -> Response {
+    let base_url = format!("{}/fhir", request_base_url(&headers));
+    fhir_response(use_case(&state).search(&auth.company_id, &resource_type, &params, &base_url).await)
+}
+
+/// GET /fhir/:resource_type/:id
+pub async fn read(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((resource_type, id)): Path<(String, String)>,
+) -> Response {
+    fhir_response(use_case(&state).read(&auth.company_id, &resource_type, &id).await)
+}
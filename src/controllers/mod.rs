@@ -12,6 +12,8 @@ pub mod target_integration;
 pub mod integration_control;
 pub mod sync;
 pub mod metrics;
+pub mod terminology;
+pub mod fhir_facade;
 pub mod routes;
 
 pub use routes::create_routes;
@@ -1,5 +1,6 @@
 use axum::{
     Router,
+    extract::DefaultBodyLimit,
     routing::{get, post, put, delete},
 };
 
@@ -8,9 +9,12 @@ use crate::controllers::{
     user, company, auth, health, database_configuration, database_column,
     database_table, database_view, database_view_mapping,
     target_integration, integration_control,
-    sync, metrics, database_model
+    sync, metrics, database_model, terminology, fhir_facade
 };
 
+/// Spreadsheet uploads are well above axum's 2MB default body limit
+const UPLOAD_BODY_LIMIT: usize = 50 * 1024 * 1024;
+
 pub fn create_routes(state: AppState) -> Router {
     Router::new()
         // Health route
@@ -42,6 +46,10 @@ pub fn create_routes(state: AppState) -> Router {
         .route("/database-configuration/test-connection", post(database_configuration::test_connection))
         .route("/database-configuration/:id", put(database_configuration::update_database_configuration))
         .route("/database-configuration/:id", delete(database_configuration::delete_database_configuration))
+        .route("/database-configuration/:id/upload", post(database_configuration::upload_file).layer(DefaultBodyLimit::max(UPLOAD_BODY_LIMIT)))
+        .route("/database-configuration/:id/datasets", get(database_configuration::get_file_datasets))
+        .route("/database-configuration/:id/discover", post(database_configuration::preview_schema_discovery))
+        .route("/database-configuration/:id/discover/apply", post(database_configuration::apply_schema_discovery))
         
         // Database Column routes
         .route("/database-columns", post(database_column::create_database_column))
@@ -65,6 +73,10 @@ pub fn create_routes(state: AppState) -> Router {
         .route("/database-view/:id", get(database_view::get_database_view_by_id))
         .route("/database-view/:id", put(database_view::update_database_view))
         .route("/database-view/:id", delete(database_view::delete_database_view))
+        .route("/database-view/schema-drift", get(database_view::get_drifting_views))
+        .route("/database-view/:id/schema-drift", get(database_view::get_schema_drift))
+        .route("/database-view/:id/schema-drift", post(database_view::check_schema_drift))
+        .route("/database-view/:id/target-compatibility", get(database_view::get_target_compatibility))
 
         // Target Integration routes
         .route("/target-integration", post(target_integration::create_target_integration))
@@ -72,6 +84,11 @@ pub fn create_routes(state: AppState) -> Router {
         .route("/target-integration/:id", get(target_integration::get_target_integration_by_id))
         .route("/target-integration/:id", put(target_integration::update_target_integration))
         .route("/target-integration/:id", delete(target_integration::delete_target_integration))
+        .route("/target-integration/certificates/expiring", get(target_integration::get_expiring_certificates))
+        .route("/target-integration/:id/certificate", post(target_integration::upload_client_certificate))
+        .route("/target-integration/:id/certificate", delete(target_integration::remove_client_certificate))
+        .route("/target-integration/:id/test-connection", post(target_integration::test_target_integration_connection))
+        .route("/target-integration/:id/discover", post(target_integration::discover_capabilities))
 
         // Integration Control routes
         .route("/integration-control", post(integration_control::create_integration_control))
@@ -95,10 +112,18 @@ pub fn create_routes(state: AppState) -> Router {
         .route("/sync/jobs/:job_id/pause", post(sync::pause_job))  // Pausar job
         .route("/sync/jobs/:job_id/resume", post(sync::resume_job))  // Retomar job pausado
         .route("/sync/jobs/:job_id/restart", post(sync::restart_job))  // Reexecutar job (qualquer status)
+        .route("/sync/export", post(sync::start_export))  // Exportação NDJSON (Bulk Data)
+        .route("/sync/export/:job_id", get(sync::get_export_status))  // Status ou manifesto da exportação
+        .route("/sync/export/:job_id/:file", get(sync::download_export_file))  // Download de um arquivo NDJSON
         .route("/sync/stats", get(sync::get_sync_stats))  // Estatísticas gerais
         .route("/sync/stats/memory", get(sync::get_memory_jobs))  // Jobs em memória (paginado)
         .route("/sync/stats/persisted", get(sync::get_persisted_jobs))  // Jobs no MongoDB (paginado)
         
+        // Read-only FHIR facade (last delivered version of each resource, per company)
+        .route("/fhir/metadata", get(fhir_facade::capability_statement))
+        .route("/fhir/:resource_type", get(fhir_facade::search))
+        .route("/fhir/:resource_type/:id", get(fhir_facade::read))
+        
         // Metrics routes (Real-time dashboard metrics)
         .route("/metrics/stream", get(metrics::stream_metrics_ws))  // WebSocket (tempo real)
         .route("/metrics", get(metrics::get_metrics_rest))           // REST (snapshot único)
@@ -115,6 +140,13 @@ pub fn create_routes(state: AppState) -> Router {
         .route("/database-model/:id/model-values/:value_id/connection/:connection_id", delete(database_model::delete_database_model_value_connection_mapping))
         .route("/database-model/:id", put(database_model::update_database_model))
         .route("/database-model/:id", delete(database_model::delete_database_model))
+        .route("/database-model/:id/terminology-check", get(terminology::check_database_model))
+
+        // Terminology routes (CodeSystems and ValueSets)
+        .route("/terminology", post(terminology::upload_terminology_resource))
+        .route("/terminology", get(terminology::get_terminology_resources))
+        .route("/terminology", delete(terminology::delete_terminology_resource))
+        .route("/terminology/validate-code", post(terminology::validate_code))
         
         .with_state(state)
 }
@@ -1,14 +1,18 @@
 // Sync controller - REST API endpoints for synchronization
 use axum::{
     extract::{State, Path, Query},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
 use serde::{Deserialize, Serialize};
 
 use crate::application::AppState;
-use crate::sync::job::{SyncJob, SyncJobConfig};
+use crate::core::AuthUser;
+use crate::sync::job::{JobKind, SyncJob, SyncJobConfig};
 use crate::domain::entities::{SyncJobDocument, JobStatus};
-use crate::utils::{ApiResponse, AppResult, PaginationQuery, PaginationResponse};
+use crate::sync::export;
+use crate::utils::{ApiResponse, AppError, AppResult, PaginationQuery, PaginationResponse};
 
 /// DTO for starting a synchronization
 #[derive(Debug, Deserialize)]
@@ -41,6 +45,11 @@ pub async fn start_sync(
     Json(payload): Json<StartSyncRequest>,
 ) -> AppResult<Json<ApiResponse<StartSyncResponse>>> {
     use tracing::info;
+
+    // Refuse integrations whose target is known not to accept the resources they produce
+    super::target_integration::target_compatibility_use_case(&state)
+        .ensure_view_compatible(&payload.database_view_id)
+        .await?;
     
     // 🔍 PASSO 1: Verificar se já existe QUALQUER job para esta integração
     if let Some(existing_job) = state.sync_job_repository
@@ -101,6 +110,7 @@ pub async fn start_sync(
     let config = SyncJobConfig {
         database_view_id: payload.database_view_id,
         page_size: payload.page_size,
+        kind: JobKind::Sync,
     };
 
     let mut job = state.sync_manager
@@ -137,6 +147,108 @@ pub async fn start_sync(
     )))
 }
 
+/// POST /sync/export
+/// Inicia a exportação de uma integração em NDJSON (FHIR Bulk Data): um arquivo por tipo de recurso
+/// Acompanhe em GET /sync/export/:job_id (Content-Location da resposta)
+pub async fn start_export(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(payload): Json<StartSyncRequest>,
+) -> AppResult<Response> {
+    // Views of other companies are reported as missing
+    state.database_view_repository
+        .find_by_id(&payload.database_view_id)
+        .await?
+        .filter(|view| view.company_id == auth.company_id)
+        .ok_or_else(|| AppError::NotFound(format!("DatabaseView {} not found", payload.database_view_id)))?;
+
+    let config = SyncJobConfig {
+        database_view_id: payload.database_view_id,
+        page_size: payload.page_size,
+        kind: JobKind::Export,
+    };
+
+    let job = state.sync_manager.submit_job(config).await?;
+    let status_url = format!("/sync/export/{}", job.id);
+
+    let response = StartSyncResponse {
+        job_id: job.id.clone(),
+        status: "pending".to_string(),
+        message: format!("Exportação iniciada! Acompanhe em {}", status_url),
+    };
+
+    Ok((
+        StatusCode::ACCEPTED,
+        [(header::CONTENT_LOCATION, status_url)],
+        Json(ApiResponse::success("Exportação Iniciada", response)),
+    ).into_response())
+}
+
+/// GET /sync/export/:job_id
+/// 202 com X-Progress enquanto a exportação roda; 200 com o manifesto Bulk Data ao concluir
+pub async fn get_export_status(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(job_id): Path<String>,
+    headers: HeaderMap,
+) -> AppResult<Response> {
+    let job = find_export_job(&state, &job_id, &auth.company_id).await?;
+
+    match job.status {
+        crate::sync::job::JobStatus::Completed => {
+            let manifest = export::manifest(&job, &super::fhir_facade::request_base_url(&headers));
+            Ok(Json(manifest).into_response())
+        }
+        crate::sync::job::JobStatus::Failed | crate::sync::job::JobStatus::Cancelled => Err(AppError::Conflict(format!(
+            "Exportação {} terminou com status {:?}",
+            job.id, job.status
+        ))),
+        _ => {
+            let progress = match job.total_records {
+                Some(total) => format!("{}/{} registros", job.processed_records, total),
+                None => "em preparação".to_string(),
+            };
+            Ok((
+                StatusCode::ACCEPTED,
+                [("x-progress", progress)],
+                Json(ApiResponse::success("Exportação em andamento", job)),
+            ).into_response())
+        }
+    }
+}
+
+/// GET /sync/export/:job_id/:file
+/// Download de um arquivo NDJSON da exportação (ex: Patient.ndjson)
+pub async fn download_export_file(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((job_id, file)): Path<(String, String)>,
+) -> AppResult<Response> {
+    let job = find_export_job(&state, &job_id, &auth.company_id).await?;
+    if !job.output.iter().any(|entry| entry.url.rsplit('/').next() == Some(file.as_str())) {
+        return Err(AppError::NotFound(format!("Arquivo {} não encontrado na exportação {}", file, job_id)));
+    }
+
+    let path = export::file_path(crate::sync::worker::DEFAULT_OUTPUT_DIR, &job.id, &file)
+        .ok_or_else(|| AppError::BadRequest(format!("Arquivo inválido: {}", file)))?;
+    let content = tokio::fs::read(&path)
+        .await
+        .map_err(|_| AppError::NotFound(format!("Arquivo {} não encontrado na exportação {}", file, job_id)))?;
+
+    Ok(([(header::CONTENT_TYPE, "application/fhir+ndjson")], content).into_response())
+}
+
+/// Export job of the caller's company, from memory (running) or MongoDB (finished)
+async fn find_export_job(state: &AppState, job_id: &str, company_id: &str) -> AppResult<SyncJob> {
+    let job = match state.sync_manager.get_job_status(job_id).await {
+        Some(job) => Some(job),
+        None => state.sync_job_repository.find_by_job_id(job_id).await?.map(|job_doc| job_doc.to_memory_job()),
+    };
+
+    job.filter(|job| job.kind == JobKind::Export && job.company_id == company_id)
+        .ok_or_else(|| AppError::NotFound(format!("Exportação {} não encontrada", job_id)))
+}
+
 /// GET /sync/status/:job_id
 /// Gets the current status of a synchronization job
 /// Busca primeiro da memória (tempo real) e depois do MongoDB (fallback)
@@ -622,6 +734,7 @@ pub async fn restart_job(
             job.processed_records = 0;
             job.failed_records = 0;
             job.failed_item_codes.clear();
+            job.output.clear();
             job.status = crate::sync::job::JobStatus::Running;
             job.started_at = None;
             job.finished_at = None;
@@ -1,15 +1,18 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Multipart, Path, Query, State},
     http::StatusCode,
     Json,
 };
+use serde::Deserialize;
 
-use crate::application::{AppState, TargetIntegrationUseCase};
+use crate::application::{AppState, TargetCompatibilityUseCase, TargetIntegrationUseCase};
 use crate::core::AuthUser;
 use crate::domain::dtos::{
-    CreateTargetIntegrationDto, TargetIntegrationEntity, UpdateTargetIntegrationDto,
+    ClientCertificateUpload, CreateTargetIntegrationDto, TargetDiscoveryResult, TargetIntegrationEntity,
+    UpdateTargetIntegrationDto,
 };
-use crate::utils::{ApiResponse, AppResult};
+use crate::domain::entities::ConnectionDiagnostics;
+use crate::utils::{ApiResponse, AppError, AppResult};
 
 pub async fn create_target_integration(
     State(state): State<AppState>,
@@ -109,3 +112,121 @@ pub async fn delete_target_integration(
         "Deleted".to_string(),
     )))
 }
+
+/// Multipart upload of the mTLS client certificate
+/// (fields: `certificate` .pfx/.p12 or PEM, `privateKey` PEM, `password`, `caBundle` PEM)
+pub async fn upload_client_certificate(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    mut multipart: Multipart,
+) -> AppResult<Json<ApiResponse<TargetIntegrationEntity>>> {
+    let mut certificate: Option<Vec<u8>> = None;
+    let mut private_key: Option<Vec<u8>> = None;
+    let mut password: Option<String> = None;
+    let mut ca_bundle: Option<Vec<u8>> = None;
+
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| AppError::BadRequest(format!("Upload inválido: {}", e)))?
+    {
+        let name = field.name().unwrap_or_default().to_string();
+        let content = field
+            .bytes()
+            .await
+            .map_err(|e| AppError::BadRequest(format!("Falha ao ler o campo {}: {}", name, e)))?;
+
+        match name.as_str() {
+            "certificate" => certificate = Some(content.to_vec()),
+            "privateKey" => private_key = Some(content.to_vec()),
+            "password" => password = Some(String::from_utf8_lossy(&content).to_string()),
+            "caBundle" => ca_bundle = Some(content.to_vec()),
+            _ => {}
+        }
+    }
+
+    let certificate = certificate
+        .filter(|content| !content.is_empty())
+        .ok_or_else(|| AppError::BadRequest("Campo certificate é obrigatório".to_string()))?;
+
+    let use_case = TargetIntegrationUseCase::new(
+        state.target_integration_repository.clone(),
+        state.database_view_repository.clone(),
+    );
+
+    let target = use_case
+        .upload_client_certificate(
+            &id,
+            ClientCertificateUpload {
+                certificate,
+                private_key: private_key.filter(|content| !content.is_empty()),
+                password: password.filter(|value| !value.is_empty()),
+                ca_bundle: ca_bundle.filter(|content| !content.is_empty()),
+            },
+        )
+        .await?;
+
+    Ok(Json(ApiResponse::success("Certificado de cliente salvo com sucesso", target)))
+}
+
+pub async fn remove_client_certificate(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> AppResult<Json<ApiResponse<TargetIntegrationEntity>>> {
+    let use_case = TargetIntegrationUseCase::new(
+        state.target_integration_repository.clone(),
+        state.database_view_repository.clone(),
+    );
+
+    let target = use_case.remove_client_certificate(&id).await?;
+    Ok(Json(ApiResponse::success("Certificado de cliente removido com sucesso", target)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExpiringCertificatesQuery {
+    pub days: Option<i64>,
+}
+
+pub async fn get_expiring_certificates(
+    State(state): State<AppState>,
+    Query(query): Query<ExpiringCertificatesQuery>,
+) -> AppResult<Json<ApiResponse<Vec<TargetIntegrationEntity>>>> {
+    let use_case = TargetIntegrationUseCase::new(
+        state.target_integration_repository.clone(),
+        state.database_view_repository.clone(),
+    );
+
+    let targets = use_case.get_expiring_certificates(query.days).await?;
+    Ok(Json(ApiResponse::success("Certificados a expirar", targets)))
+}
+
+pub async fn test_target_integration_connection(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> AppResult<Json<ConnectionDiagnostics>> {
+    let use_case = TargetIntegrationUseCase::new(
+        state.target_integration_repository.clone(),
+        state.database_view_repository.clone(),
+    );
+
+    let result = use_case.test_connection(&id).await?;
+    Ok(Json(result))
+}
+
+pub(crate) fn target_compatibility_use_case(state: &AppState) -> TargetCompatibilityUseCase {
+    TargetCompatibilityUseCase::new(
+        state.target_integration_repository.clone(),
+        state.database_view_repository.clone(),
+        state.database_view_mapping_repository.clone(),
+    )
+}
+
+/// Fetch the target's CapabilityStatement and check the integrations delivering to it
+pub async fn discover_capabilities(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> AppResult<Json<ApiResponse<TargetDiscoveryResult>>> {
+    let result = target_compatibility_use_case(&state).discover(&id).await?;
+
+    Ok(Json(ApiResponse::success("Capacidades do destino obtidas com sucesso", result)))
+}
@@ -0,0 +1,78 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::application::{AppState, TerminologyUseCase};
+use crate::domain::dtos::{ModelTerminologyCheck, ValidateCodeDto};
+use crate::domain::fhir::terminology::TerminologySummary;
+use crate::domain::fhir::CodeCheck;
+use crate::utils::{ApiResponse, AppResult};
+
+#[derive(Debug, Deserialize)]
+pub struct TerminologyUrlQuery {
+    pub url: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ModelTerminologyQuery {
+    #[serde(rename = "valueSet")]
+    pub value_set: Option<String>,
+}
+
+fn terminology_use_case(state: &AppState) -> TerminologyUseCase {
+    TerminologyUseCase::new(state.terminology_repository.clone(), state.terminology.clone())
+        .with_model_repositories(state.database_model_repository.clone(), state.database_model_value_repository.clone())
+}
+
+pub async fn upload_terminology_resource(
+    State(state): State<AppState>,
+    Json(payload): Json<Value>,
+) -> AppResult<(StatusCode, Json<ApiResponse<TerminologySummary>>)> {
+    let summary = terminology_use_case(&state).upload(payload).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse::success("Recurso de terminologia carregado com sucesso", summary)),
+    ))
+}
+
+pub async fn get_terminology_resources(
+    State(state): State<AppState>,
+) -> AppResult<Json<ApiResponse<Vec<TerminologySummary>>>> {
+    let summaries = terminology_use_case(&state).list().await;
+
+    Ok(Json(ApiResponse::success("Recursos de terminologia encontrados", summaries)))
+}
+
+/// `url` goes in the query string, since canonical urls do not fit in a path segment
+pub async fn delete_terminology_resource(
+    State(state): State<AppState>,
+    Query(query): Query<TerminologyUrlQuery>,
+) -> AppResult<Json<ApiResponse<String>>> {
+    terminology_use_case(&state).remove(&query.url).await?;
+
+    Ok(Json(ApiResponse::success("Recurso de terminologia excluído com sucesso", "Excluído".to_string())))
+}
+
+pub async fn validate_code(
+    State(state): State<AppState>,
+    Json(payload): Json<ValidateCodeDto>,
+) -> AppResult<Json<ApiResponse<CodeCheck>>> {
+    let result = terminology_use_case(&state).validate_code(payload).await?;
+
+    Ok(Json(ApiResponse::success("Código verificado", result)))
+}
+
+pub async fn check_database_model(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<ModelTerminologyQuery>,
+) -> AppResult<Json<ApiResponse<ModelTerminologyCheck>>> {
+    let result = terminology_use_case(&state).check_model(&id, query.value_set).await?;
+
+    Ok(Json(ApiResponse::success("Modelo de terminologia verificado", result)))
+}
@@ -1,3 +1,5 @@
 pub mod auth;
+pub mod secrets;
 
 pub use auth::{JwtService, Claims, AuthUser};
+pub use secrets::SecretBox;
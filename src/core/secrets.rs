@@ -0,0 +1,207 @@
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use sha2::{Digest, Sha256};
+
+use crate::utils::AppError;
+
+/// Prefix of values sealed by [`SecretBox`]: `enc:v1:<key id>:<wrapped data key>:<sealed value>`
+const PREFIX: &str = "enc:v1:";
+const KEY_LEN: usize = 32;
+const VALUE_AAD: &[u8] = b"interhealth-secret";
+
+/// A 256-bit master key and its id (first bytes of its SHA-256, in hex)
+struct MasterKey {
+    id: String,
+    key: LessSafeKey,
+}
+
+impl MasterKey {
+    /// Keys are given base64-encoded, e.g. `openssl rand -base64 32`
+    fn parse(encoded: &str) -> Result<Self, AppError> {
+        let bytes = BASE64
+            .decode(encoded.trim())
+            .map_err(|_| AppError::ConfigError("Master key must be base64-encoded".to_string()))?;
+        if bytes.len() != KEY_LEN {
+            return Err(AppError::ConfigError(format!("Master key must have {} bytes", KEY_LEN)));
+        }
+
+        Ok(Self {
+            id: hex::encode(&Sha256::digest(&bytes)[..4]),
+            key: less_safe_key(&bytes)?,
+        })
+    }
+}
+
+/// Envelope encryption of stored credentials
+///
+/// Every value gets its own random data key; only the data key is encrypted with the master key,
+/// so rotating the master key re-wraps data keys without touching the values. Without a master key
+/// values pass through unchanged.
+pub struct SecretBox {
+    current: Option<MasterKey>,
+    /// Keys still accepted for decryption while values are rotated to `current`
+    previous: Vec<MasterKey>,
+    rng: SystemRandom,
+}
+
+impl SecretBox {
+    pub fn new(master_key: Option<&str>, previous_keys: &[String]) -> Result<Self, AppError> {
+        Ok(Self {
+            current: master_key.map(MasterKey::parse).transpose()?,
+            previous: previous_keys.iter().map(|key| MasterKey::parse(key)).collect::<Result<_, _>>()?,
+            rng: SystemRandom::new(),
+        })
+    }
+
+    /// Stores values as given; used when no master key is configured
+    pub fn disabled() -> Self {
+        Self { current: None, previous: Vec::new(), rng: SystemRandom::new() }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.current.is_some()
+    }
+
+    pub fn is_sealed(value: &str) -> bool {
+        value.starts_with(PREFIX)
+    }
+
+    /// Seal a value with a fresh data key; already sealed values are kept as they are
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, AppError> {
+        let Some(master) = self.current.as_ref() else {
+            return Ok(plaintext.to_string());
+        };
+        if Self::is_sealed(plaintext) {
+            return Ok(plaintext.to_string());
+        }
+
+        let mut data_key = [0u8; KEY_LEN];
+        self.rng.fill(&mut data_key).map_err(|_| crypto_error())?;
+
+        let sealed_value = self.seal(&less_safe_key(&data_key)?, plaintext.as_bytes(), VALUE_AAD)?;
+        let wrapped_key = self.seal(&master.key, &data_key, master.id.as_bytes())?;
+
+        Ok(format!("{}{}:{}:{}", PREFIX, master.id, BASE64.encode(wrapped_key), BASE64.encode(sealed_value)))
+    }
+
+    /// Open a sealed value; plaintext left from before encryption was enabled is returned as is
+    pub fn decrypt(&self, stored: &str) -> Result<String, AppError> {
+        if !Self::is_sealed(stored) {
+            return Ok(stored.to_string());
+        }
+
+        let (key_id, wrapped_key, sealed_value) = Self::split(stored)?;
+        let data_key = self.unwrap_data_key(key_id, &wrapped_key)?;
+        let plaintext = Self::open(&less_safe_key(&data_key)?, &sealed_value, VALUE_AAD)?;
+
+        String::from_utf8(plaintext).map_err(|_| crypto_error())
+    }
+
+    /// Bring a stored value to the current master key: plaintext is sealed, values sealed with a
+    /// previous key get their data key re-wrapped. Returns None when nothing changes.
+    pub fn rotate(&self, stored: &str) -> Result<Option<String>, AppError> {
+        let Some(master) = self.current.as_ref() else {
+            return Err(AppError::ConfigError("SECRETS_MASTER_KEY must be set to rotate secrets".to_string()));
+        };
+
+        if !Self::is_sealed(stored) {
+            return self.encrypt(stored).map(Some);
+        }
+
+        let (key_id, wrapped_key, sealed_value) = Self::split(stored)?;
+        if key_id == master.id {
+            return Ok(None);
+        }
+
+        let data_key = self.unwrap_data_key(key_id, &wrapped_key)?;
+        let rewrapped = self.seal(&master.key, &data_key, master.id.as_bytes())?;
+
+        Ok(Some(format!("{}{}:{}:{}", PREFIX, master.id, BASE64.encode(rewrapped), BASE64.encode(sealed_value))))
+    }
+
+    fn unwrap_data_key(&self, key_id: &str, wrapped_key: &[u8]) -> Result<Vec<u8>, AppError> {
+        let master = self
+            .current
+            .iter()
+            .chain(self.previous.iter())
+            .find(|master| master.id == key_id)
+            .ok_or_else(|| AppError::ConfigError(format!("Master key {} is not configured", key_id)))?;
+
+        Self::open(&master.key, wrapped_key, master.id.as_bytes())
+    }
+
+    fn split(stored: &str) -> Result<(&str, Vec<u8>, Vec<u8>), AppError> {
+        let mut parts = stored[PREFIX.len()..].splitn(3, ':');
+        let (Some(key_id), Some(wrapped_key), Some(sealed_value)) = (parts.next(), parts.next(), parts.next()) else {
+            return Err(crypto_error());
+        };
+
+        let wrapped_key = BASE64.decode(wrapped_key).map_err(|_| crypto_error())?;
+        let sealed_value = BASE64.decode(sealed_value).map_err(|_| crypto_error())?;
+        Ok((key_id, wrapped_key, sealed_value))
+    }
+
+    /// nonce || ciphertext || tag
+    fn seal(&self, key: &LessSafeKey, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>, AppError> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng.fill(&mut nonce).map_err(|_| crypto_error())?;
+
+        let mut in_out = plaintext.to_vec();
+        key.seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(aad), &mut in_out)
+            .map_err(|_| crypto_error())?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend(in_out);
+        Ok(sealed)
+    }
+
+    fn open(key: &LessSafeKey, sealed: &[u8], aad: &[u8]) -> Result<Vec<u8>, AppError> {
+        if sealed.len() < NONCE_LEN {
+            return Err(crypto_error());
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| crypto_error())?;
+
+        let mut in_out = ciphertext.to_vec();
+        let plaintext = key.open_in_place(nonce, Aad::from(aad), &mut in_out).map_err(|_| crypto_error())?;
+        Ok(plaintext.to_vec())
+    }
+}
+
+fn less_safe_key(bytes: &[u8]) -> Result<LessSafeKey, AppError> {
+    UnboundKey::new(&AES_256_GCM, bytes)
+        .map(LessSafeKey::new)
+        .map_err(|_| crypto_error())
+}
+
+fn crypto_error() -> AppError {
+    AppError::ConfigError("Falha ao descriptografar credencial armazenada".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD_KEY: &str = "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=";
+    const NEW_KEY: &str = "ICEiIyQlJicoKSorLC0uLzAxMjM0NTY3ODk6Ozw9Pj8=";
+
+    #[test]
+    fn test_encrypt_decrypt_and_rotate() {
+        let old = SecretBox::new(Some(OLD_KEY), &[]).unwrap();
+        let sealed = old.encrypt("p@ss:w/rd").unwrap();
+
+        assert!(SecretBox::is_sealed(&sealed));
+        assert!(!sealed.contains("p@ss"));
+        assert_eq!(old.decrypt(&sealed).unwrap(), "p@ss:w/rd");
+        assert_eq!(old.decrypt("legacy-plaintext").unwrap(), "legacy-plaintext");
+
+        let rotated_box = SecretBox::new(Some(NEW_KEY), &[OLD_KEY.to_string()]).unwrap();
+        let rotated = rotated_box.rotate(&sealed).unwrap().expect("re-wrapped with the new key");
+        assert_eq!(rotated_box.rotate(&rotated).unwrap(), None);
+
+        let new_only = SecretBox::new(Some(NEW_KEY), &[]).unwrap();
+        assert_eq!(new_only.decrypt(&rotated).unwrap(), "p@ss:w/rd");
+        assert!(new_only.decrypt(&sealed).is_err());
+    }
+}
@@ -15,6 +15,8 @@ pub struct CompanyEntity {
     pub zipcode: Option<String>,
     pub country: Option<String>,
     pub status: bool,
+    pub timezone: String,
+    pub hl7_facility: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -32,6 +34,12 @@ pub struct CreateCompanyDto {
     pub state: Option<String>,
     pub zipcode: Option<String>,
     pub country: Option<String>,
+    /// IANA timezone name; America/Sao_Paulo when omitted
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// HL7 receiving facility (MSH-6) routed to the company
+    #[serde(default)]
+    pub hl7_facility: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,6 +56,10 @@ pub struct UpdateCompanyDto {
     pub zipcode: Option<String>,
     pub country: Option<String>,
     pub status: Option<bool>,
+    #[serde(default)]
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub hl7_facility: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
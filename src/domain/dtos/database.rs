@@ -5,6 +5,11 @@ use chrono::{DateTime, Utc};
 use serde::de::Error as DeError;
 
 pub use crate::domain::entities::ValueMappingItem;
+use crate::domain::entities::{
+    ApiSourceSettings, CertificateFormat, ClientCertificate, CompatibilityReport, DeliveryMode, OracleConnectionSettings, ReferenceStrategy, SqlServerConnectionSettings, TargetCapabilities,
+    CERTIFICATE_EXPIRY_WARNING_DAYS,
+};
+use crate::domain::fhir::CodeCheck;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatabaseDto {
@@ -52,6 +57,12 @@ pub struct CreateDatabaseConfigurationDto {
     #[serde(rename = "authType")]
     pub auth_type: Option<String>,
     pub credentials: Option<String>,
+    #[serde(rename = "apiSource", default)]
+    pub api_source: Option<ApiSourceSettings>,
+    #[serde(default)]
+    pub oracle: Option<OracleConnectionSettings>,
+    #[serde(default)]
+    pub sqlserver: Option<SqlServerConnectionSettings>,
     pub company_id: Option<String>,
 }
 
@@ -69,6 +80,12 @@ pub struct UpdateDatabaseConfigurationDto {
     #[serde(rename = "authType")]
     pub auth_type: Option<String>,
     pub credentials: Option<String>,
+    #[serde(rename = "apiSource", default)]
+    pub api_source: Option<ApiSourceSettings>,
+    #[serde(default)]
+    pub oracle: Option<OracleConnectionSettings>,
+    #[serde(default)]
+    pub sqlserver: Option<SqlServerConnectionSettings>,
     pub company_id: Option<String>,
 }
 
@@ -87,11 +104,28 @@ pub struct DatabaseConfigurationEntity {
     #[serde(rename = "authType")]
     pub auth_type: Option<String>,
     pub credentials: Option<String>,
+    #[serde(rename = "apiSource", default, skip_serializing_if = "Option::is_none")]
+    pub api_source: Option<ApiSourceSettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oracle: Option<OracleConnectionSettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sqlserver: Option<SqlServerConnectionSettings>,
     pub company_id: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
 
+/// Filters for discovering the schema of a source connector
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DiscoverSchemaDto {
+    /// Schema/owner to read (defaults to the connected user's schema)
+    pub owner: Option<String>,
+    /// Only these tables/views (case-insensitive); all when omitted
+    pub tables: Option<Vec<String>>,
+    #[serde(rename = "includeViews")]
+    pub include_views: Option<bool>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateTargetIntegrationDto {
     pub name: String,
@@ -121,11 +155,62 @@ pub struct TargetIntegrationEntity {
     #[serde(rename = "authType")]
     pub auth_type: Option<String>,
     pub credentials: Option<String>,
+    #[serde(rename = "clientCertificate", skip_serializing_if = "Option::is_none")]
+    pub client_certificate: Option<ClientCertificateSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capabilities: Option<TargetCapabilities>,
     pub company_id: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
 
+/// Result of a CapabilityStatement discovery: what the server supports and how the views delivering to it fare
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TargetDiscoveryResult {
+    pub capabilities: TargetCapabilities,
+    pub views: Vec<CompatibilityReport>,
+}
+
+/// Files of a client certificate upload (multipart `certificate`, `privateKey`, `password`, `caBundle`)
+pub struct ClientCertificateUpload {
+    /// PKCS#12 bundle (.pfx/.p12) or PEM certificate chain
+    pub certificate: Vec<u8>,
+    /// PEM private key when `certificate` is PEM and does not include it
+    pub private_key: Option<Vec<u8>>,
+    pub password: Option<String>,
+    pub ca_bundle: Option<Vec<u8>>,
+}
+
+/// Client certificate as shown to clients: identification and expiry, never the key material
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientCertificateSummary {
+    pub format: CertificateFormat,
+    pub subject: String,
+    pub issuer: String,
+    pub not_after: String,
+    pub days_until_expiry: i64,
+    /// Expired or within CERTIFICATE_EXPIRY_WARNING_DAYS of expiring
+    pub expiring: bool,
+    pub has_ca_bundle: bool,
+}
+
+impl ClientCertificateSummary {
+    pub fn from_certificate(certificate: &ClientCertificate, now: DateTime<Utc>) -> Self {
+        let days_until_expiry = certificate.days_until_expiry(now);
+        Self {
+            format: certificate.format,
+            subject: certificate.subject.clone(),
+            issuer: certificate.issuer.clone(),
+            not_after: certificate.not_after.to_rfc3339(),
+            days_until_expiry,
+            expiring: certificate.is_expired(now) || days_until_expiry < CERTIFICATE_EXPIRY_WARNING_DAYS,
+            has_ca_bundle: certificate.ca_bundle.is_some(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateIntegrationControlDto {
     pub name: String,
@@ -403,6 +488,12 @@ pub struct CreateDatabaseViewDto {
     pub database_configuration_id: String,
     #[serde(rename = "targetIntegrationId", skip_serializing_if = "Option::is_none")]
     pub target_integration_id: Option<String>,
+    #[serde(rename = "messageTypes", default, skip_serializing_if = "Option::is_none")]
+    pub message_types: Option<Vec<String>>,
+    #[serde(rename = "deliveryMode", default, skip_serializing_if = "Option::is_none")]
+    pub delivery_mode: Option<DeliveryMode>,
+    #[serde(rename = "referenceStrategy", default, skip_serializing_if = "Option::is_none")]
+    pub reference_strategy: Option<ReferenceStrategy>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub resources: Option<Vec<ResourceItemDto>>,
 }
@@ -424,6 +515,12 @@ pub struct UpdateDatabaseViewDto {
     pub database_configuration_id: Option<String>,
     #[serde(rename = "targetIntegrationId", skip_serializing_if = "Option::is_none")]
     pub target_integration_id: Option<String>,
+    #[serde(rename = "messageTypes", default, skip_serializing_if = "Option::is_none")]
+    pub message_types: Option<Vec<String>>,
+    #[serde(rename = "deliveryMode", default, skip_serializing_if = "Option::is_none")]
+    pub delivery_mode: Option<DeliveryMode>,
+    #[serde(rename = "referenceStrategy", default, skip_serializing_if = "Option::is_none")]
+    pub reference_strategy: Option<ReferenceStrategy>,
     pub status: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub resources: Option<Vec<ResourceItemDto>>,
@@ -447,6 +544,12 @@ pub struct DatabaseViewEntity {
     pub database_configuration_id: String,
     #[serde(rename = "targetIntegrationId", skip_serializing_if = "Option::is_none")]
     pub target_integration_id: Option<String>,
+    #[serde(rename = "messageTypes", skip_serializing_if = "Option::is_none")]
+    pub message_types: Option<Vec<String>>,
+    #[serde(rename = "deliveryMode")]
+    pub delivery_mode: DeliveryMode,
+    #[serde(rename = "referenceStrategy")]
+    pub reference_strategy: ReferenceStrategy,
     pub company_id: Option<String>,
     pub status: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -562,3 +665,31 @@ pub struct UpdateDatabaseModelValueClientMappingDto {
     pub code: Option<String>,
     pub description: Option<String>,
 }
+
+/// Code to check against a loaded ValueSet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidateCodeDto {
+    pub value_set: String,
+    pub system: Option<String>,
+    pub code: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelValueCheck {
+    pub code: String,
+    pub description: String,
+    #[serde(flatten)]
+    pub result: CodeCheck,
+}
+
+/// Values of a DatabaseModel checked against a ValueSet
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelTerminologyCheck {
+    pub model_id: String,
+    pub value_set: String,
+    pub invalid_count: usize,
+    pub values: Vec<ModelValueCheck>,
+}
@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+
+/// Settings of an API database configuration used as a pull source
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiSourceSettings {
+    /// Endpoint path (relative to host) of each source table, e.g. PATIENT_INTERHEALTH -> /patients
+    /// Tables not listed fall back to the lowercase entity name (PATIENT_INTERHEALTH -> /patient)
+    #[serde(default)]
+    pub endpoints: HashMap<String, String>,
+    /// JSONPath selecting the records of a response, e.g. `$.data[*]` or `$.entry[*].resource`
+    #[serde(default = "default_records_path")]
+    pub records_path: String,
+    #[serde(default)]
+    pub pagination: ApiPagination,
+    /// Records requested per API call; must match what the API actually serves
+    #[serde(default)]
+    pub page_size: Option<u64>,
+    /// JSONPath of the total record count, when the API reports it (avoids walking every page to count)
+    #[serde(default)]
+    pub total_path: Option<String>,
+    /// Static query parameters sent with every request
+    #[serde(default)]
+    pub query: HashMap<String, String>,
+}
+
+fn default_records_path() -> String {
+    "$".to_string()
+}
+
+impl Default for ApiSourceSettings {
+    fn default() -> Self {
+        Self {
+            endpoints: HashMap::new(),
+            records_path: default_records_path(),
+            pagination: ApiPagination::default(),
+            page_size: None,
+            total_path: None,
+            query: HashMap::new(),
+        }
+    }
+}
+
+/// How an API source walks through its result pages
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum ApiPagination {
+    /// Everything comes in a single response
+    #[default]
+    None,
+    /// `?page=N&size=M`, pages counted from `first_page`
+    Page {
+        #[serde(default = "default_page_param")]
+        page_param: String,
+        #[serde(default = "default_size_param")]
+        size_param: String,
+        #[serde(default = "default_first_page")]
+        first_page: u64,
+    },
+    /// `?offset=N&limit=M`
+    Offset {
+        #[serde(default = "default_offset_param")]
+        offset_param: String,
+        #[serde(default = "default_limit_param")]
+        limit_param: String,
+    },
+    /// The response carries the token of the next page at `cursor_path`, sent back as `cursor_param`
+    Cursor {
+        #[serde(default = "default_cursor_param")]
+        cursor_param: String,
+        cursor_path: String,
+        #[serde(default)]
+        size_param: Option<String>,
+    },
+    /// Follow the `Link: <...>; rel="next"` response header (RFC 8288)
+    LinkHeader {
+        #[serde(default)]
+        size_param: Option<String>,
+    },
+}
+
+fn default_page_param() -> String {
+    "page".to_string()
+}
+
+fn default_size_param() -> String {
+    "size".to_string()
+}
+
+fn default_first_page() -> u64 {
+    1
+}
+
+fn default_offset_param() -> String {
+    "offset".to_string()
+}
+
+fn default_limit_param() -> String {
+    "limit".to_string()
+}
+
+fn default_cursor_param() -> String {
+    "cursor".to_string()
+}
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use bson::oid::ObjectId;
 use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use crate::utils::utils::{date_format, object_id_format};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -23,8 +24,24 @@ pub struct Company {
     pub zipcode: Option<String>,
     pub country: Option<String>,
     pub status: bool,
+    /// IANA timezone of the company's source data, applied to dates without an offset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    /// Receiving facility (MSH-6, or MSH-5 when blank) that routes inbound HL7 messages to this company
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hl7_facility: Option<String>,
     #[serde(with = "date_format")]
     pub created_at: DateTime<Utc>,
     #[serde(with = "date_format")]
     pub updated_at: DateTime<Utc>,
 }
+
+impl Company {
+    /// Configured timezone, America/Sao_Paulo when unset
+    pub fn timezone(&self) -> Tz {
+        self.timezone
+            .as_deref()
+            .and_then(|name| name.parse().ok())
+            .unwrap_or(date_format::DEFAULT_TIMEZONE)
+    }
+}
@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CheckStatus {
+    Passed,
+    /// Works, but something needs attention (e.g. no visible rows)
+    Warning,
+    Failed,
+    /// Not run because an earlier check failed
+    Skipped,
+}
+
+/// One item of the test-connection checklist
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticCheck {
+    /// connectivity, serverVersion, schema, selectPrivilege, rowVisibility, clientCertificate
+    pub name: String,
+    pub status: CheckStatus,
+    pub message: String,
+    /// Source object the check refers to (e.g. PATIENT_INTERHEALTH)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub object: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+}
+
+impl DiagnosticCheck {
+    pub fn new(name: &str, status: CheckStatus, message: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            message: message.into(),
+            object: None,
+            latency_ms: None,
+        }
+    }
+
+    pub fn passed(name: &str, message: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Passed, message)
+    }
+
+    pub fn warning(name: &str, message: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Warning, message)
+    }
+
+    pub fn failed(name: &str, message: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Failed, message)
+    }
+
+    pub fn skipped(name: &str, message: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Skipped, message)
+    }
+
+    pub fn for_object(mut self, object: &str) -> Self {
+        self.object = Some(object.to_string());
+        self
+    }
+
+    pub fn with_latency(mut self, latency_ms: u64) -> Self {
+        self.latency_ms = Some(latency_ms);
+        self
+    }
+}
+
+/// What a source connector reports about itself
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceDiagnostics {
+    /// Round-trip of the connectivity probe
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_version: Option<String>,
+    /// Schema the session resolves unqualified names against
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    pub checks: Vec<DiagnosticCheck>,
+}
+
+/// Test-connection response: connection details plus the checklist
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionDiagnostics {
+    pub success: bool,
+    pub message: String,
+    #[serde(rename = "type")]
+    pub db_type: String,
+    pub host: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub database: Option<String>,
+    #[serde(flatten)]
+    pub diagnostics: SourceDiagnostics,
+}
+
+impl ConnectionDiagnostics {
+    /// Success means no failed check; warnings are reported but do not fail the test
+    pub fn new(db_type: String, host: String, port: Option<i32>, database: Option<String>, diagnostics: SourceDiagnostics) -> Self {
+        let failed = diagnostics.checks.iter().filter(|c| c.status == CheckStatus::Failed).count();
+        let warnings = diagnostics.checks.iter().filter(|c| c.status == CheckStatus::Warning).count();
+
+        let message = match (failed, warnings) {
+            (0, 0) => "Conexão estabelecida com sucesso".to_string(),
+            (0, warnings) => format!("Conexão estabelecida com {} alerta(s)", warnings),
+            (failed, _) => format!("Falha em {} verificação(ões) da conexão", failed),
+        };
+
+        Self {
+            success: failed == 0,
+            message,
+            db_type,
+            host,
+            port,
+            database,
+            diagnostics,
+        }
+    }
+}
@@ -30,3 +30,28 @@ pub struct DatabaseColumn {
     #[serde(with = "crate::utils::utils::date_format")]
     pub updated_at: DateTime<Utc>,
 }
+
+impl DatabaseColumn {
+    /// Map a stored type or a connector's simplified type onto the seed vocabulary
+    /// (string, integer, date, datetime, time, boolean); `reference` columns take the
+    /// type of the key they point to and have none of their own
+    pub fn canonical_data_type(data_type: &str) -> Option<&'static str> {
+        match data_type.to_lowercase().as_str() {
+            "reference" => None,
+            "integer" | "number" | "decimal" => Some("integer"),
+            "datetime" | "timestamp" => Some("datetime"),
+            "date" => Some("date"),
+            "time" => Some("time"),
+            "boolean" => Some("boolean"),
+            _ => Some("string"),
+        }
+    }
+
+    /// Whether two types agree once both are in the seed vocabulary; a reference agrees with any type
+    pub fn same_data_type(a: &str, b: &str) -> bool {
+        match (Self::canonical_data_type(a), Self::canonical_data_type(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => true,
+        }
+    }
+}
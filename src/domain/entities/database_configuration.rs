@@ -1,9 +1,11 @@
 use serde::{Deserialize, Serialize};
 use bson::oid::ObjectId;
 use chrono::{DateTime, Utc};
+use crate::utils::redact;
 use crate::utils::utils::{date_format, object_id_format};
+use super::{ApiSourceSettings, OracleConnectionSettings, SqlServerConnectionSettings};
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct DatabaseConfiguration {
     #[serde(
         rename(serialize = "id", deserialize = "_id"),
@@ -21,9 +23,38 @@ pub struct DatabaseConfiguration {
     pub password: Option<String>,
     pub auth_type: Option<String>,
     pub credentials: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_source: Option<ApiSourceSettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oracle: Option<OracleConnectionSettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sqlserver: Option<SqlServerConnectionSettings>,
     pub company_id: String,
     #[serde(with = "crate::utils::utils::date_format")]
     pub created_at: DateTime<Utc>,
     #[serde(with = "crate::utils::utils::date_format")]
     pub updated_at: DateTime<Utc>,
 }
+
+/// Debug output never carries the password or credentials
+impl std::fmt::Debug for DatabaseConfiguration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DatabaseConfiguration")
+            .field("id", &self.id)
+            .field("name", &self.name)
+            .field("db_type", &self.db_type)
+            .field("version", &self.version)
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("database", &self.database)
+            .field("username", &self.username)
+            .field("password", &redact::secret(&self.password))
+            .field("auth_type", &self.auth_type)
+            .field("credentials", &redact::secret(&self.credentials))
+            .field("api_source", &self.api_source)
+            .field("oracle", &self.oracle)
+            .field("sqlserver", &self.sqlserver)
+            .field("company_id", &self.company_id)
+            .finish()
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 use bson::oid::ObjectId;
 use chrono::{DateTime, Utc};
 use crate::utils::utils::{date_format, object_id_format};
@@ -12,6 +13,63 @@ pub struct ResourceItem {
     pub resource: Option<String>,
 }
 
+/// How generated resources are written to the target FHIR server
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DeliveryMode {
+    /// `POST Patient` with `ifNoneExist`: existing resources are left untouched
+    #[default]
+    ConditionalCreate,
+    /// `PUT Patient?identifier=system|value`: creates or overwrites the matching resource
+    ConditionalUpdate,
+    /// Reads the matching resource and updates it by id with `If-Match` on its versionId,
+    /// so concurrent changes on the server are not overwritten
+    VersionedUpdate,
+}
+
+impl DeliveryMode {
+    /// Bundle entry `request` for a resource matched by `identifier_query` (`identifier=system|value`)
+    ///
+    /// Versioned updates are sent as conditional updates; the sink swaps in the id and `If-Match`
+    /// of the resource it finds on the server.
+    pub fn request(&self, resource_type: &str, identifier_query: &str) -> Value {
+        match self {
+            DeliveryMode::ConditionalCreate => json!({
+                "method": "POST",
+                "url": resource_type,
+                "ifNoneExist": identifier_query,
+            }),
+            DeliveryMode::ConditionalUpdate | DeliveryMode::VersionedUpdate => json!({
+                "method": "PUT",
+                "url": format!("{}?{}", resource_type, identifier_query),
+            }),
+        }
+    }
+}
+
+/// How references between generated resources (`relationshipDestiny`) are sent to the target
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReferenceStrategy {
+    /// `Patient?identifier=system|code`, resolved by the server when the entry is processed
+    #[default]
+    Conditional,
+    /// Looked up on the target by identifier and sent as `Patient/{id}`; references to resources
+    /// not on the target yet stay conditional
+    ResolveOrConditional,
+    /// Looked up on the target; references to resources not on the target yet are removed
+    ResolveOrOmit,
+    /// Looked up on the target; the entry is refused while the referenced resource does not exist
+    ResolveOrFail,
+}
+
+impl ReferenceStrategy {
+    /// Whether references are looked up on the target before delivery
+    pub fn resolves(&self) -> bool {
+        *self != ReferenceStrategy::Conditional
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct DatabaseView {
     #[serde(
@@ -31,6 +89,13 @@ pub struct DatabaseView {
     pub company_id: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub target_integration_id: Option<String>,
+    /// HL7 v2 message types routed to this view by the MLLP listener (e.g. ADT_A01, ORU_R01, or ADT for every ADT event)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_types: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delivery_mode: Option<DeliveryMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference_strategy: Option<ReferenceStrategy>,
     pub status: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub job_id: Option<String>,
@@ -45,3 +110,18 @@ pub struct DatabaseView {
     #[serde(with = "crate::utils::utils::date_format")]
     pub updated_at: DateTime<Utc>,
 }
+
+impl DatabaseView {
+    /// Source table read by the sync worker and checked for schema drift (e.g. PATIENT_INTERHEALTH)
+    pub fn source_table_name(&self) -> String {
+        format!("{}_INTERHEALTH", self.entity_type.to_uppercase())
+    }
+
+    pub fn delivery_mode(&self) -> DeliveryMode {
+        self.delivery_mode.unwrap_or_default()
+    }
+
+    pub fn reference_strategy(&self) -> ReferenceStrategy {
+        self.reference_strategy.unwrap_or_default()
+    }
+}
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use bson::oid::ObjectId;
 use chrono::{DateTime, Utc};
+use crate::utils::expression::Expression;
 use crate::utils::utils::object_id_format;
 use std::collections::HashMap;
 
@@ -16,6 +17,9 @@ pub struct FieldMapping {
     pub reference_destiny: Option<std::collections::HashMap<String, String>>,
     #[serde(default, rename = "relationshipDestiny", skip_serializing_if = "Option::is_none")]
     pub relationship_destiny: Option<String>,
+    /// Identifier system of the referenced resource; defaults to the ns-codigo NamingSystem
+    #[serde(default, rename = "referenceIdentifierSystem", skip_serializing_if = "Option::is_none")]
+    pub reference_identifier_system: Option<String>,
     #[serde(default, rename = "dataType", alias = "data_type")]
     pub data_type: String,
     #[serde(default, rename = "isNullable", alias = "is_nullable")]
@@ -30,6 +34,45 @@ pub struct FieldMapping {
     pub transformation_id: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub reference: Option<HashMap<String, String>>,
+    /// Value computed from the row's columns (see `utils::expression`) instead of copied from `fieldOrigin`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expression: Option<String>,
+    /// Time column merged with the date read from `fieldOrigin`; `dataType` (date, datetime, instant) sets the precision
+    #[serde(default, rename = "timeOrigin", alias = "time_origin", skip_serializing_if = "Option::is_none")]
+    pub time_origin: Option<String>,
+}
+
+impl FieldMapping {
+    /// Expression set for this field, if any
+    pub fn expression(&self) -> Option<&str> {
+        self.expression.as_deref().map(str::trim).filter(|expression| !expression.is_empty())
+    }
+
+    /// Lowercase source columns the field reads: `fieldOrigin`, `timeOrigin` and the columns of its expression
+    pub fn source_columns(&self) -> Vec<String> {
+        let expression_columns = self
+            .expression()
+            .and_then(|expression| Expression::parse(expression).ok())
+            .map(|expression| expression.columns())
+            .unwrap_or_default();
+
+        let mut columns: Vec<String> = Vec::new();
+        for column in std::iter::once(&self.field_origin).chain(&self.time_origin).map(|c| c.to_lowercase()).chain(expression_columns) {
+            if !column.is_empty() && !columns.contains(&column) {
+                columns.push(column);
+            }
+        }
+        columns
+    }
+
+    /// Placeholder written in the generated template and swapped for the row's value:
+    /// the origin column, or `=expression` for computed values
+    pub fn placeholder(&self) -> String {
+        match self.expression() {
+            Some(expression) => format!("={}", expression),
+            None => self.field_origin.clone(),
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use bson::oid::ObjectId;
+use chrono::{DateTime, SecondsFormat, Utc};
+use crate::domain::fhir::FhirVersion;
+use crate::utils::utils::object_id_format;
+
+/// Elements holding the patient a resource belongs to (searched by `patient`)
+const PATIENT_ELEMENTS: &[&str] = &["subject", "patient", "beneficiary"];
+
+/// Last delivered version of a resource, served read-only by the FHIR facade
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeliveredResource {
+    #[serde(
+        rename(serialize = "id", deserialize = "_id"),
+        skip_serializing_if = "Option::is_none",
+        with = "object_id_format"
+    )]
+    pub id: Option<ObjectId>,
+    pub company_id: String,
+    pub database_view_id: String,
+    pub resource_type: String,
+    /// Logical id on the facade, kept across versions
+    pub resource_id: String,
+    /// First identifier (`system|value`), which identifies the resource between deliveries
+    pub identifier_key: String,
+    /// Every identifier as `system|value`
+    pub identifiers: Vec<String>,
+    /// Every identifier value, for searches without a system
+    pub identifier_values: Vec<String>,
+    /// References to the patient (`Patient/123` or `Patient?identifier=system|code`)
+    #[serde(default)]
+    pub patient_references: Vec<String>,
+    pub version_id: i64,
+    /// Release the resource was generated for; the facade advertises it in its CapabilityStatement
+    #[serde(default)]
+    pub fhir_version: FhirVersion,
+    pub resource: Value,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub last_updated: DateTime<Utc>,
+}
+
+impl DeliveredResource {
+    /// Index a delivered resource; `None` when it has no type or no identifier to recognise it by
+    pub fn from_resource(company_id: &str, database_view_id: &str, fhir_version: FhirVersion, resource: &Value) -> Option<Self> {
+        let resource_type = resource.get("resourceType").and_then(Value::as_str).filter(|t| !t.is_empty())?;
+
+        let pairs: Vec<(&str, &str)> = resource.get("identifier")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|identifier| {
+                let value = identifier.get("value").and_then(Value::as_str).filter(|v| !v.is_empty())?;
+                Some((identifier.get("system").and_then(Value::as_str).unwrap_or_default(), value))
+            })
+            .collect();
+        let identifiers: Vec<String> = pairs.iter().map(|(system, value)| format!("{}|{}", system, value)).collect();
+
+        let patient_references = PATIENT_ELEMENTS
+            .iter()
+            .filter_map(|element| resource.pointer(&format!("/{}/reference", element)).and_then(Value::as_str))
+            .filter(|reference| reference.starts_with("Patient/") || reference.starts_with("Patient?"))
+            .map(str::to_string)
+            .collect();
+
+        Some(Self {
+            id: None,
+            company_id: company_id.to_string(),
+            database_view_id: database_view_id.to_string(),
+            resource_type: resource_type.to_string(),
+            resource_id: String::new(),
+            identifier_key: identifiers.first()?.clone(),
+            identifier_values: pairs.iter().map(|(_, value)| value.to_string()).collect(),
+            identifiers,
+            patient_references,
+            version_id: 1,
+            fhir_version,
+            resource: resource.clone(),
+            last_updated: Utc::now(),
+        })
+    }
+
+    /// The resource as served: logical id plus `meta.versionId` and `meta.lastUpdated`
+    pub fn to_fhir(&self) -> Value {
+        let mut resource = self.resource.clone();
+        if let Some(object) = resource.as_object_mut() {
+            object.insert("id".to_string(), json!(self.resource_id));
+            let meta = object.entry("meta").or_insert_with(|| json!({}));
+            if let Some(meta) = meta.as_object_mut() {
+                meta.insert("versionId".to_string(), json!(self.version_id.to_string()));
+                meta.insert("lastUpdated".to_string(), json!(self.last_updated.to_rfc3339_opts(SecondsFormat::Millis, true)));
+            }
+        }
+        resource
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_indexes_identifiers_and_patient() {
+        let encounter = json!({
+            "resourceType": "Encounter",
+            "identifier": [{ "system": "sys", "value": "e1" }, { "value": "" }],
+            "subject": { "reference": "Patient?identifier=sys|p1" },
+            "serviceProvider": { "reference": "Organization/o1" }
+        });
+
+        let mut delivered = DeliveredResource::from_resource("company", "view", FhirVersion::R4, &encounter).unwrap();
+        assert_eq!(delivered.identifier_key, "sys|e1");
+        assert_eq!(delivered.identifier_values, vec!["e1"]);
+        assert_eq!(delivered.patient_references, vec!["Patient?identifier=sys|p1"]);
+
+        delivered.resource_id = "abc".to_string();
+        delivered.version_id = 2;
+        let served = delivered.to_fhir();
+        assert_eq!(served["id"], "abc");
+        assert_eq!(served["meta"]["versionId"], "2");
+
+        assert!(DeliveredResource::from_resource("company", "view", FhirVersion::R4, &json!({ "resourceType": "Patient" })).is_none());
+    }
+}
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use bson::{oid::ObjectId, Document};
+use chrono::{DateTime, Utc};
+use crate::utils::utils::object_id_format;
+
+/// Column detected in an uploaded spreadsheet
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileDatasetColumn {
+    pub name: String,
+    pub data_type: String,
+    pub is_nullable: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<i32>,
+}
+
+/// Row rejected while importing an uploaded spreadsheet (row numbers are 1-based, header included)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileRowError {
+    pub row: u64,
+    pub message: String,
+}
+
+/// Spreadsheet uploaded against a FILE database configuration
+/// Each dataset backs one source table (e.g. PATIENT_INTERHEALTH); its rows live in `file_dataset_rows`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FileDataset {
+    #[serde(
+        rename(serialize = "id", deserialize = "_id"),
+        skip_serializing_if = "Option::is_none",
+        with = "object_id_format"
+    )]
+    pub id: Option<ObjectId>,
+    pub database_configuration_id: String,
+    pub database_table_id: Option<String>,
+    pub table_name: String,
+    pub file_name: String,
+    pub format: String,
+    pub columns: Vec<FileDatasetColumn>,
+    pub total_rows: u64,
+    pub imported_rows: u64,
+    #[serde(default)]
+    pub row_errors: Vec<FileRowError>,
+    pub company_id: String,
+    #[serde(with = "crate::utils::utils::date_format")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "crate::utils::utils::date_format")]
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A single imported row of a FileDataset
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FileDatasetRow {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub dataset_id: String,
+    pub row_number: u64,
+    pub data: Document,
+}
@@ -13,13 +13,23 @@ pub mod database_model_value;
 pub mod mapping_value;
 pub mod target_integration;
 pub mod integration_control;
+pub mod file_dataset;
+pub mod api_source;
+pub mod oracle_connection;
+pub mod sqlserver_connection;
+pub mod schema_discovery;
+pub mod schema_drift;
+pub mod connection_diagnostics;
+pub mod target_capabilities;
+pub mod terminology_resource;
+pub mod delivered_resource;
 
 pub use company::Company;
 pub use user::User;
 pub use database_configuration::DatabaseConfiguration;
 pub use database_column::DatabaseColumn;
 pub use database_table::DatabaseTable;
-pub use database_view::{DatabaseView, ResourceItem};
+pub use database_view::{DatabaseView, DeliveryMode, ReferenceStrategy, ResourceItem};
 pub use database_view_mapping::{DatabaseViewMapping, FieldMapping};
 pub use database_transformation::{DatabaseTransformation, ValueMappingItem};
 pub use sync::{SyncJobDocument, JobStatus};
@@ -27,5 +37,18 @@ pub use metrics_summary::{MetricsSummary, MetricsSummaryResponse, CategoryStats}
 pub use database_model::{DatabaseModel, ModelValue};
 pub use database_model_value::{DatabaseModelValue, DatabaseModelValueClient};
 pub use mapping_value::MappingValue;
-pub use target_integration::TargetIntegration;
+pub use target_integration::{CertificateFormat, ClientCertificate, TargetIntegration, CERTIFICATE_EXPIRY_WARNING_DAYS};
 pub use integration_control::IntegrationControl;
+pub use file_dataset::{FileDataset, FileDatasetColumn, FileDatasetRow, FileRowError};
+pub use api_source::{ApiSourceSettings, ApiPagination};
+pub use oracle_connection::{OracleAddress, OracleConnectMode, OracleConnectionSettings, OracleProtocol};
+pub use sqlserver_connection::SqlServerConnectionSettings;
+pub use schema_discovery::{
+    CatalogColumn, CatalogKey, ColumnChange, DiscoveredColumn, DiscoveredTable, ForeignKeyTarget,
+    SchemaChangeAction, SchemaDiscoveryPlan, SourceObjectKind, TableChange,
+};
+pub use schema_drift::{missing_mapped_columns, SchemaDriftReport};
+pub use connection_diagnostics::{ConnectionDiagnostics, DiagnosticCheck, SourceDiagnostics};
+pub use target_capabilities::{CompatibilityReport, TargetCapabilities};
+pub use terminology_resource::TerminologyResource;
+pub use delivered_resource::DeliveredResource;
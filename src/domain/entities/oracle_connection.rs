@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+
+/// Oracle-specific options of a database configuration
+///
+/// Without them an Oracle configuration connects with Easy Connect to `host:port/database`,
+/// `database` being the service name
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OracleConnectionSettings {
+    #[serde(default)]
+    pub mode: OracleConnectMode,
+    /// Instance SID (mode SID); falls back to `database`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+    /// Net service name resolved through tnsnames.ora of the client's TNS_ADMIN (mode TNS_ALIAS)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tns_alias: Option<String>,
+    /// Full `(DESCRIPTION=...)` or Easy Connect Plus string passed to the client as is (mode DESCRIPTOR)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub descriptor: Option<String>,
+    /// Extra listeners tried after `host:port`, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failover_addresses: Vec<OracleAddress>,
+    /// Spread connections across the addresses instead of trying them in order
+    #[serde(default)]
+    pub load_balance: bool,
+    #[serde(default)]
+    pub protocol: OracleProtocol,
+    /// Directory holding the wallet (cwallet.sso / ewallet.p12) used by TCPS
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wallet_location: Option<String>,
+    /// Expected distinguished name of the server certificate, e.g. `CN=db.hospital.local`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_cert_dn: Option<String>,
+    /// Authenticate with the credentials stored in the wallet instead of username/password
+    #[serde(default)]
+    pub external_auth: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_timeout_secs: Option<u32>,
+}
+
+/// How the target database is identified
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum OracleConnectMode {
+    #[default]
+    ServiceName,
+    Sid,
+    TnsAlias,
+    Descriptor,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum OracleProtocol {
+    #[default]
+    Tcp,
+    Tcps,
+}
+
+impl OracleProtocol {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OracleProtocol::Tcp => "TCP",
+            OracleProtocol::Tcps => "TCPS",
+        }
+    }
+}
+
+/// One listener of a failover address list
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OracleAddress {
+    pub host: String,
+    pub port: u16,
+}
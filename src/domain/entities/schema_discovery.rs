@@ -0,0 +1,215 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+/// Kind of object found in the source catalog
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum SourceObjectKind {
+    Table,
+    View,
+}
+
+impl SourceObjectKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SourceObjectKind::Table => "TABLE",
+            SourceObjectKind::View => "VIEW",
+        }
+    }
+}
+
+/// Column referenced by a foreign key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForeignKeyTarget {
+    pub table: String,
+    pub column: String,
+}
+
+/// A column as reported by the source catalog
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveredColumn {
+    pub name: String,
+    /// Native type (VARCHAR2, NUMBER, datetime2, ...)
+    pub source_type: String,
+    pub is_nullable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<i32>,
+    pub is_primary_key: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub foreign_key: Option<ForeignKeyTarget>,
+}
+
+/// A table or view accessible through a source connector
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveredTable {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    pub name: String,
+    pub kind: SourceObjectKind,
+    pub columns: Vec<DiscoveredColumn>,
+}
+
+/// One row of a catalog column listing (ALL_TAB_COLUMNS, INFORMATION_SCHEMA.COLUMNS, ...)
+#[derive(Debug, Clone)]
+pub struct CatalogColumn {
+    pub owner: Option<String>,
+    pub table: String,
+    pub kind: SourceObjectKind,
+    pub column: String,
+    pub source_type: String,
+    pub max_length: Option<i32>,
+    pub is_nullable: bool,
+}
+
+/// One column of a primary or foreign key constraint
+#[derive(Debug, Clone)]
+pub struct CatalogKey {
+    pub owner: Option<String>,
+    pub table: String,
+    pub column: String,
+    /// None for primary keys, the referenced column for foreign keys
+    pub references: Option<ForeignKeyTarget>,
+}
+
+impl DiscoveredTable {
+    /// Group flat catalog rows into tables, keeping the catalog's column order
+    pub fn from_catalog(columns: Vec<CatalogColumn>, keys: Vec<CatalogKey>) -> Vec<DiscoveredTable> {
+        let mut primary: HashMap<(Option<String>, String, String), bool> = HashMap::new();
+        let mut foreign: HashMap<(Option<String>, String, String), ForeignKeyTarget> = HashMap::new();
+
+        for key in keys {
+            let id = (key.owner, key.table, key.column);
+            match key.references {
+                Some(target) => {
+                    foreign.insert(id, target);
+                }
+                None => {
+                    primary.insert(id, true);
+                }
+            }
+        }
+
+        let mut tables: BTreeMap<(Option<String>, String), DiscoveredTable> = BTreeMap::new();
+
+        for column in columns {
+            let id = (column.owner.clone(), column.table.clone(), column.column.clone());
+            let table = tables
+                .entry((column.owner.clone(), column.table.clone()))
+                .or_insert_with(|| DiscoveredTable {
+                    owner: column.owner.clone(),
+                    name: column.table.clone(),
+                    kind: column.kind,
+                    columns: Vec::new(),
+                });
+
+            table.columns.push(DiscoveredColumn {
+                name: column.column,
+                source_type: column.source_type,
+                is_nullable: column.is_nullable,
+                max_length: column.max_length,
+                is_primary_key: primary.contains_key(&id),
+                foreign_key: foreign.remove(&id),
+            });
+        }
+
+        tables.into_values().collect()
+    }
+}
+
+/// What applying a discovery does to a table or column document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum SchemaChangeAction {
+    Create,
+    Update,
+    Unchanged,
+}
+
+/// Planned change for one DatabaseColumn
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnChange {
+    pub name: String,
+    pub action: SchemaChangeAction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub database_column_id: Option<String>,
+    pub source_type: String,
+    /// Simplified type stored in DatabaseColumn.data_type
+    pub data_type: String,
+    pub is_nullable: bool,
+    pub is_primary_key: bool,
+    pub is_foreign_key: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub foreign_key: Option<ForeignKeyTarget>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<i32>,
+    /// Fields that differ from the stored column (only for UPDATE)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changed_fields: Vec<String>,
+}
+
+/// Planned change for one DatabaseTable and its columns
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableChange {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    pub name: String,
+    pub kind: SourceObjectKind,
+    pub entity_type: String,
+    pub action: SchemaChangeAction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub database_table_id: Option<String>,
+    pub columns: Vec<ColumnChange>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaDiscoverySummary {
+    pub tables_created: usize,
+    pub tables_updated: usize,
+    pub tables_unchanged: usize,
+    pub columns_created: usize,
+    pub columns_updated: usize,
+}
+
+/// Result of a discovery: the preview before applying, or what was applied
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaDiscoveryPlan {
+    pub database_configuration_id: String,
+    pub applied: bool,
+    pub summary: SchemaDiscoverySummary,
+    pub tables: Vec<TableChange>,
+}
+
+impl SchemaDiscoveryPlan {
+    pub fn new(database_configuration_id: String, tables: Vec<TableChange>) -> Self {
+        let mut summary = SchemaDiscoverySummary::default();
+
+        for table in &tables {
+            match table.action {
+                SchemaChangeAction::Create => summary.tables_created += 1,
+                SchemaChangeAction::Update => summary.tables_updated += 1,
+                SchemaChangeAction::Unchanged => summary.tables_unchanged += 1,
+            }
+            for column in &table.columns {
+                match column.action {
+                    SchemaChangeAction::Create => summary.columns_created += 1,
+                    SchemaChangeAction::Update => summary.columns_updated += 1,
+                    SchemaChangeAction::Unchanged => {}
+                }
+            }
+        }
+
+        Self {
+            database_configuration_id,
+            applied: false,
+            summary,
+            tables,
+        }
+    }
+}
@@ -0,0 +1,344 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+use crate::utils::utils::object_id_format;
+
+use super::{DatabaseColumn, DatabaseViewMapping, FieldMapping};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DriftKind {
+    /// Live column not registered as a DatabaseColumn
+    Added,
+    /// Registered (or mapped) column missing from the live source
+    Removed,
+    /// Registered column whose simplified type no longer matches the source
+    TypeChanged,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnDrift {
+    pub column: String,
+    pub kind: DriftKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stored_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub live_type: Option<String>,
+}
+
+/// A FieldMapping whose `field_origin` is hit by a drift
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AffectedMapping {
+    pub mapping_id: String,
+    pub mapping_name: String,
+    pub field_origin: String,
+    pub field_destiny: String,
+    pub kind: DriftKind,
+}
+
+/// Latest drift check of a view: stored DatabaseColumns and mappings against the live source table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchemaDriftReport {
+    #[serde(
+        rename(serialize = "id", deserialize = "_id"),
+        skip_serializing_if = "Option::is_none",
+        with = "object_id_format"
+    )]
+    pub id: Option<ObjectId>,
+    pub database_view_id: String,
+    pub database_configuration_id: String,
+    pub table_name: String,
+    pub has_drift: bool,
+    /// True when a mapping reads a column the source no longer has; sync jobs for the view are refused
+    pub blocking: bool,
+    #[serde(default)]
+    pub columns: Vec<ColumnDrift>,
+    #[serde(default)]
+    pub affected_mappings: Vec<AffectedMapping>,
+    /// Set when the live source could not be read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(with = "crate::utils::utils::date_format")]
+    pub checked_at: DateTime<Utc>,
+}
+
+impl SchemaDriftReport {
+    /// Compare stored columns and mappings with the live (name, simplified type) column list
+    /// Stored seed types and simplified connector types are compared in the seed vocabulary
+    pub fn compare(
+        database_view_id: &str,
+        database_configuration_id: &str,
+        table_name: &str,
+        stored: &[DatabaseColumn],
+        live: &[(String, String)],
+        mappings: &[DatabaseViewMapping],
+    ) -> Self {
+        let live_types: HashMap<String, &str> = live
+            .iter()
+            .map(|(name, data_type)| (name.to_lowercase(), data_type.as_str()))
+            .collect();
+        let stored_types: HashMap<String, &str> = stored
+            .iter()
+            .map(|column| (column.name.to_lowercase(), column.data_type.as_str()))
+            .collect();
+
+        let mut columns = Vec::new();
+
+        for (name, data_type) in live {
+            if !stored_types.contains_key(&name.to_lowercase()) {
+                columns.push(ColumnDrift {
+                    column: name.to_lowercase(),
+                    kind: DriftKind::Added,
+                    stored_type: None,
+                    live_type: Some(data_type.clone()),
+                });
+            }
+        }
+
+        for column in stored {
+            let name = column.name.to_lowercase();
+            match live_types.get(&name) {
+                None => columns.push(ColumnDrift {
+                    column: name,
+                    kind: DriftKind::Removed,
+                    stored_type: Some(column.data_type.clone()),
+                    live_type: None,
+                }),
+                Some(live_type) if !DatabaseColumn::same_data_type(&column.data_type, live_type) => columns.push(ColumnDrift {
+                    column: name,
+                    kind: DriftKind::TypeChanged,
+                    stored_type: Some(column.data_type.clone()),
+                    live_type: Some(live_type.to_string()),
+                }),
+                Some(_) => {}
+            }
+        }
+
+        let type_changed: HashSet<String> = columns
+            .iter()
+            .filter(|drift| drift.kind == DriftKind::TypeChanged)
+            .map(|drift| drift.column.clone())
+            .collect();
+
+        let mut affected_mappings = Vec::new();
+        let mut reported_missing = HashSet::new();
+
+        for mapping in mappings {
+            for (field, origin) in mapping
+                .field_mappings
+                .iter()
+                .flat_map(|field| field.source_columns().into_iter().map(move |column| (field, column)))
+            {
+                let kind = if !live_types.contains_key(&origin) {
+                    DriftKind::Removed
+                } else if type_changed.contains(&origin) {
+                    DriftKind::TypeChanged
+                } else {
+                    continue;
+                };
+
+                // A mapped column may have never been registered; it is still gone from the source
+                if kind == DriftKind::Removed
+                    && !stored_types.contains_key(&origin)
+                    && reported_missing.insert(origin.clone())
+                {
+                    columns.push(ColumnDrift {
+                        column: origin.clone(),
+                        kind: DriftKind::Removed,
+                        stored_type: None,
+                        live_type: None,
+                    });
+                }
+
+                affected_mappings.push(AffectedMapping {
+                    mapping_id: mapping.id.map(|id| id.to_hex()).unwrap_or_default(),
+                    mapping_name: mapping.name.clone(),
+                    field_origin: origin,
+                    field_destiny: field.field_destiny.clone(),
+                    kind,
+                });
+            }
+        }
+
+        Self {
+            id: None,
+            database_view_id: database_view_id.to_string(),
+            database_configuration_id: database_configuration_id.to_string(),
+            table_name: table_name.to_string(),
+            has_drift: !columns.is_empty(),
+            blocking: affected_mappings.iter().any(|m| m.kind == DriftKind::Removed),
+            columns,
+            affected_mappings,
+            error: None,
+            checked_at: Utc::now(),
+        }
+    }
+
+    /// Report for a view whose source could not be read; it does not block sync on its own
+    pub fn failed(database_view_id: &str, database_configuration_id: &str, table_name: &str, error: String) -> Self {
+        Self {
+            id: None,
+            database_view_id: database_view_id.to_string(),
+            database_configuration_id: database_configuration_id.to_string(),
+            table_name: table_name.to_string(),
+            has_drift: false,
+            blocking: false,
+            columns: Vec::new(),
+            affected_mappings: Vec::new(),
+            error: Some(error),
+            checked_at: Utc::now(),
+        }
+    }
+}
+
+/// Source columns read by the mappings (origin, time and expression columns) that are missing from the live column names
+pub fn missing_mapped_columns(live_columns: &[String], mappings: &[DatabaseViewMapping]) -> Vec<String> {
+    let live: HashSet<String> = live_columns.iter().map(|name| name.to_lowercase()).collect();
+    let mut missing: Vec<String> = mappings
+        .iter()
+        .flat_map(|mapping| mapping.field_mappings.iter())
+        .flat_map(FieldMapping::source_columns)
+        .filter(|origin| !live.contains(origin))
+        .collect();
+
+    missing.sort();
+    missing.dedup();
+    missing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(name: &str, data_type: &str) -> DatabaseColumn {
+        DatabaseColumn {
+            id: None,
+            name: name.to_string(),
+            reference: None,
+            data_type: data_type.to_string(),
+            is_nullable: true,
+            is_primary_key: false,
+            is_foreign_key: false,
+            description: String::new(),
+            max_length: None,
+            min_length: None,
+            database_table_id: "table-1".to_string(),
+            company_id: "company".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn mapping(origins: &[&str]) -> DatabaseViewMapping {
+        let field = |origin: &str| FieldMapping {
+            field_origin: origin.to_string(),
+            field_destiny: format!("Patient.{}", origin),
+            description: None,
+            reference_destiny: None,
+            relationship_destiny: None,
+            reference_identifier_system: None,
+            data_type: "string".to_string(),
+            is_nullable: true,
+            min_length: 0,
+            max_length: 0,
+            is_enumerable: false,
+            transformation_id: None,
+            reference: None,
+            expression: None,
+            time_origin: None,
+        };
+
+        DatabaseViewMapping {
+            id: Some(ObjectId::new()),
+            name: "Paciente".to_string(),
+            description: String::new(),
+            entity_type: "PATIENT".to_string(),
+            resource: None,
+            database_table_origin_id: "table-1".to_string(),
+            database_table_destiny_id: String::new(),
+            data_view_id: "view-1".to_string(),
+            field_mappings: origins.iter().map(|origin| field(origin)).collect(),
+            status: "active".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_compare_flags_drift_and_affected_mappings() {
+        let stored = vec![
+            column("patient_code", "integer"),
+            column("patient_name", "string"),
+            column("patient_birth_date", "date"),
+        ];
+        let live = vec![
+            ("PATIENT_CODE".to_string(), "integer".to_string()),
+            ("PATIENT_BIRTH_DATE".to_string(), "varchar".to_string()),
+            ("PATIENT_NICKNAME".to_string(), "varchar".to_string()),
+        ];
+        let mappings = vec![mapping(&["patient_code", "patient_name", "patient_birth_date", "patient_mother"])];
+
+        let report = SchemaDriftReport::compare("view-1", "cfg-1", "PATIENT_INTERHEALTH", &stored, &live, &mappings);
+
+        let kind_of = |name: &str| report.columns.iter().find(|c| c.column == name).map(|c| c.kind);
+        assert_eq!(kind_of("patient_nickname"), Some(DriftKind::Added));
+        assert_eq!(kind_of("patient_name"), Some(DriftKind::Removed));
+        assert_eq!(kind_of("patient_birth_date"), Some(DriftKind::TypeChanged));
+        assert_eq!(kind_of("patient_mother"), Some(DriftKind::Removed));
+        assert_eq!(kind_of("patient_code"), None);
+
+        let affected: Vec<(&str, DriftKind)> = report
+            .affected_mappings
+            .iter()
+            .map(|m| (m.field_origin.as_str(), m.kind))
+            .collect();
+        assert_eq!(
+            affected,
+            vec![
+                ("patient_name", DriftKind::Removed),
+                ("patient_birth_date", DriftKind::TypeChanged),
+                ("patient_mother", DriftKind::Removed),
+            ]
+        );
+        assert!(report.has_drift);
+        assert!(report.blocking);
+
+        let live_names: Vec<String> = live.iter().map(|(name, _)| name.clone()).collect();
+        assert_eq!(
+            missing_mapped_columns(&live_names, &mappings),
+            vec!["patient_mother".to_string(), "patient_name".to_string()]
+        );
+
+        // Columns read only inside an expression count too
+        let mut computed = mapping(&["patient_code"]);
+        computed.field_mappings[0].expression = Some("concat(patient_code, '-', patient_name)".to_string());
+        assert_eq!(missing_mapped_columns(&live_names, &[computed]), vec!["patient_name".to_string()]);
+    }
+
+    #[test]
+    fn test_compare_reads_seed_types_in_connector_vocabulary() {
+        let stored = vec![
+            column("patient_name", "string"),
+            column("patient_admitted_at", "datetime"),
+            column("patient_practitioner", "reference"),
+            column("patient_code", "integer"),
+        ];
+        let live = vec![
+            ("PATIENT_NAME".to_string(), "varchar".to_string()),
+            ("PATIENT_ADMITTED_AT".to_string(), "timestamp".to_string()),
+            ("PATIENT_PRACTITIONER".to_string(), "integer".to_string()),
+            ("PATIENT_CODE".to_string(), "number".to_string()),
+        ];
+
+        let report = SchemaDriftReport::compare("view-1", "cfg-1", "PATIENT_INTERHEALTH", &stored, &live, &[]);
+        assert!(!report.has_drift, "{:?}", report.columns);
+
+        let live = vec![("PATIENT_ADMITTED_AT".to_string(), "varchar".to_string())];
+        let report = SchemaDriftReport::compare("view-1", "cfg-1", "PATIENT_INTERHEALTH", &stored[1..2], &live, &[]);
+        assert_eq!(report.columns[0].kind, DriftKind::TypeChanged);
+    }
+}
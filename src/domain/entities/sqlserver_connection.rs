@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+/// SQL Server-specific options of a database configuration
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SqlServerConnectionSettings {
+    /// Accept the server certificate without validating it (self-signed certificates on local installs)
+    #[serde(default)]
+    pub trust_server_certificate: bool,
+}
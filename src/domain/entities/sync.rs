@@ -21,6 +21,10 @@ pub struct SyncJobDocument {
     
     /// Tipo de entidade (encounter, patient, etc)
     pub entity_type: String,
+
+    /// Sincronização ou exportação NDJSON
+    #[serde(default)]
+    pub kind: crate::sync::job::JobKind,
     
     /// Status do job
     pub status: JobStatus,
@@ -43,6 +47,10 @@ pub struct SyncJobDocument {
     /// Códigos dos itens que falharam (patient_code, encounter_code, etc)
     #[serde(default)]
     pub failed_item_codes: Vec<String>,
+
+    /// Arquivos NDJSON gerados por um job de exportação
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub output: Vec<crate::sync::job::ExportOutput>,
     
     /// Data de criação
     #[serde(with = "date_format")]
@@ -89,6 +97,7 @@ impl SyncJobDocument {
             database_config_id: job.database_config_id.clone(),
             company_id: job.company_id.clone(),
             entity_type: job.entity_type.clone(),
+            kind: job.kind,
             status: Self::convert_status(&job.status),
             total_records: job.total_records,
             processed_records: job.processed_records,
@@ -96,6 +105,7 @@ impl SyncJobDocument {
             current_page: job.current_page,
             page_size: job.page_size,
             failed_item_codes: job.failed_item_codes.clone(),
+            output: job.output.clone(),
             created_at: job.created_at,
             started_at: job.started_at,
             finished_at: job.finished_at,
@@ -124,6 +134,7 @@ impl SyncJobDocument {
         self.started_at = job.started_at;
         self.finished_at = job.finished_at;
         self.failed_item_codes = job.failed_item_codes.clone();
+        self.output = job.output.clone();
     }
     
     /// Converte documento MongoDB de volta para job em memória
@@ -134,6 +145,7 @@ impl SyncJobDocument {
             database_config_id: self.database_config_id.clone(),
             database_view_id: self.database_view_id.clone(),
             entity_type: self.entity_type.clone(),
+            kind: self.kind,
             company_id: self.company_id.clone(),
             status: Self::convert_status_back(&self.status),
             total_records: self.total_records,
@@ -142,6 +154,7 @@ impl SyncJobDocument {
             current_page: self.current_page,
             page_size: self.page_size,
             failed_item_codes: self.failed_item_codes.clone(),
+            output: self.output.clone(),
             started_at: self.started_at,
             finished_at: self.finished_at,
             created_at: self.created_at,
@@ -0,0 +1,380 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use chrono::{DateTime, Utc};
+
+use crate::utils::AppError;
+use super::DeliveryMode;
+
+/// Whole-system interaction used to deliver resources (Bundle of type transaction)
+pub const REQUIRED_SYSTEM_INTERACTION: &str = "transaction";
+
+/// What a target FHIR server declares in its CapabilityStatement (`GET [base]/metadata`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TargetCapabilities {
+    /// e.g. 4.0.1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fhir_version: Option<String>,
+    /// Server software name and version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub software: Option<String>,
+    #[serde(default)]
+    pub formats: Vec<String>,
+    /// Whole-system interactions (transaction, batch, search-system, history-system)
+    #[serde(default)]
+    pub system_interactions: Vec<String>,
+    #[serde(default)]
+    pub resources: Vec<ResourceCapability>,
+    #[serde(with = "crate::utils::utils::date_format")]
+    pub discovered_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceCapability {
+    pub resource_type: String,
+    /// read, vread, update, patch, delete, history-instance, history-type, create, search-type
+    #[serde(default)]
+    pub interactions: Vec<String>,
+    /// Base profile and supported profiles (canonical URLs)
+    #[serde(default)]
+    pub profiles: Vec<String>,
+    #[serde(default)]
+    pub conditional_create: bool,
+    #[serde(default)]
+    pub conditional_update: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CompatibilitySeverity {
+    /// Delivery may work but behave differently (e.g. duplicates without conditional create)
+    Warning,
+    /// Delivery would be rejected by the target; integrations of the view are refused
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompatibilityIssue {
+    pub severity: CompatibilitySeverity,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_type: Option<String>,
+    pub message: String,
+}
+
+impl CompatibilityIssue {
+    fn error(resource_type: Option<&str>, message: impl Into<String>) -> Self {
+        Self { severity: CompatibilitySeverity::Error, resource_type: resource_type.map(str::to_string), message: message.into() }
+    }
+
+    fn warning(resource_type: Option<&str>, message: impl Into<String>) -> Self {
+        Self { severity: CompatibilitySeverity::Warning, resource_type: resource_type.map(str::to_string), message: message.into() }
+    }
+}
+
+/// Resource types a view produces against what its target integration supports
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompatibilityReport {
+    pub database_view_id: String,
+    pub target_integration_id: String,
+    /// False when any issue is an error
+    pub compatible: bool,
+    pub resource_types: Vec<String>,
+    pub issues: Vec<CompatibilityIssue>,
+    #[serde(with = "crate::utils::utils::date_format")]
+    pub checked_at: DateTime<Utc>,
+}
+
+impl CompatibilityReport {
+    /// Compare the resource types of a view with the target's capabilities; without discovered
+    /// capabilities the check cannot fail, it only warns
+    pub fn check(
+        database_view_id: &str,
+        target_integration_id: &str,
+        target_version: Option<&str>,
+        delivery_mode: DeliveryMode,
+        capabilities: Option<&TargetCapabilities>,
+        resource_types: Vec<String>,
+    ) -> Self {
+        let issues = match capabilities {
+            Some(capabilities) => capabilities.issues(target_version, delivery_mode, &resource_types),
+            None => vec![CompatibilityIssue::warning(
+                None,
+                "CapabilityStatement do destino ainda não foi obtido; execute a descoberta",
+            )],
+        };
+
+        Self {
+            database_view_id: database_view_id.to_string(),
+            target_integration_id: target_integration_id.to_string(),
+            compatible: !issues.iter().any(|issue| issue.severity == CompatibilitySeverity::Error),
+            resource_types,
+            issues,
+            checked_at: Utc::now(),
+        }
+    }
+
+    pub fn errors(&self) -> Vec<&str> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == CompatibilitySeverity::Error)
+            .map(|issue| issue.message.as_str())
+            .collect()
+    }
+}
+
+impl TargetCapabilities {
+    /// Read the server part (`rest.mode = server`) of a CapabilityStatement
+    pub fn from_capability_statement(statement: &Value) -> Result<Self, AppError> {
+        if statement.get("resourceType").and_then(Value::as_str) != Some("CapabilityStatement") {
+            return Err(AppError::Validation("A resposta de /metadata não é um CapabilityStatement".to_string()));
+        }
+
+        let software = statement.get("software").and_then(|software| {
+            let name = software.get("name").and_then(Value::as_str)?;
+            Some(match software.get("version").and_then(Value::as_str) {
+                Some(version) => format!("{} {}", name, version),
+                None => name.to_string(),
+            })
+        });
+
+        let server = statement
+            .get("rest")
+            .and_then(Value::as_array)
+            .and_then(|rest| rest.iter().find(|rest| rest.get("mode").and_then(Value::as_str) == Some("server")));
+
+        let resources = server
+            .and_then(|server| server.get("resource"))
+            .and_then(Value::as_array)
+            .map(|resources| resources.iter().filter_map(ResourceCapability::from_json).collect())
+            .unwrap_or_default();
+
+        Ok(Self {
+            fhir_version: statement.get("fhirVersion").and_then(Value::as_str).map(str::to_string),
+            software,
+            formats: strings(statement.get("format")),
+            system_interactions: server.map(|server| interaction_codes(server.get("interaction"))).unwrap_or_default(),
+            resources,
+            discovered_at: Utc::now(),
+        })
+    }
+
+    pub fn resource(&self, resource_type: &str) -> Option<&ResourceCapability> {
+        self.resources.iter().find(|resource| resource.resource_type == resource_type)
+    }
+
+    fn issues(&self, target_version: Option<&str>, delivery_mode: DeliveryMode, resource_types: &[String]) -> Vec<CompatibilityIssue> {
+        let mut issues = Vec::new();
+
+        match (target_version.and_then(fhir_release), self.fhir_version.as_deref()) {
+            (Some(expected), Some(declared)) if fhir_release(declared) != Some(expected) => {
+                issues.push(CompatibilityIssue::error(
+                    None,
+                    format!("Destino declara FHIR {} mas a integração está configurada para {}", declared, expected),
+                ));
+            }
+            (Some(_), None) => issues.push(CompatibilityIssue::warning(None, "Destino não declara fhirVersion")),
+            _ => {}
+        }
+
+        if !self.system_interactions.iter().any(|code| code == REQUIRED_SYSTEM_INTERACTION) {
+            issues.push(CompatibilityIssue::error(
+                None,
+                "Destino não suporta a interação transaction, usada para enviar os recursos",
+            ));
+        }
+
+        for resource_type in resource_types {
+            let Some(resource) = self.resource(resource_type) else {
+                issues.push(CompatibilityIssue::error(
+                    Some(resource_type),
+                    format!("Destino não suporta o recurso {}", resource_type),
+                ));
+                continue;
+            };
+
+            let supports = |code: &str| resource.interactions.iter().any(|interaction| interaction == code);
+            match delivery_mode {
+                DeliveryMode::ConditionalCreate if !supports("create") => {
+                    issues.push(CompatibilityIssue::error(
+                        Some(resource_type),
+                        format!("Destino não permite create de {}", resource_type),
+                    ));
+                }
+                DeliveryMode::ConditionalCreate if !resource.conditional_create => {
+                    issues.push(CompatibilityIssue::warning(
+                        Some(resource_type),
+                        format!("Destino não declara conditionalCreate para {}; reenvios podem duplicar registros", resource_type),
+                    ));
+                }
+                DeliveryMode::ConditionalCreate => {}
+                DeliveryMode::ConditionalUpdate if !supports("update") => {
+                    issues.push(CompatibilityIssue::error(
+                        Some(resource_type),
+                        format!("Destino não permite update de {}", resource_type),
+                    ));
+                }
+                DeliveryMode::ConditionalUpdate if !resource.conditional_update => {
+                    issues.push(CompatibilityIssue::error(
+                        Some(resource_type),
+                        format!("Destino não declara conditionalUpdate para {}; PUT {}?identifier=... seria rejeitado", resource_type, resource_type),
+                    ));
+                }
+                DeliveryMode::ConditionalUpdate => {}
+                DeliveryMode::VersionedUpdate => {
+                    for code in ["search-type", "create", "update"] {
+                        if !supports(code) {
+                            issues.push(CompatibilityIssue::error(
+                                Some(resource_type),
+                                format!("Destino não permite {} de {}", code, resource_type),
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        issues
+    }
+}
+
+impl ResourceCapability {
+    fn from_json(resource: &Value) -> Option<Self> {
+        let mut profiles: Vec<String> = resource
+            .get("profile")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .into_iter()
+            .collect();
+        profiles.extend(strings(resource.get("supportedProfile")));
+
+        Some(Self {
+            resource_type: resource.get("type").and_then(Value::as_str)?.to_string(),
+            interactions: interaction_codes(resource.get("interaction")),
+            profiles,
+            conditional_create: resource.get("conditionalCreate").and_then(Value::as_bool).unwrap_or(false),
+            conditional_update: resource.get("conditionalUpdate").and_then(Value::as_bool).unwrap_or(false),
+        })
+    }
+}
+
+/// FHIR release of a version as configured (R4, 4.0.1, r4b, 5.0.0, STU3)
+pub fn fhir_release(version: &str) -> Option<&'static str> {
+    let version = version.trim().to_uppercase();
+    match version.as_str() {
+        "STU3" | "R3" => return Some("STU3"),
+        "R4" => return Some("R4"),
+        "R4B" => return Some("R4B"),
+        "R5" => return Some("R5"),
+        _ => {}
+    }
+
+    let mut parts = version.split('.');
+    match (parts.next(), parts.next()) {
+        (Some("3"), Some("0")) => Some("STU3"),
+        (Some("4"), Some("0")) => Some("R4"),
+        (Some("4"), Some("3")) => Some("R4B"),
+        (Some("5"), Some("0")) => Some("R5"),
+        _ => None,
+    }
+}
+
+fn interaction_codes(interactions: Option<&Value>) -> Vec<String> {
+    interactions
+        .and_then(Value::as_array)
+        .map(|interactions| {
+            interactions
+                .iter()
+                .filter_map(|interaction| interaction.get("code").and_then(Value::as_str).map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn strings(value: Option<&Value>) -> Vec<String> {
+    value
+        .and_then(Value::as_array)
+        .map(|values| values.iter().filter_map(Value::as_str).map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn statement() -> Value {
+        json!({
+            "resourceType": "CapabilityStatement",
+            "fhirVersion": "4.0.1",
+            "software": { "name": "HAPI FHIR Server", "version": "7.2.0" },
+            "format": ["application/fhir+json"],
+            "rest": [{
+                "mode": "server",
+                "interaction": [{ "code": "transaction" }, { "code": "batch" }],
+                "resource": [
+                    {
+                        "type": "Patient",
+                        "profile": "http://hl7.org/fhir/StructureDefinition/Patient",
+                        "supportedProfile": ["http://www.saude.gov.br/fhir/r4/StructureDefinition/BRIndividuo-1.0"],
+                        "interaction": [{ "code": "read" }, { "code": "create" }],
+                        "conditionalCreate": true
+                    },
+                    { "type": "Observation", "interaction": [{ "code": "create" }] },
+                    { "type": "Practitioner", "interaction": [{ "code": "read" }] }
+                ]
+            }]
+        })
+    }
+
+    #[test]
+    fn test_parses_server_capabilities() {
+        let capabilities = TargetCapabilities::from_capability_statement(&statement()).unwrap();
+
+        assert_eq!(capabilities.fhir_version.as_deref(), Some("4.0.1"));
+        assert_eq!(capabilities.software.as_deref(), Some("HAPI FHIR Server 7.2.0"));
+        assert_eq!(capabilities.system_interactions, vec!["transaction", "batch"]);
+        let patient = capabilities.resource("Patient").unwrap();
+        assert_eq!(patient.profiles.len(), 2);
+        assert!(patient.conditional_create);
+
+        assert!(TargetCapabilities::from_capability_statement(&json!({ "resourceType": "Bundle" })).is_err());
+    }
+
+    #[test]
+    fn test_reports_unsupported_resources_and_versions() {
+        let capabilities = TargetCapabilities::from_capability_statement(&statement()).unwrap();
+        let types = |names: &[&str]| names.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+
+        let report = CompatibilityReport::check("view", "target", Some("R4"), DeliveryMode::ConditionalCreate, Some(&capabilities), types(&["Patient"]));
+        assert!(report.compatible);
+        assert!(report.issues.is_empty());
+
+        let report = CompatibilityReport::check(
+            "view",
+            "target",
+            Some("4.0.1"),
+            DeliveryMode::ConditionalCreate,
+            Some(&capabilities),
+            types(&["Observation", "Practitioner", "Encounter"]),
+        );
+        assert!(!report.compatible);
+        assert_eq!(report.errors().len(), 2);
+        assert_eq!(report.issues[0].severity, CompatibilitySeverity::Warning);
+
+        let report = CompatibilityReport::check("view", "target", Some("R5"), DeliveryMode::ConditionalCreate, Some(&capabilities), types(&["Patient"]));
+        assert!(!report.compatible);
+
+        let report = CompatibilityReport::check("view", "target", Some("R4"), DeliveryMode::ConditionalCreate, None, types(&["Patient"]));
+        assert!(report.compatible);
+        assert_eq!(report.issues.len(), 1);
+
+        // Patient allows create but not update
+        let report = CompatibilityReport::check("view", "target", Some("R4"), DeliveryMode::ConditionalUpdate, Some(&capabilities), types(&["Patient"]));
+        assert_eq!(report.errors(), vec!["Destino não permite update de Patient"]);
+        let report = CompatibilityReport::check("view", "target", Some("R4"), DeliveryMode::VersionedUpdate, Some(&capabilities), types(&["Patient"]));
+        assert_eq!(report.errors().len(), 2);
+    }
+}
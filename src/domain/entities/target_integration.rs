@@ -1,9 +1,11 @@
 use serde::{Deserialize, Serialize};
 use bson::oid::ObjectId;
 use chrono::{DateTime, Utc};
+use super::TargetCapabilities;
+use crate::utils::redact;
 use crate::utils::utils::object_id_format;
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TargetIntegration {
     #[serde(
@@ -17,9 +19,93 @@ pub struct TargetIntegration {
     pub host: String,
     pub auth_type: Option<String>,
     pub credentials: Option<String>,
+    /// Client certificate for targets that require mutual TLS
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_certificate: Option<ClientCertificate>,
+    /// CapabilityStatement of the server, from the latest discovery
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capabilities: Option<TargetCapabilities>,
     pub company_id: String,
     #[serde(with = "crate::utils::utils::date_format")]
     pub created_at: DateTime<Utc>,
     #[serde(with = "crate::utils::utils::date_format")]
     pub updated_at: DateTime<Utc>,
 }
+
+/// Debug output never carries the credentials
+impl std::fmt::Debug for TargetIntegration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TargetIntegration")
+            .field("id", &self.id)
+            .field("name", &self.name)
+            .field("version", &self.version)
+            .field("host", &self.host)
+            .field("auth_type", &self.auth_type)
+            .field("credentials", &redact::secret(&self.credentials))
+            .field("client_certificate", &self.client_certificate)
+            .field("company_id", &self.company_id)
+            .finish()
+    }
+}
+
+/// Days before `notAfter` from which a client certificate is reported as expiring
+pub const CERTIFICATE_EXPIRY_WARNING_DAYS: i64 = 30;
+
+/// Encoding of an uploaded client certificate
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum CertificateFormat {
+    /// `.pfx` / `.p12` bundle with certificate, chain and key, usually password protected
+    Pkcs12,
+    /// Certificate chain and PKCS#8 private key as separate PEM files
+    Pem,
+}
+
+/// Client certificate presented to the target (mTLS), e.g. an ICP-Brasil A1 certificate
+///
+/// `certificate`, `private_key` and `password` are sealed at rest; subject, issuer and expiry
+/// are read once at upload so they can be reported without opening the bundle.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientCertificate {
+    pub format: CertificateFormat,
+    /// Base64 PKCS#12 bundle, or the PEM certificate chain
+    pub certificate: String,
+    /// PEM format only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_key: Option<String>,
+    /// PKCS#12 format only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// PEM bundle of CAs trusted for the target besides the system roots
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_bundle: Option<String>,
+    pub subject: String,
+    pub issuer: String,
+    #[serde(with = "crate::utils::utils::date_format")]
+    pub not_after: DateTime<Utc>,
+}
+
+impl ClientCertificate {
+    /// Whole days left until `not_after`; negative once expired
+    pub fn days_until_expiry(&self, now: DateTime<Utc>) -> i64 {
+        (self.not_after - now).num_days()
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.not_after <= now
+    }
+}
+
+/// Debug output never carries the certificate material
+impl std::fmt::Debug for ClientCertificate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientCertificate")
+            .field("format", &self.format)
+            .field("subject", &self.subject)
+            .field("issuer", &self.issuer)
+            .field("not_after", &self.not_after)
+            .field("ca_bundle", &self.ca_bundle.is_some())
+            .finish()
+    }
+}
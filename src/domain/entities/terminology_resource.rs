@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+use crate::utils::utils::object_id_format;
+
+/// A CodeSystem or ValueSet uploaded as JSON, reloaded into the terminology at startup
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TerminologyResource {
+    #[serde(
+        rename(serialize = "id", deserialize = "_id"),
+        skip_serializing_if = "Option::is_none",
+        with = "object_id_format"
+    )]
+    pub id: Option<ObjectId>,
+    /// Canonical url, without version
+    pub url: String,
+    pub resource_type: String,
+    pub resource: Value,
+    #[serde(with = "crate::utils::utils::date_format")]
+    pub updated_at: DateTime<Utc>,
+}
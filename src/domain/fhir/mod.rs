@@ -1 +1,12 @@
+pub mod profiles;
 pub mod r4;
+pub mod r4b;
+pub mod r5;
+pub mod reference;
+pub mod terminology;
+pub mod version;
+
+pub use profiles::ProfileRegistry;
+pub use reference::{ConditionalReference, DEFAULT_IDENTIFIER_SYSTEM};
+pub use terminology::{CodeCheck, Terminology};
+pub use version::{FhirVersion, PathRename};
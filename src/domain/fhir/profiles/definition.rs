@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+/// The parts of an ElementDefinition the validator checks
+#[derive(Debug, Clone, Default)]
+pub struct ElementDefinition {
+    pub id: String,
+    pub path: String,
+    pub slice_name: Option<String>,
+    pub min: Option<u64>,
+    /// `*`, or the maximum as text
+    pub max: Option<String>,
+    pub types: Vec<String>,
+    /// `#Questionnaire.item`, for elements defined elsewhere in the same structure
+    pub content_reference: Option<String>,
+    /// `fixed[x]`, compared exactly
+    pub fixed: Option<Value>,
+    /// `pattern[x]`, compared as a subset
+    pub pattern: Option<Value>,
+    pub binding: Option<Binding>,
+    pub slicing: Option<Slicing>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Binding {
+    /// required, extensible, preferred or example
+    pub strength: String,
+    pub value_set: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Slicing {
+    /// (type, path) pairs, e.g. ("value", "system")
+    pub discriminators: Vec<(String, String)>,
+    pub closed: bool,
+}
+
+impl ElementDefinition {
+    pub fn from_json(element: &Value) -> Option<Self> {
+        let path = element.get("path")?.as_str()?.to_string();
+        let id = element.get("id").and_then(Value::as_str).map(str::to_string).unwrap_or_else(|| path.clone());
+
+        let mut definition = ElementDefinition { id, path, ..Default::default() };
+        definition.merge(element);
+        Some(definition)
+    }
+
+    /// Overlay the constraints a differential element sets
+    pub fn merge(&mut self, element: &Value) {
+        let Some(object) = element.as_object() else {
+            return;
+        };
+
+        for (key, value) in object {
+            match key.as_str() {
+                "sliceName" => self.slice_name = value.as_str().map(str::to_string),
+                "min" => self.min = value.as_u64(),
+                "max" => self.max = value.as_str().map(str::to_string),
+                "contentReference" => self.content_reference = value.as_str().map(str::to_string),
+                "type" => {
+                    let types: Vec<String> = value
+                        .as_array()
+                        .map(|types| {
+                            types
+                                .iter()
+                                .filter_map(|t| t.get("code").and_then(Value::as_str))
+                                .map(str::to_string)
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    if !types.is_empty() {
+                        self.types = types;
+                    }
+                }
+                "binding" => {
+                    self.binding = value.get("strength").and_then(Value::as_str).map(|strength| Binding {
+                        strength: strength.to_string(),
+                        value_set: value.get("valueSet").and_then(Value::as_str).map(|url| canonical(url).to_string()),
+                    });
+                }
+                "slicing" => {
+                    self.slicing = Some(Slicing {
+                        discriminators: value
+                            .get("discriminator")
+                            .and_then(Value::as_array)
+                            .map(|discriminators| {
+                                discriminators
+                                    .iter()
+                                    .filter_map(|d| {
+                                        Some((
+                                            d.get("type")?.as_str()?.to_string(),
+                                            d.get("path")?.as_str()?.to_string(),
+                                        ))
+                                    })
+                                    .collect()
+                            })
+                            .unwrap_or_default(),
+                        closed: value.get("rules").and_then(Value::as_str) == Some("closed"),
+                    });
+                }
+                _ if key.starts_with("fixed") => self.fixed = Some(value.clone()),
+                _ if key.starts_with("pattern") => self.pattern = Some(value.clone()),
+                _ => {}
+            }
+        }
+    }
+
+    /// Last element of the path, e.g. `value[x]`
+    pub fn name(&self) -> &str {
+        self.path.rsplit('.').next().unwrap_or(&self.path)
+    }
+
+    pub fn is_prohibited(&self) -> bool {
+        self.max.as_deref() == Some("0")
+    }
+
+    pub fn is_repeating(&self) -> bool {
+        !matches!(self.max.as_deref(), Some("0") | Some("1") | None)
+    }
+
+    /// This element with the type and cardinality it leaves unset taken from `base`
+    pub fn or_inherit(&self, base: &ElementDefinition) -> ElementDefinition {
+        ElementDefinition {
+            min: self.min.or(base.min),
+            max: self.max.clone().or_else(|| base.max.clone()),
+            types: if self.types.is_empty() { base.types.clone() } else { self.types.clone() },
+            ..self.clone()
+        }
+    }
+
+    pub fn max_count(&self) -> Option<usize> {
+        match self.max.as_deref() {
+            Some("*") | None => None,
+            Some(max) => max.parse().ok(),
+        }
+    }
+}
+
+/// A StructureDefinition's snapshot, indexed by element id
+#[derive(Debug, Clone)]
+pub struct Profile {
+    pub url: String,
+    pub name: String,
+    /// Type the profile constrains, e.g. `Patient`
+    pub type_name: String,
+    elements: HashMap<String, ElementDefinition>,
+    /// Unsliced child ids by parent id, in definition order
+    children: HashMap<String, Vec<String>>,
+    /// Slice ids by the id of the sliced element
+    slices: HashMap<String, Vec<String>>,
+}
+
+impl Profile {
+    pub fn new(url: String, name: String, type_name: String, elements: Vec<ElementDefinition>) -> Self {
+        let mut profile = Profile {
+            url,
+            name,
+            type_name,
+            elements: HashMap::new(),
+            children: HashMap::new(),
+            slices: HashMap::new(),
+        };
+        for element in elements {
+            profile.insert(element);
+        }
+        profile
+    }
+
+    /// Profile constraining this one with a differential, for packages published without snapshots
+    pub fn derive(&self, url: String, name: String, differential: &[Value]) -> Profile {
+        let mut profile = Profile { url, name, ..self.clone() };
+
+        for element in differential {
+            let Some(id) = element.get("id").or_else(|| element.get("path")).and_then(Value::as_str) else {
+                continue;
+            };
+            match profile.elements.get_mut(id) {
+                Some(existing) => existing.merge(element),
+                // Slices and their children start from the element they slice
+                None => {
+                    let inherited = profile.element(id).cloned().map(|mut base| {
+                        base.id = id.to_string();
+                        base.slice_name = None;
+                        base.slicing = None;
+                        base.merge(element);
+                        base
+                    });
+                    if let Some(definition) = inherited.or_else(|| ElementDefinition::from_json(element)) {
+                        profile.insert(definition);
+                    }
+                }
+            }
+        }
+
+        profile
+    }
+
+    pub fn insert(&mut self, element: ElementDefinition) {
+        let id = element.id.clone();
+        if self.elements.insert(id.clone(), element).is_some() {
+            return;
+        }
+
+        let Some((parent, name)) = id.rsplit_once('.') else {
+            return;
+        };
+        match name.split_once(':') {
+            Some((base, _)) => self.slices.entry(format!("{}.{}", parent, base)).or_default().push(id),
+            None => self.children.entry(parent.to_string()).or_default().push(id),
+        }
+    }
+
+    /// Element at `id`; inside a slice, elements the slice does not constrain come from the sliced element
+    pub fn element(&self, id: &str) -> Option<&ElementDefinition> {
+        unsliced_variants(id).find_map(|variant| self.elements.get(&variant))
+    }
+
+    /// Names of the child elements of `id`, including those inherited from the sliced element
+    pub fn child_names(&self, id: &str) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+
+        for parent in unsliced_variants(id) {
+            for child in self.children.get(&parent).into_iter().flatten() {
+                let name = child[parent.len() + 1..].to_string();
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+
+        names
+    }
+
+    /// Slice names of the sliced element at `id`
+    pub fn slice_names(&self, id: &str) -> Vec<String> {
+        unsliced_variants(id)
+            .find_map(|variant| {
+                self.slices.get(&variant).map(|slices| {
+                    slices.iter().map(|slice| slice[variant.len() + 1..].to_string()).collect()
+                })
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// `id` itself, then `id` with its slice names removed one at a time from the left:
+/// `A.b:x.c:y.d` -> `A.b:x.c:y.d`, `A.b.c:y.d`, `A.b.c.d`
+fn unsliced_variants(id: &str) -> impl Iterator<Item = String> {
+    std::iter::successors(Some(id.to_string()), |current| {
+        let start = current.find(':')?;
+        let end = current[start..].find('.').map(|offset| start + offset).unwrap_or(current.len());
+        Some(format!("{}{}", &current[..start], &current[end..]))
+    })
+}
+
+/// `http://hl7.org/fhir/ValueSet/administrative-gender|4.0.1` -> without the version
+pub fn canonical(url: &str) -> &str {
+    url.split('|').next().unwrap_or(url)
+}
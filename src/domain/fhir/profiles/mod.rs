@@ -0,0 +1,113 @@
+pub mod definition;
+pub mod package;
+mod validate;
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use definition::{canonical, ElementDefinition, Profile};
+
+const CORE_PREFIX: &str = "http://hl7.org/fhir/StructureDefinition/";
+
+/// StructureDefinitions loaded from local FHIR packages (.tgz)
+///
+/// Holds the base definitions (e.g. `hl7.fhir.r4.core`) and any national or custom profiles;
+/// resources are validated against the profiles in `meta.profile`, or the base definition of their type.
+#[derive(Debug, Default)]
+pub struct ProfileRegistry {
+    profiles: HashMap<String, Profile>,
+    /// Base definition url by type name
+    base: HashMap<String, String>,
+}
+
+impl ProfileRegistry {
+    /// Index the StructureDefinitions among `resources`, ignoring anything else
+    pub fn from_resources(resources: &[Value]) -> Self {
+        let mut registry = ProfileRegistry::default();
+        let mut pending: Vec<&Value> = Vec::new();
+
+        for resource in resources {
+            let Some(url) = resource.get("url").and_then(Value::as_str).map(|url| canonical(url).to_string()) else {
+                continue;
+            };
+
+            if resource.get("resourceType").and_then(Value::as_str) != Some("StructureDefinition") {
+                continue;
+            }
+
+            // Logical models describe no wire format, and extensions are not validated
+            let kind = resource.get("kind").and_then(Value::as_str);
+            let type_name = resource.get("type").and_then(Value::as_str);
+            if kind == Some("logical") || (type_name == Some("Extension") && url != format!("{}Extension", CORE_PREFIX)) {
+                continue;
+            }
+
+            match resource.pointer("/snapshot/element").and_then(Value::as_array) {
+                Some(elements) => registry.add_profile(url, resource, elements.iter().filter_map(ElementDefinition::from_json).collect()),
+                None => pending.push(resource),
+            }
+        }
+
+        // Profiles published with only a differential are derived from their base, once it is available
+        while !pending.is_empty() {
+            let before = pending.len();
+            pending.retain(|resource| !registry.derive_profile(resource));
+            if pending.len() == before {
+                break;
+            }
+        }
+        for resource in pending {
+            let url = resource.get("url").and_then(Value::as_str).unwrap_or_default();
+            tracing::warn!("[FHIR] StructureDefinition {} ignored: base definition not loaded", url);
+        }
+
+        registry
+    }
+
+    fn add_profile(&mut self, url: String, resource: &Value, elements: Vec<ElementDefinition>) {
+        let name = resource.get("name").and_then(Value::as_str).unwrap_or(&url).to_string();
+        let type_name = resource.get("type").and_then(Value::as_str).unwrap_or_default().to_string();
+
+        if url == format!("{}{}", CORE_PREFIX, type_name) {
+            self.base.insert(type_name.clone(), url.clone());
+        }
+        self.profiles.insert(url.clone(), Profile::new(url, name, type_name, elements));
+    }
+
+    /// Derive a differential-only profile; false while its base is not loaded yet
+    fn derive_profile(&mut self, resource: &Value) -> bool {
+        let Some(base) = resource
+            .get("baseDefinition")
+            .and_then(Value::as_str)
+            .and_then(|base| self.profiles.get(canonical(base)))
+        else {
+            return false;
+        };
+
+        let url = canonical(resource.get("url").and_then(Value::as_str).unwrap_or_default()).to_string();
+        let name = resource.get("name").and_then(Value::as_str).unwrap_or(&url).to_string();
+        let differential = resource
+            .pointer("/differential/element")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        let profile = base.derive(url.clone(), name, differential);
+        self.profiles.insert(url, profile);
+        true
+    }
+
+    pub fn profile_count(&self) -> usize {
+        self.profiles.len()
+    }
+
+    pub fn profile(&self, url: &str) -> Option<&Profile> {
+        self.profiles.get(canonical(url))
+    }
+
+    /// Base definition of a resource or data type, e.g. `Patient` or `Identifier`
+    pub fn base_profile(&self, type_name: &str) -> Option<&Profile> {
+        self.base.get(type_name).and_then(|url| self.profiles.get(url))
+    }
+}
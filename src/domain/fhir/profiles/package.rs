@@ -0,0 +1,169 @@
+use std::io::Read;
+use std::path::Path;
+
+use flate2::read::GzDecoder;
+use serde_json::Value;
+
+use crate::utils::AppError;
+
+const BLOCK: usize = 512;
+
+/// Conformance resources of every FHIR package (.tgz) in `dir`
+pub fn load_dir(dir: &Path) -> Result<Vec<Value>, AppError> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| AppError::ConfigError(format!("Cannot read FHIR_PACKAGES_DIR {}: {}", dir.display(), e)))?;
+
+    let mut resources = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("tgz") {
+            continue;
+        }
+
+        let bytes = std::fs::read(&path)
+            .map_err(|e| AppError::ConfigError(format!("Cannot read FHIR package {}: {}", path.display(), e)))?;
+        let package = read_package(&bytes).map_err(|e| AppError::ConfigError(format!("{}: {}", path.display(), e)))?;
+        tracing::info!("[FHIR] Package {} loaded ({} conformance resources)", path.display(), package.len());
+        resources.extend(package);
+    }
+
+    Ok(resources)
+}
+
+/// Conformance resources (StructureDefinition, ValueSet, CodeSystem) of a FHIR package (.tgz)
+///
+/// Only the JSON files directly under `package/` are read; examples and other folders are skipped.
+pub fn read_package(bytes: &[u8]) -> Result<Vec<Value>, AppError> {
+    let mut archive = Vec::new();
+    GzDecoder::new(bytes)
+        .read_to_end(&mut archive)
+        .map_err(|e| AppError::BadRequest(format!("Pacote FHIR inválido (gzip): {}", e)))?;
+
+    let mut resources = Vec::new();
+    for (name, content) in tar_entries(&archive)? {
+        let Some(file) = name.strip_prefix("package/") else {
+            continue;
+        };
+        if file.contains('/') || !file.ends_with(".json") || file.starts_with('.') || file == "package.json" {
+            continue;
+        }
+
+        let Ok(resource) = serde_json::from_slice::<Value>(content) else {
+            continue;
+        };
+        if matches!(
+            resource.get("resourceType").and_then(Value::as_str),
+            Some("StructureDefinition") | Some("ValueSet") | Some("CodeSystem")
+        ) {
+            resources.push(resource);
+        }
+    }
+
+    Ok(resources)
+}
+
+/// Regular files of a ustar archive, honoring PAX and GNU long names
+fn tar_entries(archive: &[u8]) -> Result<Vec<(String, &[u8])>, AppError> {
+    let invalid = || AppError::BadRequest("Pacote FHIR inválido (tar)".to_string());
+
+    let mut entries = Vec::new();
+    let mut long_name: Option<String> = None;
+    let mut offset = 0;
+
+    while offset + BLOCK <= archive.len() {
+        let header = &archive[offset..offset + BLOCK];
+        if header.iter().all(|b| *b == 0) {
+            break;
+        }
+
+        let size = octal(&header[124..136]).ok_or_else(invalid)?;
+        let start = offset + BLOCK;
+        let end = start.checked_add(size).filter(|end| *end <= archive.len()).ok_or_else(invalid)?;
+        let content = &archive[start..end];
+        offset = start + size.div_ceil(BLOCK) * BLOCK;
+
+        match header[156] {
+            // PAX extended header: "<len> path=<name>\n" records
+            b'x' => {
+                long_name = String::from_utf8_lossy(content)
+                    .lines()
+                    .find_map(|record| record.split_once(" path=").map(|(_, path)| path.to_string()));
+            }
+            // GNU long name
+            b'L' => long_name = Some(text(content)),
+            b'0' | 0 => {
+                let name = long_name.take().unwrap_or_else(|| {
+                    let prefix = text(&header[345..500]);
+                    let name = text(&header[0..100]);
+                    if prefix.is_empty() { name } else { format!("{}/{}", prefix, name) }
+                });
+                entries.push((name, content));
+            }
+            _ => long_name = None,
+        }
+    }
+
+    Ok(entries)
+}
+
+fn text(field: &[u8]) -> String {
+    let end = field.iter().position(|b| *b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).to_string()
+}
+
+fn octal(field: &[u8]) -> Option<usize> {
+    let digits = text(field);
+    let digits = digits.trim();
+    if digits.is_empty() {
+        return Some(0);
+    }
+    usize::from_str_radix(digits, 8).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use serde_json::json;
+    use std::io::Write;
+
+    /// Gzipped ustar archive with the given files
+    fn package(files: &[(&str, Value)]) -> Vec<u8> {
+        let mut archive = Vec::new();
+        for (name, content) in files {
+            let content = serde_json::to_vec(content).unwrap();
+
+            let mut header = [0u8; BLOCK];
+            header[..name.len()].copy_from_slice(name.as_bytes());
+            header[124..135].copy_from_slice(format!("{:011o}", content.len()).as_bytes());
+            header[156] = b'0';
+            header[257..262].copy_from_slice(b"ustar");
+
+            archive.extend_from_slice(&header);
+            archive.extend_from_slice(&content);
+            archive.resize(archive.len().div_ceil(BLOCK) * BLOCK, 0);
+        }
+        archive.extend_from_slice(&[0u8; BLOCK * 2]);
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&archive).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_reads_conformance_resources_from_package() {
+        let bytes = package(&[
+            ("package/package.json", json!({ "name": "br.core" })),
+            ("package/ValueSet-sexo.json", json!({ "resourceType": "ValueSet", "url": "http://example.org/vs" })),
+            ("package/example/Patient-1.json", json!({ "resourceType": "StructureDefinition" })),
+            ("package/Patient-1.json", json!({ "resourceType": "Patient" })),
+        ]);
+
+        let resources = read_package(&bytes).unwrap();
+        assert_eq!(resources.len(), 1);
+        assert_eq!(resources[0]["url"], "http://example.org/vs");
+
+        assert!(read_package(b"not a package").is_err());
+    }
+}
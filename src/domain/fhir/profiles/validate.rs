@@ -0,0 +1,608 @@
+use chrono::{DateTime, NaiveDate};
+use serde_json::{Map, Value};
+
+use super::definition::{Binding, ElementDefinition, Profile, Slicing};
+use super::ProfileRegistry;
+use crate::domain::fhir::terminology::{CodeCheck, Terminology};
+use crate::utils::ValidationRecommendation;
+
+impl ProfileRegistry {
+    /// Check a resource against the profiles in its `meta.profile`, or the base definition of its type;
+    /// coded elements are checked against their required and extensible bindings in `terminology`
+    ///
+    /// None when no definition of the resource type is loaded, so callers can fall back to other checks.
+    pub fn validate(&self, resource: &Value, terminology: &Terminology) -> Option<Vec<ValidationRecommendation>> {
+        let resource_type = resource.get("resourceType")?.as_str()?;
+        let object = resource.as_object()?;
+        let mut check = Check { registry: self, terminology, recommendations: Vec::new() };
+
+        let mut profiles = Vec::new();
+        for url in resource.pointer("/meta/profile").and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str) {
+            match self.profile(url) {
+                Some(profile) if profile.type_name == resource_type => profiles.push(profile),
+                _ => check.push(
+                    "warning",
+                    "meta.profile",
+                    format!("Profile '{}' is not loaded", url),
+                    "Add the package defining this profile to FHIR_PACKAGES_DIR".to_string(),
+                ),
+            }
+        }
+        if profiles.is_empty() {
+            profiles.push(self.base_profile(resource_type)?);
+        }
+
+        for profile in profiles {
+            check.object(profile, &profile.type_name, None, object, "");
+        }
+        Some(check.recommendations)
+    }
+}
+
+struct Check<'a> {
+    registry: &'a ProfileRegistry,
+    terminology: &'a Terminology,
+    recommendations: Vec<ValidationRecommendation>,
+}
+
+impl<'a> Check<'a> {
+    fn push(&mut self, severity: &str, field: &str, message: String, recommendation: String) {
+        self.recommendations.push(ValidationRecommendation {
+            severity: severity.to_string(),
+            field: field.to_string(),
+            message,
+            recommendation,
+        });
+    }
+
+    fn error(&mut self, profile: &Profile, field: &str, message: String) {
+        let recommendation = format!("Required by {} ({})", profile.name, profile.url);
+        self.push("error", field, message, recommendation);
+    }
+
+    /// Check the elements of an object defined at `id`; elements the profile does not list come
+    /// from the base definition of the element's data type, when given
+    fn object(
+        &mut self,
+        profile: &'a Profile,
+        id: &str,
+        data_type: Option<&'a Profile>,
+        object: &Map<String, Value>,
+        path: &str,
+    ) {
+        let mut children: Vec<(&'a Profile, String, String)> = profile
+            .child_names(id)
+            .into_iter()
+            .map(|name| (profile, format!("{}.{}", id, name), name))
+            .collect();
+        if let Some(data_type) = data_type {
+            for name in data_type.child_names(&data_type.type_name) {
+                if !children.iter().any(|(_, _, existing)| *existing == name) {
+                    children.push((data_type, format!("{}.{}", data_type.type_name, name), name));
+                }
+            }
+        }
+
+        let mut known = vec!["resourceType".to_string()];
+        for (owner, child_id, name) in &children {
+            let Some(definition) = owner.element(child_id) else {
+                continue;
+            };
+            // Profiles published as differentials may constrain a data type's element without restating it
+            let definition = match data_type.and_then(|dt| dt.element(&format!("{}.{}", dt.type_name, name))) {
+                Some(base) => definition.or_inherit(base),
+                None => definition.clone(),
+            };
+            let definition = &definition;
+
+            // value[x] appears as valueQuantity, valueString, ...
+            let keys: Vec<(&String, Option<&str>)> = match name.strip_suffix("[x]") {
+                Some(prefix) => object
+                    .keys()
+                    .filter(|key| {
+                        key.strip_prefix(prefix)
+                            .is_some_and(|suffix| suffix.starts_with(|c: char| c.is_ascii_uppercase()))
+                    })
+                    .map(|key| (key, Some(&key[prefix.len()..])))
+                    .collect(),
+                None => object.get_key_value(name.as_str()).map(|(key, _)| (key, None)).into_iter().collect(),
+            };
+            for (key, _) in &keys {
+                known.push(key.to_string());
+            }
+            known.push(format!("_{}", name));
+
+            let count: usize = keys
+                .iter()
+                .map(|(key, _)| object[key.as_str()].as_array().map_or(1, Vec::len))
+                .sum::<usize>()
+                .max(usize::from(object.contains_key(&format!("_{}", name))));
+            let field = join(path, name);
+            self.cardinality(owner, definition, &field, count);
+
+            for (key, suffix) in keys {
+                let value = &object[key.as_str()];
+                let field = join(path, key);
+
+                let type_code = match suffix {
+                    Some(suffix) => match definition.types.iter().find(|t| t.eq_ignore_ascii_case(suffix)) {
+                        Some(type_code) => Some(type_code.as_str()),
+                        None => {
+                            self.error(owner, &field, format!("Type '{}' is not allowed for '{}'", suffix, name));
+                            continue;
+                        }
+                    },
+                    None => definition.types.first().map(String::as_str),
+                };
+
+                if definition.is_repeating() && !value.is_array() {
+                    self.error(owner, &field, format!("Element '{}' must be an array", key));
+                    continue;
+                }
+                if !definition.is_repeating() && value.is_array() {
+                    self.error(owner, &field, format!("Element '{}' must not be an array", key));
+                    continue;
+                }
+
+                let items: Vec<&Value> = match value.as_array() {
+                    Some(items) => items.iter().collect(),
+                    None => vec![value],
+                };
+                let slices = match &definition.slicing {
+                    Some(slicing) => self.slices(owner, child_id, slicing, &items, &field),
+                    None => vec![None; items.len()],
+                };
+
+                for (index, (item, slice)) in items.into_iter().zip(slices).enumerate() {
+                    let item_field = if value.is_array() { format!("{}[{}]", field, index) } else { field.clone() };
+                    let item_definition = slice.as_deref().and_then(|slice| owner.element(slice)).unwrap_or(definition);
+                    let item_id = slice.as_deref().unwrap_or(child_id);
+                    self.item(owner, item_id, item_definition, type_code, item, &item_field);
+                }
+            }
+        }
+
+        for key in object.keys() {
+            if !known.contains(key) {
+                self.error(
+                    profile,
+                    &join(path, key),
+                    format!("Unknown element '{}' in {}", key, id.rsplit('.').next().unwrap_or(id)),
+                );
+            }
+        }
+    }
+
+    fn cardinality(&mut self, profile: &Profile, definition: &ElementDefinition, field: &str, count: usize) {
+        let min = definition.min.unwrap_or(0) as usize;
+        if count < min {
+            self.error(
+                profile,
+                field,
+                format!("Missing required element '{}' (minimum {}, found {})", definition.name(), min, count),
+            );
+        }
+        if let Some(max) = definition.max_count() {
+            if count > max {
+                let message = if max == 0 {
+                    format!("Element '{}' is not allowed", definition.name())
+                } else {
+                    format!("Element '{}' allows at most {} (found {})", definition.name(), max, count)
+                };
+                self.error(profile, field, message);
+            }
+        }
+    }
+
+    fn item(
+        &mut self,
+        profile: &'a Profile,
+        id: &str,
+        definition: &ElementDefinition,
+        type_code: Option<&str>,
+        item: &Value,
+        field: &str,
+    ) {
+        if let Some(fixed) = &definition.fixed {
+            if item != fixed {
+                self.error(profile, field, format!("Value must be exactly {}", fixed));
+            }
+        }
+        if let Some(pattern) = &definition.pattern {
+            if !matches_pattern(item, pattern) {
+                self.error(profile, field, format!("Value must match pattern {}", pattern));
+            }
+        }
+        if let (Some(binding), Some(type_code)) = (&definition.binding, type_code) {
+            self.binding(profile, binding, type_code, item, field);
+        }
+
+        let Some(type_code) = type_code else {
+            // Elements defined elsewhere in the structure, e.g. Questionnaire.item.item
+            if let (Some(reference), Some(object)) = (&definition.content_reference, item.as_object()) {
+                let target = reference.split('#').nth(1).unwrap_or(reference);
+                self.object(profile, target, None, object, field);
+            }
+            return;
+        };
+
+        if let Some(primitive) = primitive_type(type_code) {
+            if !primitive_matches(primitive, item) {
+                self.error(profile, field, format!("Value {} is not a valid {}", item, primitive));
+            }
+            return;
+        }
+
+        let Some(object) = item.as_object() else {
+            self.error(profile, field, format!("Element must be a {} object", type_code));
+            return;
+        };
+
+        if matches!(type_code, "Resource" | "DomainResource") {
+            let nested = object.get("resourceType").and_then(Value::as_str);
+            if let Some(base) = nested.and_then(|resource_type| self.registry.base_profile(resource_type)) {
+                self.object(base, &base.type_name, None, object, field);
+            }
+            return;
+        }
+
+        let data_type = self.registry.base_profile(type_code);
+        if profile.child_names(id).is_empty() {
+            if let Some(data_type) = data_type {
+                self.object(data_type, &data_type.type_name, None, object, field);
+            }
+        } else {
+            self.object(profile, id, data_type, object, field);
+        }
+    }
+
+    /// Required bindings are errors and extensible ones warnings; preferred and example bindings are not checked
+    fn binding(&mut self, profile: &Profile, binding: &Binding, type_code: &str, item: &Value, field: &str) {
+        let severity = match binding.strength.as_str() {
+            "required" => "error",
+            "extensible" => "warning",
+            _ => return,
+        };
+        let Some(value_set) = binding.value_set.as_deref() else {
+            return;
+        };
+
+        let codings: Vec<(Option<&str>, &str)> = match type_code {
+            "Coding" => coding(item).into_iter().collect(),
+            "CodeableConcept" => item.get("coding").and_then(Value::as_array).into_iter().flatten().filter_map(coding).collect(),
+            "Quantity" | "Reference" => Vec::new(),
+            _ => item.as_str().map(|code| (None, code)).into_iter().collect(),
+        };
+        if codings.is_empty() {
+            return;
+        }
+
+        let checks: Vec<CodeCheck> = codings
+            .iter()
+            .map(|(system, code)| self.terminology.validate_code(value_set, *system, code))
+            .collect();
+        if checks.iter().any(|check| *check != CodeCheck::Invalid) {
+            return;
+        }
+
+        let codes: Vec<&str> = codings.iter().map(|(_, code)| *code).collect();
+        self.push(
+            severity,
+            field,
+            format!("Code '{}' is not in the {} value set {}", codes.join("', '"), binding.strength, value_set),
+            format!("Bound by {} ({}); map the source value to a code of this value set", profile.name, profile.url),
+        );
+    }
+
+    /// Assign each item to the slice its discriminators match and check the slice cardinalities
+    fn slices(
+        &mut self,
+        profile: &Profile,
+        id: &str,
+        slicing: &Slicing,
+        items: &[&Value],
+        field: &str,
+    ) -> Vec<Option<String>> {
+        let slice_ids: Vec<String> = profile.slice_names(id).into_iter().map(|name| format!("{}:{}", id, name)).collect();
+
+        let assigned: Vec<Option<String>> = items
+            .iter()
+            .map(|item| {
+                slice_ids
+                    .iter()
+                    .find(|slice_id| matches_discriminators(profile, slice_id, slicing, item))
+                    .cloned()
+            })
+            .collect();
+
+        for slice_id in &slice_ids {
+            let Some(slice) = profile.element(slice_id) else {
+                continue;
+            };
+            let count = assigned.iter().filter(|assigned| assigned.as_ref() == Some(slice_id)).count();
+            let name = slice.slice_name.as_deref().unwrap_or_default();
+
+            if count < slice.min.unwrap_or(0) as usize {
+                self.error(
+                    profile,
+                    field,
+                    format!("Missing required slice '{}' of '{}' (minimum {}, found {})", name, slice.name(), slice.min.unwrap_or(0), count),
+                );
+            }
+            if let Some(max) = slice.max_count().filter(|max| count > *max) {
+                self.error(profile, field, format!("Slice '{}' of '{}' allows at most {} (found {})", name, slice.name(), max, count));
+            }
+        }
+
+        if slicing.closed {
+            for (index, slice) in assigned.iter().enumerate() {
+                if slice.is_none() {
+                    self.error(profile, &format!("{}[{}]", field, index), "Item does not match any allowed slice".to_string());
+                }
+            }
+        }
+
+        assigned
+    }
+}
+
+/// (system, code) of a Coding
+fn coding(item: &Value) -> Option<(Option<&str>, &str)> {
+    Some((item.get("system").and_then(Value::as_str), item.get("code")?.as_str()?))
+}
+
+/// Whether an item matches the fixed or pattern values the slice sets at each discriminator path
+fn matches_discriminators(profile: &Profile, slice_id: &str, slicing: &Slicing, item: &Value) -> bool {
+    !slicing.discriminators.is_empty()
+        && slicing.discriminators.iter().all(|(kind, path)| {
+            let (target, values) = if path == "$this" {
+                (slice_id.to_string(), vec![item])
+            } else {
+                (format!("{}.{}", slice_id, path), values_at(item, path))
+            };
+            let Some(definition) = profile.element(&target) else {
+                return false;
+            };
+
+            match kind.as_str() {
+                "value" | "pattern" => match (&definition.fixed, &definition.pattern) {
+                    (Some(fixed), _) => values.contains(&fixed),
+                    (None, Some(pattern)) => values.iter().any(|value| matches_pattern(value, pattern)),
+                    (None, None) => false,
+                },
+                "exists" => match definition.min {
+                    Some(min) if min > 0 => !values.is_empty(),
+                    _ if definition.is_prohibited() => values.is_empty(),
+                    _ => false,
+                },
+                "type" => values.iter().any(|value| {
+                    value
+                        .get("resourceType")
+                        .and_then(Value::as_str)
+                        .is_some_and(|resource_type| definition.types.iter().any(|t| t == resource_type))
+                }),
+                _ => false,
+            }
+        })
+}
+
+/// Values at a dotted element path, flattening arrays
+fn values_at<'v>(item: &'v Value, path: &str) -> Vec<&'v Value> {
+    let mut values = vec![item];
+    for segment in path.split('.') {
+        values = values
+            .into_iter()
+            .filter_map(|value| value.get(segment))
+            .flat_map(|value| match value.as_array() {
+                Some(items) => items.iter().collect(),
+                None => vec![value],
+            })
+            .collect();
+    }
+    values
+}
+
+/// Pattern semantics: every property and array item of the pattern is present in the value
+fn matches_pattern(value: &Value, pattern: &Value) -> bool {
+    match (value, pattern) {
+        (Value::Object(value), Value::Object(pattern)) => pattern
+            .iter()
+            .all(|(key, expected)| value.get(key).is_some_and(|actual| matches_pattern(actual, expected))),
+        (Value::Array(values), Value::Array(patterns)) => patterns
+            .iter()
+            .all(|expected| values.iter().any(|actual| matches_pattern(actual, expected))),
+        _ => value == pattern,
+    }
+}
+
+/// FHIR primitive type of a type code; primitives start lowercase and System types back `id` and `extension.url`
+fn primitive_type(type_code: &str) -> Option<&str> {
+    if let Some(system) = type_code.strip_prefix("http://hl7.org/fhirpath/System.") {
+        return Some(match system {
+            "Boolean" => "boolean",
+            "Integer" => "integer",
+            "Decimal" => "decimal",
+            "Date" => "date",
+            "DateTime" => "dateTime",
+            "Time" => "time",
+            _ => "string",
+        });
+    }
+    type_code.starts_with(|c: char| c.is_ascii_lowercase()).then_some(type_code)
+}
+
+fn primitive_matches(primitive: &str, value: &Value) -> bool {
+    match primitive {
+        "boolean" => value.is_boolean(),
+        "integer" => value.is_i64(),
+        "unsignedInt" => value.as_u64().is_some(),
+        "positiveInt" => value.as_u64().is_some_and(|v| v > 0),
+        "decimal" => value.is_number(),
+        "date" => value.as_str().is_some_and(is_date),
+        "dateTime" => value.as_str().is_some_and(|v| is_date(v) || DateTime::parse_from_rfc3339(v).is_ok()),
+        "instant" => value.as_str().is_some_and(|v| DateTime::parse_from_rfc3339(v).is_ok()),
+        _ => value.as_str().is_some_and(|v| !v.is_empty()),
+    }
+}
+
+/// `YYYY`, `YYYY-MM` or `YYYY-MM-DD`
+fn is_date(value: &str) -> bool {
+    match value.len() {
+        4 => value.chars().all(|c| c.is_ascii_digit()),
+        7 => NaiveDate::parse_from_str(&format!("{}-01", value), "%Y-%m-%d").is_ok(),
+        10 => NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok(),
+        _ => false,
+    }
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() { key.to_string() } else { format!("{}.{}", path, key) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn element(id: &str, min: u64, max: &str, types: &[&str]) -> Value {
+        json!({
+            "id": id,
+            "path": id.split(':').next().unwrap(),
+            "min": min,
+            "max": max,
+            "type": types.iter().map(|code| json!({ "code": code })).collect::<Vec<_>>(),
+        })
+    }
+
+    fn structure(url: &str, type_name: &str, elements: Vec<Value>) -> Value {
+        json!({
+            "resourceType": "StructureDefinition",
+            "url": url,
+            "name": type_name,
+            "type": type_name,
+            "kind": "resource",
+            "snapshot": { "element": elements },
+        })
+    }
+
+    fn conformance() -> (ProfileRegistry, Terminology) {
+        let patient = structure(
+            "http://hl7.org/fhir/StructureDefinition/Patient",
+            "Patient",
+            vec![
+                element("Patient", 0, "*", &[]),
+                element("Patient.meta", 0, "1", &["Meta"]),
+                element("Patient.identifier", 0, "*", &["Identifier"]),
+                json!({
+                    "id": "Patient.gender", "path": "Patient.gender", "min": 0, "max": "1",
+                    "type": [{ "code": "code" }],
+                    "binding": { "strength": "required", "valueSet": "http://hl7.org/fhir/ValueSet/administrative-gender|4.0.1" }
+                }),
+                element("Patient.birthDate", 0, "1", &["date"]),
+                element("Patient.deceased[x]", 0, "1", &["boolean", "dateTime"]),
+            ],
+        );
+        let identifier = json!({
+            "resourceType": "StructureDefinition",
+            "url": "http://hl7.org/fhir/StructureDefinition/Identifier",
+            "name": "Identifier",
+            "type": "Identifier",
+            "kind": "complex-type",
+            "snapshot": { "element": [
+                element("Identifier", 0, "*", &[]),
+                element("Identifier.system", 0, "1", &["uri"]),
+                element("Identifier.value", 0, "1", &["string"]),
+            ]},
+        });
+        // National profile published with only a differential
+        let br_patient = json!({
+            "resourceType": "StructureDefinition",
+            "url": "http://example.org/StructureDefinition/BRPaciente",
+            "name": "BRPaciente",
+            "type": "Patient",
+            "kind": "resource",
+            "baseDefinition": "http://hl7.org/fhir/StructureDefinition/Patient",
+            "differential": { "element": [
+                { "id": "Patient.identifier", "path": "Patient.identifier", "min": 1,
+                  "slicing": { "discriminator": [{ "type": "value", "path": "system" }], "rules": "open" } },
+                { "id": "Patient.identifier:cpf", "path": "Patient.identifier", "sliceName": "cpf", "min": 1, "max": "1" },
+                { "id": "Patient.identifier:cpf.system", "path": "Patient.identifier.system", "fixedUri": "urn:oid:2.16.840.1.113883.13.237" },
+                { "id": "Patient.identifier:cpf.value", "path": "Patient.identifier.value", "min": 1 },
+                { "id": "Patient.birthDate", "path": "Patient.birthDate", "min": 1 },
+            ]},
+        });
+        let gender = json!({
+            "resourceType": "ValueSet",
+            "url": "http://hl7.org/fhir/ValueSet/administrative-gender",
+            "compose": { "include": [{ "system": "http://hl7.org/fhir/administrative-gender" }] },
+        });
+        let gender_codes = json!({
+            "resourceType": "CodeSystem",
+            "url": "http://hl7.org/fhir/administrative-gender",
+            "content": "complete",
+            "concept": [{ "code": "male" }, { "code": "female" }, { "code": "other" }, { "code": "unknown" }],
+        });
+
+        let resources = vec![br_patient, patient, identifier, gender, gender_codes];
+        (ProfileRegistry::from_resources(&resources), Terminology::from_resources(&resources))
+    }
+
+    fn fields(recommendations: &[ValidationRecommendation]) -> Vec<&str> {
+        recommendations.iter().map(|r| r.field.as_str()).collect()
+    }
+
+    #[test]
+    fn test_validates_against_base_definition() {
+        let (registry, terminology) = conformance();
+
+        let valid = json!({
+            "resourceType": "Patient",
+            "identifier": [{ "system": "urn:oid:1", "value": "123" }],
+            "gender": "female",
+            "deceasedBoolean": false,
+        });
+        assert_eq!(registry.validate(&valid, &terminology).unwrap().len(), 0);
+
+        let invalid = json!({
+            "resourceType": "Patient",
+            "identifier": { "value": "123" },
+            "gender": "feminino",
+            "birthDate": "12/04/1990",
+            "deceasedString": "no",
+            "class": { "code": "IMP" },
+        });
+        let recommendations = registry.validate(&invalid, &terminology).unwrap();
+        assert_eq!(fields(&recommendations), vec!["identifier", "gender", "birthDate", "deceasedString", "class"]);
+        assert!(recommendations.iter().all(|r| r.severity == "error"));
+
+        assert!(registry.validate(&json!({ "resourceType": "Encounter" }), &terminology).is_none());
+    }
+
+    #[test]
+    fn test_validates_profile_slices_from_differential() {
+        let (registry, terminology) = conformance();
+        let profiled = |identifiers: Value| {
+            json!({
+                "resourceType": "Patient",
+                "meta": { "profile": ["http://example.org/StructureDefinition/BRPaciente"] },
+                "identifier": identifiers,
+                "birthDate": "1990-04-12",
+            })
+        };
+
+        let valid = profiled(json!([
+            { "system": "urn:oid:2.16.840.1.113883.13.237", "value": "12345678909" },
+            { "system": "urn:oid:1", "value": "abc" },
+        ]));
+        let recommendations = registry.validate(&valid, &terminology).unwrap();
+        assert!(recommendations.is_empty(), "{:?}", recommendations);
+
+        let missing_cpf_value = profiled(json!([{ "system": "urn:oid:2.16.840.1.113883.13.237" }]));
+        assert_eq!(fields(&registry.validate(&missing_cpf_value, &terminology).unwrap()), vec!["identifier[0].value"]);
+
+        let without_cpf = profiled(json!([{ "system": "urn:oid:1", "value": "abc" }]));
+        let recommendations = registry.validate(&without_cpf, &terminology).unwrap();
+        assert_eq!(fields(&recommendations), vec!["identifier"]);
+        assert!(recommendations[0].message.contains("slice 'cpf'"));
+    }
+}
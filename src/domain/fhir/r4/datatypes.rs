@@ -0,0 +1,228 @@
+// R4 datatypes used by the supported resources
+use super::model::fhir_element;
+use super::primitives::*;
+
+fhir_element! {
+    Extension {
+        "url" => url: Option<Uri>,
+        "valueBase64Binary" => value_base64_binary: Option<Base64Binary>,
+        "valueBoolean" => value_boolean: Option<Boolean>,
+        "valueCanonical" => value_canonical: Option<Uri>,
+        "valueCode" => value_code: Option<Code>,
+        "valueDate" => value_date: Option<Date>,
+        "valueDateTime" => value_date_time: Option<DateTime>,
+        "valueDecimal" => value_decimal: Option<Decimal>,
+        "valueId" => value_id: Option<Id>,
+        "valueInstant" => value_instant: Option<Instant>,
+        "valueInteger" => value_integer: Option<Integer>,
+        "valueMarkdown" => value_markdown: Option<Markdown>,
+        "valueOid" => value_oid: Option<Uri>,
+        "valuePositiveInt" => value_positive_int: Option<PositiveInt>,
+        "valueString" => value_string: Option<FhirString>,
+        "valueTime" => value_time: Option<Time>,
+        "valueUnsignedInt" => value_unsigned_int: Option<UnsignedInt>,
+        "valueUri" => value_uri: Option<Uri>,
+        "valueUrl" => value_url: Option<Uri>,
+        "valueAddress" => value_address: Option<Address>,
+        "valueAnnotation" => value_annotation: Option<Annotation>,
+        "valueAttachment" => value_attachment: Option<Attachment>,
+        "valueCodeableConcept" => value_codeable_concept: Option<CodeableConcept>,
+        "valueCoding" => value_coding: Option<Coding>,
+        "valueContactPoint" => value_contact_point: Option<ContactPoint>,
+        "valueHumanName" => value_human_name: Option<HumanName>,
+        "valueIdentifier" => value_identifier: Option<Identifier>,
+        "valuePeriod" => value_period: Option<Period>,
+        "valueQuantity" => value_quantity: Option<Quantity>,
+        "valueRange" => value_range: Option<Range>,
+        "valueRatio" => value_ratio: Option<Ratio>,
+        "valueReference" => value_reference: Option<Reference>,
+    }
+}
+
+fhir_element! {
+    Coding {
+        "system" => system: Option<Uri>,
+        "version" => version: Option<FhirString>,
+        "code" => code: Option<Code>,
+        "display" => display: Option<FhirString>,
+        "userSelected" => user_selected: Option<Boolean>,
+    }
+}
+
+fhir_element! {
+    CodeableConcept {
+        "coding" => coding: Vec<Coding>,
+        "text" => text: Option<FhirString>,
+    }
+}
+
+fhir_element! {
+    Identifier {
+        "use" => use_: Option<Code>,
+        "type" => type_: Option<CodeableConcept>,
+        "system" => system: Option<Uri>,
+        "value" => value: Option<FhirString>,
+        "period" => period: Option<Period>,
+        "assigner" => assigner: Option<Box<Reference>>,
+    }
+}
+
+fhir_element! {
+    Reference {
+        "reference" => reference: Option<FhirString>,
+        "type" => type_: Option<Uri>,
+        "identifier" => identifier: Option<Identifier>,
+        "display" => display: Option<FhirString>,
+    }
+}
+
+fhir_element! {
+    Period {
+        "start" => start: Option<DateTime>,
+        "end" => end: Option<DateTime>,
+    }
+}
+
+fhir_element! {
+    HumanName {
+        "use" => use_: Option<Code>,
+        "text" => text: Option<FhirString>,
+        "family" => family: Option<FhirString>,
+        "given" => given: Vec<FhirString>,
+        "prefix" => prefix: Vec<FhirString>,
+        "suffix" => suffix: Vec<FhirString>,
+        "period" => period: Option<Period>,
+    }
+}
+
+fhir_element! {
+    ContactPoint {
+        "system" => system: Option<Code>,
+        "value" => value: Option<FhirString>,
+        "use" => use_: Option<Code>,
+        "rank" => rank: Option<PositiveInt>,
+        "period" => period: Option<Period>,
+    }
+}
+
+fhir_element! {
+    Address {
+        "use" => use_: Option<Code>,
+        "type" => type_: Option<Code>,
+        "text" => text: Option<FhirString>,
+        "line" => line: Vec<FhirString>,
+        "city" => city: Option<FhirString>,
+        "district" => district: Option<FhirString>,
+        "state" => state: Option<FhirString>,
+        "postalCode" => postal_code: Option<FhirString>,
+        "country" => country: Option<FhirString>,
+        "period" => period: Option<Period>,
+    }
+}
+
+fhir_element! {
+    /// Also Age, Count, Distance, Duration and SimpleQuantity
+    Quantity {
+        "value" => value: Option<Decimal>,
+        "comparator" => comparator: Option<Code>,
+        "unit" => unit: Option<FhirString>,
+        "system" => system: Option<Uri>,
+        "code" => code: Option<Code>,
+    }
+}
+
+fhir_element! {
+    Range {
+        "low" => low: Option<Quantity>,
+        "high" => high: Option<Quantity>,
+    }
+}
+
+fhir_element! {
+    Ratio {
+        "numerator" => numerator: Option<Quantity>,
+        "denominator" => denominator: Option<Quantity>,
+    }
+}
+
+fhir_element! {
+    Attachment {
+        "contentType" => content_type: Option<Code>,
+        "language" => language: Option<Code>,
+        "data" => data: Option<Base64Binary>,
+        "url" => url: Option<Uri>,
+        "size" => size: Option<UnsignedInt>,
+        "hash" => hash: Option<Base64Binary>,
+        "title" => title: Option<FhirString>,
+        "creation" => creation: Option<DateTime>,
+    }
+}
+
+fhir_element! {
+    Annotation {
+        "authorReference" => author_reference: Option<Reference>,
+        "authorString" => author_string: Option<FhirString>,
+        "time" => time: Option<DateTime>,
+        "text" => text: Option<Markdown>,
+    }
+}
+
+fhir_element! {
+    SampledData {
+        "origin" => origin: Option<Quantity>,
+        "period" => period: Option<Decimal>,
+        "factor" => factor: Option<Decimal>,
+        "lowerLimit" => lower_limit: Option<Decimal>,
+        "upperLimit" => upper_limit: Option<Decimal>,
+        "dimensions" => dimensions: Option<PositiveInt>,
+        "data" => data: Option<FhirString>,
+    }
+}
+
+fhir_element! {
+    Timing {
+        "event" => event: Vec<DateTime>,
+        "repeat" => repeat: Option<TimingRepeat>,
+        "code" => code: Option<CodeableConcept>,
+    }
+}
+
+fhir_element! {
+    TimingRepeat {
+        "boundsDuration" => bounds_duration: Option<Quantity>,
+        "boundsRange" => bounds_range: Option<Range>,
+        "boundsPeriod" => bounds_period: Option<Period>,
+        "count" => count: Option<PositiveInt>,
+        "countMax" => count_max: Option<PositiveInt>,
+        "duration" => duration: Option<Decimal>,
+        "durationMax" => duration_max: Option<Decimal>,
+        "durationUnit" => duration_unit: Option<Code>,
+        "frequency" => frequency: Option<PositiveInt>,
+        "frequencyMax" => frequency_max: Option<PositiveInt>,
+        "period" => period: Option<Decimal>,
+        "periodMax" => period_max: Option<Decimal>,
+        "periodUnit" => period_unit: Option<Code>,
+        "dayOfWeek" => day_of_week: Vec<Code>,
+        "timeOfDay" => time_of_day: Vec<Time>,
+        "when" => when: Vec<Code>,
+        "offset" => offset: Option<UnsignedInt>,
+    }
+}
+
+fhir_element! {
+    Meta {
+        "versionId" => version_id: Option<Id>,
+        "lastUpdated" => last_updated: Option<Instant>,
+        "source" => source: Option<Uri>,
+        "profile" => profile: Vec<Uri>,
+        "security" => security: Vec<Coding>,
+        "tag" => tag: Vec<Coding>,
+    }
+}
+
+fhir_element! {
+    Narrative {
+        "status" => status: Option<Code>,
+        "div" => div: Option<Xhtml>,
+    }
+}
@@ -1,2 +1,8 @@
 pub mod resource;
-pub mod bundle;
\ No newline at end of file
+pub mod bundle;
+pub mod datatypes;
+pub mod model;
+pub mod primitives;
+pub mod resources;
+
+pub use model::{check_path, conform, primitive_at, prune_empty, StructureError};
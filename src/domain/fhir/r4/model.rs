@@ -0,0 +1,463 @@
+// Typed R4 model - element tables for path checks and serde conversion for FHIR JSON rules
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{self, DeserializeOwned, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+use serde::{Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::utils::AppError;
+use super::resources;
+
+/// Type of an element in the typed model: a primitive, a datatype, a backbone element or a resource
+#[derive(Debug)]
+pub struct ElementType {
+    pub name: &'static str,
+    pub primitive: bool,
+    pub children: &'static [Child],
+}
+
+/// Element of a complex type, by its JSON name
+#[derive(Debug)]
+pub struct Child {
+    pub name: &'static str,
+    pub repeats: bool,
+    pub element_type: fn() -> &'static ElementType,
+}
+
+impl ElementType {
+    pub fn child(&self, name: &str) -> Option<&'static Child> {
+        self.children.iter().find(|child| child.name == name)
+    }
+}
+
+/// A type of the typed model
+pub trait Element {
+    fn element_type() -> &'static ElementType;
+}
+
+/// Contained resources are kept as JSON
+impl Element for Value {
+    fn element_type() -> &'static ElementType {
+        static TYPE: ElementType = ElementType { name: "Resource", primitive: false, children: &[] };
+        &TYPE
+    }
+}
+
+/// Recursive datatypes (`Identifier.assigner`)
+impl<T: Element> Element for Box<T> {
+    fn element_type() -> &'static ElementType {
+        T::element_type()
+    }
+}
+
+/// `Option<T>` for elements with max cardinality 1, `Vec<T>` for repeating elements
+pub trait Cardinality: Sized {
+    type Inner: Element;
+    const REPEATS: bool;
+
+    /// Never serialized: FHIR JSON has no empty arrays or absent values
+    fn is_empty(&self) -> bool;
+
+    fn deserialize_element<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>;
+}
+
+impl<T: Element + DeserializeOwned> Cardinality for Option<T> {
+    type Inner = T;
+    const REPEATS: bool = false;
+
+    fn is_empty(&self) -> bool {
+        self.is_none()
+    }
+
+    /// A list where a single value is expected is refused (serde would read a struct from it by position)
+    fn deserialize_element<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(One(PhantomData))
+    }
+}
+
+impl<T: Element + DeserializeOwned> Cardinality for Vec<T> {
+    type Inner = T;
+    const REPEATS: bool = true;
+
+    fn is_empty(&self) -> bool {
+        Vec::is_empty(self)
+    }
+
+    /// A single value where a list is expected (`contact.name.text` without an index) is its first item
+    fn deserialize_element<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(OneOrMany(PhantomData))
+    }
+}
+
+struct One<T>(PhantomData<T>);
+
+impl<'de, T: DeserializeOwned> Visitor<'de> for One<T> {
+    type Value = Option<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("um único valor, não uma lista")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        T::deserialize(de::value::MapAccessDeserializer::new(map)).map(Some)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        T::deserialize(value.into_deserializer()).map(Some)
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<Self::Value, E> {
+        T::deserialize(value.into_deserializer()).map(Some)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        T::deserialize(value.into_deserializer()).map(Some)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        T::deserialize(value.into_deserializer()).map(Some)
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Self::Value, E> {
+        T::deserialize(value.into_deserializer()).map(Some)
+    }
+}
+
+struct OneOrMany<T>(PhantomData<T>);
+
+impl<'de, T: DeserializeOwned> Visitor<'de> for OneOrMany<T> {
+    type Value = Vec<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("uma lista")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(items)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        T::deserialize(de::value::MapAccessDeserializer::new(map)).map(|item| vec![item])
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        T::deserialize(value.into_deserializer()).map(|item| vec![item])
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<Self::Value, E> {
+        T::deserialize(value.into_deserializer()).map(|item| vec![item])
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        T::deserialize(value.into_deserializer()).map(|item| vec![item])
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        T::deserialize(value.into_deserializer()).map(|item| vec![item])
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Self::Value, E> {
+        T::deserialize(value.into_deserializer()).map(|item| vec![item])
+    }
+}
+
+/// Struct of the typed model with its element table; every field is an `Option` or a `Vec`
+macro_rules! fhir_struct {
+    ($(#[$meta:meta])* $name:ident { $($json:literal => $field:ident: $ty:ty),* $(,)? }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+        #[serde(deny_unknown_fields)]
+        pub struct $name {
+            $(
+                #[serde(
+                    rename = $json,
+                    default,
+                    skip_serializing_if = "crate::domain::fhir::r4::model::Cardinality::is_empty",
+                    deserialize_with = "crate::domain::fhir::r4::model::Cardinality::deserialize_element"
+                )]
+                pub $field: $ty,
+            )*
+        }
+
+        impl $crate::domain::fhir::r4::model::Element for $name {
+            fn element_type() -> &'static $crate::domain::fhir::r4::model::ElementType {
+                use $crate::domain::fhir::r4::model::{Cardinality, Child, Element, ElementType};
+                static TYPE: ElementType = ElementType {
+                    name: stringify!($name),
+                    primitive: false,
+                    children: &[
+                        $(Child {
+                            name: $json,
+                            repeats: <$ty as Cardinality>::REPEATS,
+                            element_type: <<$ty as Cardinality>::Inner as Element>::element_type,
+                        },)*
+                    ],
+                };
+                &TYPE
+            }
+        }
+    };
+}
+
+/// Datatype: every element has `id` and `extension`
+macro_rules! fhir_element {
+    ($(#[$meta:meta])* $name:ident { $($body:tt)* }) => {
+        $crate::domain::fhir::r4::model::fhir_struct! {
+            $(#[$meta])*
+            $name {
+                "id" => id: Option<FhirString>,
+                "extension" => extension: Vec<Extension>,
+                $($body)*
+            }
+        }
+    };
+}
+
+/// Backbone element: a datatype that may also carry modifier extensions
+macro_rules! fhir_backbone {
+    ($(#[$meta:meta])* $name:ident { $($body:tt)* }) => {
+        $crate::domain::fhir::r4::model::fhir_element! {
+            $(#[$meta])*
+            $name {
+                "modifierExtension" => modifier_extension: Vec<Extension>,
+                $($body)*
+            }
+        }
+    };
+}
+
+/// Domain resource: `resourceType` is written by [`conform`], not by the struct
+macro_rules! fhir_resource {
+    ($(#[$meta:meta])* $name:ident { $($body:tt)* }) => {
+        $crate::domain::fhir::r4::model::fhir_struct! {
+            $(#[$meta])*
+            $name {
+                "id" => id: Option<Id>,
+                "meta" => meta: Option<Meta>,
+                "implicitRules" => implicit_rules: Option<Uri>,
+                "language" => language: Option<Code>,
+                "text" => text: Option<Narrative>,
+                "contained" => contained: Vec<serde_json::Value>,
+                "extension" => extension: Vec<Extension>,
+                "modifierExtension" => modifier_extension: Vec<Extension>,
+                $($body)*
+            }
+        }
+    };
+}
+
+pub(crate) use {fhir_backbone, fhir_element, fhir_resource, fhir_struct};
+
+/// Element path or value that does not fit the typed model
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructureError {
+    /// `Patient.birthDate`, `Encounter.participant[0].individual`
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for StructureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+impl From<StructureError> for AppError {
+    fn from(error: StructureError) -> Self {
+        AppError::Validation(error.to_string())
+    }
+}
+
+/// Check a mapped element path (`identifier[0].value`) against the typed model of `resource_type`
+///
+/// The path must name existing elements, index only repeating ones and end on a primitive.
+/// A repeating element without an index stands for its first item. Resource types without
+/// a typed model are not checked.
+pub fn check_path(resource_type: &str, path: &str) -> Result<(), StructureError> {
+    primitive_at(resource_type, path).map(|_| ())
+}
+
+/// FHIR type of the primitive a mapping path ends on (`date`, `dateTime`, `code`...), checked as in
+/// `check_path`; None for resource types without a typed model
+pub fn primitive_at(resource_type: &str, path: &str) -> Result<Option<&'static str>, StructureError> {
+    let Some(mut current) = resources::element_type(resource_type) else {
+        return Ok(None);
+    };
+
+    let mut walked = resource_type.to_string();
+    let error = |walked: &str, message: String| StructureError { path: walked.to_string(), message };
+
+    for segment in path.split('.') {
+        let (name, index) = match segment.split_once('[') {
+            Some((name, rest)) => {
+                let index = rest.strip_suffix(']').filter(|index| index.parse::<usize>().is_ok());
+                if index.is_none() {
+                    return Err(error(&walked, format!("índice inválido em '{}'", segment)));
+                }
+                (name, index)
+            }
+            None => (segment, None),
+        };
+
+        if current.primitive {
+            return Err(error(&walked, format!("{} é primitivo e não possui o elemento '{}'", current.name, name)));
+        }
+
+        let child = current
+            .child(name)
+            .ok_or_else(|| error(&walked, format!("'{}' não é um elemento de {}", name, current.name)))?;
+        if index.is_some() && !child.repeats {
+            return Err(error(&walked, format!("'{}' não se repete e não aceita índice", name)));
+        }
+
+        walked = format!("{}.{}", walked, segment);
+        current = (child.element_type)();
+    }
+
+    if !current.primitive {
+        return Err(error(&walked, format!("o caminho termina em {}; mapeie um de seus elementos primitivos", current.name)));
+    }
+
+    Ok(Some(current.name))
+}
+
+/// Remove what FHIR JSON does not allow: null, blank strings, and arrays or objects left empty
+pub fn prune_empty(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for item in map.values_mut() {
+                prune_empty(item);
+            }
+            map.retain(|_, item| !is_empty(item));
+        }
+        Value::Array(items) => {
+            for item in items.iter_mut() {
+                prune_empty(item);
+            }
+            items.retain(|item| !is_empty(item));
+        }
+        _ => {}
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(text) => text.trim().is_empty(),
+        Value::Array(items) => items.is_empty(),
+        Value::Object(map) => map.is_empty(),
+        _ => false,
+    }
+}
+
+/// A resource as FHIR JSON: empty values removed and, for types with a typed model, every element
+/// and primitive format checked (booleans and numbers written as strings become JSON booleans and numbers)
+pub fn conform(resource: &Value) -> Result<Value, StructureError> {
+    let mut resource = resource.clone();
+    prune_empty(&mut resource);
+
+    let resource_type = resource.get("resourceType").and_then(Value::as_str).unwrap_or_default().to_string();
+    match resources::conform_typed(&resource_type, resource.clone()) {
+        Some(conformed) => conformed,
+        None => Ok(resource),
+    }
+}
+
+/// Round trip through the typed struct of the resource
+pub(super) fn conform_as<T: DeserializeOwned + Serialize>(resource_type: &str, mut resource: Value) -> Result<Value, StructureError> {
+    if let Some(object) = resource.as_object_mut() {
+        object.remove("resourceType");
+    }
+
+    let typed: T = serde_path_to_error::deserialize(resource).map_err(|e| {
+        let path = e.path().to_string();
+        StructureError {
+            path: if path == "." { resource_type.to_string() } else { format!("{}.{}", resource_type, path) },
+            message: e.inner().to_string(),
+        }
+    })?;
+
+    let mut conformed = serde_json::to_value(&typed).map_err(|e| StructureError {
+        path: resource_type.to_string(),
+        message: e.to_string(),
+    })?;
+    if let Some(object) = conformed.as_object_mut() {
+        object.insert("resourceType".to_string(), Value::String(resource_type.to_string()));
+    }
+
+    Ok(conformed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_checks_mapped_paths() {
+        assert!(check_path("Patient", "identifier[0].value").is_ok());
+        assert!(check_path("Patient", "extension[4].valueAddress.city").is_ok());
+        assert!(check_path("Patient", "contact.name.text").is_ok());
+        assert!(check_path("Encounter", "participant[0].individual.reference").is_ok());
+        assert!(check_path("Location", "position.latitude").is_ok());
+        assert!(check_path("Basic", "anything.at[0].all").is_ok());
+
+        let error = check_path("Patient", "name[0].firstName").unwrap_err();
+        assert_eq!(error.path, "Patient.name[0]");
+        assert!(error.message.contains("firstName"));
+
+        assert!(check_path("Patient", "birthDate[0]").is_err());
+        assert!(check_path("Patient", "birthDate.value").is_err());
+        assert!(check_path("Patient", "maritalStatus").is_err());
+        assert!(check_path("Encounter", "class[0].coding[0].code").is_err());
+        assert!(check_path("Patient", "identifier[x].value").is_err());
+    }
+
+    #[test]
+    fn test_conform_writes_fhir_json() {
+        let patient = json!({
+            "resourceType": "Patient",
+            "active": "true",
+            "birthDate": "1990-05-01",
+            "gender": "",
+            "name": [{ "text": "  Maria  ", "given": [""] }, { "text": "" }],
+            "telecom": [{ "system": "phone", "value": "5199", "rank": "1" }],
+            "contact": { "name": { "text": "João" } },
+            "address": [{ "line": [null, ""] }]
+        });
+
+        let conformed = conform(&patient).unwrap();
+        assert_eq!(conformed, json!({
+            "resourceType": "Patient",
+            "active": true,
+            "birthDate": "1990-05-01",
+            "name": [{ "text": "Maria" }],
+            "telecom": [{ "system": "phone", "value": "5199", "rank": 1 }],
+            "contact": [{ "name": { "text": "João" } }]
+        }));
+
+        let error = conform(&json!({ "resourceType": "Patient", "birthDate": "31/12/1990" })).unwrap_err();
+        assert_eq!(error.path, "Patient.birthDate");
+
+        let error = conform(&json!({ "resourceType": "Encounter", "period": { "start": "2024-01-01T10:00:00" } })).unwrap_err();
+        assert_eq!(error.path, "Encounter.period.start");
+
+        let error = conform(&json!({ "resourceType": "Encounter", "class": [{ "code": "AMB" }] })).unwrap_err();
+        assert_eq!(error.path, "Encounter.class");
+
+        assert!(conform(&json!({ "resourceType": "Patient", "nickname": "Mari" })).is_err());
+        assert_eq!(
+            conform(&json!({ "resourceType": "Basic", "code": { "text": "" }, "note": "x" })).unwrap(),
+            json!({ "resourceType": "Basic", "note": "x" })
+        );
+    }
+}
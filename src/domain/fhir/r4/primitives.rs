@@ -0,0 +1,289 @@
+// R4 primitive types - values are checked against the FHIR lexical format when read
+use std::fmt;
+
+use chrono::{DateTime as ChronoDateTime, NaiveDate, NaiveTime};
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Number;
+
+use super::model::{Element, ElementType};
+
+/// String-based primitive: trimmed, and refused when `valid` rejects it
+macro_rules! string_primitive {
+    ($(#[$meta:meta])* $name:ident, $fhir:literal, $valid:expr) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Default, PartialEq, Eq)]
+        pub struct $name(pub String);
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&self.0)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let text = deserializer.deserialize_any(TextVisitor($fhir))?;
+                let valid: fn(&str) -> bool = $valid;
+                if valid(&text) {
+                    Ok($name(text))
+                } else {
+                    Err(de::Error::custom(format!("{} inválido: '{}'", $fhir, text)))
+                }
+            }
+        }
+
+        impl Element for $name {
+            fn element_type() -> &'static ElementType {
+                static TYPE: ElementType = ElementType { name: $fhir, primitive: true, children: &[] };
+                &TYPE
+            }
+        }
+    };
+}
+
+string_primitive!(FhirString, "string", |text| !text.is_empty());
+string_primitive!(Markdown, "markdown", |text| !text.is_empty());
+string_primitive!(
+    /// Narrative XHTML (`Narrative.div`)
+    Xhtml, "xhtml", |text| !text.is_empty()
+);
+string_primitive!(Code, "code", |text| !text.is_empty() && !text.contains(['\t', '\n', '\r']) && !text.contains("  "));
+string_primitive!(Id, "id", |text| {
+    (1..=64).contains(&text.len()) && text.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+});
+string_primitive!(
+    /// Also used for `url`, `canonical` and `oid`
+    Uri, "uri", |text| !text.is_empty() && !text.contains(char::is_whitespace)
+);
+string_primitive!(Base64Binary, "base64Binary", |text| {
+    text.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '=' | ' ' | '\n' | '\r'))
+});
+string_primitive!(Date, "date", is_date);
+string_primitive!(
+    /// Partial dates, or date and time with seconds and a timezone offset
+    DateTime, "dateTime", |text| match text.split_once('T') {
+        Some(_) => ChronoDateTime::parse_from_rfc3339(text).is_ok(),
+        None => is_date(text),
+    }
+);
+string_primitive!(Instant, "instant", |text| text.contains('T') && ChronoDateTime::parse_from_rfc3339(text).is_ok());
+string_primitive!(Time, "time", |text| text.len() >= 8 && NaiveTime::parse_from_str(text, "%H:%M:%S%.f").is_ok());
+
+/// `YYYY`, `YYYY-MM` or `YYYY-MM-DD`
+fn is_date(text: &str) -> bool {
+    let digits = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit());
+    if !text.is_ascii() {
+        return false;
+    }
+    match text.len() {
+        4 => digits(text),
+        7 => digits(&text[..4]) && NaiveDate::parse_from_str(&format!("{}-01", text), "%Y-%m-%d").is_ok() && &text[4..5] == "-",
+        10 => digits(&text[..4]) && &text[4..5] == "-" && &text[7..8] == "-" && NaiveDate::parse_from_str(text, "%Y-%m-%d").is_ok(),
+        _ => false,
+    }
+}
+
+/// String primitives read from JSON strings only
+struct TextVisitor(&'static str);
+
+impl<'de> Visitor<'de> for TextVisitor {
+    type Value = String;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "um texto ({})", self.0)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<String, E> {
+        Ok(value.trim().to_string())
+    }
+}
+
+/// `true`/`false`, also when the source column holds them as text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Boolean(pub bool);
+
+impl Serialize for Boolean {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bool(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Boolean {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BooleanVisitor;
+
+        impl<'de> Visitor<'de> for BooleanVisitor {
+            type Value = Boolean;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("true ou false")
+            }
+
+            fn visit_bool<E: de::Error>(self, value: bool) -> Result<Boolean, E> {
+                Ok(Boolean(value))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Boolean, E> {
+                match value.trim().to_lowercase().as_str() {
+                    "true" => Ok(Boolean(true)),
+                    "false" => Ok(Boolean(false)),
+                    _ => Err(E::custom(format!("boolean inválido: '{}'", value))),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(BooleanVisitor)
+    }
+}
+
+impl Element for Boolean {
+    fn element_type() -> &'static ElementType {
+        static TYPE: ElementType = ElementType { name: "boolean", primitive: true, children: &[] };
+        &TYPE
+    }
+}
+
+/// 32-bit integers at or above `MIN`: `integer` (any), `unsignedInt` (0) and `positiveInt` (1)
+macro_rules! integer_primitive {
+    ($name:ident, $fhir:literal, $min:expr) => {
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+        pub struct $name(pub i32);
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_i32(self.0)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = deserializer.deserialize_any(IntegerVisitor($fhir))?;
+                i32::try_from(value)
+                    .ok()
+                    .filter(|value| *value >= $min)
+                    .map($name)
+                    .ok_or_else(|| de::Error::custom(format!("{} inválido: {}", $fhir, value)))
+            }
+        }
+
+        impl Element for $name {
+            fn element_type() -> &'static ElementType {
+                static TYPE: ElementType = ElementType { name: $fhir, primitive: true, children: &[] };
+                &TYPE
+            }
+        }
+    };
+}
+
+integer_primitive!(Integer, "integer", i32::MIN);
+integer_primitive!(UnsignedInt, "unsignedInt", 0);
+integer_primitive!(PositiveInt, "positiveInt", 1);
+
+struct IntegerVisitor(&'static str);
+
+impl<'de> Visitor<'de> for IntegerVisitor {
+    type Value = i64;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "um número inteiro ({})", self.0)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<i64, E> {
+        Ok(value)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<i64, E> {
+        i64::try_from(value).map_err(|_| E::custom(format!("{} inválido: {}", self.0, value)))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<i64, E> {
+        value.trim().parse().map_err(|_| E::custom(format!("{} inválido: '{}'", self.0, value)))
+    }
+}
+
+/// Decimal, written as a JSON number
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decimal(pub Number);
+
+impl Default for Decimal {
+    fn default() -> Self {
+        Decimal(Number::from(0))
+    }
+}
+
+impl Serialize for Decimal {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Decimal {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DecimalVisitor;
+
+        impl<'de> Visitor<'de> for DecimalVisitor {
+            type Value = Decimal;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("um número decimal")
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Decimal, E> {
+                Ok(Decimal(value.into()))
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Decimal, E> {
+                Ok(Decimal(value.into()))
+            }
+
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<Decimal, E> {
+                Number::from_f64(value).map(Decimal).ok_or_else(|| E::custom(format!("decimal inválido: {}", value)))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Decimal, E> {
+                value
+                    .trim()
+                    .parse::<Number>()
+                    .map(Decimal)
+                    .map_err(|_| E::custom(format!("decimal inválido: '{}'", value)))
+            }
+        }
+
+        deserializer.deserialize_any(DecimalVisitor)
+    }
+}
+
+impl Element for Decimal {
+    fn element_type() -> &'static ElementType {
+        static TYPE: ElementType = ElementType { name: "decimal", primitive: true, children: &[] };
+        &TYPE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_reads_primitive_formats() {
+        assert!(serde_json::from_value::<Date>(json!("2024-02-29")).is_ok());
+        assert!(serde_json::from_value::<Date>(json!("2024-02")).is_ok());
+        assert!(serde_json::from_value::<Date>(json!("2023-02-29")).is_err());
+        assert!(serde_json::from_value::<Date>(json!("2024-2-1")).is_err());
+        assert!(serde_json::from_value::<Date>(json!("2024-0é-01")).is_err());
+        assert!(serde_json::from_value::<DateTime>(json!("2024-01-01T10:00:00-03:00")).is_ok());
+        assert!(serde_json::from_value::<DateTime>(json!("2024-01-01T10:00:00")).is_err());
+        assert!(serde_json::from_value::<Instant>(json!("2024-01-01")).is_err());
+        assert!(serde_json::from_value::<Time>(json!("08:30:00")).is_ok());
+        assert!(serde_json::from_value::<Code>(json!("male female")).is_ok());
+        assert!(serde_json::from_value::<Id>(json!("a b")).is_err());
+
+        assert_eq!(serde_json::from_value::<Boolean>(json!(" TRUE ")).unwrap(), Boolean(true));
+        assert_eq!(serde_json::from_value::<PositiveInt>(json!("3")).unwrap(), PositiveInt(3));
+        assert!(serde_json::from_value::<PositiveInt>(json!(0)).is_err());
+        assert_eq!(serde_json::to_value(serde_json::from_value::<Decimal>(json!("36.5")).unwrap()).unwrap(), json!(36.5));
+        assert!(serde_json::from_value::<FhirString>(json!(12)).is_err());
+    }
+}
@@ -0,0 +1,354 @@
+// R4 resources generated from view mappings
+use serde_json::Value;
+
+use super::datatypes::*;
+use super::model::{conform_as, fhir_backbone, fhir_resource, Element, ElementType, StructureError};
+use super::primitives::*;
+
+fhir_resource! {
+    Patient {
+        "identifier" => identifier: Vec<Identifier>,
+        "active" => active: Option<Boolean>,
+        "name" => name: Vec<HumanName>,
+        "telecom" => telecom: Vec<ContactPoint>,
+        "gender" => gender: Option<Code>,
+        "birthDate" => birth_date: Option<Date>,
+        "deceasedBoolean" => deceased_boolean: Option<Boolean>,
+        "deceasedDateTime" => deceased_date_time: Option<DateTime>,
+        "address" => address: Vec<Address>,
+        "maritalStatus" => marital_status: Option<CodeableConcept>,
+        "multipleBirthBoolean" => multiple_birth_boolean: Option<Boolean>,
+        "multipleBirthInteger" => multiple_birth_integer: Option<Integer>,
+        "photo" => photo: Vec<Attachment>,
+        "contact" => contact: Vec<PatientContact>,
+        "communication" => communication: Vec<PatientCommunication>,
+        "generalPractitioner" => general_practitioner: Vec<Reference>,
+        "managingOrganization" => managing_organization: Option<Reference>,
+        "link" => link: Vec<PatientLink>,
+    }
+}
+
+fhir_backbone! {
+    PatientContact {
+        "relationship" => relationship: Vec<CodeableConcept>,
+        "name" => name: Option<HumanName>,
+        "telecom" => telecom: Vec<ContactPoint>,
+        "address" => address: Option<Address>,
+        "gender" => gender: Option<Code>,
+        "organization" => organization: Option<Reference>,
+        "period" => period: Option<Period>,
+    }
+}
+
+fhir_backbone! {
+    PatientCommunication {
+        "language" => language: Option<CodeableConcept>,
+        "preferred" => preferred: Option<Boolean>,
+    }
+}
+
+fhir_backbone! {
+    PatientLink {
+        "other" => other: Option<Reference>,
+        "type" => type_: Option<Code>,
+    }
+}
+
+fhir_resource! {
+    Encounter {
+        "identifier" => identifier: Vec<Identifier>,
+        "status" => status: Option<Code>,
+        "statusHistory" => status_history: Vec<EncounterStatusHistory>,
+        "class" => class: Option<Coding>,
+        "classHistory" => class_history: Vec<EncounterClassHistory>,
+        "type" => type_: Vec<CodeableConcept>,
+        "serviceType" => service_type: Option<CodeableConcept>,
+        "priority" => priority: Option<CodeableConcept>,
+        "subject" => subject: Option<Reference>,
+        "episodeOfCare" => episode_of_care: Vec<Reference>,
+        "basedOn" => based_on: Vec<Reference>,
+        "participant" => participant: Vec<EncounterParticipant>,
+        "appointment" => appointment: Vec<Reference>,
+        "period" => period: Option<Period>,
+        "length" => length: Option<Quantity>,
+        "reasonCode" => reason_code: Vec<CodeableConcept>,
+        "reasonReference" => reason_reference: Vec<Reference>,
+        "diagnosis" => diagnosis: Vec<EncounterDiagnosis>,
+        "account" => account: Vec<Reference>,
+        "hospitalization" => hospitalization: Option<EncounterHospitalization>,
+        "location" => location: Vec<EncounterLocation>,
+        "serviceProvider" => service_provider: Option<Reference>,
+        "partOf" => part_of: Option<Reference>,
+    }
+}
+
+fhir_backbone! {
+    EncounterStatusHistory {
+        "status" => status: Option<Code>,
+        "period" => period: Option<Period>,
+    }
+}
+
+fhir_backbone! {
+    EncounterClassHistory {
+        "class" => class: Option<Coding>,
+        "period" => period: Option<Period>,
+    }
+}
+
+fhir_backbone! {
+    EncounterParticipant {
+        "type" => type_: Vec<CodeableConcept>,
+        "period" => period: Option<Period>,
+        "individual" => individual: Option<Reference>,
+    }
+}
+
+fhir_backbone! {
+    EncounterDiagnosis {
+        "condition" => condition: Option<Reference>,
+        "use" => use_: Option<CodeableConcept>,
+        "rank" => rank: Option<PositiveInt>,
+    }
+}
+
+fhir_backbone! {
+    EncounterHospitalization {
+        "preAdmissionIdentifier" => pre_admission_identifier: Option<Identifier>,
+        "origin" => origin: Option<Reference>,
+        "admitSource" => admit_source: Option<CodeableConcept>,
+        "reAdmission" => re_admission: Option<CodeableConcept>,
+        "dietPreference" => diet_preference: Vec<CodeableConcept>,
+        "specialCourtesy" => special_courtesy: Vec<CodeableConcept>,
+        "specialArrangement" => special_arrangement: Vec<CodeableConcept>,
+        "destination" => destination: Option<Reference>,
+        "dischargeDisposition" => discharge_disposition: Option<CodeableConcept>,
+    }
+}
+
+fhir_backbone! {
+    EncounterLocation {
+        "location" => location: Option<Reference>,
+        "status" => status: Option<Code>,
+        "physicalType" => physical_type: Option<CodeableConcept>,
+        "period" => period: Option<Period>,
+    }
+}
+
+fhir_resource! {
+    Practitioner {
+        "identifier" => identifier: Vec<Identifier>,
+        "active" => active: Option<Boolean>,
+        "name" => name: Vec<HumanName>,
+        "telecom" => telecom: Vec<ContactPoint>,
+        "address" => address: Vec<Address>,
+        "gender" => gender: Option<Code>,
+        "birthDate" => birth_date: Option<Date>,
+        "photo" => photo: Vec<Attachment>,
+        "qualification" => qualification: Vec<PractitionerQualification>,
+        "communication" => communication: Vec<CodeableConcept>,
+    }
+}
+
+fhir_backbone! {
+    PractitionerQualification {
+        "identifier" => identifier: Vec<Identifier>,
+        "code" => code: Option<CodeableConcept>,
+        "period" => period: Option<Period>,
+        "issuer" => issuer: Option<Reference>,
+    }
+}
+
+fhir_resource! {
+    PractitionerRole {
+        "identifier" => identifier: Vec<Identifier>,
+        "active" => active: Option<Boolean>,
+        "period" => period: Option<Period>,
+        "practitioner" => practitioner: Option<Reference>,
+        "organization" => organization: Option<Reference>,
+        "code" => code: Vec<CodeableConcept>,
+        "specialty" => specialty: Vec<CodeableConcept>,
+        "location" => location: Vec<Reference>,
+        "healthcareService" => healthcare_service: Vec<Reference>,
+        "telecom" => telecom: Vec<ContactPoint>,
+        "availableTime" => available_time: Vec<PractitionerRoleAvailableTime>,
+        "notAvailable" => not_available: Vec<PractitionerRoleNotAvailable>,
+        "availabilityExceptions" => availability_exceptions: Option<FhirString>,
+        "endpoint" => endpoint: Vec<Reference>,
+    }
+}
+
+fhir_backbone! {
+    PractitionerRoleAvailableTime {
+        "daysOfWeek" => days_of_week: Vec<Code>,
+        "allDay" => all_day: Option<Boolean>,
+        "availableStartTime" => available_start_time: Option<Time>,
+        "availableEndTime" => available_end_time: Option<Time>,
+    }
+}
+
+fhir_backbone! {
+    PractitionerRoleNotAvailable {
+        "description" => description: Option<FhirString>,
+        "during" => during: Option<Period>,
+    }
+}
+
+fhir_resource! {
+    Organization {
+        "identifier" => identifier: Vec<Identifier>,
+        "active" => active: Option<Boolean>,
+        "type" => type_: Vec<CodeableConcept>,
+        "name" => name: Option<FhirString>,
+        "alias" => alias: Vec<FhirString>,
+        "telecom" => telecom: Vec<ContactPoint>,
+        "address" => address: Vec<Address>,
+        "partOf" => part_of: Option<Reference>,
+        "contact" => contact: Vec<OrganizationContact>,
+        "endpoint" => endpoint: Vec<Reference>,
+    }
+}
+
+fhir_backbone! {
+    OrganizationContact {
+        "purpose" => purpose: Option<CodeableConcept>,
+        "name" => name: Option<HumanName>,
+        "telecom" => telecom: Vec<ContactPoint>,
+        "address" => address: Option<Address>,
+    }
+}
+
+fhir_resource! {
+    Location {
+        "identifier" => identifier: Vec<Identifier>,
+        "status" => status: Option<Code>,
+        "operationalStatus" => operational_status: Option<Coding>,
+        "name" => name: Option<FhirString>,
+        "alias" => alias: Vec<FhirString>,
+        "description" => description: Option<FhirString>,
+        "mode" => mode: Option<Code>,
+        "type" => type_: Vec<CodeableConcept>,
+        "telecom" => telecom: Vec<ContactPoint>,
+        "address" => address: Option<Address>,
+        "physicalType" => physical_type: Option<CodeableConcept>,
+        "position" => position: Option<LocationPosition>,
+        "managingOrganization" => managing_organization: Option<Reference>,
+        "partOf" => part_of: Option<Reference>,
+        "hoursOfOperation" => hours_of_operation: Vec<LocationHoursOfOperation>,
+        "availabilityExceptions" => availability_exceptions: Option<FhirString>,
+        "endpoint" => endpoint: Vec<Reference>,
+    }
+}
+
+fhir_backbone! {
+    LocationPosition {
+        "longitude" => longitude: Option<Decimal>,
+        "latitude" => latitude: Option<Decimal>,
+        "altitude" => altitude: Option<Decimal>,
+    }
+}
+
+fhir_backbone! {
+    LocationHoursOfOperation {
+        "daysOfWeek" => days_of_week: Vec<Code>,
+        "allDay" => all_day: Option<Boolean>,
+        "openingTime" => opening_time: Option<Time>,
+        "closingTime" => closing_time: Option<Time>,
+    }
+}
+
+fhir_resource! {
+    Observation {
+        "identifier" => identifier: Vec<Identifier>,
+        "basedOn" => based_on: Vec<Reference>,
+        "partOf" => part_of: Vec<Reference>,
+        "status" => status: Option<Code>,
+        "category" => category: Vec<CodeableConcept>,
+        "code" => code: Option<CodeableConcept>,
+        "subject" => subject: Option<Reference>,
+        "focus" => focus: Vec<Reference>,
+        "encounter" => encounter: Option<Reference>,
+        "effectiveDateTime" => effective_date_time: Option<DateTime>,
+        "effectivePeriod" => effective_period: Option<Period>,
+        "effectiveTiming" => effective_timing: Option<Timing>,
+        "effectiveInstant" => effective_instant: Option<Instant>,
+        "issued" => issued: Option<Instant>,
+        "performer" => performer: Vec<Reference>,
+        "valueQuantity" => value_quantity: Option<Quantity>,
+        "valueCodeableConcept" => value_codeable_concept: Option<CodeableConcept>,
+        "valueString" => value_string: Option<FhirString>,
+        "valueBoolean" => value_boolean: Option<Boolean>,
+        "valueInteger" => value_integer: Option<Integer>,
+        "valueRange" => value_range: Option<Range>,
+        "valueRatio" => value_ratio: Option<Ratio>,
+        "valueSampledData" => value_sampled_data: Option<SampledData>,
+        "valueTime" => value_time: Option<Time>,
+        "valueDateTime" => value_date_time: Option<DateTime>,
+        "valuePeriod" => value_period: Option<Period>,
+        "dataAbsentReason" => data_absent_reason: Option<CodeableConcept>,
+        "interpretation" => interpretation: Vec<CodeableConcept>,
+        "note" => note: Vec<Annotation>,
+        "bodySite" => body_site: Option<CodeableConcept>,
+        "method" => method: Option<CodeableConcept>,
+        "specimen" => specimen: Option<Reference>,
+        "device" => device: Option<Reference>,
+        "referenceRange" => reference_range: Vec<ObservationReferenceRange>,
+        "hasMember" => has_member: Vec<Reference>,
+        "derivedFrom" => derived_from: Vec<Reference>,
+        "component" => component: Vec<ObservationComponent>,
+    }
+}
+
+fhir_backbone! {
+    ObservationReferenceRange {
+        "low" => low: Option<Quantity>,
+        "high" => high: Option<Quantity>,
+        "type" => type_: Option<CodeableConcept>,
+        "appliesTo" => applies_to: Vec<CodeableConcept>,
+        "age" => age: Option<Range>,
+        "text" => text: Option<FhirString>,
+    }
+}
+
+fhir_backbone! {
+    ObservationComponent {
+        "code" => code: Option<CodeableConcept>,
+        "valueQuantity" => value_quantity: Option<Quantity>,
+        "valueCodeableConcept" => value_codeable_concept: Option<CodeableConcept>,
+        "valueString" => value_string: Option<FhirString>,
+        "valueBoolean" => value_boolean: Option<Boolean>,
+        "valueInteger" => value_integer: Option<Integer>,
+        "valueRange" => value_range: Option<Range>,
+        "valueRatio" => value_ratio: Option<Ratio>,
+        "valueSampledData" => value_sampled_data: Option<SampledData>,
+        "valueTime" => value_time: Option<Time>,
+        "valueDateTime" => value_date_time: Option<DateTime>,
+        "valuePeriod" => value_period: Option<Period>,
+        "dataAbsentReason" => data_absent_reason: Option<CodeableConcept>,
+        "interpretation" => interpretation: Vec<CodeableConcept>,
+        "referenceRange" => reference_range: Vec<ObservationReferenceRange>,
+    }
+}
+
+/// Resource types with a typed model
+macro_rules! supported_resources {
+    ($($name:ident),* $(,)?) => {
+        /// Element table of a resource type; None when it has no typed model
+        pub fn element_type(resource_type: &str) -> Option<&'static ElementType> {
+            match resource_type {
+                $(stringify!($name) => Some($name::element_type()),)*
+                _ => None,
+            }
+        }
+
+        /// Resource through its typed struct; None when its type has no typed model
+        pub(super) fn conform_typed(resource_type: &str, resource: Value) -> Option<Result<Value, StructureError>> {
+            match resource_type {
+                $(stringify!($name) => Some(conform_as::<$name>(resource_type, resource)),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+supported_resources!(Patient, Encounter, Practitioner, PractitionerRole, Organization, Location, Observation);
@@ -0,0 +1,7 @@
+// R4B keeps the R4 shape of the Bundle, the transaction entry and every element mapped today
+// (its changes are in medication definition and evidence resources)
+pub use super::r4::{bundle, resource};
+
+use super::PathRename;
+
+pub const RENAMES: &[PathRename] = &[];
@@ -0,0 +1,9 @@
+use serde_json::{json, Value};
+
+pub fn get_template() -> Value {
+    json!({
+        "resourceType": "Bundle",
+        "type": "transaction",
+        "entry": []
+    })
+}
\ No newline at end of file
@@ -0,0 +1,5 @@
+pub mod bundle;
+pub mod resource;
+pub mod renames;
+
+pub use renames::{adapt_values, RENAMES};
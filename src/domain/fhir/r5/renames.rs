@@ -0,0 +1,62 @@
+use serde_json::Value;
+
+use crate::domain::fhir::PathRename;
+
+const fn rename(resource_type: &'static str, from: &'static str, to: &'static str) -> PathRename {
+    PathRename { resource_type, from, to }
+}
+
+/// R4 elements renamed or retyped in R5, for the resources mapped today
+pub const RENAMES: &[PathRename] = &[
+    // Encounter.class became 0..* CodeableConcept
+    rename("Encounter", "class", "class[0].coding[0]"),
+    rename("Encounter", "period", "actualPeriod"),
+    rename("Encounter", "hospitalization", "admission"),
+    rename("Encounter", "reasonCode", "reason[{i}].value[0].concept"),
+    rename("Encounter", "reasonReference", "reason[{i}].value[0].reference"),
+    rename("Encounter", "serviceType", "serviceType[0].concept"),
+    rename("Encounter", "participant.individual", "actor"),
+    rename("Encounter", "location.physicalType", "form"),
+    rename("Encounter", "diagnosis.condition", "condition[0].reference"),
+    rename("Encounter", "diagnosis.use", "use[0]"),
+    // medication[x] became a CodeableReference
+    rename("MedicationRequest", "medicationCodeableConcept", "medication.concept"),
+    rename("MedicationRequest", "medicationReference", "medication.reference"),
+    rename("MedicationRequest", "reasonCode", "reason[{i}].concept"),
+    rename("MedicationRequest", "reasonReference", "reason[{i}].reference"),
+    rename("MedicationStatement", "medicationCodeableConcept", "medication.concept"),
+    rename("MedicationStatement", "medicationReference", "medication.reference"),
+    rename("MedicationStatement", "context", "encounter"),
+    rename("MedicationAdministration", "medicationCodeableConcept", "medication.concept"),
+    rename("MedicationAdministration", "medicationReference", "medication.reference"),
+    rename("MedicationAdministration", "context", "encounter"),
+    rename("MedicationDispense", "medicationCodeableConcept", "medication.concept"),
+    rename("MedicationDispense", "medicationReference", "medication.reference"),
+    // performed[x] became occurrence[x]
+    rename("Procedure", "performedDateTime", "occurrenceDateTime"),
+    rename("Procedure", "performedPeriod", "occurrencePeriod"),
+    rename("Procedure", "performedString", "occurrenceString"),
+    rename("Procedure", "reasonCode", "reason[{i}].concept"),
+    rename("Procedure", "reasonReference", "reason[{i}].reference"),
+    rename("ServiceRequest", "reasonCode", "reason[{i}].concept"),
+    rename("ServiceRequest", "reasonReference", "reason[{i}].reference"),
+    rename("Immunization", "reasonCode", "reason[{i}].concept"),
+    rename("Immunization", "reasonReference", "reason[{i}].reference"),
+];
+
+/// Codes whose value set changed in R5; applied once the record values are in place
+pub fn adapt_values(resource: &mut Value) {
+    if resource.get("resourceType").and_then(Value::as_str) != Some("Encounter") {
+        return;
+    }
+
+    if let Some(status) = resource.get_mut("status") {
+        let adapted = match status.as_str() {
+            Some("finished") => "completed",
+            Some("arrived") | Some("triaged") => "in-progress",
+            Some("onleave") => "on-hold",
+            _ => return,
+        };
+        *status = Value::String(adapted.to_string());
+    }
+}
@@ -0,0 +1,64 @@
+use std::fmt;
+
+/// Identifier system of the source codes (`identifier[0].system` of every generated resource)
+pub const DEFAULT_IDENTIFIER_SYSTEM: &str = "https://interop.interhealth.com.br/NamingSystem/ns-codigo";
+
+/// Reference to a resource by business identifier, e.g. `Patient?identifier=system|code`
+///
+/// The source code is not the logical id on the target, so references are sent in this form and
+/// resolved by the server (or looked up beforehand, see `ReferenceStrategy`).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ConditionalReference {
+    pub resource_type: String,
+    pub system: String,
+    pub value: String,
+}
+
+impl ConditionalReference {
+    pub fn new(resource_type: &str, system: &str, value: &str) -> Self {
+        Self {
+            resource_type: resource_type.to_string(),
+            system: system.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    /// Parse `Type?identifier=system|value`; `None` for literal references (`Patient/123`, `urn:uuid:...`)
+    pub fn parse(reference: &str) -> Option<Self> {
+        let (resource_type, query) = reference.split_once('?')?;
+        let (system, value) = query.strip_prefix("identifier=")?.rsplit_once('|')?;
+
+        if resource_type.is_empty() || resource_type.contains('/') || value.is_empty() {
+            return None;
+        }
+        Some(Self::new(resource_type, system, value))
+    }
+
+    /// `identifier=system|value`, as used in `ifNoneExist` and searches
+    pub fn query(&self) -> String {
+        format!("identifier={}|{}", self.system, self.value)
+    }
+}
+
+impl fmt::Display for ConditionalReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}?{}", self.resource_type, self.query())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_conditional_references() {
+        let reference = ConditionalReference::new("Patient", DEFAULT_IDENTIFIER_SYSTEM, "000177482");
+        let text = reference.to_string();
+
+        assert_eq!(text, "Patient?identifier=https://interop.interhealth.com.br/NamingSystem/ns-codigo|000177482");
+        assert_eq!(ConditionalReference::parse(&text), Some(reference));
+        assert_eq!(ConditionalReference::parse("Patient/000177482"), None);
+        assert_eq!(ConditionalReference::parse("urn:uuid:7f0c"), None);
+        assert_eq!(ConditionalReference::parse("Patient?name=Maria"), None);
+    }
+}
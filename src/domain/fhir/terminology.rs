@@ -0,0 +1,412 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::profiles::definition::canonical;
+use crate::utils::{AppError, ValidationRecommendation};
+
+/// A code of a code system, as found in an expansion
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Concept {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+    pub code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display: Option<String>,
+}
+
+/// Outcome of checking a code against a value set
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum CodeCheck {
+    Valid {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        display: Option<String>,
+    },
+    Invalid,
+    /// The value set is not loaded or cannot be expanded locally (filters, incomplete code systems)
+    Unknown,
+}
+
+/// A loaded CodeSystem or ValueSet, for listing
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminologySummary {
+    pub resource_type: String,
+    pub url: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Concepts of the code system, or of the expansion of the value set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub concepts: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+struct CodeSystem {
+    name: String,
+    version: Option<String>,
+    /// `content` is complete: codes outside `concepts` are invalid
+    complete: bool,
+    concepts: Vec<Concept>,
+}
+
+/// CodeSystems and ValueSets loaded from FHIR packages or uploaded as JSON
+///
+/// Value sets are expanded locally when their compose lists concepts or includes whole,
+/// complete code systems; filters are not evaluated, so those value sets report `Unknown`.
+#[derive(Debug, Default)]
+pub struct Terminology {
+    code_systems: HashMap<String, CodeSystem>,
+    value_sets: HashMap<String, Value>,
+    /// Expansions by value set url, indexed by code
+    expansions: HashMap<String, HashMap<String, Vec<Concept>>>,
+}
+
+impl Terminology {
+    /// Terminology made of the CodeSystems and ValueSets among `resources`
+    pub fn from_resources(resources: &[Value]) -> Self {
+        let mut terminology = Terminology::default();
+        terminology.extend(resources);
+        terminology
+    }
+
+    /// Load the CodeSystems and ValueSets among `resources`, ignoring anything else
+    pub fn extend(&mut self, resources: &[Value]) {
+        for resource in resources {
+            let _ = self.insert(resource);
+        }
+        self.expand_all();
+    }
+
+    /// Load one CodeSystem or ValueSet, replacing a loaded one with the same url
+    pub fn add(&mut self, resource: &Value) -> Result<String, AppError> {
+        let url = self.insert(resource)?;
+        self.expand_all();
+        Ok(url)
+    }
+
+    pub fn remove(&mut self, url: &str) -> bool {
+        let url = canonical(url);
+        let code_system = self.code_systems.remove(url).is_some();
+        let value_set = self.value_sets.remove(url).is_some();
+        let removed = code_system || value_set;
+        if removed {
+            self.expand_all();
+        }
+        removed
+    }
+
+    fn insert(&mut self, resource: &Value) -> Result<String, AppError> {
+        let url = resource
+            .get("url")
+            .and_then(Value::as_str)
+            .map(|url| canonical(url).to_string())
+            .ok_or_else(|| AppError::Validation("Recurso de terminologia sem 'url'".to_string()))?;
+
+        match resource.get("resourceType").and_then(Value::as_str) {
+            Some("CodeSystem") => {
+                let mut concepts = Vec::new();
+                collect_concepts(resource.get("concept"), &url, &mut concepts);
+                self.code_systems.insert(
+                    url.clone(),
+                    CodeSystem {
+                        name: text(resource, "name").unwrap_or_else(|| url.clone()),
+                        version: text(resource, "version"),
+                        complete: matches!(resource.get("content").and_then(Value::as_str), None | Some("complete")),
+                        concepts,
+                    },
+                );
+            }
+            Some("ValueSet") => {
+                self.value_sets.insert(url.clone(), resource.clone());
+            }
+            _ => {
+                return Err(AppError::Validation(
+                    "Apenas recursos CodeSystem e ValueSet são aceitos".to_string(),
+                ))
+            }
+        }
+
+        Ok(url)
+    }
+
+    fn expand_all(&mut self) {
+        let mut expansions = HashMap::new();
+        for url in self.value_sets.keys() {
+            if let Some(concepts) = self.expand_value_set(url, &mut Vec::new()) {
+                let mut by_code: HashMap<String, Vec<Concept>> = HashMap::new();
+                for concept in concepts {
+                    by_code.entry(concept.code.clone()).or_default().push(concept);
+                }
+                expansions.insert(url.clone(), by_code);
+            }
+        }
+        self.expansions = expansions;
+    }
+
+    fn expand_value_set(&self, url: &str, visiting: &mut Vec<String>) -> Option<Vec<Concept>> {
+        if visiting.iter().any(|visited| visited == url) {
+            return None;
+        }
+        let value_set = self.value_sets.get(url)?;
+
+        if let Some(contains) = value_set.pointer("/expansion/contains") {
+            let mut concepts = Vec::new();
+            collect_expansion(Some(contains), &mut concepts);
+            return Some(concepts);
+        }
+
+        visiting.push(url.to_string());
+        let included = self.compose(value_set.pointer("/compose/include"), visiting);
+        let excluded = self.compose(value_set.pointer("/compose/exclude"), visiting);
+        visiting.pop();
+
+        let excluded = excluded.unwrap_or_default();
+        Some(
+            included?
+                .into_iter()
+                .filter(|concept| !excluded.iter().any(|e| e.code == concept.code && e.system == concept.system))
+                .collect(),
+        )
+    }
+
+    /// Concepts of `compose.include` (or `exclude`) entries
+    fn compose(&self, entries: Option<&Value>, visiting: &mut Vec<String>) -> Option<Vec<Concept>> {
+        let mut concepts = Vec::new();
+
+        for entry in entries.and_then(Value::as_array).into_iter().flatten() {
+            if entry.get("filter").is_some() {
+                return None;
+            }
+            let system = entry.get("system").and_then(Value::as_str).map(|system| canonical(system).to_string());
+
+            if let Some(listed) = entry.get("concept").and_then(Value::as_array) {
+                for concept in listed {
+                    let Some(code) = concept.get("code").and_then(Value::as_str) else {
+                        continue;
+                    };
+                    let display = text(concept, "display").or_else(|| {
+                        system.as_deref().and_then(|system| self.lookup(system, code)).and_then(|c| c.display.clone())
+                    });
+                    concepts.push(Concept { system: system.clone(), code: code.to_string(), display });
+                }
+            } else if let Some(system) = &system {
+                let code_system = self.code_systems.get(system).filter(|code_system| code_system.complete)?;
+                concepts.extend(code_system.concepts.iter().cloned());
+            }
+
+            for nested in entry.get("valueSet").and_then(Value::as_array).into_iter().flatten() {
+                concepts.extend(self.expand_value_set(canonical(nested.as_str()?), visiting)?);
+            }
+        }
+
+        Some(concepts)
+    }
+
+    /// Check a code, optionally qualified by its system, against a value set
+    pub fn validate_code(&self, value_set: &str, system: Option<&str>, code: &str) -> CodeCheck {
+        let Some(expansion) = self.expansions.get(canonical(value_set)) else {
+            return CodeCheck::Unknown;
+        };
+
+        let matched = expansion.get(code).and_then(|concepts| {
+            concepts
+                .iter()
+                .find(|concept| system.is_none() || concept.system.is_none() || concept.system.as_deref() == system)
+        });
+        match matched {
+            Some(concept) => CodeCheck::Valid { display: concept.display.clone() },
+            None => CodeCheck::Invalid,
+        }
+    }
+
+    pub fn has_value_set(&self, url: &str) -> bool {
+        self.expansions.contains_key(canonical(url))
+    }
+
+    /// Concept of a loaded code system
+    pub fn lookup(&self, system: &str, code: &str) -> Option<&Concept> {
+        self.code_systems
+            .get(canonical(system))?
+            .concepts
+            .iter()
+            .find(|concept| concept.code == code)
+    }
+
+    /// Codings of a resource whose system is a complete, loaded code system but whose code is not in it
+    pub fn check_codings(&self, resource: &Value) -> Vec<ValidationRecommendation> {
+        let mut recommendations = Vec::new();
+        self.walk_codings(resource, "", &mut recommendations);
+        recommendations
+    }
+
+    fn walk_codings(&self, value: &Value, path: &str, recommendations: &mut Vec<ValidationRecommendation>) {
+        match value {
+            Value::Object(map) => {
+                if let (Some(system), Some(code)) =
+                    (map.get("system").and_then(Value::as_str), map.get("code").and_then(Value::as_str))
+                {
+                    let code_system = self.code_systems.get(canonical(system)).filter(|code_system| code_system.complete);
+                    if code_system.is_some() && self.lookup(system, code).is_none() {
+                        recommendations.push(ValidationRecommendation {
+                            severity: "error".to_string(),
+                            field: if path.is_empty() { "code".to_string() } else { format!("{}.code", path) },
+                            message: format!("Code '{}' does not exist in code system {}", code, system),
+                            recommendation: "Map the source value to a code of this code system".to_string(),
+                        });
+                    }
+                }
+
+                for (key, nested) in map {
+                    let nested_path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                    self.walk_codings(nested, &nested_path, recommendations);
+                }
+            }
+            Value::Array(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    self.walk_codings(item, &format!("{}[{}]", path, index), recommendations);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn summaries(&self) -> Vec<TerminologySummary> {
+        let mut summaries: Vec<TerminologySummary> = self
+            .code_systems
+            .iter()
+            .map(|(url, code_system)| TerminologySummary {
+                resource_type: "CodeSystem".to_string(),
+                url: url.clone(),
+                name: code_system.name.clone(),
+                version: code_system.version.clone(),
+                concepts: Some(code_system.concepts.len()),
+            })
+            .chain(self.value_sets.iter().map(|(url, value_set)| TerminologySummary {
+                resource_type: "ValueSet".to_string(),
+                url: url.clone(),
+                name: text(value_set, "name").unwrap_or_else(|| url.clone()),
+                version: text(value_set, "version"),
+                concepts: self.expansions.get(url).map(|expansion| expansion.values().map(Vec::len).sum()),
+            }))
+            .collect();
+
+        summaries.sort_by(|a, b| a.url.cmp(&b.url));
+        summaries
+    }
+}
+
+fn text(resource: &Value, key: &str) -> Option<String> {
+    resource.get(key).and_then(Value::as_str).map(str::to_string)
+}
+
+fn collect_concepts(concepts: Option<&Value>, system: &str, out: &mut Vec<Concept>) {
+    for concept in concepts.and_then(Value::as_array).into_iter().flatten() {
+        if let Some(code) = concept.get("code").and_then(Value::as_str) {
+            out.push(Concept { system: Some(system.to_string()), code: code.to_string(), display: text(concept, "display") });
+        }
+        collect_concepts(concept.get("concept"), system, out);
+    }
+}
+
+fn collect_expansion(contains: Option<&Value>, out: &mut Vec<Concept>) {
+    for entry in contains.and_then(Value::as_array).into_iter().flatten() {
+        if let Some(code) = entry.get("code").and_then(Value::as_str) {
+            out.push(Concept {
+                system: entry.get("system").and_then(Value::as_str).map(|system| canonical(system).to_string()),
+                code: code.to_string(),
+                display: text(entry, "display"),
+            });
+        }
+        collect_expansion(entry.get("contains"), out);
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use serde_json::json;
+
+    pub(crate) fn encounter_status() -> Vec<Value> {
+        vec![
+            json!({
+                "resourceType": "CodeSystem",
+                "url": "http://hl7.org/fhir/encounter-status",
+                "name": "EncounterStatus",
+                "content": "complete",
+                "concept": [
+                    { "code": "planned", "display": "Planned" },
+                    { "code": "in-progress", "display": "In Progress" },
+                    { "code": "finished", "display": "Finished" },
+                ],
+            }),
+            json!({
+                "resourceType": "ValueSet",
+                "url": "http://hl7.org/fhir/ValueSet/encounter-status",
+                "name": "EncounterStatus",
+                "compose": { "include": [{ "system": "http://hl7.org/fhir/encounter-status" }] },
+            }),
+            json!({
+                "resourceType": "ValueSet",
+                "url": "http://example.org/ValueSet/active-encounter-status",
+                "compose": {
+                    "include": [{ "valueSet": ["http://hl7.org/fhir/ValueSet/encounter-status|4.0.1"] }],
+                    "exclude": [{ "system": "http://hl7.org/fhir/encounter-status", "concept": [{ "code": "finished" }] }],
+                },
+            }),
+            json!({
+                "resourceType": "ValueSet",
+                "url": "http://example.org/ValueSet/filtered",
+                "compose": { "include": [{ "system": "http://loinc.org", "filter": [{ "property": "class", "op": "=", "value": "LAB" }] }] },
+            }),
+        ]
+    }
+
+    #[test]
+    fn test_validates_codes_against_expanded_value_sets() {
+        let terminology = Terminology::from_resources(&encounter_status());
+
+        assert_eq!(
+            terminology.validate_code("http://hl7.org/fhir/ValueSet/encounter-status|4.0.1", None, "finished"),
+            CodeCheck::Valid { display: Some("Finished".to_string()) }
+        );
+        assert_eq!(
+            terminology.validate_code("http://hl7.org/fhir/ValueSet/encounter-status", Some("http://other"), "finished"),
+            CodeCheck::Invalid
+        );
+        assert_eq!(terminology.validate_code("http://example.org/ValueSet/active-encounter-status", None, "finished"), CodeCheck::Invalid);
+        assert_eq!(terminology.validate_code("http://example.org/ValueSet/filtered", None, "1234-5"), CodeCheck::Unknown);
+
+        let codings = terminology.check_codings(&json!({
+            "resourceType": "Encounter",
+            "class": { "system": "http://hl7.org/fhir/encounter-status", "code": "done" },
+            "type": [{ "coding": [{ "system": "http://loinc.org", "code": "anything" }] }],
+        }));
+        assert_eq!(codings.len(), 1);
+        assert_eq!(codings[0].field, "class.code");
+    }
+
+    #[test]
+    fn test_uploaded_resources_replace_and_remove() {
+        let mut terminology = Terminology::from_resources(&encounter_status());
+        let url = terminology
+            .add(&json!({
+                "resourceType": "ValueSet",
+                "url": "http://hl7.org/fhir/ValueSet/encounter-status",
+                "compose": { "include": [{ "system": "http://hl7.org/fhir/encounter-status", "concept": [{ "code": "planned" }] }] },
+            }))
+            .unwrap();
+
+        assert_eq!(terminology.validate_code(&url, None, "finished"), CodeCheck::Invalid);
+        assert_eq!(
+            terminology.validate_code(&url, None, "planned"),
+            CodeCheck::Valid { display: Some("Planned".to_string()) }
+        );
+
+        assert!(terminology.remove(&url));
+        assert_eq!(terminology.validate_code(&url, None, "planned"), CodeCheck::Unknown);
+        assert!(terminology.add(&json!({ "resourceType": "Patient", "url": "x" })).is_err());
+    }
+}
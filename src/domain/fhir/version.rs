@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{r4, r4b, r5};
+use super::r4::StructureError;
+use crate::domain::entities::target_capabilities::fhir_release;
+use crate::domain::entities::FieldMapping;
+
+/// FHIR release resources are generated for, taken from the target integration's `version`
+///
+/// Mappings are written against R4 element paths; other releases translate them with their
+/// [`PathRename`] table before the resource is built.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum FhirVersion {
+    #[default]
+    R4,
+    R4B,
+    R5,
+}
+
+/// An R4 element path renamed or retyped in another release
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathRename {
+    pub resource_type: &'static str,
+    /// R4 element path without indices, e.g. `participant.individual`
+    pub from: &'static str,
+    /// Replacement of the last element of `from`; `{i}` stands for that element's index in the mapped path
+    pub to: &'static str,
+}
+
+impl FhirVersion {
+    /// Release of a configured version (R4, 4.0.1, r4b, 5.0.0); None when unknown or unsupported
+    pub fn parse(version: &str) -> Option<Self> {
+        match fhir_release(version)? {
+            "R4" => Some(FhirVersion::R4),
+            "R4B" => Some(FhirVersion::R4B),
+            "R5" => Some(FhirVersion::R5),
+            _ => None,
+        }
+    }
+
+    /// Version of a target integration; targets without a recognized version get R4
+    pub fn for_target(version: Option<&str>) -> Self {
+        version.and_then(Self::parse).unwrap_or_default()
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FhirVersion::R4 => "R4",
+            FhirVersion::R4B => "R4B",
+            FhirVersion::R5 => "R5",
+        }
+    }
+
+    /// Published version number, as declared in `CapabilityStatement.fhirVersion`
+    pub fn number(&self) -> &'static str {
+        match self {
+            FhirVersion::R4 => "4.0.1",
+            FhirVersion::R4B => "4.3.0",
+            FhirVersion::R5 => "5.0.0",
+        }
+    }
+
+    pub fn bundle_template(&self) -> Value {
+        match self {
+            FhirVersion::R4 => r4::bundle::get_template(),
+            FhirVersion::R4B => r4b::bundle::get_template(),
+            FhirVersion::R5 => r5::bundle::get_template(),
+        }
+    }
+
+    /// Transaction entry wrapping a generated resource
+    pub fn entry_template(&self) -> Value {
+        match self {
+            FhirVersion::R4 => r4::resource::get_template(),
+            FhirVersion::R4B => r4b::resource::get_template(),
+            FhirVersion::R5 => r5::resource::get_template(),
+        }
+    }
+
+    fn renames(&self) -> &'static [PathRename] {
+        match self {
+            FhirVersion::R4 => &[],
+            FhirVersion::R4B => r4b::RENAMES,
+            FhirVersion::R5 => r5::RENAMES,
+        }
+    }
+
+    /// Translate an R4 element path of `resource_type` (e.g. `class.code` of Encounter) to this release
+    pub fn translate_path(&self, resource_type: &str, path: &str) -> String {
+        let segments: Vec<&str> = path.split('.').collect();
+
+        for rename in self.renames().iter().filter(|rename| rename.resource_type == resource_type) {
+            let from: Vec<&str> = rename.from.split('.').collect();
+            if segments.len() < from.len()
+                || !from.iter().zip(&segments).all(|(name, segment)| element_name(segment) == *name)
+            {
+                continue;
+            }
+
+            let last = from.len() - 1;
+            let replaced = rename.to.replace("{i}", &element_index(segments[last]).to_string());
+
+            let mut translated: Vec<String> = segments[..last].iter().map(|s| s.to_string()).collect();
+            translated.push(replaced);
+            translated.extend(segments[from.len()..].iter().map(|s| s.to_string()));
+            return translated.join(".");
+        }
+
+        path.to_string()
+    }
+
+    /// Field mappings with `fieldDestiny` and the `referenceDestiny` keys translated to this release
+    pub fn translate_field_mappings(&self, resource_type: &str, field_mappings: &[FieldMapping]) -> Vec<FieldMapping> {
+        field_mappings
+            .iter()
+            .map(|field_mapping| FieldMapping {
+                field_destiny: self.translate_path(resource_type, &field_mapping.field_destiny),
+                reference_destiny: field_mapping.reference_destiny.as_ref().map(|references| {
+                    references
+                        .iter()
+                        .map(|(path, value)| (self.translate_path(resource_type, path), value.clone()))
+                        .collect()
+                }),
+                ..field_mapping.clone()
+            })
+            .collect()
+    }
+
+    /// Resource as sent: empty values removed and, where the typed R4 model applies (R4, R4B),
+    /// elements and primitive formats checked
+    pub fn conform(&self, resource: &Value) -> Result<Value, StructureError> {
+        match self {
+            FhirVersion::R4 | FhirVersion::R4B => r4::conform(resource),
+            FhirVersion::R5 => {
+                let mut resource = resource.clone();
+                r4::prune_empty(&mut resource);
+                Ok(resource)
+            }
+        }
+    }
+
+    /// Adjust values whose codes changed in this release, once the record values are in place
+    pub fn adapt_values(&self, resource: &mut Value) {
+        if *self == FhirVersion::R5 {
+            r5::adapt_values(resource);
+        }
+    }
+}
+
+/// `reasonCode[1]` -> `reasonCode`
+fn element_name(segment: &str) -> &str {
+    segment.split('[').next().unwrap_or(segment)
+}
+
+/// `reasonCode[1]` -> 1; 0 without an index
+fn element_index(segment: &str) -> usize {
+    segment
+        .split_once('[')
+        .and_then(|(_, rest)| rest.split(']').next())
+        .and_then(|index| index.parse().ok())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_translates_renamed_elements_to_r5() {
+        let r5 = FhirVersion::R5;
+
+        assert_eq!(r5.translate_path("Encounter", "class.code"), "class[0].coding[0].code");
+        assert_eq!(
+            r5.translate_path("Encounter", "reasonCode[1].coding[0].code"),
+            "reason[1].value[0].concept.coding[0].code"
+        );
+        assert_eq!(r5.translate_path("Encounter", "participant[0].individual.reference"), "participant[0].actor.reference");
+        assert_eq!(r5.translate_path("Encounter", "periodicity"), "periodicity");
+        assert_eq!(r5.translate_path("Patient", "class.code"), "class.code");
+        assert_eq!(FhirVersion::R4.translate_path("Encounter", "class.code"), "class.code");
+        assert_eq!(FhirVersion::R4B.translate_path("Encounter", "class.code"), "class.code");
+
+        let mut encounter = json!({ "resourceType": "Encounter", "status": "finished" });
+        r5.adapt_values(&mut encounter);
+        assert_eq!(encounter["status"], "completed");
+    }
+
+    #[test]
+    fn test_parses_configured_versions() {
+        assert_eq!(FhirVersion::parse("4.0.1"), Some(FhirVersion::R4));
+        assert_eq!(FhirVersion::parse("r4b"), Some(FhirVersion::R4B));
+        assert_eq!(FhirVersion::parse("5.0.0"), Some(FhirVersion::R5));
+        assert_eq!(FhirVersion::parse("STU3"), None);
+        assert_eq!(FhirVersion::for_target(None), FhirVersion::R4);
+        assert_eq!(FhirVersion::parse(FhirVersion::R4B.number()), Some(FhirVersion::R4B));
+    }
+}
@@ -0,0 +1,136 @@
+// HL7 v2 acknowledgements (original mode)
+use chrono::Utc;
+
+use super::message::{Encoding, Hl7Message};
+
+/// MSA-1 acknowledgment code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AckCode {
+    /// AA - message transformed and delivered
+    Accept,
+    /// AE - message understood but transform or delivery failed; the sender may retry
+    Error,
+    /// AR - message rejected (unparseable or not routed to any view); retrying will not help
+    Reject,
+}
+
+impl AckCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AckCode::Accept => "AA",
+            AckCode::Error => "AE",
+            AckCode::Reject => "AR",
+        }
+    }
+}
+
+/// Build the ACK for a received message; `message` is None when the original could not be parsed
+pub fn build_ack(message: Option<&Hl7Message>, code: AckCode, text: &str) -> String {
+    let encoding = message.map(|m| m.encoding.clone()).unwrap_or_default();
+    let field = |segment: &str, index: usize| {
+        message
+            .and_then(|m| m.value(segment, index, 1))
+            .map(|value| escape(&value, &encoding))
+            .unwrap_or_default()
+    };
+
+    let separator = encoding.field.to_string();
+    let encoding_chars = format!("{}{}{}{}", encoding.component, encoding.repetition, encoding.escape, encoding.subcomponent);
+    let trigger = message.and_then(|m| m.value("MSH", 9, 2)).unwrap_or_default();
+    let version = message.and_then(|m| m.value("MSH", 12, 1)).unwrap_or_else(|| "2.5".to_string());
+    let control_id = message.map(|m| m.control_id()).unwrap_or_default();
+    let now = Utc::now();
+
+    // Sender and receiver are swapped in the reply
+    let msh = [
+        "MSH".to_string(),
+        encoding_chars,
+        field("MSH", 5),
+        field("MSH", 6),
+        field("MSH", 3),
+        field("MSH", 4),
+        now.format("%Y%m%d%H%M%S").to_string(),
+        String::new(),
+        format!("ACK{}{}{}ACK", encoding.component, trigger, encoding.component),
+        format!("ACK{}", now.timestamp_millis()),
+        field("MSH", 11),
+        version,
+    ]
+    .join(&separator);
+
+    let text = escape(text, &encoding);
+    let msa = ["MSA", code.as_str(), &escape(&control_id, &encoding), &text].join(&separator);
+
+    let mut segments = vec![msh, msa];
+    if code != AckCode::Accept {
+        // ERR-3 uses table 0357: 100 = segment sequence error, 207 = application internal error
+        let error_code = match code {
+            AckCode::Reject => format!("100{}Segment sequence error{}HL70357", encoding.component, encoding.component),
+            _ => format!("207{}Application internal error{}HL70357", encoding.component, encoding.component),
+        };
+        segments.push(["ERR", "", "", &error_code, "E", "", "", "", &text].join(&separator));
+    }
+
+    segments.join("\r") + "\r"
+}
+
+/// Escape separators so free text cannot break the ACK structure
+fn escape(value: &str, encoding: &Encoding) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        let sequence = if c == encoding.escape {
+            Some('E')
+        } else if c == encoding.field {
+            Some('F')
+        } else if c == encoding.component {
+            Some('S')
+        } else if c == encoding.subcomponent {
+            Some('T')
+        } else if c == encoding.repetition {
+            Some('R')
+        } else {
+            None
+        };
+
+        match sequence {
+            Some(sequence) => {
+                escaped.push(encoding.escape);
+                escaped.push(sequence);
+                escaped.push(encoding.escape);
+            }
+            None if c == '\r' || c == '\n' => escaped.push(' '),
+            None => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ack_swaps_sender_and_echoes_control_id() {
+        let message = Hl7Message::parse("MSH|^~\\&|LAB|HOSP|IH|CLINIC|20240115||ORU^R01|MSG7|P|2.5\rPID|1||1").unwrap();
+
+        let ack = Hl7Message::parse(&build_ack(Some(&message), AckCode::Accept, "2 recursos entregues")).unwrap();
+        assert_eq!(ack.value("MSH", 3, 1).as_deref(), Some("IH"));
+        assert_eq!(ack.value("MSH", 5, 1).as_deref(), Some("LAB"));
+        assert_eq!(ack.message_type(), "ACK_R01");
+        assert_eq!(ack.value("MSA", 1, 1).as_deref(), Some("AA"));
+        assert_eq!(ack.value("MSA", 2, 1).as_deref(), Some("MSG7"));
+        assert!(ack.segment("ERR").is_none());
+    }
+
+    #[test]
+    fn test_nack_escapes_error_text() {
+        let nack = build_ack(None, AckCode::Reject, "campo|inválido^x");
+        let parsed = Hl7Message::parse(&nack).unwrap();
+
+        assert_eq!(parsed.value("MSA", 1, 1).as_deref(), Some("AR"));
+        assert_eq!(parsed.value("MSA", 3, 1).as_deref(), Some("campo|inválido^x"));
+        assert_eq!(parsed.value("ERR", 3, 1).as_deref(), Some("100"));
+    }
+}
@@ -0,0 +1,378 @@
+// HL7 v2 message parsing and flattening into the column maps consumed by the Replacer
+use std::collections::HashMap;
+
+use crate::utils::AppError;
+
+/// Separators declared in MSH-1/MSH-2
+#[derive(Debug, Clone, PartialEq)]
+pub struct Encoding {
+    pub field: char,
+    pub component: char,
+    pub repetition: char,
+    pub escape: char,
+    pub subcomponent: char,
+}
+
+impl Default for Encoding {
+    fn default() -> Self {
+        Self { field: '|', component: '^', repetition: '~', escape: '\\', subcomponent: '&' }
+    }
+}
+
+/// A segment with its raw fields; `fields[n]` is field n (MSH numbering honored, `fields[0]` is the name)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hl7Segment {
+    pub name: String,
+    fields: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hl7Message {
+    pub encoding: Encoding,
+    pub segments: Vec<Hl7Segment>,
+}
+
+/// Well-known fields exposed under the column names used by our views: (column, segment, field, component)
+const PATIENT_COLUMNS: &[(&str, &str, usize, usize)] = &[
+    ("patient_code", "PID", 3, 1),
+    ("patient_birth_date", "PID", 7, 1),
+    ("patient_gender", "PID", 8, 1),
+    ("patient_mother_name", "PID", 6, 1),
+    ("patient_address", "PID", 11, 1),
+    ("patient_city", "PID", 11, 3),
+    ("patient_state", "PID", 11, 4),
+    ("patient_zipcode", "PID", 11, 5),
+    ("patient_country", "PID", 11, 6),
+    ("patient_phone", "PID", 13, 1),
+    ("patient_marital_status", "PID", 16, 1),
+    ("patient_primary_document", "PID", 19, 1),
+];
+
+const ENCOUNTER_COLUMNS: &[(&str, &str, usize, usize)] = &[
+    ("encounter_code", "PV1", 19, 1),
+    ("encounter_patient_code", "PID", 3, 1),
+    ("encounter_type", "PV1", 2, 1),
+    ("encounter_location_code", "PV1", 3, 1),
+    ("encounter_provider_code", "PV1", 7, 1),
+    ("encounter_start_date", "PV1", 44, 1),
+    ("encounter_end_date", "PV1", 45, 1),
+];
+
+const OBSERVATION_COLUMNS: &[(&str, &str, usize, usize)] = &[
+    ("observation_type_code", "OBX", 3, 1),
+    ("observation_display", "OBX", 3, 2),
+    ("observation_value", "OBX", 5, 1),
+    ("observation_unit", "OBX", 6, 1),
+    ("observation_status", "OBX", 11, 1),
+    ("observation_date", "OBX", 14, 1),
+    ("observation_patient_code", "PID", 3, 1),
+    ("observation_encounter_code", "PV1", 19, 1),
+];
+
+/// Normalize a message type to the MSH-9 form used for routing: `ADT^A01^ADT_A01` / `adt_a01` -> `ADT_A01`
+pub fn normalize_message_type(message_type: &str) -> String {
+    message_type
+        .trim()
+        .to_uppercase()
+        .split(['^', '_'])
+        .filter(|part| !part.is_empty())
+        .take(2)
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+impl Hl7Message {
+    /// Parse an ER7 (pipe-delimited) message; segments may be separated by CR, LF or CRLF
+    pub fn parse(text: &str) -> Result<Self, AppError> {
+        let text = text.trim_matches(|c: char| c.is_whitespace() || c == '\u{0b}' || c == '\u{1c}');
+        if !text.starts_with("MSH") || text.len() < 8 {
+            return Err(AppError::BadRequest("Mensagem HL7 inválida: segmento MSH ausente".to_string()));
+        }
+
+        let mut chars = text[3..].chars();
+        let field = chars.next().unwrap_or('|');
+        let declared: Vec<char> = chars.take_while(|c| *c != field).collect();
+        let defaults = Encoding::default();
+        let encoding = Encoding {
+            field,
+            component: declared.first().copied().unwrap_or(defaults.component),
+            repetition: declared.get(1).copied().unwrap_or(defaults.repetition),
+            escape: declared.get(2).copied().unwrap_or(defaults.escape),
+            subcomponent: declared.get(3).copied().unwrap_or(defaults.subcomponent),
+        };
+
+        let segments = text
+            .split(['\r', '\n'])
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let mut fields: Vec<String> = line.split(field).map(str::to_string).collect();
+                let name = fields[0].to_uppercase();
+                if name == "MSH" {
+                    // MSH-1 is the field separator itself, so MSH fields are shifted by one
+                    fields.insert(1, field.to_string());
+                }
+                Hl7Segment { name, fields }
+            })
+            .collect();
+
+        Ok(Self { encoding, segments })
+    }
+
+    /// First segment with the given name
+    pub fn segment(&self, name: &str) -> Option<&Hl7Segment> {
+        self.segments.iter().find(|segment| segment.name == name)
+    }
+
+    /// Every segment with the given name, in message order
+    pub fn segments_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Hl7Segment> + 'a {
+        self.segments.iter().filter(move |segment| segment.name == name)
+    }
+
+    /// Component (1-based) of the first repetition of a field, unescaped
+    pub fn component(&self, segment: &Hl7Segment, field: usize, component: usize) -> Option<String> {
+        let raw = segment.fields.get(field)?;
+        if segment.name == "MSH" && field <= 2 {
+            return Some(raw.clone());
+        }
+
+        let repetition = raw.split(self.encoding.repetition).next()?;
+        let value = repetition.split(self.encoding.component).nth(component.checked_sub(1)?)?;
+        let value = value.split(self.encoding.subcomponent).next().unwrap_or(value);
+        let value = self.unescape(value);
+
+        (!value.is_empty()).then_some(value)
+    }
+
+    /// Component of the first segment with the given name
+    pub fn value(&self, segment: &str, field: usize, component: usize) -> Option<String> {
+        self.component(self.segment(segment)?, field, component)
+    }
+
+    /// Routing type from MSH-9, e.g. `ADT_A01`
+    pub fn message_type(&self) -> String {
+        let code = self.value("MSH", 9, 1).unwrap_or_default();
+        let event = self.value("MSH", 9, 2).unwrap_or_default();
+        normalize_message_type(&format!("{}^{}", code, event))
+    }
+
+    /// Receiving facility (MSH-6), falling back to the receiving application (MSH-5)
+    pub fn receiving_facility(&self) -> Option<String> {
+        self.value("MSH", 6, 1).or_else(|| self.value("MSH", 5, 1))
+    }
+
+    /// MSH-10, echoed back in MSA-2
+    pub fn control_id(&self) -> String {
+        self.value("MSH", 10, 1).unwrap_or_default()
+    }
+
+    /// Flat records for the mapping pipeline: one per OBX segment when present, otherwise one per message
+    ///
+    /// Every field is available as `<segment>_<field>` (first component) and `<segment>_<field>_<component>`,
+    /// plus the well-known `patient_*`, `encounter_*` and `observation_*` columns.
+    pub fn records(&self) -> Vec<HashMap<String, String>> {
+        let mut base = HashMap::new();
+        for segment in self.segments.iter().filter(|s| s.name != "OBX") {
+            // Repeated segments (NK1, AL1, ...) only contribute their first occurrence
+            if self.segment(&segment.name).map(|first| std::ptr::eq(first, segment)) == Some(true) {
+                self.flatten_segment(segment, &mut base);
+            }
+        }
+        self.add_columns(&mut base, PATIENT_COLUMNS, None);
+        self.add_columns(&mut base, ENCOUNTER_COLUMNS, None);
+
+        if let Some(name) = self.patient_name() {
+            base.insert("patient_name".to_string(), name);
+        }
+
+        let observations: Vec<&Hl7Segment> = self.segments_named("OBX").collect();
+        if observations.is_empty() {
+            return vec![base];
+        }
+
+        let order_code = self.value("OBR", 3, 1).or_else(|| self.value("OBR", 2, 1)).unwrap_or_else(|| self.control_id());
+
+        observations
+            .into_iter()
+            .enumerate()
+            .map(|(idx, observation)| {
+                let mut record = base.clone();
+                self.flatten_segment(observation, &mut record);
+                self.add_columns(&mut record, OBSERVATION_COLUMNS, Some(observation));
+
+                let set_id = self.component(observation, 1, 1).unwrap_or_else(|| (idx + 1).to_string());
+                record.insert("observation_code".to_string(), format!("{}-{}", order_code, set_id));
+                record
+            })
+            .collect()
+    }
+
+    fn flatten_segment(&self, segment: &Hl7Segment, record: &mut HashMap<String, String>) {
+        let prefix = segment.name.to_lowercase();
+
+        for (field, raw) in segment.fields.iter().enumerate().skip(1) {
+            if raw.is_empty() {
+                continue;
+            }
+            let components = raw.split(self.encoding.repetition).next().unwrap_or_default().split(self.encoding.component).count();
+
+            if let Some(first) = self.component(segment, field, 1) {
+                record.insert(format!("{}_{}", prefix, field), first);
+            }
+            if components > 1 {
+                for component in 1..=components {
+                    if let Some(value) = self.component(segment, field, component) {
+                        record.insert(format!("{}_{}_{}", prefix, field, component), value);
+                    }
+                }
+            }
+        }
+    }
+
+    fn add_columns(
+        &self,
+        record: &mut HashMap<String, String>,
+        columns: &[(&str, &str, usize, usize)],
+        observation: Option<&Hl7Segment>,
+    ) {
+        for (column, segment_name, field, component) in columns {
+            let value = match observation {
+                Some(observation) if *segment_name == "OBX" => self.component(observation, *field, *component),
+                _ => self.value(segment_name, *field, *component),
+            };
+
+            if let Some(value) = value {
+                let value = if column.ends_with("_date") { format_timestamp(&value) } else { value };
+                record.insert(column.to_string(), value);
+            }
+        }
+    }
+
+    /// PID-5 as "given family"
+    fn patient_name(&self) -> Option<String> {
+        let family = self.value("PID", 5, 1);
+        let given = self.value("PID", 5, 2);
+
+        match (given, family) {
+            (Some(given), Some(family)) => Some(format!("{} {}", given, family)),
+            (given, family) => given.or(family),
+        }
+    }
+
+    /// Resolve the standard escape sequences (\F\ \S\ \T\ \R\ \E\)
+    fn unescape(&self, value: &str) -> String {
+        let escape = self.encoding.escape;
+        if !value.contains(escape) {
+            return value.to_string();
+        }
+
+        let mut result = String::with_capacity(value.len());
+        let mut parts = value.split(escape);
+        result.push_str(parts.next().unwrap_or_default());
+
+        let rest: Vec<&str> = parts.collect();
+        let mut i = 0;
+        while i < rest.len() {
+            let replacement = match rest[i] {
+                "F" => Some(self.encoding.field),
+                "S" => Some(self.encoding.component),
+                "T" => Some(self.encoding.subcomponent),
+                "R" => Some(self.encoding.repetition),
+                "E" => Some(escape),
+                _ => None,
+            };
+
+            match (replacement, rest.get(i + 1)) {
+                (Some(c), Some(after)) => {
+                    result.push(c);
+                    result.push_str(after);
+                    i += 2;
+                }
+                _ => {
+                    result.push(escape);
+                    result.push_str(rest[i]);
+                    i += 1;
+                }
+            }
+        }
+
+        result
+    }
+}
+
+/// Convert an HL7 TS (YYYYMMDD[HHMM[SS]]) to ISO 8601; other values are kept as sent
+fn format_timestamp(value: &str) -> String {
+    let digits: String = value.chars().take_while(|c| c.is_ascii_digit()).collect();
+
+    match digits.len() {
+        8 => format!("{}-{}-{}", &digits[0..4], &digits[4..6], &digits[6..8]),
+        12 => format!("{}-{}-{}T{}:{}:00", &digits[0..4], &digits[4..6], &digits[6..8], &digits[8..10], &digits[10..12]),
+        len if len >= 14 => format!(
+            "{}-{}-{}T{}:{}:{}",
+            &digits[0..4], &digits[4..6], &digits[6..8], &digits[8..10], &digits[10..12], &digits[12..14]
+        ),
+        _ => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORU: &str = "MSH|^~\\&|LAB|HOSP|INTERHEALTH|CLINIC|20240115103000||ORU^R01^ORU_R01|MSG0001|P|2.5\r\
+PID|1||12345^^^HOSP^MR||Silva^Ana^M||19900412|F|||Rua A\\S\\B^^Recife^PE^50000-000^BR||5581999990000\r\
+PV1|1|I|UTI^01^A||||9988^House^Greg||||||||||||ENC42\r\
+OBR|1||ORD77|GLU^Glucose\r\
+OBX|1|NM|2345-7^Glucose^LN||98|mg/dL|||||F|||20240115100000\r\
+OBX|2|NM|2951-2^Sodium^LN||140|mmol/L|||||F\r";
+
+    #[test]
+    fn test_parse_header() {
+        let message = Hl7Message::parse(ORU).unwrap();
+
+        assert_eq!(message.message_type(), "ORU_R01");
+        assert_eq!(message.control_id(), "MSG0001");
+        assert_eq!(message.value("MSH", 3, 1).as_deref(), Some("LAB"));
+        assert_eq!(message.value("MSH", 2, 1).as_deref(), Some("^~\\&"));
+        assert_eq!(message.receiving_facility().as_deref(), Some("CLINIC"));
+        assert_eq!(normalize_message_type("adt^a01^ADT_A01"), "ADT_A01");
+        assert_eq!(normalize_message_type("ADT"), "ADT");
+        assert!(Hl7Message::parse("PID|1||123").is_err());
+    }
+
+    #[test]
+    fn test_records_per_observation() {
+        let records = Hl7Message::parse(ORU).unwrap().records();
+        assert_eq!(records.len(), 2);
+
+        let glucose = &records[0];
+        assert_eq!(glucose["patient_code"], "12345");
+        assert_eq!(glucose["patient_name"], "Ana Silva");
+        assert_eq!(glucose["patient_birth_date"], "1990-04-12");
+        assert_eq!(glucose["patient_address"], "Rua A^B");
+        assert_eq!(glucose["encounter_code"], "ENC42");
+        assert_eq!(glucose["encounter_provider_code"], "9988");
+        assert_eq!(glucose["observation_code"], "ORD77-1");
+        assert_eq!(glucose["observation_type_code"], "2345-7");
+        assert_eq!(glucose["observation_value"], "98");
+        assert_eq!(glucose["observation_date"], "2024-01-15T10:00:00");
+        assert_eq!(glucose["pid_5_2"], "Ana");
+        assert_eq!(glucose["obx_5"], "98");
+
+        assert_eq!(records[1]["observation_code"], "ORD77-2");
+        assert_eq!(records[1]["observation_unit"], "mmol/L");
+        assert!(!records[1].contains_key("observation_date"));
+    }
+
+    #[test]
+    fn test_adt_yields_single_record() {
+        let adt = "MSH|^~\\&|ADT|HOSP|||20240115||ADT^A01|42|P|2.3\nPID|1||777||Souza^Jose\nPV1|1|E";
+        let records = Hl7Message::parse(adt).unwrap().records();
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(Hl7Message::parse(adt).unwrap().receiving_facility(), None);
+        assert_eq!(records[0]["patient_code"], "777");
+        assert_eq!(records[0]["encounter_type"], "E");
+        assert!(!records[0].contains_key("observation_code"));
+    }
+}
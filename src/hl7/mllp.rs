@@ -0,0 +1,275 @@
+// MLLP (Minimal Lower Layer Protocol) listener: <VT> message <FS><CR> framing over TCP
+use std::io::{Error, ErrorKind};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{error, info, warn};
+
+use crate::utils::AppError;
+use super::ack::{build_ack, AckCode};
+use super::message::Hl7Message;
+use super::pipeline::Hl7Pipeline;
+
+const START_BLOCK: u8 = 0x0b;
+const END_BLOCK: u8 = 0x1c;
+const CARRIAGE_RETURN: u8 = 0x0d;
+
+/// Frames larger than this are rejected instead of buffered
+const MAX_FRAME_SIZE: usize = 10 * 1024 * 1024;
+
+/// Connections idle for longer than this are closed
+const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Wrap a message in an MLLP frame
+pub fn frame(message: &str) -> Vec<u8> {
+    let mut framed = Vec::with_capacity(message.len() + 3);
+    framed.push(START_BLOCK);
+    framed.extend_from_slice(message.as_bytes());
+    framed.push(END_BLOCK);
+    framed.push(CARRIAGE_RETURN);
+    framed
+}
+
+/// Incremental decoder: feed it bytes as they arrive, get complete messages back
+#[derive(Default)]
+pub struct MllpDecoder {
+    buffer: Vec<u8>,
+    /// Bytes of a pending frame already searched for the end block, so reads resume there
+    scanned: usize,
+}
+
+impl MllpDecoder {
+    /// Fails once a frame grows past `MAX_FRAME_SIZE`; the connection should then be dropped
+    pub fn push(&mut self, bytes: &[u8]) -> std::io::Result<Vec<String>> {
+        self.buffer.extend_from_slice(bytes);
+        let mut messages = Vec::new();
+
+        loop {
+            // Anything before the start block is noise between frames
+            let Some(start) = self.buffer.iter().position(|b| *b == START_BLOCK) else {
+                self.buffer.clear();
+                self.scanned = 0;
+                break;
+            };
+            // The end block may have arrived as the last byte of the previous read
+            let from = (start + 1).max(self.scanned.saturating_sub(1));
+            let Some(end) = self.buffer[from..]
+                .windows(2)
+                .position(|w| w == [END_BLOCK, CARRIAGE_RETURN])
+                .map(|offset| from + offset)
+            else {
+                self.buffer.drain(..start);
+                if self.buffer.len() > MAX_FRAME_SIZE {
+                    self.buffer.clear();
+                    self.scanned = 0;
+                    return Err(Self::oversized());
+                }
+                self.scanned = self.buffer.len();
+                break;
+            };
+
+            self.scanned = 0;
+            if end - start - 1 > MAX_FRAME_SIZE {
+                self.buffer.clear();
+                return Err(Self::oversized());
+            }
+
+            messages.push(String::from_utf8_lossy(&self.buffer[start + 1..end]).into_owned());
+            self.buffer.drain(..end + 2);
+        }
+
+        Ok(messages)
+    }
+
+    fn oversized() -> Error {
+        Error::new(ErrorKind::InvalidData, format!("MLLP frame over {} bytes rejected", MAX_FRAME_SIZE))
+    }
+}
+
+/// TCP listener that acknowledges every message with the outcome of the pipeline
+pub struct MllpListener {
+    pipeline: Arc<dyn Hl7Pipeline>,
+    read_timeout: Duration,
+}
+
+impl MllpListener {
+    pub fn new(pipeline: Arc<dyn Hl7Pipeline>) -> Self {
+        Self { pipeline, read_timeout: DEFAULT_READ_TIMEOUT }
+    }
+
+    /// Close connections idle for longer than `read_timeout`
+    pub fn with_read_timeout(mut self, read_timeout: Duration) -> Self {
+        self.read_timeout = read_timeout;
+        self
+    }
+
+    /// Accept connections until the listener fails; each connection runs in its own task
+    pub async fn serve(self: Arc<Self>, listener: TcpListener) {
+        loop {
+            match listener.accept().await {
+                Ok((stream, peer)) => {
+                    info!("📡 MLLP connection from {}", peer);
+                    let this = self.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = this.handle_connection(stream).await {
+                            warn!("MLLP connection {} closed: {}", peer, e);
+                        }
+                    });
+                }
+                Err(e) => {
+                    error!("MLLP accept failed: {}", e);
+                    return;
+                }
+            }
+        }
+    }
+
+    async fn handle_connection(&self, mut stream: TcpStream) -> std::io::Result<()> {
+        let mut decoder = MllpDecoder::default();
+        let mut buffer = [0u8; 8192];
+
+        loop {
+            let read = tokio::time::timeout(self.read_timeout, stream.read(&mut buffer))
+                .await
+                .map_err(|_| Error::new(ErrorKind::TimedOut, "MLLP read timed out"))??;
+            if read == 0 {
+                return Ok(());
+            }
+
+            for message in decoder.push(&buffer[..read])? {
+                let ack = self.handle_message(&message).await;
+                stream.write_all(&frame(&ack)).await?;
+            }
+        }
+    }
+
+    /// Parse, process and acknowledge a single message
+    pub async fn handle_message(&self, raw: &str) -> String {
+        let message = match Hl7Message::parse(raw) {
+            Ok(message) => message,
+            Err(e) => return build_ack(None, AckCode::Reject, &e.to_string()),
+        };
+
+        match self.pipeline.process(&message).await {
+            Ok(delivered) => build_ack(Some(&message), AckCode::Accept, &format!("{} recursos entregues", delivered)),
+            Err(e) => {
+                warn!("HL7 {} {} failed: {}", message.message_type(), message.control_id(), e);
+                let code = match e {
+                    AppError::NotFound(_) | AppError::BadRequest(_) => AckCode::Reject,
+                    _ => AckCode::Error,
+                };
+                build_ack(Some(&message), code, &e.to_string())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    /// Accepts ADT, fails ORU delivery, routes nothing else
+    struct StubPipeline;
+
+    #[async_trait]
+    impl Hl7Pipeline for StubPipeline {
+        async fn process(&self, message: &Hl7Message) -> Result<usize, AppError> {
+            match message.message_type().as_str() {
+                "ADT_A01" => Ok(message.records().len()),
+                "ORU_R01" => Err(AppError::DatabaseError("destino indisponível".to_string())),
+                other => Err(AppError::NotFound(format!("Nenhuma view configurada para mensagens {}", other))),
+            }
+        }
+    }
+
+    fn msa(ack: &str) -> (String, String) {
+        let parsed = Hl7Message::parse(ack).unwrap();
+        (
+            parsed.value("MSA", 1, 1).unwrap_or_default(),
+            parsed.value("MSA", 2, 1).unwrap_or_default(),
+        )
+    }
+
+    #[test]
+    fn test_decoder_handles_split_and_batched_frames() {
+        let mut decoder = MllpDecoder::default();
+        let first = frame("MSH|one");
+        let second = frame("MSH|two");
+
+        assert!(decoder.push(&first[..4]).unwrap().is_empty());
+        let mut rest = first[4..].to_vec();
+        rest.extend_from_slice(&second);
+        assert_eq!(decoder.push(&rest).unwrap(), vec!["MSH|one".to_string(), "MSH|two".to_string()]);
+        assert!(decoder.push(b"noise").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_decoder_resumes_scan_across_reads() {
+        let mut decoder = MllpDecoder::default();
+        let framed = frame("MSH|one");
+
+        // End block and carriage return arrive in separate reads
+        for byte in &framed[..framed.len() - 1] {
+            assert!(decoder.push(std::slice::from_ref(byte)).unwrap().is_empty());
+        }
+        assert_eq!(decoder.scanned, framed.len() - 1);
+        assert_eq!(decoder.push(&framed[framed.len() - 1..]).unwrap(), vec!["MSH|one".to_string()]);
+        assert_eq!(decoder.scanned, 0);
+    }
+
+    #[test]
+    fn test_decoder_rejects_oversized_frames() {
+        let mut decoder = MllpDecoder::default();
+        let oversized = frame(&"A".repeat(MAX_FRAME_SIZE + 1));
+
+        assert!(decoder.push(&oversized[..MAX_FRAME_SIZE / 2]).unwrap().is_empty());
+        assert_eq!(decoder.push(&oversized[MAX_FRAME_SIZE / 2..]).unwrap_err().kind(), ErrorKind::InvalidData);
+        assert!(decoder.push(&oversized).is_err());
+        assert_eq!(decoder.push(&frame("MSH|next")).unwrap(), vec!["MSH|next".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_listener_closes_idle_connections() {
+        let tcp = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = tcp.local_addr().unwrap();
+        let listener = MllpListener::new(Arc::new(StubPipeline)).with_read_timeout(Duration::from_millis(50));
+        tokio::spawn(Arc::new(listener).serve(tcp));
+
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        let mut buffer = [0u8; 16];
+        let read = tokio::time::timeout(Duration::from_secs(5), stream.read(&mut buffer)).await.unwrap();
+        assert_eq!(read.unwrap_or(0), 0);
+    }
+
+    #[tokio::test]
+    async fn test_listener_acknowledges_outcomes() {
+        let tcp = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = tcp.local_addr().unwrap();
+        tokio::spawn(Arc::new(MllpListener::new(Arc::new(StubPipeline))).serve(tcp));
+
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        let mut decoder = MllpDecoder::default();
+        let mut buffer = [0u8; 4096];
+
+        let cases = [
+            ("MSH|^~\\&|ADT|HOSP|IH|CLINIC|20240115||ADT^A01|C1|P|2.5\rPID|1||1", "AA", "C1"),
+            ("MSH|^~\\&|LAB|HOSP|IH|CLINIC|20240115||ORU^R01|C2|P|2.5\rPID|1||1", "AE", "C2"),
+            ("MSH|^~\\&|SIU|HOSP|IH|CLINIC|20240115||SIU^S12|C3|P|2.5", "AR", "C3"),
+            ("PID|1||no header", "AR", ""),
+        ];
+
+        for (message, code, control_id) in cases {
+            stream.write_all(&frame(message)).await.unwrap();
+
+            let ack = loop {
+                let read = stream.read(&mut buffer).await.unwrap();
+                if let Some(ack) = decoder.push(&buffer[..read]).unwrap().pop() {
+                    break ack;
+                }
+            };
+            assert_eq!(msa(&ack), (code.to_string(), control_id.to_string()), "{}", message);
+        }
+    }
+}
@@ -0,0 +1,9 @@
+// HL7 v2 inbound source - hospital systems push ADT/ORU/... messages over MLLP
+pub mod ack;
+pub mod message;
+pub mod mllp;
+pub mod pipeline;
+
+pub use message::normalize_message_type;
+pub use mllp::MllpListener;
+pub use pipeline::ViewPipeline;
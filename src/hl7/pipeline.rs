@@ -0,0 +1,211 @@
+// Inbound HL7 pipeline - routes a message to its views and runs the mapping/Replacer transform
+use std::sync::Arc;
+use async_trait::async_trait;
+use tokio::fs;
+use tracing::{info, warn};
+
+use crate::application::usecases::SyncUseCase;
+use crate::domain::entities::{Company, DatabaseView, DeliveredResource, TargetIntegration};
+use crate::domain::fhir::FhirVersion;
+use crate::infrastructure::repositories::{
+    CompanyRepository, DatabaseTransformationRepository, DatabaseViewMappingRepository, DatabaseViewRepository,
+    DeliveredResourceRepository, TargetIntegrationRepository,
+};
+use crate::sync::TargetDelivery;
+use crate::utils::AppError;
+use super::message::Hl7Message;
+
+/// Default folder where resources built from HL7 messages are written
+const DEFAULT_OUTPUT_DIR: &str = "sync_data_fhir_test";
+
+/// Longest folder/file name segment taken from message or view data
+const MAX_SEGMENT_LEN: usize = 64;
+
+/// Everything the MLLP listener does with a parsed message
+///
+/// Errors decide the reply: `NotFound`/`BadRequest` are rejected (AR), anything else is an application error (AE)
+#[async_trait]
+pub trait Hl7Pipeline: Send + Sync {
+    /// Transform and deliver a message, returning how many resources were delivered
+    async fn process(&self, message: &Hl7Message) -> Result<usize, AppError>;
+}
+
+/// Pipeline backed by the views routed to each message type (`DatabaseView.message_types`)
+///
+/// Messages are routed to the company whose `hl7_facility` matches the receiving facility,
+/// and only that company's views are considered.
+pub struct ViewPipeline {
+    view_repo: Arc<DatabaseViewRepository>,
+    target_repo: Arc<TargetIntegrationRepository>,
+    companies: Arc<CompanyRepository>,
+    sync_use_case: SyncUseCase,
+    output_dir: String,
+    delivered_resources: Option<Arc<DeliveredResourceRepository>>,
+}
+
+impl ViewPipeline {
+    pub fn new(
+        view_repo: Arc<DatabaseViewRepository>,
+        mapping_repo: Arc<DatabaseViewMappingRepository>,
+        transformation_repo: Arc<DatabaseTransformationRepository>,
+        target_repo: Arc<TargetIntegrationRepository>,
+        companies: Arc<CompanyRepository>,
+    ) -> Self {
+        Self {
+            view_repo,
+            target_repo,
+            companies,
+            sync_use_case: SyncUseCase::new(mapping_repo, transformation_repo),
+            output_dir: DEFAULT_OUTPUT_DIR.to_string(),
+            delivered_resources: None,
+        }
+    }
+
+    /// Write delivered resources under `{output_dir}/hl7/` instead of the default folder
+    pub fn with_output_dir(mut self, output_dir: impl Into<String>) -> Self {
+        self.output_dir = output_dir.into();
+        self
+    }
+
+    /// Keep the last delivered version of each resource for the FHIR facade
+    pub fn with_delivered_resources(mut self, delivered_resources: Arc<DeliveredResourceRepository>) -> Self {
+        self.delivered_resources = Some(delivered_resources);
+        self
+    }
+
+    /// Record what was delivered; a failure here does not reject the message
+    async fn record_delivered(&self, view: &DatabaseView, view_id: &str, version: FhirVersion, resources: &[serde_json::Value]) {
+        let Some(repository) = &self.delivered_resources else {
+            return;
+        };
+
+        for resource in resources {
+            let Some(delivered) = DeliveredResource::from_resource(&view.company_id, view_id, version, resource) else {
+                continue;
+            };
+            if let Err(e) = repository.record(delivered).await {
+                warn!("Falha ao registrar recurso entregue da view {}: {}", view.name, e);
+            }
+        }
+    }
+
+    /// Target integration of the view, if any
+    async fn target(&self, view: &DatabaseView) -> Result<Option<TargetIntegration>, AppError> {
+        match view.target_integration_id.as_deref() {
+            Some(target_id) => self.target_repo.find_by_id(target_id).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Company addressed by the message's receiving facility
+    async fn company(&self, message: &Hl7Message) -> Result<Company, AppError> {
+        let facility = message.receiving_facility()
+            .ok_or_else(|| AppError::BadRequest("Mensagem HL7 sem facility de destino (MSH-6)".to_string()))?;
+
+        self.companies.find_by_hl7_facility(&facility).await?
+            .ok_or_else(|| AppError::NotFound(format!("Nenhuma empresa configurada para a facility HL7 {}", facility)))
+    }
+
+    /// Without a target server, same output as the sync worker: one JSON file per resource
+    async fn write_files(&self, control_id: &str, entity_type: &str, resources: &[serde_json::Value]) -> Result<(), AppError> {
+        let message_dir = format!("{}/hl7/{}", self.output_dir, path_segment(control_id));
+
+        fs::create_dir_all(&message_dir).await
+            .map_err(|e| AppError::DatabaseError(format!("Falha ao criar {}: {}", message_dir, e)))?;
+
+        for (idx, resource) in resources.iter().enumerate() {
+            let filename = format!("{}/{}_{:04}.json", message_dir, path_segment(&entity_type.to_lowercase()), idx);
+            let content = serde_json::to_string_pretty(resource)
+                .map_err(|e| AppError::DatabaseError(format!("Falha ao serializar recurso: {}", e)))?;
+
+            fs::write(&filename, content).await
+                .map_err(|e| AppError::DatabaseError(format!("Falha ao gravar {}: {}", filename, e)))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Hl7Pipeline for ViewPipeline {
+    async fn process(&self, message: &Hl7Message) -> Result<usize, AppError> {
+        let message_type = message.message_type();
+        let message_code = message_type.split('_').next().unwrap_or_default().to_string();
+        let company = self.company(message).await?;
+        let company_id = company.id.map(|id| id.to_hex()).unwrap_or_default();
+        let timezone = company.timezone();
+
+        let views = self.view_repo
+            .find_by_message_types(&company_id, &[message_type.clone(), message_code])
+            .await?;
+
+        if views.is_empty() {
+            return Err(AppError::NotFound(format!("Nenhuma view configurada para mensagens {}", message_type)));
+        }
+
+        let records = message.records();
+        let mut delivered = 0;
+
+        for view in views {
+            let view_id = view.id.map(|id| id.to_hex()).unwrap_or_default();
+            let target = self.target(&view).await?;
+            // FHIR version of the view's target integration (R4 without one)
+            let version = FhirVersion::for_target(target.as_ref().and_then(|target| target.version.as_deref()));
+            let delivery = TargetDelivery::for_view(&view, target.as_ref()).await?;
+            let mode = delivery.as_ref().map(TargetDelivery::mode).unwrap_or_default();
+
+            let entries = self.sync_use_case
+                .transform_records_to_fhir(&view_id, records.clone(), version, timezone, mode)
+                .await?;
+
+            if entries.is_empty() {
+                return Err(AppError::Validation(format!("A view {} não possui mapeamentos", view.name)));
+            }
+
+            let resources: Vec<serde_json::Value> = entries.iter().cloned().map(SyncUseCase::entry_resource).collect();
+            match &delivery {
+                Some(delivery) => {
+                    // Entries the server accepted before a refusal are recorded all the same
+                    let report = delivery.send(&entries).await;
+                    self.record_delivered(&view, &view_id, version, &resources[..report.delivered()]).await;
+                    report.into_result()?;
+                }
+                None => {
+                    self.write_files(&message.control_id(), &view.entity_type, &resources).await?;
+                    self.record_delivered(&view, &view_id, version, &resources).await;
+                }
+            }
+            info!("📨 HL7 {} {} -> view {}: {} recursos", message_type, message.control_id(), view.name, resources.len());
+            delivered += resources.len();
+        }
+
+        Ok(delivered)
+    }
+}
+
+/// Single path segment built from untrusted text: only ASCII alphanumerics and '-' survive,
+/// so separators and dot segments cannot leave the output folder
+fn path_segment(value: &str) -> String {
+    let segment: String = value
+        .chars()
+        .take(MAX_SEGMENT_LEN)
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+
+    if segment.is_empty() { "_".to_string() } else { segment }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_segment_stays_inside_the_output_folder() {
+        assert_eq!(path_segment("MSG-00123"), "MSG-00123");
+        assert_eq!(path_segment("../../etc/passwd"), "______etc_passwd");
+        assert_eq!(path_segment(".."), "__");
+        assert_eq!(path_segment("C:\\temp"), "C__temp");
+        assert_eq!(path_segment(""), "_");
+        assert_eq!(path_segment(&"9".repeat(200)).len(), MAX_SEGMENT_LEN);
+    }
+}
@@ -0,0 +1,443 @@
+use std::collections::HashSet;
+use std::io::Cursor;
+use std::sync::Arc;
+
+use calamine::{open_workbook_auto_from_rs, Data, Reader};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use serde_json::{Map, Value};
+
+use crate::domain::entities::{FileDatasetColumn, FileRowError};
+use crate::infrastructure::repositories::FileDatasetRepository;
+use crate::utils::AppError;
+
+/// Spreadsheet parsed from an upload, ready to be stored as a FileDataset
+#[derive(Debug, Clone)]
+pub struct ParsedSpreadsheet {
+    pub format: String,
+    pub columns: Vec<FileDatasetColumn>,
+    /// Accepted rows with their 1-based row number in the file (header is row 1)
+    pub rows: Vec<(u64, Map<String, Value>)>,
+    /// Non-empty data rows found in the file, accepted or not
+    pub total_rows: u64,
+    pub row_errors: Vec<FileRowError>,
+}
+
+/// Parse an uploaded CSV or Excel file; the first row must hold the column names
+pub fn parse_spreadsheet(file_name: &str, content: &[u8]) -> Result<ParsedSpreadsheet, AppError> {
+    let format = file_name
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_lowercase())
+        .unwrap_or_default();
+
+    let (headers, raw_rows, mut row_errors) = match format.as_str() {
+        "csv" | "txt" => read_csv(content)?,
+        "xlsx" | "xlsm" | "xlsb" | "xls" | "ods" => read_workbook(content)?,
+        _ => {
+            return Err(AppError::BadRequest(format!(
+                "Formato de arquivo não suportado: {} (use CSV ou XLSX)",
+                file_name
+            )));
+        }
+    };
+
+    let headers = normalize_headers(&headers)?;
+    let mut rows = Vec::new();
+    // Rows that could not even be read still count as data rows of the file
+    let mut total_rows = row_errors.len() as u64;
+
+    for (row_number, cells) in raw_rows {
+        if cells.iter().all(Value::is_null) {
+            continue;
+        }
+        total_rows += 1;
+
+        let extra = cells.iter().skip(headers.len()).filter(|cell| !cell.is_null()).count();
+        if extra > 0 {
+            row_errors.push(FileRowError {
+                row: row_number,
+                message: format!("{} valores a mais do que as {} colunas do cabeçalho", extra, headers.len()),
+            });
+            continue;
+        }
+
+        let record: Map<String, Value> = headers
+            .iter()
+            .enumerate()
+            .map(|(idx, header)| (header.clone(), cells.get(idx).cloned().unwrap_or(Value::Null)))
+            .collect();
+        rows.push((row_number, record));
+    }
+
+    row_errors.sort_by_key(|e| e.row);
+
+    let columns = headers
+        .iter()
+        .map(|header| detect_column(header, rows.iter().map(|(_, record)| &record[header])))
+        .collect();
+
+    Ok(ParsedSpreadsheet {
+        format,
+        columns,
+        rows,
+        total_rows,
+        row_errors,
+    })
+}
+
+type RawRows = (Vec<String>, Vec<(u64, Vec<Value>)>, Vec<FileRowError>);
+
+fn read_csv(content: &[u8]) -> Result<RawRows, AppError> {
+    let content = content.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(content);
+
+    // Spreadsheets exported with pt-BR locale use ';' as separator
+    let first_line = content.split(|b| *b == b'\n').next().unwrap_or_default();
+    let delimiter = if first_line.iter().filter(|b| **b == b';').count() > first_line.iter().filter(|b| **b == b',').count() {
+        b';'
+    } else {
+        b','
+    };
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(content);
+
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| AppError::BadRequest(format!("Cabeçalho do CSV inválido: {}", e)))?
+        .iter()
+        .map(|h| h.to_string())
+        .collect();
+
+    let mut rows = Vec::new();
+    let mut row_errors = Vec::new();
+
+    for (idx, record) in reader.records().enumerate() {
+        match record {
+            Ok(record) => {
+                let row_number = record.position().map(|p| p.line()).unwrap_or(idx as u64 + 2);
+                let cells = record
+                    .iter()
+                    .map(|cell| {
+                        let cell = cell.trim();
+                        if cell.is_empty() { Value::Null } else { Value::String(cell.to_string()) }
+                    })
+                    .collect();
+                rows.push((row_number, cells));
+            }
+            Err(e) => {
+                let row_number = e.position().map(|p| p.line()).unwrap_or(idx as u64 + 2);
+                row_errors.push(FileRowError {
+                    row: row_number,
+                    message: format!("Linha ilegível: {}", e),
+                });
+            }
+        }
+    }
+
+    Ok((headers, rows, row_errors))
+}
+
+fn read_workbook(content: &[u8]) -> Result<RawRows, AppError> {
+    let mut workbook = open_workbook_auto_from_rs(Cursor::new(content.to_vec()))
+        .map_err(|e| AppError::BadRequest(format!("Planilha inválida: {}", e)))?;
+
+    let range = workbook
+        .worksheet_range_at(0)
+        .ok_or_else(|| AppError::BadRequest("A planilha não possui abas".to_string()))?
+        .map_err(|e| AppError::BadRequest(format!("Falha ao ler a planilha: {}", e)))?;
+
+    // Row numbers follow the sheet, so blank leading rows keep their offset
+    let first_row = range.start().map(|(row, _)| row as u64 + 1).unwrap_or(1);
+    let mut sheet_rows = range.rows();
+
+    let headers = sheet_rows
+        .next()
+        .ok_or_else(|| AppError::BadRequest("A planilha está vazia".to_string()))?
+        .iter()
+        .map(|cell| cell.to_string())
+        .collect();
+
+    let mut rows = Vec::new();
+    let mut row_errors = Vec::new();
+
+    for (idx, cells) in sheet_rows.enumerate() {
+        let row_number = first_row + idx as u64 + 1;
+        let mut values = Vec::with_capacity(cells.len());
+        let mut cell_error = None;
+
+        for (col, cell) in cells.iter().enumerate() {
+            match cell_to_value(cell) {
+                Ok(value) => values.push(value),
+                Err(message) => {
+                    cell_error = Some(format!("Coluna {}: {}", col + 1, message));
+                    break;
+                }
+            }
+        }
+
+        match cell_error {
+            Some(message) => row_errors.push(FileRowError { row: row_number, message }),
+            None => rows.push((row_number, values)),
+        }
+    }
+
+    Ok((headers, rows, row_errors))
+}
+
+fn cell_to_value(cell: &Data) -> Result<Value, String> {
+    Ok(match cell {
+        Data::Empty => Value::Null,
+        Data::String(s) => {
+            let s = s.trim();
+            if s.is_empty() { Value::Null } else { Value::String(s.to_string()) }
+        }
+        Data::Int(i) => Value::from(*i),
+        Data::Float(f) if f.fract() == 0.0 && f.abs() < i64::MAX as f64 => Value::from(*f as i64),
+        Data::Float(f) => serde_json::Number::from_f64(*f).map(Value::Number).unwrap_or(Value::Null),
+        Data::Bool(b) => Value::Bool(*b),
+        Data::DateTime(dt) => match dt.as_datetime() {
+            Some(dt) if dt.time() == NaiveTime::MIN => Value::String(dt.format("%Y-%m-%d").to_string()),
+            Some(dt) => Value::String(dt.format("%Y-%m-%dT%H:%M:%S").to_string()),
+            None => Value::String(dt.as_f64().to_string()),
+        },
+        Data::DateTimeIso(s) | Data::DurationIso(s) => Value::String(s.clone()),
+        Data::Error(e) => return Err(format!("erro de célula {}", e)),
+    })
+}
+
+/// Turn header cells into column names (lowercase, `_` separated) and reject blanks and duplicates
+fn normalize_headers(headers: &[String]) -> Result<Vec<String>, AppError> {
+    let mut seen = HashSet::new();
+    let mut names = Vec::with_capacity(headers.len());
+
+    for (idx, header) in headers.iter().enumerate() {
+        let name = header
+            .trim()
+            .to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("_");
+
+        if name.is_empty() {
+            return Err(AppError::BadRequest(format!("A coluna {} do cabeçalho está sem nome", idx + 1)));
+        }
+        if !seen.insert(name.clone()) {
+            return Err(AppError::BadRequest(format!("Coluna duplicada no cabeçalho: {}", name)));
+        }
+        names.push(name);
+    }
+
+    if names.is_empty() {
+        return Err(AppError::BadRequest("O arquivo não possui cabeçalho".to_string()));
+    }
+
+    Ok(names)
+}
+
+/// Infer the column type from its non-null values
+/// Mixed integer/decimal becomes decimal, mixed date/datetime becomes datetime, anything else is string
+fn detect_column<'a>(name: &str, values: impl Iterator<Item = &'a Value>) -> FileDatasetColumn {
+    let mut detected: Option<&'static str> = None;
+    let mut is_nullable = false;
+    let mut max_length = 0usize;
+
+    for value in values {
+        let kind = match value {
+            Value::Null => {
+                is_nullable = true;
+                continue;
+            }
+            Value::Bool(_) => "boolean",
+            Value::Number(n) if n.is_f64() => "decimal",
+            Value::Number(_) => "integer",
+            Value::String(s) => {
+                max_length = max_length.max(s.chars().count());
+                detect_text_kind(s)
+            }
+            _ => "string",
+        };
+
+        detected = Some(match (detected, kind) {
+            (None, kind) => kind,
+            (Some(current), kind) if current == kind => current,
+            (Some("integer"), "decimal") | (Some("decimal"), "integer") => "decimal",
+            (Some("date"), "datetime") | (Some("datetime"), "date") => "datetime",
+            _ => "string",
+        });
+    }
+
+    let data_type = detected.unwrap_or("string");
+
+    FileDatasetColumn {
+        name: name.to_string(),
+        data_type: data_type.to_string(),
+        is_nullable: is_nullable || detected.is_none(),
+        max_length: (data_type == "string" && max_length > 0).then_some(max_length as i32),
+    }
+}
+
+fn detect_text_kind(value: &str) -> &'static str {
+    const DATE_FORMATS: [&str; 3] = ["%Y-%m-%d", "%d/%m/%Y", "%d-%m-%Y"];
+    const DATETIME_FORMATS: [&str; 6] = [
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%d %H:%M:%S",
+        "%d/%m/%Y %H:%M:%S",
+        "%d/%m/%Y %H:%M",
+        "%d-%m-%Y %H:%M:%S",
+        "%Y/%m/%d %H:%M:%S",
+    ];
+
+    // Leading zeros mean an identifier (CPF, CEP, prontuário), not a number
+    let has_leading_zero = value.len() > 1 && value.starts_with('0') && !value.starts_with("0.");
+
+    if !has_leading_zero && value.parse::<i64>().is_ok() {
+        "integer"
+    } else if !has_leading_zero && value.parse::<f64>().is_ok_and(f64::is_finite) {
+        "decimal"
+    } else if DATE_FORMATS.iter().any(|f| NaiveDate::parse_from_str(value, f).is_ok()) {
+        "date"
+    } else if DATETIME_FORMATS.iter().any(|f| NaiveDateTime::parse_from_str(value, f).is_ok()) {
+        "datetime"
+    } else if matches!(value.to_lowercase().as_str(), "true" | "false") {
+        "boolean"
+    } else {
+        "string"
+    }
+}
+
+/// Source connector over spreadsheets uploaded to a FILE database configuration
+pub struct FileConnector {
+    repository: Arc<FileDatasetRepository>,
+    database_configuration_id: String,
+}
+
+impl FileConnector {
+    pub fn new(repository: Arc<FileDatasetRepository>, database_configuration_id: &str) -> Self {
+        Self {
+            repository,
+            database_configuration_id: database_configuration_id.to_string(),
+        }
+    }
+
+    pub async fn test_connection(&self) -> Result<bool, AppError> {
+        self.repository.ping().await
+    }
+
+    /// Columns detected for the dataset, in the same shape as the database connectors
+    pub async fn get_table_columns(&self, table_name: &str) -> Result<Vec<Value>, AppError> {
+        let dataset = self.dataset(table_name).await?;
+
+        Ok(dataset.columns.iter().enumerate().map(|(idx, column)| {
+            serde_json::json!({
+                "id": (idx + 1).to_string(),
+                "name": column.name.to_uppercase(),
+                "dataType": column.data_type,
+                "dataLength": column.max_length,
+                "dataPrecision": null,
+                "dataScale": null,
+                "isNullable": column.is_nullable,
+                "defaultValue": null
+            })
+        }).collect())
+    }
+
+    pub async fn count_records(&self, table_name: &str) -> Result<u64, AppError> {
+        let dataset = self.dataset(table_name).await?;
+        self.repository.count_rows(&Self::dataset_id(&dataset)).await
+    }
+
+    /// Fetch one page of rows in file order (page is 0-indexed)
+    pub async fn fetch_page_data(&self, table_name: &str, page: u64, page_size: u64) -> Result<Vec<Value>, AppError> {
+        let dataset = self.dataset(table_name).await?;
+        let rows = self.repository
+            .find_rows(&Self::dataset_id(&dataset), page * page_size, page_size)
+            .await?;
+
+        Ok(rows.into_iter().map(|row| bson::Bson::Document(row.data).into_relaxed_extjson()).collect())
+    }
+
+    pub async fn fetch_by_key(&self, table_name: &str, key_column: &str, key_value: &str) -> Result<Option<Value>, AppError> {
+        let dataset = self.dataset(table_name).await?;
+        let row = self.repository
+            .find_row_by_value(&Self::dataset_id(&dataset), &key_column.to_lowercase(), key_value)
+            .await?;
+
+        Ok(row.map(|row| bson::Bson::Document(row.data).into_relaxed_extjson()))
+    }
+
+    /// Map detected spreadsheet types to the simplified types used by DatabaseColumn
+    pub fn simplify_data_type(data_type: &str) -> &'static str {
+        match data_type {
+            "integer" | "decimal" => "integer",
+            "date" => "date",
+            "datetime" => "timestamp",
+            "boolean" => "boolean",
+            _ => "varchar",
+        }
+    }
+
+    async fn dataset(&self, table_name: &str) -> Result<crate::domain::entities::FileDataset, AppError> {
+        self.repository
+            .find_by_table(&self.database_configuration_id, &table_name.to_uppercase())
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Nenhum arquivo enviado para a tabela {}", table_name)))
+    }
+
+    fn dataset_id(dataset: &crate::domain::entities::FileDataset) -> String {
+        dataset.id.map(|id| id.to_hex()).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_csv_detects_columns() {
+        let content = "\u{feff}Patient Code;Patient Name;Birth Date;Weight\n001;Ana;12/04/1990;61.5\n002;Bruno;;70\n";
+        let parsed = parse_spreadsheet("pacientes.csv", content.as_bytes()).unwrap();
+
+        assert_eq!(parsed.format, "csv");
+        assert_eq!(parsed.total_rows, 2);
+        assert!(parsed.row_errors.is_empty());
+
+        let types: Vec<(&str, &str, bool)> = parsed.columns
+            .iter()
+            .map(|c| (c.name.as_str(), c.data_type.as_str(), c.is_nullable))
+            .collect();
+        assert_eq!(types, vec![
+            ("patient_code", "string", false),
+            ("patient_name", "string", false),
+            ("birth_date", "date", true),
+            ("weight", "decimal", false),
+        ]);
+
+        assert_eq!(parsed.rows[1].0, 3);
+        assert_eq!(parsed.rows[1].1["patient_code"], json!("002"));
+        assert_eq!(parsed.rows[1].1["birth_date"], Value::Null);
+    }
+
+    #[test]
+    fn test_parse_csv_reports_row_errors() {
+        let content = "code,name\n1,Ana\n2,Bruno,extra\n\n3,Carla\n";
+        let parsed = parse_spreadsheet("dados.csv", content.as_bytes()).unwrap();
+
+        assert_eq!(parsed.total_rows, 3);
+        assert_eq!(parsed.rows.len(), 2);
+        assert_eq!(parsed.row_errors, vec![FileRowError {
+            row: 3,
+            message: "1 valores a mais do que as 2 colunas do cabeçalho".to_string(),
+        }]);
+        assert_eq!(parsed.columns[0].data_type, "integer");
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_headers_and_formats() {
+        assert!(matches!(parse_spreadsheet("dados.csv", b"code,Code\n1,2\n"), Err(AppError::BadRequest(_))));
+        assert!(matches!(parse_spreadsheet("dados.csv", b"code,\n1,2\n"), Err(AppError::BadRequest(_))));
+        assert!(matches!(parse_spreadsheet("dados.pdf", b"%PDF"), Err(AppError::BadRequest(_))));
+    }
+}
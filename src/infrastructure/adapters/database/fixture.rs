@@ -0,0 +1,421 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+
+use serde_json::Value;
+
+use crate::domain::entities::{DiscoveredColumn, DiscoveredTable, SourceObjectKind};
+use crate::utils::AppError;
+
+type FixtureTables = HashMap<String, Vec<Value>>;
+
+/// Process-wide registry of in-memory fixture datasets
+/// Tests register rows here and point a FIXTURE configuration at the dataset name
+pub struct FixtureRegistry;
+
+impl FixtureRegistry {
+    fn datasets() -> &'static RwLock<HashMap<String, FixtureTables>> {
+        static DATASETS: OnceLock<RwLock<HashMap<String, FixtureTables>>> = OnceLock::new();
+        DATASETS.get_or_init(|| RwLock::new(HashMap::new()))
+    }
+
+    /// Register (or replace) the rows of a table within a dataset
+    #[cfg(test)]
+    pub fn register(dataset: &str, table_name: &str, rows: Vec<Value>) {
+        let mut datasets = Self::datasets().write().unwrap_or_else(|e| e.into_inner());
+        datasets
+            .entry(dataset.to_string())
+            .or_default()
+            .insert(table_name.to_uppercase(), rows.into_iter().map(normalize_record).collect());
+    }
+
+    /// Remove a dataset and all of its tables
+    #[cfg(test)]
+    pub fn remove(dataset: &str) {
+        let mut datasets = Self::datasets().write().unwrap_or_else(|e| e.into_inner());
+        datasets.remove(dataset);
+    }
+
+    fn get(dataset: &str) -> Option<FixtureTables> {
+        let datasets = Self::datasets().read().unwrap_or_else(|e| e.into_inner());
+        datasets.get(dataset).cloned()
+    }
+}
+
+/// Source connector that serves rows from fixtures instead of a live database
+///
+/// A FIXTURE configuration resolves its rows in this order:
+/// - `database` names a dataset registered in [`FixtureRegistry`]
+/// - `host` is a directory under the fixtures root holding one `<TABLE>.json` (array of objects)
+///   or `<TABLE>.csv` per table
+pub struct FixtureConnector {
+    dataset: String,
+    tables: FixtureTables,
+}
+
+impl FixtureConnector {
+    /// Create a connector over an explicit set of tables
+    #[cfg(test)]
+    pub fn from_tables(dataset: &str, tables: HashMap<String, Vec<Value>>) -> Self {
+        Self {
+            dataset: dataset.to_string(),
+            tables: tables
+                .into_iter()
+                .map(|(name, rows)| (name.to_uppercase(), rows.into_iter().map(normalize_record).collect()))
+                .collect(),
+        }
+    }
+
+    /// Create a connector from a dataset registered in the fixture registry
+    pub fn from_registry(dataset: &str) -> Result<Self, AppError> {
+        let tables = FixtureRegistry::get(dataset)
+            .ok_or_else(|| AppError::NotFound(format!("Fixture dataset {} not registered", dataset)))?;
+
+        Ok(Self {
+            dataset: dataset.to_string(),
+            tables,
+        })
+    }
+
+    /// Create a connector from a directory of JSON/CSV fixture files
+    pub async fn from_directory(path: &Path) -> Result<Self, AppError> {
+        let mut entries = tokio::fs::read_dir(path)
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Failed to read fixture directory {}: {}", path.display(), e)))?;
+
+        let mut tables = HashMap::new();
+
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Failed to read fixture directory {}: {}", path.display(), e)))?
+        {
+            let file_path = entry.path();
+            let (Some(stem), Some(extension)) = (
+                file_path.file_stem().and_then(|s| s.to_str()),
+                file_path.extension().and_then(|s| s.to_str()),
+            ) else {
+                continue;
+            };
+
+            let rows = match extension.to_lowercase().as_str() {
+                "json" => Self::read_json(&file_path).await?,
+                "csv" => Self::read_csv(&file_path).await?,
+                _ => continue,
+            };
+
+            tables.insert(stem.to_uppercase(), rows);
+        }
+
+        Ok(Self {
+            dataset: path.display().to_string(),
+            tables,
+        })
+    }
+
+    /// Open the fixture named by a configuration: a registered dataset first, then a directory
+    /// under `root` (directories are refused without one)
+    pub async fn open(host: &str, dataset: Option<&str>, root: Option<&Path>) -> Result<Self, AppError> {
+        if let Some(dataset) = dataset {
+            if FixtureRegistry::get(dataset).is_some() {
+                return Self::from_registry(dataset);
+            }
+        }
+
+        let root = root.ok_or_else(|| AppError::BadRequest("Diretório de fixtures não configurado".to_string()))?;
+        let directory = Self::resolve_directory(root, host).await?;
+        Self::from_directory(&directory).await
+    }
+
+    /// Resolve `host` inside the canonical fixtures `root`, rejecting anything that escapes it
+    async fn resolve_directory(root: &Path, host: &str) -> Result<PathBuf, AppError> {
+        let invalid = || AppError::BadRequest(format!("Diretório de fixtures inválido: {}", host));
+
+        if host.trim().is_empty() || Path::new(host).is_absolute() {
+            return Err(invalid());
+        }
+
+        let directory = tokio::fs::canonicalize(root.join(host)).await.map_err(|_| invalid())?;
+        if !directory.starts_with(root) {
+            return Err(invalid());
+        }
+
+        Ok(directory)
+    }
+
+    /// Get the column metadata of a table, inferred from its first row
+    pub fn get_table_columns(&self, table_name: &str) -> Result<Vec<Value>, AppError> {
+        let rows = self.rows(table_name)?;
+
+        let columns = rows
+            .first()
+            .and_then(|row| row.as_object())
+            .map(|row| {
+                row.iter().enumerate().map(|(idx, (name, value))| {
+                    let data_type = match value {
+                        Value::Number(n) if n.is_f64() => "FLOAT",
+                        Value::Number(_) => "NUMBER",
+                        Value::Bool(_) => "BOOLEAN",
+                        _ => "VARCHAR",
+                    };
+
+                    serde_json::json!({
+                        "id": (idx + 1).to_string(),
+                        "name": name.to_uppercase(),
+                        "dataType": data_type,
+                        "dataLength": null,
+                        "dataPrecision": null,
+                        "dataScale": null,
+                        "isNullable": true,
+                        "defaultValue": null
+                    })
+                }).collect()
+            })
+            .unwrap_or_default();
+
+        Ok(columns)
+    }
+
+    /// List every fixture table with the columns inferred from its first row
+    pub fn discover_schema(&self) -> Result<Vec<DiscoveredTable>, AppError> {
+        let mut names: Vec<&String> = self.tables.keys().collect();
+        names.sort();
+
+        names
+            .into_iter()
+            .map(|name| {
+                let columns = self
+                    .get_table_columns(name)?
+                    .iter()
+                    .map(|column| DiscoveredColumn {
+                        name: column["name"].as_str().unwrap_or_default().to_string(),
+                        source_type: column["dataType"].as_str().unwrap_or_default().to_string(),
+                        is_nullable: true,
+                        max_length: None,
+                        is_primary_key: false,
+                        foreign_key: None,
+                    })
+                    .collect();
+
+                Ok(DiscoveredTable {
+                    owner: None,
+                    name: name.clone(),
+                    kind: SourceObjectKind::Table,
+                    columns,
+                })
+            })
+            .collect()
+    }
+
+    pub fn count_records(&self, table_name: &str) -> Result<u64, AppError> {
+        Ok(self.rows(table_name)?.len() as u64)
+    }
+
+    /// Fetch one page of rows (page is 0-indexed)
+    pub fn fetch_page_data(&self, table_name: &str, page: u64, page_size: u64) -> Result<Vec<Value>, AppError> {
+        Ok(self
+            .rows(table_name)?
+            .iter()
+            .skip((page * page_size) as usize)
+            .take(page_size as usize)
+            .cloned()
+            .collect())
+    }
+
+    /// Fetch the first row whose `key_column` renders as `key_value`
+    pub fn fetch_by_key(&self, table_name: &str, key_column: &str, key_value: &str) -> Result<Option<Value>, AppError> {
+        let key_column = key_column.to_lowercase();
+
+        Ok(self
+            .rows(table_name)?
+            .iter()
+            .find(|row| match row.get(&key_column) {
+                Some(Value::String(s)) => s == key_value,
+                Some(Value::Number(number)) => number.to_string() == key_value,
+                Some(Value::Bool(flag)) => flag.to_string() == key_value,
+                _ => false,
+            })
+            .cloned())
+    }
+
+    /// Map fixture data types to the simplified types used by DatabaseColumn
+    pub fn simplify_data_type(data_type: &str) -> &'static str {
+        match data_type.to_uppercase().as_str() {
+            "NUMBER" | "FLOAT" => "integer",
+            "BOOLEAN" => "boolean",
+            _ => "varchar",
+        }
+    }
+
+    fn rows(&self, table_name: &str) -> Result<&Vec<Value>, AppError> {
+        self.tables
+            .get(&table_name.to_uppercase())
+            .ok_or_else(|| AppError::NotFound(format!("Fixture table {} not found in {}", table_name, self.dataset)))
+    }
+
+    async fn read_json(path: &Path) -> Result<Vec<Value>, AppError> {
+        let content = tokio::fs::read_to_string(path)
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Failed to read fixture {}: {}", path.display(), e)))?;
+
+        let rows: Vec<Value> = serde_json::from_str(&content)
+            .map_err(|e| AppError::DatabaseError(format!("Invalid fixture {}: {}", path.display(), e)))?;
+
+        Ok(rows.into_iter().map(normalize_record).collect())
+    }
+
+    async fn read_csv(path: &Path) -> Result<Vec<Value>, AppError> {
+        let content = tokio::fs::read(path)
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Failed to read fixture {}: {}", path.display(), e)))?;
+
+        parse_csv(&content).map_err(|e| AppError::DatabaseError(format!("Invalid fixture {}: {}", path.display(), e)))
+    }
+}
+
+/// Parse CSV content into records keyed by lowercase header; empty cells become null
+fn parse_csv(content: &[u8]) -> Result<Vec<Value>, csv::Error> {
+    let mut reader = csv::Reader::from_reader(content);
+    let headers: Vec<String> = reader.headers()?.iter().map(|h| h.trim().to_lowercase()).collect();
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let row: serde_json::Map<String, Value> = headers
+            .iter()
+            .zip(record.iter())
+            .map(|(header, cell)| {
+                let value = if cell.is_empty() { Value::Null } else { Value::String(cell.to_string()) };
+                (header.clone(), value)
+            })
+            .collect();
+        rows.push(Value::Object(row));
+    }
+
+    Ok(rows)
+}
+
+/// Lowercase record keys so fixtures match the shape returned by the database connectors
+fn normalize_record(record: Value) -> Value {
+    match record {
+        Value::Object(map) => Value::Object(map.into_iter().map(|(k, v)| (k.to_lowercase(), v)).collect()),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn connector() -> FixtureConnector {
+        let mut tables = HashMap::new();
+        tables.insert(
+            "patient_interhealth".to_string(),
+            vec![
+                json!({ "PATIENT_CODE": "1", "NAME": "Ana" }),
+                json!({ "PATIENT_CODE": "2", "NAME": "Bruno" }),
+                json!({ "PATIENT_CODE": 3, "NAME": "Carla" }),
+            ],
+        );
+        FixtureConnector::from_tables("unit", tables)
+    }
+
+    #[test]
+    fn test_pages_and_keys_are_case_insensitive() {
+        let connector = connector();
+
+        assert_eq!(connector.count_records("PATIENT_INTERHEALTH").unwrap(), 3);
+
+        let page = connector.fetch_page_data("PATIENT_INTERHEALTH", 1, 2).unwrap();
+        assert_eq!(page, vec![json!({ "patient_code": 3, "name": "Carla" })]);
+
+        let row = connector.fetch_by_key("PATIENT_INTERHEALTH", "PATIENT_CODE", "2").unwrap();
+        assert_eq!(row.unwrap()["name"], "Bruno");
+        let numeric = connector.fetch_by_key("PATIENT_INTERHEALTH", "patient_code", "3").unwrap();
+        assert_eq!(numeric.unwrap()["name"], "Carla");
+    }
+
+    #[test]
+    fn test_unknown_table_is_not_found() {
+        assert!(matches!(connector().count_records("ENCOUNTER_INTERHEALTH"), Err(AppError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let rows = parse_csv(b"Patient_Code,Name,Birth_Date\n1,Ana,1990-01-01\n2,Bruno,\n").unwrap();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0], json!({ "patient_code": "1", "name": "Ana", "birth_date": "1990-01-01" }));
+        assert_eq!(rows[1]["birth_date"], Value::Null);
+    }
+
+    #[test]
+    fn test_registry_round_trip() {
+        FixtureRegistry::register("registry-test", "Encounter_Interhealth", vec![json!({ "ENCOUNTER_CODE": "E1" })]);
+
+        let connector = FixtureConnector::from_registry("registry-test").unwrap();
+        assert_eq!(connector.count_records("ENCOUNTER_INTERHEALTH").unwrap(), 1);
+
+        FixtureRegistry::remove("registry-test");
+        assert!(FixtureConnector::from_registry("registry-test").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_directories_stay_inside_the_fixtures_root() {
+        let base = std::env::temp_dir().join(format!("interhealth-fixtures-{}", std::process::id()));
+        let root = base.join("root");
+        tokio::fs::create_dir_all(root.join("site")).await.unwrap();
+        tokio::fs::create_dir_all(base.join("outside")).await.unwrap();
+        tokio::fs::write(root.join("site/PATIENT_INTERHEALTH.json"), r#"[{"PATIENT_CODE": "1"}]"#).await.unwrap();
+        let root = tokio::fs::canonicalize(&root).await.unwrap();
+
+        let connector = FixtureConnector::open("site", None, Some(&root)).await.unwrap();
+        assert_eq!(connector.count_records("PATIENT_INTERHEALTH").unwrap(), 1);
+
+        for host in ["../outside", "/etc", "", "missing"] {
+            assert!(matches!(FixtureConnector::open(host, None, Some(&root)).await, Err(AppError::BadRequest(_))), "{}", host);
+        }
+        assert!(matches!(FixtureConnector::open("site", None, None).await, Err(AppError::BadRequest(_))));
+
+        tokio::fs::remove_dir_all(&base).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_diagnose_reports_a_checklist() {
+        use crate::domain::entities::connection_diagnostics::CheckStatus;
+        use crate::domain::entities::ConnectionDiagnostics;
+        use crate::infrastructure::factories::SourceConnector;
+
+        let mut tables = HashMap::new();
+        tables.insert("patient_interhealth".to_string(), vec![json!({ "PATIENT_CODE": "1" })]);
+        tables.insert("encounter_interhealth".to_string(), Vec::new());
+        let connector = FixtureConnector::from_tables("diagnose", tables);
+
+        let objects = ["PATIENT_INTERHEALTH", "ENCOUNTER_INTERHEALTH", "EXAM_INTERHEALTH"].map(String::from);
+        let diagnostics = connector.diagnose(&objects).await.unwrap();
+
+        let statuses: Vec<(&str, Option<&str>, CheckStatus)> = diagnostics
+            .checks
+            .iter()
+            .map(|check| (check.name.as_str(), check.object.as_deref(), check.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("connectivity", None, CheckStatus::Passed),
+                ("selectPrivilege", Some("PATIENT_INTERHEALTH"), CheckStatus::Passed),
+                ("rowVisibility", Some("PATIENT_INTERHEALTH"), CheckStatus::Passed),
+                ("selectPrivilege", Some("ENCOUNTER_INTERHEALTH"), CheckStatus::Passed),
+                ("rowVisibility", Some("ENCOUNTER_INTERHEALTH"), CheckStatus::Warning),
+                ("selectPrivilege", Some("EXAM_INTERHEALTH"), CheckStatus::Failed),
+                ("rowVisibility", Some("EXAM_INTERHEALTH"), CheckStatus::Skipped),
+            ]
+        );
+        assert!(diagnostics.latency_ms.is_some());
+
+        let result = ConnectionDiagnostics::new("FIXTURE".to_string(), String::new(), None, None, diagnostics);
+        assert!(!result.success);
+        assert_eq!(result.message, "Falha em 1 verificação(ões) da conexão");
+    }
+}
@@ -1,5 +1,11 @@
+pub mod file;
+pub mod fixture;
 pub mod mongodb;
 pub mod oracledb;
+pub mod sqlserver;
 
+pub use file::*;
+pub use fixture::*;
 pub use mongodb::*;
 pub use oracledb::*;
+pub use sqlserver::*;
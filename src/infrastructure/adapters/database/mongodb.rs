@@ -1,8 +1,8 @@
 use mongodb::{Client, Database};
-use crate::utils::AppError;
+use crate::utils::{redact, AppError};
 
 /// MongoDB database connection configuration
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct MongoDBConfig {
     pub connection_string: String,
     pub database_name: Option<String>,
@@ -10,6 +10,18 @@ pub struct MongoDBConfig {
     pub min_pool_size: Option<u32>,
 }
 
+/// Debug output masks the password of the connection string
+impl std::fmt::Debug for MongoDBConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MongoDBConfig")
+            .field("connection_string", &redact::url(&self.connection_string))
+            .field("database_name", &self.database_name)
+            .field("max_pool_size", &self.max_pool_size)
+            .field("min_pool_size", &self.min_pool_size)
+            .finish()
+    }
+}
+
 impl MongoDBConfig {
     /// Create a new MongoDB configuration
     pub fn new(connection_string: String) -> Self {
@@ -1,14 +1,32 @@
-use crate::utils::AppError;
-use reqwest::{Client, header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE}};
+use crate::utils::{redact, AppError};
+use super::oauth::TokenProvider;
+use super::tls;
+use crate::domain::entities::{ClientCertificate, DeliveryMode, TargetIntegration};
+use reqwest::{Client, RequestBuilder, Response, StatusCode, Url, header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE, IF_MATCH, LINK}};
+use serde::Serialize;
 use serde_json::Value;
 use std::time::Duration;
 
 /// API connection configuration
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct ApiConfig {
     pub host: String,
     pub auth_type: Option<String>,
     pub credentials: Option<String>,
+    /// Presented to targets that require mutual TLS
+    pub client_certificate: Option<ClientCertificate>,
+}
+
+/// Debug output never carries the credentials
+impl std::fmt::Debug for ApiConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiConfig")
+            .field("host", &self.host)
+            .field("auth_type", &self.auth_type)
+            .field("credentials", &redact::secret(&self.credentials))
+            .field("client_certificate", &self.client_certificate)
+            .finish()
+    }
 }
 
 impl ApiConfig {
@@ -18,6 +36,34 @@ impl ApiConfig {
             host,
             auth_type,
             credentials,
+            client_certificate: None,
+        }
+    }
+
+    pub fn with_client_certificate(mut self, certificate: Option<ClientCertificate>) -> Self {
+        self.client_certificate = certificate;
+        self
+    }
+}
+
+/// What the FHIR server did with a delivered resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DeliveryOutcome {
+    Created,
+    Updated,
+    /// A conditional create matched an existing resource, which was left as is
+    Unchanged,
+}
+
+impl DeliveryOutcome {
+    /// 201 is always a create; 200 is an update for PUT and an ifNoneExist match for POST
+    pub fn from_status(method: &str, status: u16) -> Option<Self> {
+        match (method, status) {
+            (_, 201) => Some(DeliveryOutcome::Created),
+            ("PUT", 200) => Some(DeliveryOutcome::Updated),
+            ("POST", 200) => Some(DeliveryOutcome::Unchanged),
+            _ => None,
         }
     }
 }
@@ -26,43 +72,60 @@ impl ApiConfig {
 pub struct ApiConnector {
     config: ApiConfig,
     client: Client,
+    /// Set for the OAuth2 auth types (client credentials, SMART backend services)
+    token_provider: Option<TokenProvider>,
 }
 
 impl ApiConnector {
     /// Create a new API connector
     pub async fn new(host: &str, auth_type: Option<String>, credentials: Option<String>) -> Result<Self, AppError> {
         let config = ApiConfig::new(host.to_string(), auth_type, credentials);
-        
-        // Build HTTP client with timeout
-        let client = Client::builder()
-            .timeout(Duration::from_secs(30))
-            .build()
-            .map_err(|e| AppError::DatabaseError(format!("Failed to create HTTP client: {}", e)))?;
-        
-        Ok(Self {
-            config,
-            client,
-        })
+        Self::from_config(config).await
+    }
+
+    /// Connector delivering to a target integration, presenting its client certificate if any
+    pub async fn for_target(target: &TargetIntegration) -> Result<Self, AppError> {
+        let config = ApiConfig::new(target.host.clone(), target.auth_type.clone(), target.credentials.clone())
+            .with_client_certificate(target.client_certificate.clone());
+        Self::from_config(config).await
     }
 
     /// Create connector from config
     pub async fn from_config(config: ApiConfig) -> Result<Self, AppError> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(30))
-            .build()
-            .map_err(|e| AppError::DatabaseError(format!("Failed to create HTTP client: {}", e)))?;
-        
+        let client = tls::http_client(config.client_certificate.as_ref(), Duration::from_secs(30))?;
+
+        let token_provider = match (&config.auth_type, &config.credentials) {
+            (Some(auth_type), Some(credentials)) if TokenProvider::handles(auth_type) => {
+                Some(TokenProvider::new(auth_type, credentials)?)
+            }
+            (Some(auth_type), None) if TokenProvider::handles(auth_type) => {
+                return Err(AppError::BadRequest(format!("Credenciais são obrigatórias para {}", auth_type)));
+            }
+            _ => None,
+        };
+
         Ok(Self {
             config,
             client,
+            token_provider,
         })
     }
 
     /// Build headers with authentication
-    fn build_headers(&self) -> Result<HeaderMap, AppError> {
+    async fn build_headers(&self) -> Result<HeaderMap, AppError> {
         let mut headers = HeaderMap::new();
         headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
 
+        if let Some(provider) = &self.token_provider {
+            let token = provider.access_token(&self.client).await?;
+            headers.insert(
+                AUTHORIZATION,
+                HeaderValue::from_str(&format!("Bearer {}", token))
+                    .map_err(|e| AppError::BadRequest(format!("Invalid authorization header: {}", e)))?
+            );
+            return Ok(headers);
+        }
+
         if let (Some(auth_type), Some(credentials)) = (&self.config.auth_type, &self.config.credentials) {
             match auth_type.to_lowercase().as_str() {
                 "bearer" => {
@@ -102,14 +165,63 @@ impl ApiConnector {
         Ok(headers)
     }
 
+    /// Send a request built with the auth headers; a 401 with an OAuth2 token drops the
+    /// cached token and retries once with a fresh one
+    async fn send<F>(&self, label: &str, build: F) -> Result<Response, AppError>
+    where
+        F: Fn(HeaderMap) -> RequestBuilder,
+    {
+        let response = build(self.build_headers().await?)
+            .send()
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("{} request failed: {}", label, e)))?;
+
+        match &self.token_provider {
+            Some(provider) if response.status() == StatusCode::UNAUTHORIZED => {
+                provider.invalidate();
+                build(self.build_headers().await?)
+                    .send()
+                    .await
+                    .map_err(|e| AppError::DatabaseError(format!("{} request failed: {}", label, e)))
+            }
+            _ => Ok(response),
+        }
+    }
+
+    /// Fail on non-2xx and parse the JSON body
+    async fn json_body(response: Response) -> Result<Value, AppError> {
+        if !response.status().is_success() {
+            return Err(AppError::DatabaseError(format!(
+                "API request failed with status: {}",
+                response.status()
+            )));
+        }
+
+        response.json::<Value>()
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Failed to parse JSON response: {}", e)))
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.config.host.trim_end_matches('/'), path)
+    }
+
+    /// Whether an absolute URL has the configured host's scheme, host and port
+    fn same_origin(&self, url: &str) -> bool {
+        let origin = |url: &str| {
+            Url::parse(url)
+                .ok()
+                .map(|url| (url.scheme().to_string(), url.host_str().map(str::to_lowercase), url.port_or_known_default()))
+        };
+        matches!((origin(url), origin(&self.config.host)), (Some(target), Some(host)) if target == host)
+    }
+
     /// Test the API connection
     pub async fn test_connection(&self) -> Result<bool, AppError> {
-        let headers = self.build_headers()?;
-        
         // Try to make a simple GET request to the host
         let response = self.client
             .get(&self.config.host)
-            .headers(headers)
+            .headers(self.build_headers().await?)
             .send()
             .await
             .map_err(|e| AppError::DatabaseError(format!("API connection failed: {}", e)))?;
@@ -122,117 +234,245 @@ impl ApiConnector {
 
     /// Execute a GET request
     pub async fn get(&self, path: &str) -> Result<Value, AppError> {
-        let headers = self.build_headers()?;
-        let url = format!("{}{}", self.config.host.trim_end_matches('/'), path);
-        
-        let response = self.client
-            .get(&url)
-            .headers(headers)
-            .send()
-            .await
-            .map_err(|e| AppError::DatabaseError(format!("GET request failed: {}", e)))?;
+        let url = self.url(path);
+        let response = self.send("GET", |headers| self.client.get(&url).headers(headers)).await?;
+        Self::json_body(response).await
+    }
 
-        if !response.status().is_success() {
-            return Err(AppError::DatabaseError(format!(
-                "API request failed with status: {}",
-                response.status()
-            )));
-        }
+    /// Execute a GET request with query parameters, returning the body and the `rel="next"` link, if any
+    /// `target` is either a path relative to the host or an absolute URL (as found in Link headers);
+    /// absolute URLs must share the host's scheme, host and port so the auth headers never leave it
+    pub async fn get_with_next_link(&self, target: &str, query: &[(String, String)]) -> Result<(Value, Option<String>), AppError> {
+        let url = if target.starts_with("http://") || target.starts_with("https://") {
+            if !self.same_origin(target) {
+                return Err(AppError::BadRequest(format!(
+                    "Link de próxima página fora do host configurado: {}",
+                    target
+                )));
+            }
+            target.to_string()
+        } else {
+            self.url(target)
+        };
 
-        let json = response.json::<Value>()
-            .await
-            .map_err(|e| AppError::DatabaseError(format!("Failed to parse JSON response: {}", e)))?;
+        let response = self.send("GET", |headers| self.client.get(&url).headers(headers).query(query)).await?;
 
-        Ok(json)
+        let next_link = response.headers()
+            .get_all(LINK)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .find_map(parse_next_link);
+
+        Ok((Self::json_body(response).await?, next_link))
     }
 
-    /// Execute a POST request
-    pub async fn post(&self, path: &str, body: Value) -> Result<Value, AppError> {
-        let headers = self.build_headers()?;
-        let url = format!("{}{}", self.config.host.trim_end_matches('/'), path);
-        
-        let response = self.client
-            .post(&url)
-            .headers(headers)
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| AppError::DatabaseError(format!("POST request failed: {}", e)))?;
+    /// Fetch the FHIR CapabilityStatement of the server (`GET [base]/metadata`)
+    pub async fn capability_statement(&self) -> Result<Value, AppError> {
+        let url = self.url("/metadata");
+        let response = self
+            .send("GET", |mut headers| {
+                headers.insert(ACCEPT, HeaderValue::from_static("application/fhir+json"));
+                self.client.get(&url).headers(headers)
+            })
+            .await?;
+        Self::json_body(response).await
+    }
 
-        if !response.status().is_success() {
-            return Err(AppError::DatabaseError(format!(
-                "API request failed with status: {}",
-                response.status()
-            )));
+    /// Deliver a generated bundle entry (`resource` + `request`) following the view's delivery mode
+    ///
+    /// Versioned updates search the entry's identifier first: no match creates the resource, one
+    /// match is updated by id with `If-Match` on its versionId, several matches are refused.
+    pub async fn deliver(&self, entry: &Value, mode: DeliveryMode) -> Result<DeliveryOutcome, AppError> {
+        let resource = entry.get("resource")
+            .ok_or_else(|| AppError::Validation("Entrada sem 'resource'".to_string()))?;
+        let method = entry.pointer("/request/method").and_then(Value::as_str).unwrap_or("POST");
+        let url = entry.pointer("/request/url").and_then(Value::as_str)
+            .ok_or_else(|| AppError::Validation("Entrada sem 'request.url'".to_string()))?;
+        let if_none_exist = entry.pointer("/request/ifNoneExist").and_then(Value::as_str);
+
+        if mode != DeliveryMode::VersionedUpdate {
+            return self.write_resource(method, url, resource, if_none_exist, None).await;
         }
 
-        let json = response.json::<Value>()
-            .await
-            .map_err(|e| AppError::DatabaseError(format!("Failed to parse JSON response: {}", e)))?;
+        let (resource_type, query) = url.split_once('?')
+            .ok_or_else(|| AppError::Validation(format!("Atualização versionada exige URL condicional, recebido '{}'", url)))?;
+        let search = self.get(&format!("/{}?{}", resource_type, query)).await?;
+        let matches: Vec<&Value> = search.get("entry")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter(|entry| entry.pointer("/search/mode").and_then(Value::as_str).unwrap_or("match") == "match")
+            .filter_map(|entry| entry.get("resource"))
+            .collect();
+
+        match matches.as_slice() {
+            [] => self.write_resource("POST", resource_type, resource, Some(query), None).await,
+            [existing] => {
+                let id = existing.get("id").and_then(Value::as_str)
+                    .ok_or_else(|| AppError::DatabaseError(format!("{} encontrado sem id", resource_type)))?;
+                let version = existing.pointer("/meta/versionId").and_then(Value::as_str);
 
-        Ok(json)
+                let mut resource = resource.clone();
+                resource["id"] = Value::String(id.to_string());
+                let path = format!("{}/{}", resource_type, id);
+                self.write_resource("PUT", &path, &resource, None, version).await
+            }
+            _ => Err(AppError::Conflict(format!(
+                "{} recursos {} correspondem a {}; atualização não realizada",
+                matches.len(),
+                resource_type,
+                query
+            ))),
+        }
     }
 
-    /// Execute a PUT request
-    pub async fn put(&self, path: &str, body: Value) -> Result<Value, AppError> {
-        let headers = self.build_headers()?;
-        let url = format!("{}{}", self.config.host.trim_end_matches('/'), path);
-        
-        let response = self.client
-            .put(&url)
-            .headers(headers)
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| AppError::DatabaseError(format!("PUT request failed: {}", e)))?;
+    /// POST or PUT a FHIR resource and interpret the status
+    async fn write_resource(
+        &self,
+        method: &str,
+        path: &str,
+        resource: &Value,
+        if_none_exist: Option<&str>,
+        if_match: Option<&str>,
+    ) -> Result<DeliveryOutcome, AppError> {
+        let url = self.url(&format!("/{}", path.trim_start_matches('/')));
+        let response = self
+            .send(method, |mut headers| {
+                headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/fhir+json"));
+                if let Some(query) = if_none_exist.and_then(|query| HeaderValue::from_str(query).ok()) {
+                    headers.insert("If-None-Exist", query);
+                }
+                if let Some(version) = if_match.and_then(|version| HeaderValue::from_str(&format!("W/\"{}\"", version)).ok()) {
+                    headers.insert(IF_MATCH, version);
+                }
+                let request = if method == "PUT" { self.client.put(&url) } else { self.client.post(&url) };
+                request.headers(headers).json(resource)
+            })
+            .await?;
 
-        if !response.status().is_success() {
-            return Err(AppError::DatabaseError(format!(
-                "API request failed with status: {}",
-                response.status()
+        let status = response.status();
+        if status == StatusCode::PRECONDITION_FAILED {
+            return Err(AppError::Conflict(format!(
+                "{} {} recusado (412): recurso alterado no servidor ou critério condicional com múltiplos resultados",
+                method, path
             )));
         }
 
-        let json = response.json::<Value>()
-            .await
-            .map_err(|e| AppError::DatabaseError(format!("Failed to parse JSON response: {}", e)))?;
-
-        Ok(json)
+        DeliveryOutcome::from_status(method, status.as_u16())
+            .ok_or_else(|| AppError::DatabaseError(format!("{} {} failed with status: {}", method, path, status)))
     }
+}
 
-    /// Execute a DELETE request
-    pub async fn delete(&self, path: &str) -> Result<Value, AppError> {
-        let headers = self.build_headers()?;
-        let url = format!("{}{}", self.config.host.trim_end_matches('/'), path);
-        
-        let response = self.client
-            .delete(&url)
-            .headers(headers)
-            .send()
-            .await
-            .map_err(|e| AppError::DatabaseError(format!("DELETE request failed: {}", e)))?;
+/// Extract the `rel="next"` target of a Link header (RFC 8288), e.g. `<https://api/x?page=2>; rel="next"`
+pub fn parse_next_link(header: &str) -> Option<String> {
+    header.split(',').find_map(|link| {
+        let mut parts = link.split(';');
+        let target = parts.next()?.trim().strip_prefix('<')?.strip_suffix('>')?;
+        let is_next = parts.any(|param| {
+            let param = param.trim().to_lowercase();
+            param == "rel=\"next\"" || param == "rel=next"
+        });
+        is_next.then(|| target.to_string())
+    })
+}
 
-        if !response.status().is_success() {
-            return Err(AppError::DatabaseError(format!(
-                "API request failed with status: {}",
-                response.status()
-            )));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        extract::{Path, RawQuery},
+        http::{HeaderMap as AxumHeaders, StatusCode as AxumStatus},
+        routing::{post, put},
+        Json, Router,
+    };
+    use serde_json::json;
+
+    /// FHIR server with one Patient (id p1, version 3) matching identifier `sys|1`
+    async fn spawn_server() -> String {
+        async fn create(headers: AxumHeaders) -> AxumStatus {
+            match headers.get("If-None-Exist").and_then(|value| value.to_str().ok()) {
+                Some("identifier=sys|1") => AxumStatus::OK,
+                _ => AxumStatus::CREATED,
+            }
         }
 
-        let json = response.json::<Value>()
-            .await
-            .map_err(|e| AppError::DatabaseError(format!("Failed to parse JSON response: {}", e)))?;
+        async fn search(RawQuery(query): RawQuery) -> Json<Value> {
+            let found = query.is_some_and(|query| query.contains("sys|1") || query.contains("sys%7C1"));
+            let entries = if found {
+                vec![json!({ "resource": { "resourceType": "Patient", "id": "p1", "meta": { "versionId": "3" } }, "search": { "mode": "match" } })]
+            } else {
+                Vec::new()
+            };
+            Json(json!({ "resourceType": "Bundle", "type": "searchset", "entry": entries }))
+        }
+
+        async fn update(Path(id): Path<String>, headers: AxumHeaders, Json(resource): Json<Value>) -> AxumStatus {
+            let if_match = headers.get("If-Match").and_then(|value| value.to_str().ok());
+            match (id.as_str(), if_match) {
+                ("p1", Some("W/\"3\"")) if resource["id"] == "p1" => AxumStatus::OK,
+                _ => AxumStatus::PRECONDITION_FAILED,
+            }
+        }
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let app = Router::new()
+            .route("/Patient", post(create).put(|| async { AxumStatus::OK }).get(search))
+            .route("/Patient/:id", put(update).get(|| async { AxumStatus::NOT_FOUND }));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        format!("http://{}", address)
+    }
+
+    fn entry(mode: DeliveryMode, identifier: &str) -> Value {
+        json!({
+            "resource": { "resourceType": "Patient", "identifier": [{ "system": "sys", "value": identifier }] },
+            "request": mode.request("Patient", &format!("identifier=sys|{}", identifier)),
+        })
+    }
 
-        Ok(json)
+    #[test]
+    fn test_outcome_from_status() {
+        assert_eq!(DeliveryOutcome::from_status("POST", 201), Some(DeliveryOutcome::Created));
+        assert_eq!(DeliveryOutcome::from_status("POST", 200), Some(DeliveryOutcome::Unchanged));
+        assert_eq!(DeliveryOutcome::from_status("PUT", 200), Some(DeliveryOutcome::Updated));
+        assert_eq!(DeliveryOutcome::from_status("PUT", 400), None);
     }
 
-    /// Get connection status
-    pub fn is_connected(&self) -> bool {
-        true // HTTP client is always "connected" until test_connection is called
+    #[tokio::test]
+    async fn test_delivers_each_mode() {
+        let host = spawn_server().await;
+        let connector = ApiConnector::new(&host, None, None).await.unwrap();
+
+        let create = DeliveryMode::ConditionalCreate;
+        assert_eq!(connector.deliver(&entry(create, "1"), create).await.unwrap(), DeliveryOutcome::Unchanged);
+        assert_eq!(connector.deliver(&entry(create, "2"), create).await.unwrap(), DeliveryOutcome::Created);
+
+        let update = DeliveryMode::ConditionalUpdate;
+        assert_eq!(entry(update, "1")["request"], json!({ "method": "PUT", "url": "Patient?identifier=sys|1" }));
+        assert_eq!(connector.deliver(&entry(update, "1"), update).await.unwrap(), DeliveryOutcome::Updated);
+
+        let versioned = DeliveryMode::VersionedUpdate;
+        assert_eq!(connector.deliver(&entry(versioned, "1"), versioned).await.unwrap(), DeliveryOutcome::Updated);
+        assert_eq!(connector.deliver(&entry(versioned, "2"), versioned).await.unwrap(), DeliveryOutcome::Created);
     }
 
-    /// Get connection configuration
-    pub fn get_config(&self) -> &ApiConfig {
-        &self.config
+    #[tokio::test]
+    async fn test_next_links_stay_on_the_configured_host() {
+        let host = spawn_server().await;
+        let connector = ApiConnector::new(&host, Some("bearer".to_string()), Some("token".to_string())).await.unwrap();
+
+        let (search, _) = connector.get_with_next_link(&format!("{}/Patient", host), &[]).await.unwrap();
+        assert_eq!(search["resourceType"], "Bundle");
+
+        let port = host.rsplit(':').next().unwrap().parse::<u16>().unwrap();
+        for foreign in [
+            "http://attacker.example/Patient".to_string(),
+            format!("https://{}/Patient", host.trim_start_matches("http://")),
+            format!("http://127.0.0.1:{}/Patient", port.wrapping_add(1)),
+        ] {
+            let error = connector.get_with_next_link(&foreign, &[]).await.unwrap_err();
+            assert!(matches!(error, AppError::BadRequest(_)), "{} was followed", foreign);
+        }
     }
 }
@@ -0,0 +1,472 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use serde_json::{Map, Value};
+
+use crate::domain::entities::{ApiPagination, ApiSourceSettings};
+use crate::utils::json_path;
+use crate::utils::AppError;
+use super::api::ApiConnector;
+
+/// Records requested per call when the configuration does not set a page size
+const DEFAULT_API_PAGE_SIZE: u64 = 100;
+
+/// How to request a given API page
+#[derive(Debug, Clone)]
+enum PageRequest {
+    First,
+    Cursor(String),
+    Url(String),
+}
+
+/// One page of records returned by the API
+struct ApiPage {
+    records: Vec<Value>,
+    next: Option<PageRequest>,
+    body: Value,
+}
+
+/// Source connector that pulls JSON records from a partner REST API
+///
+/// The worker asks for pages of its own size; they are assembled from API pages of `page_size` records.
+/// Cursor and Link header pagination can only move forward, so the request of every API page already
+/// reached is remembered and later pages are found by walking from the closest known one.
+pub struct ApiSourceConnector {
+    api: ApiConnector,
+    settings: ApiSourceSettings,
+    page_requests: Mutex<HashMap<String, Vec<PageRequest>>>,
+}
+
+impl ApiSourceConnector {
+    pub async fn new(host: &str, auth_type: Option<String>, credentials: Option<String>, settings: ApiSourceSettings) -> Result<Self, AppError> {
+        let api = ApiConnector::new(host, auth_type, credentials).await?;
+
+        Ok(Self {
+            api,
+            settings,
+            page_requests: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub async fn test_connection(&self) -> Result<bool, AppError> {
+        self.api.test_connection().await
+    }
+
+    /// Column metadata inferred from the first record of the endpoint
+    pub async fn get_table_columns(&self, table_name: &str) -> Result<Vec<Value>, AppError> {
+        let first = self.fetch_page_data(table_name, 0, 1).await?;
+
+        let columns = first
+            .first()
+            .and_then(|record| record.as_object())
+            .map(|record| {
+                record.iter().enumerate().map(|(idx, (name, value))| {
+                    let data_type = match value {
+                        Value::Number(n) if n.is_f64() => "FLOAT",
+                        Value::Number(_) => "NUMBER",
+                        Value::Bool(_) => "BOOLEAN",
+                        _ => "VARCHAR",
+                    };
+
+                    serde_json::json!({
+                        "id": (idx + 1).to_string(),
+                        "name": name.to_uppercase(),
+                        "dataType": data_type,
+                        "dataLength": null,
+                        "dataPrecision": null,
+                        "dataScale": null,
+                        "isNullable": true,
+                        "defaultValue": null
+                    })
+                }).collect()
+            })
+            .unwrap_or_default();
+
+        Ok(columns)
+    }
+
+    /// Total records of the endpoint: read from `total_path` when configured, otherwise counted page by page
+    pub async fn count_records(&self, table_name: &str) -> Result<u64, AppError> {
+        if let Some(total_path) = &self.settings.total_path {
+            let first = self.api_page(table_name, 0).await?;
+            let total = first
+                .as_ref()
+                .map(|page| json_path::select_first(&page.body, total_path))
+                .transpose()?
+                .flatten()
+                .and_then(|value| value.as_u64().or_else(|| value.as_str().and_then(|s| s.parse().ok())));
+
+            if let Some(total) = total {
+                return Ok(total);
+            }
+        }
+
+        let mut total = 0;
+        let mut api_page = 0;
+        while let Some(page) = self.api_page(table_name, api_page).await? {
+            total += page.records.len() as u64;
+            if self.is_last(&page) {
+                break;
+            }
+            api_page += 1;
+        }
+
+        Ok(total)
+    }
+
+    /// Fetch one page of flattened records (page is 0-indexed, in the worker's page size)
+    pub async fn fetch_page_data(&self, table_name: &str, page: u64, page_size: u64) -> Result<Vec<Value>, AppError> {
+        let api_size = self.api_page_size();
+        let start = page * page_size;
+        let end = start + page_size;
+
+        let mut records = Vec::new();
+        let mut api_page = start / api_size;
+
+        while let Some(batch) = self.api_page(table_name, api_page).await? {
+            let batch_start = api_page * api_size;
+            let last = self.is_last(&batch);
+
+            records.extend(
+                batch.records
+                    .iter()
+                    .enumerate()
+                    .filter(|(idx, _)| (start..end).contains(&(batch_start + *idx as u64)))
+                    .map(|(_, record)| flatten_record(record)),
+            );
+
+            if last || batch_start.saturating_add(api_size) >= end {
+                break;
+            }
+            api_page += 1;
+        }
+
+        Ok(records)
+    }
+
+    /// First record whose flattened `key_column` renders as `key_value`
+    pub async fn fetch_by_key(&self, table_name: &str, key_column: &str, key_value: &str) -> Result<Option<Value>, AppError> {
+        let key_column = key_column.to_lowercase();
+        let mut api_page = 0;
+
+        while let Some(page) = self.api_page(table_name, api_page).await? {
+            let found = page.records.iter().map(flatten_record).find(|record| match record.get(&key_column) {
+                Some(Value::String(s)) => s == key_value,
+                Some(Value::Number(number)) => number.to_string() == key_value,
+                Some(Value::Bool(flag)) => flag.to_string() == key_value,
+                _ => false,
+            });
+
+            if found.is_some() {
+                return Ok(found);
+            }
+            if self.is_last(&page) {
+                break;
+            }
+            api_page += 1;
+        }
+
+        Ok(None)
+    }
+
+    /// Map inferred JSON types to the simplified types used by DatabaseColumn
+    pub fn simplify_data_type(data_type: &str) -> &'static str {
+        match data_type.to_uppercase().as_str() {
+            "NUMBER" | "FLOAT" => "integer",
+            "BOOLEAN" => "boolean",
+            _ => "varchar",
+        }
+    }
+
+    fn api_page_size(&self) -> u64 {
+        match self.settings.pagination {
+            ApiPagination::None => u64::MAX,
+            _ => self.settings.page_size.filter(|size| *size > 0).unwrap_or(DEFAULT_API_PAGE_SIZE),
+        }
+    }
+
+    fn endpoint(&self, table_name: &str) -> String {
+        let table_name = table_name.to_uppercase();
+
+        self.settings.endpoints
+            .iter()
+            .find(|(table, _)| table.to_uppercase() == table_name)
+            .map(|(_, endpoint)| endpoint.clone())
+            .unwrap_or_else(|| {
+                let entity = table_name.strip_suffix("_INTERHEALTH").unwrap_or(&table_name);
+                format!("/{}", entity.to_lowercase())
+            })
+    }
+
+    fn is_last(&self, page: &ApiPage) -> bool {
+        match self.settings.pagination {
+            ApiPagination::None => true,
+            ApiPagination::Page { .. } | ApiPagination::Offset { .. } => {
+                (page.records.len() as u64) < self.api_page_size()
+            }
+            ApiPagination::Cursor { .. } | ApiPagination::LinkHeader { .. } => page.next.is_none(),
+        }
+    }
+
+    /// Fetch API page `api_page` (0-indexed); None once past the last page
+    async fn api_page(&self, table_name: &str, api_page: u64) -> Result<Option<ApiPage>, AppError> {
+        match &self.settings.pagination {
+            ApiPagination::None => {
+                if api_page > 0 {
+                    return Ok(None);
+                }
+                self.request(table_name, &PageRequest::First, Vec::new()).await.map(Some)
+            }
+            ApiPagination::Page { page_param, size_param, first_page } => {
+                let params = vec![
+                    (page_param.clone(), (first_page + api_page).to_string()),
+                    (size_param.clone(), self.api_page_size().to_string()),
+                ];
+                self.request(table_name, &PageRequest::First, params).await.map(Some)
+            }
+            ApiPagination::Offset { offset_param, limit_param } => {
+                let params = vec![
+                    (offset_param.clone(), (api_page * self.api_page_size()).to_string()),
+                    (limit_param.clone(), self.api_page_size().to_string()),
+                ];
+                self.request(table_name, &PageRequest::First, params).await.map(Some)
+            }
+            ApiPagination::Cursor { .. } | ApiPagination::LinkHeader { .. } => {
+                self.sequential_page(table_name, api_page as usize).await
+            }
+        }
+    }
+
+    /// Cursor/Link pages: start from the closest page whose request is known and walk forward
+    async fn sequential_page(&self, table_name: &str, api_page: usize) -> Result<Option<ApiPage>, AppError> {
+        let known = {
+            let requests = self.page_requests.lock().unwrap_or_else(|e| e.into_inner());
+            requests.get(table_name).cloned().unwrap_or_else(|| vec![PageRequest::First])
+        };
+
+        let mut index = api_page.min(known.len() - 1);
+        let mut request = known[index].clone();
+
+        loop {
+            let size_param = match &self.settings.pagination {
+                ApiPagination::Cursor { size_param, .. } | ApiPagination::LinkHeader { size_param } => size_param.clone(),
+                _ => None,
+            };
+            let params = match (&request, size_param) {
+                (PageRequest::Url(_), _) | (_, None) => Vec::new(),
+                (_, Some(size_param)) => vec![(size_param, self.api_page_size().to_string())],
+            };
+
+            let page = self.request(table_name, &request, params).await?;
+
+            if let Some(next) = &page.next {
+                let mut requests = self.page_requests.lock().unwrap_or_else(|e| e.into_inner());
+                let entry = requests.entry(table_name.to_string()).or_insert_with(|| vec![PageRequest::First]);
+                if entry.len() == index + 1 {
+                    entry.push(next.clone());
+                }
+            }
+
+            if index == api_page {
+                return Ok(Some(page));
+            }
+
+            match page.next {
+                Some(next) => {
+                    request = next;
+                    index += 1;
+                }
+                None => return Ok(None),
+            }
+        }
+    }
+
+    async fn request(&self, table_name: &str, request: &PageRequest, mut params: Vec<(String, String)>) -> Result<ApiPage, AppError> {
+        let target = match request {
+            PageRequest::Url(url) => url.clone(),
+            _ => {
+                params.extend(self.settings.query.iter().map(|(k, v)| (k.clone(), v.clone())));
+                self.endpoint(table_name)
+            }
+        };
+
+        if let (PageRequest::Cursor(cursor), ApiPagination::Cursor { cursor_param, .. }) = (request, &self.settings.pagination) {
+            params.push((cursor_param.clone(), cursor.clone()));
+        }
+
+        let (body, next_link) = self.api.get_with_next_link(&target, &params).await?;
+        let records = select_records(&body, &self.settings.records_path)?;
+
+        let next = match &self.settings.pagination {
+            ApiPagination::Cursor { cursor_path, .. } => json_path::select_first(&body, cursor_path)?
+                .and_then(|value| match value {
+                    Value::String(s) if !s.is_empty() => Some(s.clone()),
+                    Value::Number(n) => Some(n.to_string()),
+                    _ => None,
+                })
+                .map(PageRequest::Cursor),
+            ApiPagination::LinkHeader { .. } => next_link.map(PageRequest::Url),
+            _ => None,
+        };
+
+        Ok(ApiPage { records, next, body })
+    }
+}
+
+/// Records matched by the selector; a single matched array is taken as the list of records
+fn select_records(body: &Value, records_path: &str) -> Result<Vec<Value>, AppError> {
+    let selected = json_path::select(body, records_path)?;
+
+    Ok(match selected.as_slice() {
+        [Value::Array(items)] => items.clone(),
+        _ => selected.into_iter().cloned().collect(),
+    })
+}
+
+/// Flatten a JSON record into the column map consumed by the Replacer
+/// Nested keys are joined with `_` (`name.given` -> `name_given`, `telecom[0].value` -> `telecom_0_value`)
+pub fn flatten_record(record: &Value) -> Value {
+    let mut columns = Map::new();
+    flatten_into(&mut columns, None, record);
+    Value::Object(columns)
+}
+
+fn flatten_into(columns: &mut Map<String, Value>, prefix: Option<&str>, value: &Value) {
+    let join = |key: &str| match prefix {
+        Some(prefix) => format!("{}_{}", prefix, key.to_lowercase()),
+        None => key.to_lowercase(),
+    };
+
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                flatten_into(columns, Some(&join(key)), child);
+            }
+        }
+        Value::Array(items) => {
+            for (idx, child) in items.iter().enumerate() {
+                flatten_into(columns, Some(&join(&idx.to_string())), child);
+            }
+        }
+        scalar => {
+            columns.insert(prefix.unwrap_or("value").to_string(), scalar.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::Query, http::header, response::IntoResponse, routing::get, Json, Router};
+    use serde_json::json;
+
+    fn patients() -> Vec<Value> {
+        (1..=5).map(|i| json!({ "id": i, "name": { "given": format!("P{}", i) } })).collect()
+    }
+
+    /// Serves 5 patients, 2 per page, through each pagination style
+    async fn spawn_api() -> String {
+        async fn by_page(Query(q): Query<HashMap<String, String>>) -> Json<Value> {
+            let page: usize = q["page"].parse().unwrap();
+            let size: usize = q["size"].parse().unwrap();
+            let data: Vec<Value> = patients().into_iter().skip((page - 1) * size).take(size).collect();
+            Json(json!({ "data": data, "total": 5 }))
+        }
+
+        async fn by_cursor(Query(q): Query<HashMap<String, String>>) -> Json<Value> {
+            let start: usize = q.get("cursor").map(|c| c.parse().unwrap()).unwrap_or(0);
+            let data: Vec<Value> = patients().into_iter().skip(start).take(2).collect();
+            let next = (start + 2 < 5).then(|| (start + 2).to_string());
+            Json(json!({ "items": data, "meta": { "next": next } }))
+        }
+
+        async fn by_link(Query(q): Query<HashMap<String, String>>) -> impl IntoResponse {
+            let start: usize = q.get("from").map(|c| c.parse().unwrap()).unwrap_or(0);
+            let data: Vec<Value> = patients().into_iter().skip(start).take(2).collect();
+            let link = if start + 2 < 5 {
+                format!("</linked?from={}>; rel=\"next\"", start + 2)
+            } else {
+                String::new()
+            };
+            ([(header::LINK, link)], Json(Value::Array(data)))
+        }
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let app = Router::new()
+            .route("/paged", get(by_page))
+            .route("/cursor", get(by_cursor))
+            .route("/linked", get(by_link));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        format!("http://{}", address)
+    }
+
+    async fn connector(host: &str, endpoint: &str, records_path: &str, pagination: ApiPagination) -> ApiSourceConnector {
+        let settings = ApiSourceSettings {
+            endpoints: HashMap::from([("PATIENT_INTERHEALTH".to_string(), endpoint.to_string())]),
+            records_path: records_path.to_string(),
+            pagination,
+            page_size: Some(2),
+            ..Default::default()
+        };
+        ApiSourceConnector::new(host, None, None, settings).await.unwrap()
+    }
+
+    fn ids(records: &[Value]) -> Vec<i64> {
+        records.iter().map(|r| r["id"].as_i64().unwrap()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_pagination_strategies() {
+        let host = spawn_api().await;
+
+        let paged = connector(&host, "/paged", "$.data[*]", ApiPagination::Page {
+            page_param: "page".to_string(),
+            size_param: "size".to_string(),
+            first_page: 1,
+        }).await;
+        let cursor = connector(&host, "/cursor", "$.items", ApiPagination::Cursor {
+            cursor_param: "cursor".to_string(),
+            cursor_path: "$.meta.next".to_string(),
+            size_param: None,
+        }).await;
+        let linked = connector(&host, "/linked", "$", ApiPagination::LinkHeader { size_param: None }).await;
+
+        for source in [&paged, &cursor, &linked] {
+            assert_eq!(source.count_records("PATIENT_INTERHEALTH").await.unwrap(), 5);
+            // Worker pages of 3 span API pages of 2
+            assert_eq!(ids(&source.fetch_page_data("PATIENT_INTERHEALTH", 0, 3).await.unwrap()), vec![1, 2, 3]);
+            assert_eq!(ids(&source.fetch_page_data("PATIENT_INTERHEALTH", 1, 3).await.unwrap()), vec![4, 5]);
+            assert!(source.fetch_page_data("PATIENT_INTERHEALTH", 2, 3).await.unwrap().is_empty());
+
+            let found = source.fetch_by_key("PATIENT_INTERHEALTH", "NAME_GIVEN", "P4").await.unwrap();
+            assert_eq!(found.unwrap()["id"], 4);
+        }
+    }
+
+    #[test]
+    fn test_flatten_record() {
+        let record = json!({
+            "ID": 7,
+            "name": { "given": "Ana", "family": "Silva" },
+            "telecom": [{ "value": "555" }],
+            "active": true
+        });
+
+        assert_eq!(flatten_record(&record), json!({
+            "id": 7,
+            "name_given": "Ana",
+            "name_family": "Silva",
+            "telecom_0_value": "555",
+            "active": true
+        }));
+    }
+
+    #[test]
+    fn test_parse_next_link() {
+        let header = "<https://api/p?page=1>; rel=\"prev\", <https://api/p?page=3>; rel=\"next\"";
+        assert_eq!(super::super::api::parse_next_link(header).as_deref(), Some("https://api/p?page=3"));
+        assert_eq!(super::super::api::parse_next_link("<https://api/p?page=1>; rel=\"prev\""), None);
+    }
+}
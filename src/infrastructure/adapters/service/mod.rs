@@ -1,3 +1,10 @@
 pub mod api;
+pub mod api_source;
+pub mod oauth;
+pub mod reference;
+pub mod tls;
 
-pub use api::*;
\ No newline at end of file
+pub use api::*;
+pub use api_source::*;
+pub use oauth::TokenProvider;
+pub use reference::ReferenceResolver;
@@ -0,0 +1,248 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use serde_json::Value;
+
+use super::api::ApiConnector;
+use crate::domain::entities::ReferenceStrategy;
+use crate::domain::fhir::ConditionalReference;
+use crate::utils::AppError;
+
+/// Looks up referenced resources on the target by identifier so references can be sent as `Type/{id}`
+///
+/// Found ids are cached for the lifetime of the resolver; misses are searched again, since the
+/// referenced resource may be delivered in the meantime.
+pub struct ReferenceResolver {
+    connector: ApiConnector,
+    cache: Mutex<HashMap<ConditionalReference, String>>,
+}
+
+impl ReferenceResolver {
+    pub fn new(connector: ApiConnector) -> Self {
+        Self { connector, cache: Mutex::new(HashMap::new()) }
+    }
+
+    /// Logical id on the target of the resource with the reference's identifier
+    pub async fn lookup(&self, reference: &ConditionalReference) -> Result<Option<String>, AppError> {
+        if let Some(id) = self.cache.lock().unwrap().get(reference) {
+            return Ok(Some(id.clone()));
+        }
+
+        let query = vec![
+            ("identifier".to_string(), format!("{}|{}", reference.system, reference.value)),
+            ("_elements".to_string(), "id".to_string()),
+        ];
+        let (search, _) = self.connector.get_with_next_link(&format!("/{}", reference.resource_type), &query).await?;
+
+        let ids: Vec<&str> = search.get("entry")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter(|entry| entry.pointer("/search/mode").and_then(Value::as_str).unwrap_or("match") == "match")
+            .filter_map(|entry| entry.pointer("/resource/id").and_then(Value::as_str))
+            .collect();
+
+        match ids.as_slice() {
+            [] => Ok(None),
+            [id] => {
+                self.cache.lock().unwrap().insert(reference.clone(), id.to_string());
+                Ok(Some(id.to_string()))
+            }
+            _ => Err(AppError::Conflict(format!(
+                "{} recursos correspondem a {}; referência ambígua",
+                ids.len(),
+                reference
+            ))),
+        }
+    }
+
+    /// Replace the conditional references of a transaction bundle following the view's strategy,
+    /// returning how many were resolved to a server id
+    ///
+    /// References to resources of the same bundle are left conditional: the server resolves them
+    /// while processing the transaction.
+    pub async fn resolve_bundle(&self, bundle: &mut Value, strategy: ReferenceStrategy) -> Result<usize, AppError> {
+        if !strategy.resolves() {
+            return Ok(0);
+        }
+
+        let in_bundle = bundle_identifiers(bundle);
+        let mut references = HashSet::new();
+        collect_references(bundle, &mut references);
+
+        let mut resolved = HashMap::new();
+        for reference in references.into_iter().filter(|reference| !in_bundle.contains(reference)) {
+            let id = self.lookup(&reference).await?;
+            if id.is_none() && strategy == ReferenceStrategy::ResolveOrFail {
+                return Err(AppError::Validation(format!(
+                    "{} com identificador {} não encontrado no destino; referência não resolvida",
+                    reference.resource_type, reference.value
+                )));
+            }
+            resolved.insert(reference, id);
+        }
+
+        let omit = strategy == ReferenceStrategy::ResolveOrOmit;
+        apply_references(bundle, &resolved, omit);
+        Ok(resolved.values().filter(|id| id.is_some()).count())
+    }
+}
+
+/// Identifiers of the resources carried by the bundle, as the conditional references that point to them
+fn bundle_identifiers(bundle: &Value) -> HashSet<ConditionalReference> {
+    bundle.get("entry")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.get("resource"))
+        .flat_map(|resource| {
+            let resource_type = resource.get("resourceType").and_then(Value::as_str).unwrap_or_default();
+            resource.get("identifier")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(move |identifier| {
+                    let system = identifier.get("system").and_then(Value::as_str)?;
+                    let value = identifier.get("value").and_then(Value::as_str)?;
+                    Some(ConditionalReference::new(resource_type, system, value))
+                })
+        })
+        .collect()
+}
+
+fn conditional_reference(value: &Value) -> Option<ConditionalReference> {
+    value.get("reference").and_then(Value::as_str).and_then(ConditionalReference::parse)
+}
+
+fn collect_references(value: &Value, references: &mut HashSet<ConditionalReference>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(reference) = conditional_reference(value) {
+                references.insert(reference);
+            }
+            map.values().for_each(|child| collect_references(child, references));
+        }
+        Value::Array(items) => items.iter().for_each(|item| collect_references(item, references)),
+        _ => {}
+    }
+}
+
+/// Point resolved references to `Type/{id}`; with `omit`, drop the Reference elements whose target was not found
+fn apply_references(value: &mut Value, resolved: &HashMap<ConditionalReference, Option<String>>, omit: bool) {
+    let is_missing = |value: &Value| {
+        omit && conditional_reference(value).is_some_and(|reference| matches!(resolved.get(&reference), Some(None)))
+    };
+
+    match value {
+        Value::Object(map) => {
+            map.retain(|_, child| !is_missing(child));
+            if let Some(reference) = map.get("reference").and_then(Value::as_str).and_then(ConditionalReference::parse) {
+                if let Some(Some(id)) = resolved.get(&reference) {
+                    map.insert("reference".to_string(), Value::String(format!("{}/{}", reference.resource_type, id)));
+                }
+            }
+            for child in map.values_mut() {
+                apply_references(child, resolved, omit);
+            }
+            map.retain(|_, child| !matches!(child, Value::Array(items) if items.is_empty()));
+        }
+        Value::Array(items) => {
+            items.retain(|item| !is_missing(item));
+            for item in items.iter_mut() {
+                apply_references(item, resolved, omit);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::{RawQuery, State}, routing::get, Json, Router};
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// FHIR server with Patient p1 (identifier `sys|1`) and two Patients sharing `sys|dup`; counts searches
+    async fn spawn_server(searches: Arc<AtomicUsize>) -> String {
+        async fn search(State(searches): State<Arc<AtomicUsize>>, RawQuery(query): RawQuery) -> Json<Value> {
+            searches.fetch_add(1, Ordering::SeqCst);
+            let query = query.unwrap_or_default().replace("%7C", "|");
+            let ids: &[&str] = if query.contains("sys|1&") {
+                &["p1"]
+            } else if query.contains("sys|dup&") {
+                &["p2", "p3"]
+            } else {
+                &[]
+            };
+            let entries: Vec<Value> = ids.iter().map(|id| json!({ "resource": { "resourceType": "Patient", "id": id } })).collect();
+            Json(json!({ "resourceType": "Bundle", "type": "searchset", "entry": entries }))
+        }
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let app = Router::new().route("/Patient", get(search)).with_state(searches);
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        format!("http://{}", address)
+    }
+
+    fn bundle(patient: &str) -> Value {
+        json!({
+            "resourceType": "Bundle",
+            "type": "transaction",
+            "entry": [
+                {
+                    "resource": {
+                        "resourceType": "Encounter",
+                        "identifier": [{ "system": "sys", "value": "e1" }],
+                        "subject": { "reference": format!("Patient?identifier=sys|{}", patient) },
+                        "participant": [{ "individual": { "reference": "Practitioner?identifier=sys|m1" } }]
+                    }
+                },
+                {
+                    "resource": { "resourceType": "Practitioner", "identifier": [{ "system": "sys", "value": "m1" }] }
+                }
+            ]
+        })
+    }
+
+    #[tokio::test]
+    async fn test_resolves_references_by_strategy() {
+        let searches = Arc::new(AtomicUsize::new(0));
+        let host = spawn_server(searches.clone()).await;
+        let resolver = ReferenceResolver::new(ApiConnector::new(&host, None, None).await.unwrap());
+
+        let mut found = bundle("1");
+        assert_eq!(resolver.resolve_bundle(&mut found, ReferenceStrategy::ResolveOrConditional).await.unwrap(), 1);
+        assert_eq!(found.pointer("/entry/0/resource/subject/reference"), Some(&json!("Patient/p1")));
+        // Practitioner travels in the same bundle and is resolved by the server
+        assert_eq!(
+            found.pointer("/entry/0/resource/participant/0/individual/reference"),
+            Some(&json!("Practitioner?identifier=sys|m1"))
+        );
+
+        let mut cached = bundle("1");
+        resolver.resolve_bundle(&mut cached, ReferenceStrategy::ResolveOrFail).await.unwrap();
+        assert_eq!(searches.load(Ordering::SeqCst), 1);
+
+        let mut missing = bundle("2");
+        assert_eq!(resolver.resolve_bundle(&mut missing, ReferenceStrategy::ResolveOrConditional).await.unwrap(), 0);
+        assert_eq!(missing.pointer("/entry/0/resource/subject/reference"), Some(&json!("Patient?identifier=sys|2")));
+
+        let mut omitted = bundle("2");
+        resolver.resolve_bundle(&mut omitted, ReferenceStrategy::ResolveOrOmit).await.unwrap();
+        assert_eq!(omitted.pointer("/entry/0/resource/subject"), None);
+
+        let mut failed = bundle("2");
+        assert!(matches!(resolver.resolve_bundle(&mut failed, ReferenceStrategy::ResolveOrFail).await, Err(AppError::Validation(_))));
+
+        let mut ambiguous = bundle("dup");
+        assert!(matches!(resolver.resolve_bundle(&mut ambiguous, ReferenceStrategy::ResolveOrOmit).await, Err(AppError::Conflict(_))));
+
+        let mut conditional = bundle("2");
+        assert_eq!(resolver.resolve_bundle(&mut conditional, ReferenceStrategy::Conditional).await.unwrap(), 0);
+        assert_eq!(conditional, bundle("2"));
+    }
+}
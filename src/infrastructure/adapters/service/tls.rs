@@ -0,0 +1,238 @@
+use std::time::Duration;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::{DateTime, Utc};
+use openssl::asn1::Asn1Time;
+use openssl::pkcs12::Pkcs12;
+use openssl::pkey::{PKey, Private};
+use openssl::x509::{X509NameRef, X509};
+use reqwest::{Certificate, Client, Identity};
+
+use crate::domain::dtos::ClientCertificateUpload;
+use crate::domain::entities::{CertificateFormat, ClientCertificate};
+use crate::utils::AppError;
+
+/// Validate an uploaded client certificate and read its subject, issuer and expiry
+///
+/// PEM keys are stored as PKCS#8, the only PEM key encoding the TLS backend accepts.
+pub fn read_client_certificate(upload: ClientCertificateUpload) -> Result<ClientCertificate, AppError> {
+    let ca_bundle = upload.ca_bundle.map(read_ca_bundle).transpose()?;
+
+    let (format, leaf, key, certificate, private_key, password) = if is_pem(&upload.certificate) {
+        let chain = X509::stack_from_pem(&upload.certificate)
+            .map_err(|e| AppError::BadRequest(format!("Certificado PEM inválido: {}", e)))?;
+        let leaf = chain
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::BadRequest("Certificado PEM não contém nenhum certificado".to_string()))?;
+
+        // The key may come in its own file or appended to the certificate chain
+        let key_pem = upload.private_key.as_deref().unwrap_or(&upload.certificate);
+        let key = PKey::private_key_from_pem(key_pem)
+            .map_err(|e| AppError::BadRequest(format!("Chave privada PEM inválida ou ausente: {}", e)))?;
+        let private_key = key
+            .private_key_to_pem_pkcs8()
+            .map_err(|e| AppError::BadRequest(format!("Falha ao converter chave privada: {}", e)))?;
+
+        let certificate = String::from_utf8(upload.certificate)
+            .map_err(|_| AppError::BadRequest("Certificado PEM inválido".to_string()))?;
+        let private_key = String::from_utf8(private_key)
+            .map_err(|_| AppError::BadRequest("Chave privada PEM inválida".to_string()))?;
+
+        (CertificateFormat::Pem, leaf, key, certificate, Some(private_key), None)
+    } else {
+        let password = upload.password.unwrap_or_default();
+        let parsed = Pkcs12::from_der(&upload.certificate)
+            .and_then(|bundle| bundle.parse2(&password))
+            .map_err(|e| AppError::BadRequest(format!("Certificado PKCS#12 inválido ou senha incorreta: {}", e)))?;
+
+        let leaf = parsed
+            .cert
+            .ok_or_else(|| AppError::BadRequest("Certificado PKCS#12 não contém certificado".to_string()))?;
+        let key = parsed
+            .pkey
+            .ok_or_else(|| AppError::BadRequest("Certificado PKCS#12 não contém chave privada".to_string()))?;
+
+        // Validate that the TLS backend opens the bundle as well, since it is what presents it
+        Identity::from_pkcs12_der(&upload.certificate, &password)
+            .map_err(|e| AppError::BadRequest(format!("Certificado PKCS#12 não suportado: {}", e)))?;
+
+        let password = (!password.is_empty()).then_some(password);
+        (CertificateFormat::Pkcs12, leaf, key, BASE64.encode(&upload.certificate), None, password)
+    };
+
+    ensure_key_matches(&leaf, &key)?;
+
+    let not_after = asn1_to_datetime(leaf.not_after())?;
+    if not_after <= Utc::now() {
+        return Err(AppError::BadRequest(format!(
+            "Certificado expirado em {}",
+            not_after.to_rfc3339()
+        )));
+    }
+
+    Ok(ClientCertificate {
+        format,
+        certificate,
+        private_key,
+        password,
+        ca_bundle,
+        subject: name_to_string(leaf.subject_name()),
+        issuer: name_to_string(leaf.issuer_name()),
+        not_after,
+    })
+}
+
+/// HTTP client presenting the client certificate, if any, and trusting its CA bundle besides the system roots
+pub fn http_client(certificate: Option<&ClientCertificate>, timeout: Duration) -> Result<Client, AppError> {
+    let mut builder = Client::builder().timeout(timeout);
+
+    if let Some(certificate) = certificate {
+        builder = builder.identity(identity(certificate)?);
+
+        if let Some(bundle) = &certificate.ca_bundle {
+            let roots = Certificate::from_pem_bundle(bundle.as_bytes())
+                .map_err(|e| AppError::BadRequest(format!("Bundle de CAs inválido: {}", e)))?;
+            for root in roots {
+                builder = builder.add_root_certificate(root);
+            }
+        }
+    }
+
+    builder
+        .build()
+        .map_err(|e| AppError::DatabaseError(format!("Failed to create HTTP client: {}", e)))
+}
+
+fn identity(certificate: &ClientCertificate) -> Result<Identity, AppError> {
+    match certificate.format {
+        CertificateFormat::Pkcs12 => {
+            let der = BASE64
+                .decode(&certificate.certificate)
+                .map_err(|_| AppError::BadRequest("Certificado PKCS#12 armazenado inválido".to_string()))?;
+            Identity::from_pkcs12_der(&der, certificate.password.as_deref().unwrap_or_default())
+        }
+        CertificateFormat::Pem => {
+            let key = certificate.private_key.as_deref().unwrap_or_default();
+            Identity::from_pkcs8_pem(certificate.certificate.as_bytes(), key.as_bytes())
+        }
+    }
+    .map_err(|e| AppError::BadRequest(format!("Certificado de cliente inválido: {}", e)))
+}
+
+fn read_ca_bundle(bundle: Vec<u8>) -> Result<String, AppError> {
+    let roots = X509::stack_from_pem(&bundle)
+        .map_err(|e| AppError::BadRequest(format!("Bundle de CAs inválido: {}", e)))?;
+    if roots.is_empty() {
+        return Err(AppError::BadRequest("Bundle de CAs não contém nenhum certificado".to_string()));
+    }
+
+    String::from_utf8(bundle).map_err(|_| AppError::BadRequest("Bundle de CAs deve estar em PEM".to_string()))
+}
+
+fn ensure_key_matches(leaf: &X509, key: &PKey<Private>) -> Result<(), AppError> {
+    let public_key = leaf
+        .public_key()
+        .map_err(|e| AppError::BadRequest(format!("Certificado inválido: {}", e)))?;
+
+    if !public_key.public_eq(key) {
+        return Err(AppError::BadRequest("A chave privada não corresponde ao certificado".to_string()));
+    }
+    Ok(())
+}
+
+fn is_pem(bytes: &[u8]) -> bool {
+    String::from_utf8_lossy(&bytes[..bytes.len().min(64)]).trim_start().starts_with("-----BEGIN")
+}
+
+/// `CN=..., O=..., C=BR`
+fn name_to_string(name: &X509NameRef) -> String {
+    name.entries()
+        .map(|entry| {
+            let field = entry.object().nid().short_name().unwrap_or("?");
+            let value = entry.data().to_string().unwrap_or_default();
+            format!("{}={}", field, value)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn asn1_to_datetime(time: &openssl::asn1::Asn1TimeRef) -> Result<DateTime<Utc>, AppError> {
+    let invalid = |e: openssl::error::ErrorStack| AppError::BadRequest(format!("Validade do certificado inválida: {}", e));
+    let epoch = Asn1Time::from_unix(0).map_err(invalid)?;
+    let diff = epoch.diff(time).map_err(invalid)?;
+
+    DateTime::from_timestamp(diff.days as i64 * 86_400 + diff.secs as i64, 0)
+        .ok_or_else(|| AppError::BadRequest("Validade do certificado inválida".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openssl::bn::BigNum;
+    use openssl::ec::{EcGroup, EcKey};
+    use openssl::hash::MessageDigest;
+    use openssl::nid::Nid;
+    use openssl::x509::X509NameBuilder;
+
+    fn key() -> PKey<Private> {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap()
+    }
+
+    fn self_signed(key: &PKey<Private>, days: u32) -> X509 {
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_text("C", "BR").unwrap();
+        name.append_entry_by_text("CN", "HOSPITAL EXEMPLO:12345678000199").unwrap();
+        let name = name.build();
+
+        let mut builder = X509::builder().unwrap();
+        builder.set_version(2).unwrap();
+        builder.set_serial_number(&BigNum::from_u32(1).unwrap().to_asn1_integer().unwrap()).unwrap();
+        builder.set_subject_name(&name).unwrap();
+        builder.set_issuer_name(&name).unwrap();
+        builder.set_pubkey(key).unwrap();
+        builder.set_not_before(&Asn1Time::days_from_now(0).unwrap()).unwrap();
+        builder.set_not_after(&Asn1Time::days_from_now(days).unwrap()).unwrap();
+        builder.sign(key, MessageDigest::sha256()).unwrap();
+        builder.build()
+    }
+
+    fn upload(certificate: Vec<u8>, private_key: Option<Vec<u8>>, password: Option<&str>) -> ClientCertificateUpload {
+        ClientCertificateUpload { certificate, private_key, password: password.map(str::to_string), ca_bundle: None }
+    }
+
+    #[test]
+    fn test_reads_pem_and_pkcs12_certificates() {
+        let key = key();
+        let cert = self_signed(&key, 10);
+
+        let pem = read_client_certificate(upload(
+            cert.to_pem().unwrap(),
+            Some(key.private_key_to_pem_pkcs8().unwrap()),
+            None,
+        ))
+        .unwrap();
+        assert_eq!(pem.format, CertificateFormat::Pem);
+        assert_eq!(pem.subject, "C=BR, CN=HOSPITAL EXEMPLO:12345678000199");
+        assert_eq!(pem.days_until_expiry(Utc::now()), 9);
+        assert!(http_client(Some(&pem), Duration::from_secs(5)).is_ok());
+
+        let p12 = Pkcs12::builder().name("a1").pkey(&key).cert(&cert).build2("senha").unwrap();
+        let der = p12.to_der().unwrap();
+        let pkcs12 = read_client_certificate(upload(der.clone(), None, Some("senha"))).unwrap();
+        assert_eq!(pkcs12.format, CertificateFormat::Pkcs12);
+        assert_eq!(pkcs12.issuer, pkcs12.subject);
+        assert_eq!(pkcs12.not_after, pem.not_after);
+
+        assert!(read_client_certificate(upload(der, None, Some("errada"))).is_err());
+    }
+
+    #[test]
+    fn test_rejects_mismatched_key() {
+        let cert = self_signed(&key(), 10);
+        let other = key().private_key_to_pem_pkcs8().unwrap();
+
+        assert!(read_client_certificate(upload(cert.to_pem().unwrap(), Some(other), None)).is_err());
+    }
+}
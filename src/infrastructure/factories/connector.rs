@@ -1,6 +1,22 @@
-use crate::utils::AppError;
-use crate::infrastructure::adapters::{MongoDBConnector, MongoDBConfig, OracleConnector};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use mongodb::bson::{doc, Bson, Document};
+use mongodb::options::FindOptions;
+use serde_json::Value;
+
+use crate::utils::{userinfo, AppError};
+use crate::infrastructure::adapters::{ApiSourceConnector, FileConnector, FixtureConnector, MongoDBConnector, MongoDBConfig, OracleConfig, OracleConnector, SqlServerConfig, SqlServerConnector};
 use crate::domain::dtos::CreateDatabaseConfigurationDto;
+use crate::domain::entities::{DatabaseConfiguration, DiagnosticCheck, DiscoveredTable, OracleConnectionSettings, SourceDiagnostics, SqlServerConnectionSettings};
+use crate::infrastructure::repositories::FileDatasetRepository;
+
+/// Listener port used when an Oracle configuration does not set one
+const DEFAULT_ORACLE_PORT: u16 = 1521;
 
 /// Enum representing different database types
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -9,6 +25,10 @@ pub enum DatabaseType {
     PostgreSQL,
     Oracle,
     MySQL,
+    SqlServer,
+    Fixture,
+    File,
+    Api,
 }
 
 /// Enum representing different client aliases
@@ -18,7 +38,11 @@ pub enum ClientAlias {
     TASY,
     TOTVS,
     Oracle,
+    SqlServer,
     Database,
+    Fixture,
+    File,
+    Api,
     Custom(String),
 }
 
@@ -29,24 +53,40 @@ impl ClientAlias {
             "TASY" => ClientAlias::TASY,
             "TOTVS" => ClientAlias::TOTVS,
             "ORACLE" => ClientAlias::Oracle,
+            "SQLSERVER" | "MSSQL" => ClientAlias::SqlServer,
             "DATABASE" => ClientAlias::Database,
+            "FIXTURE" => ClientAlias::Fixture,
+            "FILE" => ClientAlias::File,
+            "API" => ClientAlias::Api,
             _ => ClientAlias::Custom(alias.to_string()),
         }
     }
 }
 
-/// Configuration for database connection
-#[derive(Debug, Clone)]
-pub struct DatabaseConfig {
-    pub connection_string: String,
-    pub database_name: Option<String>,
-    pub max_connections: Option<u32>,
-}
-
 /// Factory for creating database connections
-pub struct ConnectorFactory;
+///
+/// Holds what connectors share with the application; cheap to clone into use cases and workers
+#[derive(Clone, Default)]
+pub struct ConnectorFactory {
+    /// Canonical directory FIXTURE configurations may read from
+    fixtures_root: Option<PathBuf>,
+    /// Application store of the spreadsheets uploaded to FILE configurations
+    file_datasets: Option<Arc<FileDatasetRepository>>,
+}
 
 impl ConnectorFactory {
+    /// Enable FIXTURE sources reading directories under `root` (already canonical)
+    pub fn with_fixtures_root(mut self, root: Option<PathBuf>) -> Self {
+        self.fixtures_root = root;
+        self
+    }
+
+    /// Read FILE sources from the application's dataset store
+    pub fn with_file_datasets(mut self, file_datasets: Arc<FileDatasetRepository>) -> Self {
+        self.file_datasets = Some(file_datasets);
+        self
+    }
+
     /// Determine database type based on client alias
     pub fn get_database_type(alias: &ClientAlias) -> DatabaseType {
         match alias {
@@ -54,251 +94,704 @@ impl ConnectorFactory {
             ClientAlias::TASY => DatabaseType::Oracle,
             ClientAlias::TOTVS => DatabaseType::Oracle,
             ClientAlias::Oracle => DatabaseType::Oracle,
+            ClientAlias::SqlServer => DatabaseType::SqlServer,
             ClientAlias::Database => DatabaseType::Oracle,
-            ClientAlias::Custom(_) => DatabaseType::MongoDB, // Default to MongoDB
+            ClientAlias::Fixture => DatabaseType::Fixture,
+            ClientAlias::File => DatabaseType::File,
+            ClientAlias::Api => DatabaseType::Api,
+            ClientAlias::Custom(name) => match name.to_uppercase().as_str() {
+                "POSTGRESQL" => DatabaseType::PostgreSQL,
+                "MYSQL" => DatabaseType::MySQL,
+                _ => DatabaseType::MongoDB, // Default to MongoDB
+            },
+        }
+    }
+
+    /// Determine database type from client alias and the configuration's structured settings
+    /// ERP aliases (MV, TASY, TOTVS, DATABASE) default to Oracle; sites running them on SQL Server
+    /// are configured with `sqlserver` settings
+    pub fn resolve_database_type(alias: &ClientAlias, sqlserver: Option<&SqlServerConnectionSettings>) -> DatabaseType {
+        match alias {
+            ClientAlias::MV | ClientAlias::TASY | ClientAlias::TOTVS | ClientAlias::Database if sqlserver.is_some() => {
+                DatabaseType::SqlServer
+            }
+            _ => Self::get_database_type(alias),
         }
     }
 
     /// Build connection string based on database type and connection parameters
     pub fn build_connection_string(data: &CreateDatabaseConfigurationDto) -> Result<String, AppError> {
-        let connection_string = match data.db_type.to_uppercase().as_str() {
-            "MV" | "TASY" | "TOTVS" | "ORACLE" | "DATABASE" => {
-                let username = data.username.as_ref().ok_or_else(|| AppError::BadRequest("Username is required for database connections".to_string()))?;
-                let password = data.password.as_ref().ok_or_else(|| AppError::BadRequest("Password is required for database connections".to_string()))?;
-                let port = data.port.ok_or_else(|| AppError::BadRequest("Port is required for database connections".to_string()))?;
-                let database = data.database.as_ref().ok_or_else(|| AppError::BadRequest("Database name is required for database connections".to_string()))?;
-                format!(
-                    "oracle://{}:{}@{}:{}/{}",
-                    username, password, data.host, port, database
-                )
-            }
-            "MONGODB" => {
-                let username = data.username.as_ref().ok_or_else(|| AppError::BadRequest("Username is required for database connections".to_string()))?;
-                let password = data.password.as_ref().ok_or_else(|| AppError::BadRequest("Password is required for database connections".to_string()))?;
-                let port = data.port.ok_or_else(|| AppError::BadRequest("Port is required for database connections".to_string()))?;
-                let database = data.database.as_ref().ok_or_else(|| AppError::BadRequest("Database name is required for database connections".to_string()))?;
-                format!(
-                    "mongodb://{}:{}@{}:{}/{}",
-                    username, password, data.host, port, database
-                )
-            }
-            "POSTGRESQL" => {
-                let username = data.username.as_ref().ok_or_else(|| AppError::BadRequest("Username is required for database connections".to_string()))?;
-                let password = data.password.as_ref().ok_or_else(|| AppError::BadRequest("Password is required for database connections".to_string()))?;
-                let port = data.port.ok_or_else(|| AppError::BadRequest("Port is required for database connections".to_string()))?;
-                let database = data.database.as_ref().ok_or_else(|| AppError::BadRequest("Database name is required for database connections".to_string()))?;
-                format!(
-                    "postgresql://{}:{}@{}:{}/{}",
-                    username, password, data.host, port, database
-                )
-            }
-            "MYSQL" => {
-                let username = data.username.as_ref().ok_or_else(|| AppError::BadRequest("Username is required for database connections".to_string()))?;
-                let password = data.password.as_ref().ok_or_else(|| AppError::BadRequest("Password is required for database connections".to_string()))?;
-                let port = data.port.ok_or_else(|| AppError::BadRequest("Port is required for database connections".to_string()))?;
-                let database = data.database.as_ref().ok_or_else(|| AppError::BadRequest("Database name is required for database connections".to_string()))?;
-                format!(
-                    "mysql://{}:{}@{}:{}/{}",
-                    username, password, data.host, port, database
-                )
+        Self::connection_string_for(
+            &data.db_type,
+            data.sqlserver.as_ref(),
+            &data.host,
+            data.port,
+            data.database.as_deref(),
+            data.username.as_deref(),
+            data.password.as_deref(),
+        )
+    }
+
+    /// Build connection string from the individual connection parameters
+    fn connection_string_for(
+        db_type: &str,
+        sqlserver: Option<&SqlServerConnectionSettings>,
+        host: &str,
+        port: Option<i32>,
+        database: Option<&str>,
+        username: Option<&str>,
+        password: Option<&str>,
+    ) -> Result<String, AppError> {
+        let scheme = match db_type.to_uppercase().as_str() {
+            "MV" | "TASY" | "TOTVS" | "ORACLE" | "DATABASE" | "SQLSERVER" | "MSSQL" => {
+                match Self::resolve_database_type(&ClientAlias::from_str(db_type), sqlserver) {
+                    DatabaseType::SqlServer => "sqlserver",
+                    _ => "oracle",
+                }
             }
+            "MONGODB" => "mongodb",
+            "POSTGRESQL" => "postgresql",
+            "MYSQL" => "mysql",
             _ => {
                 return Err(AppError::BadRequest(format!(
                     "Unsupported database type: {}",
-                    data.db_type
+                    db_type
                 )));
             }
         };
-        Ok(connection_string)
+
+        let username = username.ok_or_else(|| AppError::BadRequest("Username is required for database connections".to_string()))?;
+        let password = password.ok_or_else(|| AppError::BadRequest("Password is required for database connections".to_string()))?;
+        let port = port.ok_or_else(|| AppError::BadRequest("Port is required for database connections".to_string()))?;
+        let database = database.ok_or_else(|| AppError::BadRequest("Database name is required for database connections".to_string()))?;
+
+        Ok(format!(
+            "{}://{}:{}@{}:{}/{}",
+            scheme,
+            userinfo::encode(username),
+            userinfo::encode(password),
+            host,
+            port,
+            database
+        ))
+    }
+
+    /// Oracle is opened from the configuration fields so SID, TNS aliases, descriptors and
+    /// wallets do not have to fit in a connection URL
+    fn oracle_config_for(
+        host: &str,
+        port: Option<i32>,
+        database: Option<&str>,
+        username: Option<&str>,
+        password: Option<&str>,
+        settings: Option<OracleConnectionSettings>,
+    ) -> Result<OracleConfig, AppError> {
+        let settings = settings.unwrap_or_default();
+
+        let (username, password) = if settings.external_auth {
+            (String::new(), String::new())
+        } else {
+            (
+                username.ok_or_else(|| AppError::BadRequest("Username is required for database connections".to_string()))?.to_string(),
+                password.ok_or_else(|| AppError::BadRequest("Password is required for database connections".to_string()))?.to_string(),
+            )
+        };
+
+        let port = match port {
+            Some(port) => u16::try_from(port).map_err(|_| AppError::BadRequest("Invalid port number".to_string()))?,
+            None => DEFAULT_ORACLE_PORT,
+        };
+
+        Ok(OracleConfig {
+            host: host.to_string(),
+            port,
+            service_name: database.unwrap_or_default().to_string(),
+            username,
+            password,
+            max_connections: None,
+            settings,
+        })
+    }
+
+    fn is_oracle(alias: &str, sqlserver: Option<&SqlServerConnectionSettings>) -> bool {
+        Self::resolve_database_type(&ClientAlias::from_str(alias), sqlserver) == DatabaseType::Oracle
+    }
+
+    /// Create a source connector for a stored database configuration
+    pub async fn create_source(&self, config: &DatabaseConfiguration) -> Result<Box<dyn SourceConnector>, AppError> {
+        if Self::is_fixture(&config.db_type) {
+            let connector = self.open_fixture(&config.host, config.database.as_deref()).await?;
+            return Ok(Box::new(connector));
+        }
+
+        if Self::is_file(&config.db_type) {
+            let configuration_id = config.id.map(|id| id.to_hex()).unwrap_or_default();
+            return Ok(Box::new(self.open_file(&configuration_id)?));
+        }
+
+        if Self::is_api(&config.db_type) {
+            let connector = ApiSourceConnector::new(
+                &config.host,
+                config.auth_type.clone(),
+                config.credentials.clone(),
+                config.api_source.clone().unwrap_or_default(),
+            ).await?;
+            return Ok(Box::new(connector));
+        }
+
+        if Self::is_oracle(&config.db_type, config.sqlserver.as_ref()) {
+            let oracle_config = Self::oracle_config_for(
+                &config.host,
+                config.port,
+                config.database.as_deref(),
+                config.username.as_deref(),
+                config.password.as_deref(),
+                config.oracle.clone(),
+            )?;
+            return Ok(Box::new(OracleConnector::from_config(oracle_config).await?));
+        }
+
+        let connection_string = Self::connection_string_for(
+            &config.db_type,
+            config.sqlserver.as_ref(),
+            &config.host,
+            config.port,
+            config.database.as_deref(),
+            config.username.as_deref(),
+            config.password.as_deref(),
+        )?;
+
+        Self::open_source(
+            &config.db_type,
+            &connection_string,
+            config.database.clone(),
+            config.sqlserver.as_ref(),
+        ).await
+    }
+
+    /// Create a source connector for a configuration that has not been saved yet
+    pub async fn create_source_from_dto(&self, data: &CreateDatabaseConfigurationDto) -> Result<Box<dyn SourceConnector>, AppError> {
+        if Self::is_fixture(&data.db_type) {
+            let connector = self.open_fixture(&data.host, data.database.as_deref()).await?;
+            return Ok(Box::new(connector));
+        }
+
+        if Self::is_file(&data.db_type) {
+            // Nothing is uploaded before the configuration is saved; this only checks the dataset store
+            return Ok(Box::new(self.open_file("")?));
+        }
+
+        if Self::is_api(&data.db_type) {
+            let connector = ApiSourceConnector::new(
+                &data.host,
+                data.auth_type.clone(),
+                data.credentials.clone(),
+                data.api_source.clone().unwrap_or_default(),
+            ).await?;
+            return Ok(Box::new(connector));
+        }
+
+        if Self::is_oracle(&data.db_type, data.sqlserver.as_ref()) {
+            let oracle_config = Self::oracle_config_for(
+                &data.host,
+                data.port,
+                data.database.as_deref(),
+                data.username.as_deref(),
+                data.password.as_deref(),
+                data.oracle.clone(),
+            )?;
+            return Ok(Box::new(OracleConnector::from_config(oracle_config).await?));
+        }
+
+        let connection_string = Self::build_connection_string(data)?;
+
+        Self::open_source(
+            &data.db_type,
+            &connection_string,
+            data.database.clone(),
+            data.sqlserver.as_ref(),
+        ).await
     }
 
-    /// Create a database connection based on alias
-    pub async fn create_connection(
+    /// Fixture sources are addressed by dataset or directory instead of a connection string
+    fn is_fixture(alias: &str) -> bool {
+        ClientAlias::from_str(alias) == ClientAlias::Fixture
+    }
+
+    /// Fixtures are test data: outside test builds they need a configured fixtures root
+    async fn open_fixture(&self, host: &str, dataset: Option<&str>) -> Result<FixtureConnector, AppError> {
+        if self.fixtures_root.is_none() && !cfg!(test) {
+            return Err(AppError::BadRequest("Fontes FIXTURE estão desabilitadas (FIXTURES_DIR não configurado)".to_string()));
+        }
+
+        FixtureConnector::open(host, dataset, self.fixtures_root.as_deref()).await
+    }
+
+    /// File sources read uploaded spreadsheets stored alongside the application data
+    fn is_file(alias: &str) -> bool {
+        ClientAlias::from_str(alias) == ClientAlias::File
+    }
+
+    fn open_file(&self, configuration_id: &str) -> Result<FileConnector, AppError> {
+        let repository = self.file_datasets.clone()
+            .ok_or_else(|| AppError::BadRequest("Fontes FILE indisponíveis: repositório de arquivos não configurado".to_string()))?;
+
+        Ok(FileConnector::new(repository, configuration_id))
+    }
+
+    /// API sources pull records over HTTP using the configuration's `api_source` settings
+    fn is_api(alias: &str) -> bool {
+        ClientAlias::from_str(alias) == ClientAlias::Api
+    }
+
+    /// Open the connector matching the alias and configured engine
+    async fn open_source(
         alias: &str,
-        config: DatabaseConfig,
-    ) -> Result<Box<dyn DatabaseConnection>, AppError> {
+        connection_string: &str,
+        database_name: Option<String>,
+        sqlserver: Option<&SqlServerConnectionSettings>,
+    ) -> Result<Box<dyn SourceConnector>, AppError> {
         let client_alias = ClientAlias::from_str(alias);
-        let db_type = Self::get_database_type(&client_alias);
 
-        match db_type {
-            DatabaseType::MongoDB => {
-                let connection = MongoDBConnection::new(config).await?;
-                Ok(Box::new(connection))
-            }
+        match Self::resolve_database_type(&client_alias, sqlserver) {
             DatabaseType::Oracle => {
-                let connection = OracleConnection::new(config).await?;
-                Ok(Box::new(connection))
+                let connector = OracleConnector::new(connection_string).await?;
+                Ok(Box::new(connector))
             }
-            DatabaseType::PostgreSQL => {
-                let connection = PostgreSQLConnection::new(config).await?;
-                Ok(Box::new(connection))
+            DatabaseType::SqlServer => {
+                let sqlserver_config = SqlServerConfig::from_connection_string(connection_string)?.with_settings(sqlserver);
+                let connector = SqlServerConnector::from_config(sqlserver_config).await?;
+                Ok(Box::new(connector))
             }
-            DatabaseType::MySQL => {
-                let connection = MySQLConnection::new(config).await?;
-                Ok(Box::new(connection))
+            DatabaseType::MongoDB => {
+                let mongo_config = MongoDBConfig::new(connection_string.to_string())
+                    .with_database_name(database_name.unwrap_or_else(|| "interhealth".to_string()));
+                let connector = MongoDBConnector::from_config(mongo_config).await?;
+                Ok(Box::new(connector))
             }
+            DatabaseType::Fixture | DatabaseType::File | DatabaseType::Api => Err(AppError::BadRequest(
+                "Fixture, file and API sources are opened from their database configuration.".to_string()
+            )),
+            DatabaseType::PostgreSQL => Err(AppError::BadRequest(
+                "PostgreSQL connections are not yet implemented.".to_string()
+            )),
+            DatabaseType::MySQL => Err(AppError::BadRequest(
+                "MySQL connections are not yet implemented.".to_string()
+            )),
         }
     }
+}
 
-    /// Create connection by database type directly
-    pub async fn create_by_type(
-        db_type: DatabaseType,
-        config: DatabaseConfig,
-    ) -> Result<Box<dyn DatabaseConnection>, AppError> {
-        match db_type {
-            DatabaseType::MongoDB => {
-                let connection = MongoDBConnection::new(config).await?;
-                Ok(Box::new(connection))
+/// Flatten a fetched record into the string map consumed by the Replacer
+pub fn record_to_string_map(record: &Value) -> HashMap<String, String> {
+    let mut data = HashMap::new();
+
+    if let Some(object) = record.as_object() {
+        for (key, value) in object {
+            let text = match value {
+                Value::Null => String::new(),
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            data.insert(key.to_lowercase(), text);
+        }
+    }
+
+    data
+}
+
+/// Source of records for mapping, preview and synchronization
+/// Every external database plugs in through this trait so callers never depend on a concrete driver
+#[async_trait]
+pub trait SourceConnector: Send + Sync {
+    fn get_type(&self) -> DatabaseType;
+
+    /// Check that the source is reachable with the configured credentials
+    async fn test_connection(&self) -> Result<bool, AppError>;
+
+    /// List the columns of a table (id, name, dataType, isNullable, ...)
+    async fn introspect(&self, table_name: &str) -> Result<Vec<Value>, AppError>;
+
+    /// Count the records available in a table
+    async fn count(&self, table_name: &str) -> Result<u64, AppError>;
+
+    /// Fetch one page of records (page is 0-indexed)
+    async fn fetch_page(&self, table_name: &str, page: u64, page_size: u64) -> Result<Vec<Value>, AppError>;
+
+    /// Fetch a single record by the value of a key column
+    async fn fetch_by_key(&self, table_name: &str, key_column: &str, key_value: &str) -> Result<Option<Value>, AppError>;
+
+    /// Fetch the first record of a table as column -> value strings
+    async fn fetch_first_row(&self, table_name: &str) -> Result<HashMap<String, String>, AppError> {
+        let records = self.fetch_page(table_name, 0, 1).await?;
+        records
+            .first()
+            .map(record_to_string_map)
+            .ok_or_else(|| AppError::NotFound(format!("No data found in table {}", table_name)))
+    }
+
+    /// List the tables and views accessible through the source, with their columns and keys
+    /// `owner` narrows the listing to one schema; sources without a catalog reject discovery
+    async fn discover_schema(&self, _owner: Option<&str>) -> Result<Vec<DiscoveredTable>, AppError> {
+        Err(AppError::BadRequest(format!(
+            "Descoberta de schema não suportada para conectores {:?}",
+            self.get_type()
+        )))
+    }
+
+    /// Test-connection checklist for the source and the objects the company's views read
+    /// The default probes connectivity and reads one row of each object
+    async fn diagnose(&self, objects: &[String]) -> Result<SourceDiagnostics, AppError> {
+        let mut diagnostics = SourceDiagnostics::default();
+
+        let started = Instant::now();
+        let connected = self.test_connection().await;
+        let latency_ms = started.elapsed().as_millis() as u64;
+
+        match connected {
+            Ok(true) => {
+                diagnostics.latency_ms = Some(latency_ms);
+                diagnostics.checks.push(
+                    DiagnosticCheck::passed("connectivity", "Conexão estabelecida").with_latency(latency_ms),
+                );
             }
-            DatabaseType::Oracle => {
-                let connection = OracleConnection::new(config).await?;
-                Ok(Box::new(connection))
+            Ok(false) => {
+                diagnostics.checks.push(DiagnosticCheck::failed("connectivity", "Conexão não estabelecida"));
+                return Ok(diagnostics);
             }
-            DatabaseType::PostgreSQL => {
-                let connection = PostgreSQLConnection::new(config).await?;
-                Ok(Box::new(connection))
+            Err(e) => {
+                diagnostics.checks.push(DiagnosticCheck::failed("connectivity", e.to_string()));
+                return Ok(diagnostics);
             }
-            DatabaseType::MySQL => {
-                let connection = MySQLConnection::new(config).await?;
-                Ok(Box::new(connection))
+        }
+
+        for object in objects {
+            match self.fetch_page(object, 0, 1).await {
+                Ok(rows) => {
+                    diagnostics.checks.push(DiagnosticCheck::passed("selectPrivilege", "Leitura permitida").for_object(object));
+                    diagnostics.checks.push(if rows.is_empty() {
+                        DiagnosticCheck::warning("rowVisibility", "Nenhuma linha visível").for_object(object)
+                    } else {
+                        DiagnosticCheck::passed("rowVisibility", "Linhas visíveis").for_object(object)
+                    });
+                }
+                Err(e) => {
+                    diagnostics.checks.push(DiagnosticCheck::failed("selectPrivilege", e.to_string()).for_object(object));
+                    diagnostics.checks.push(DiagnosticCheck::skipped("rowVisibility", "Sem acesso ao objeto").for_object(object));
+                }
             }
         }
+
+        Ok(diagnostics)
     }
-}
 
-/// Trait for database connections
-pub trait DatabaseConnection: Send + Sync {
-    fn get_type(&self) -> DatabaseType;
-    fn is_connected(&self) -> bool;
-    fn get_connection_string(&self) -> &str;
+    /// Map a source data type to the simplified types used by DatabaseColumn
+    fn simplify_data_type(&self, data_type: &str) -> &'static str;
+
+    /// Release the underlying connection
+    async fn close(&mut self) -> Result<(), AppError>;
 }
 
-/// MongoDB connection implementation
-struct MongoDBConnection {
-    connector: MongoDBConnector,
-    config: DatabaseConfig,
+#[async_trait]
+impl SourceConnector for OracleConnector {
+    fn get_type(&self) -> DatabaseType {
+        DatabaseType::Oracle
+    }
+
+    async fn test_connection(&self) -> Result<bool, AppError> {
+        OracleConnector::test_connection(self).await
+    }
+
+    async fn introspect(&self, table_name: &str) -> Result<Vec<Value>, AppError> {
+        self.get_table_columns(table_name).await
+    }
+
+    async fn count(&self, table_name: &str) -> Result<u64, AppError> {
+        self.count_records(table_name).await
+    }
+
+    async fn fetch_page(&self, table_name: &str, page: u64, page_size: u64) -> Result<Vec<Value>, AppError> {
+        self.fetch_page_data(table_name, page, page_size).await
+    }
+
+    async fn fetch_by_key(&self, table_name: &str, key_column: &str, key_value: &str) -> Result<Option<Value>, AppError> {
+        OracleConnector::fetch_by_key(self, table_name, key_column, key_value).await
+    }
+
+    async fn fetch_first_row(&self, table_name: &str) -> Result<HashMap<String, String>, AppError> {
+        OracleConnector::fetch_first_row(self, table_name).await
+    }
+
+    async fn discover_schema(&self, owner: Option<&str>) -> Result<Vec<DiscoveredTable>, AppError> {
+        OracleConnector::discover_schema(self, owner).await
+    }
+
+    async fn diagnose(&self, objects: &[String]) -> Result<SourceDiagnostics, AppError> {
+        OracleConnector::diagnose(self, objects).await
+    }
+
+    fn simplify_data_type(&self, data_type: &str) -> &'static str {
+        OracleConnector::simplify_data_type(data_type)
+    }
+
+    async fn close(&mut self) -> Result<(), AppError> {
+        OracleConnector::close(self).await
+    }
 }
 
-impl MongoDBConnection {
-    async fn new(config: DatabaseConfig) -> Result<Self, AppError> {
-        let mongo_config = MongoDBConfig::new(config.connection_string.clone())
-            .with_database_name(config.database_name.clone().unwrap_or_else(|| "interhealth".to_string()));
-        
-        let connector = MongoDBConnector::from_config(mongo_config).await?;
-        
-        Ok(Self {
-            connector,
-            config,
-        })
+#[async_trait]
+impl SourceConnector for SqlServerConnector {
+    fn get_type(&self) -> DatabaseType {
+        DatabaseType::SqlServer
+    }
+
+    async fn test_connection(&self) -> Result<bool, AppError> {
+        SqlServerConnector::test_connection(self).await
+    }
+
+    async fn introspect(&self, table_name: &str) -> Result<Vec<Value>, AppError> {
+        self.get_table_columns(table_name).await
+    }
+
+    async fn count(&self, table_name: &str) -> Result<u64, AppError> {
+        self.count_records(table_name).await
+    }
+
+    async fn fetch_page(&self, table_name: &str, page: u64, page_size: u64) -> Result<Vec<Value>, AppError> {
+        self.fetch_page_data(table_name, page, page_size).await
+    }
+
+    async fn fetch_by_key(&self, table_name: &str, key_column: &str, key_value: &str) -> Result<Option<Value>, AppError> {
+        SqlServerConnector::fetch_by_key(self, table_name, key_column, key_value).await
+    }
+
+    async fn fetch_first_row(&self, table_name: &str) -> Result<HashMap<String, String>, AppError> {
+        SqlServerConnector::fetch_first_row(self, table_name).await
+    }
+
+    async fn discover_schema(&self, owner: Option<&str>) -> Result<Vec<DiscoveredTable>, AppError> {
+        SqlServerConnector::discover_schema(self, owner).await
     }
-    
-    pub fn connector(&self) -> &MongoDBConnector {
-        &self.connector
+
+    fn simplify_data_type(&self, data_type: &str) -> &'static str {
+        SqlServerConnector::simplify_data_type(data_type)
+    }
+
+    async fn close(&mut self) -> Result<(), AppError> {
+        SqlServerConnector::close(self).await
     }
 }
 
-impl DatabaseConnection for MongoDBConnection {
+#[async_trait]
+impl SourceConnector for MongoDBConnector {
     fn get_type(&self) -> DatabaseType {
         DatabaseType::MongoDB
     }
 
-    fn is_connected(&self) -> bool {
-        // MongoDB connector is always "connected" once created successfully
-        true
+    async fn test_connection(&self) -> Result<bool, AppError> {
+        MongoDBConnector::test_connection(self).await
     }
 
-    fn get_connection_string(&self) -> &str {
-        &self.config.connection_string
+    async fn introspect(&self, table_name: &str) -> Result<Vec<Value>, AppError> {
+        // Collections have no fixed schema, so columns are inferred from a sample document
+        let sample = self.database()
+            .collection::<Document>(table_name)
+            .find_one(None, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Failed to read collection: {}", e)))?;
+
+        let columns = sample
+            .map(|document| {
+                document.iter().enumerate().map(|(idx, (name, value))| {
+                    serde_json::json!({
+                        "id": (idx + 1).to_string(),
+                        "name": name,
+                        "dataType": format!("{:?}", value.element_type()),
+                        "isNullable": true
+                    })
+                }).collect()
+            })
+            .unwrap_or_default();
+
+        Ok(columns)
     }
-}
 
-/// Oracle connection implementation
-struct OracleConnection {
-    connector: OracleConnector,
-    config: DatabaseConfig,
-}
+    async fn count(&self, table_name: &str) -> Result<u64, AppError> {
+        self.database()
+            .collection::<Document>(table_name)
+            .count_documents(None, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Failed to count documents: {}", e)))
+    }
 
-impl OracleConnection {
-    async fn new(config: DatabaseConfig) -> Result<Self, AppError> {
-        let connector = OracleConnector::new(&config.connection_string).await?;
-        
-        Ok(Self {
-            connector,
-            config,
-        })
+    async fn fetch_page(&self, table_name: &str, page: u64, page_size: u64) -> Result<Vec<Value>, AppError> {
+        let options = FindOptions::builder()
+            .skip(page * page_size)
+            .limit(page_size as i64)
+            .build();
+
+        let cursor = self.database()
+            .collection::<Document>(table_name)
+            .find(None, options)
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Failed to query collection: {}", e)))?;
+
+        let documents: Vec<Document> = cursor
+            .try_collect()
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Failed to fetch documents: {}", e)))?;
+
+        Ok(documents
+            .into_iter()
+            .map(|document| Bson::Document(document).into_relaxed_extjson())
+            .collect())
     }
-    
-    pub fn connector(&self) -> &OracleConnector {
-        &self.connector
+
+    async fn fetch_by_key(&self, table_name: &str, key_column: &str, key_value: &str) -> Result<Option<Value>, AppError> {
+        let document = self.database()
+            .collection::<Document>(table_name)
+            .find_one(doc! { key_column: key_value }, None)
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Failed to query collection: {}", e)))?;
+
+        Ok(document.map(|document| Bson::Document(document).into_relaxed_extjson()))
+    }
+
+    fn simplify_data_type(&self, data_type: &str) -> &'static str {
+        match data_type {
+            "Int32" | "Int64" | "Double" | "Decimal128" => "integer",
+            "DateTime" | "Timestamp" => "timestamp",
+            "Boolean" => "boolean",
+            _ => "varchar",
+        }
+    }
+
+    async fn close(&mut self) -> Result<(), AppError> {
+        // The driver releases pooled connections when the client is dropped
+        Ok(())
     }
 }
 
-impl DatabaseConnection for OracleConnection {
+#[async_trait]
+impl SourceConnector for FixtureConnector {
     fn get_type(&self) -> DatabaseType {
-        DatabaseType::Oracle
+        DatabaseType::Fixture
     }
 
-    fn is_connected(&self) -> bool {
-        self.connector.is_connected()
+    async fn test_connection(&self) -> Result<bool, AppError> {
+        Ok(true)
     }
 
-    fn get_connection_string(&self) -> &str {
-        &self.config.connection_string
+    async fn introspect(&self, table_name: &str) -> Result<Vec<Value>, AppError> {
+        self.get_table_columns(table_name)
     }
-}
 
-/// PostgreSQL connection implementation
-struct PostgreSQLConnection {
-    config: DatabaseConfig,
-    connected: bool,
-}
+    async fn count(&self, table_name: &str) -> Result<u64, AppError> {
+        self.count_records(table_name)
+    }
 
-impl PostgreSQLConnection {
-    async fn new(_config: DatabaseConfig) -> Result<Self, AppError> {
-        Err(AppError::BadRequest(
-            "PostgreSQL connections are not yet implemented. Please use MongoDB.".to_string()
-        ))
+    async fn fetch_page(&self, table_name: &str, page: u64, page_size: u64) -> Result<Vec<Value>, AppError> {
+        self.fetch_page_data(table_name, page, page_size)
+    }
+
+    async fn fetch_by_key(&self, table_name: &str, key_column: &str, key_value: &str) -> Result<Option<Value>, AppError> {
+        FixtureConnector::fetch_by_key(self, table_name, key_column, key_value)
+    }
+
+    async fn discover_schema(&self, _owner: Option<&str>) -> Result<Vec<DiscoveredTable>, AppError> {
+        FixtureConnector::discover_schema(self)
+    }
+
+    fn simplify_data_type(&self, data_type: &str) -> &'static str {
+        FixtureConnector::simplify_data_type(data_type)
+    }
+
+    async fn close(&mut self) -> Result<(), AppError> {
+        Ok(())
     }
 }
 
-impl DatabaseConnection for PostgreSQLConnection {
+#[async_trait]
+impl SourceConnector for FileConnector {
     fn get_type(&self) -> DatabaseType {
-        DatabaseType::PostgreSQL
+        DatabaseType::File
     }
 
-    fn is_connected(&self) -> bool {
-        self.connected
+    async fn test_connection(&self) -> Result<bool, AppError> {
+        FileConnector::test_connection(self).await
     }
 
-    fn get_connection_string(&self) -> &str {
-        &self.config.connection_string
+    async fn introspect(&self, table_name: &str) -> Result<Vec<Value>, AppError> {
+        self.get_table_columns(table_name).await
     }
-}
 
-/// MySQL connection implementation
-struct MySQLConnection {
-    config: DatabaseConfig,
-    connected: bool,
-}
+    async fn count(&self, table_name: &str) -> Result<u64, AppError> {
+        self.count_records(table_name).await
+    }
 
-impl MySQLConnection {
-    async fn new(_config: DatabaseConfig) -> Result<Self, AppError> {
-        Err(AppError::BadRequest(
-            "MySQL connections are not yet implemented. Please use MongoDB.".to_string()
-        ))
+    async fn fetch_page(&self, table_name: &str, page: u64, page_size: u64) -> Result<Vec<Value>, AppError> {
+        self.fetch_page_data(table_name, page, page_size).await
+    }
+
+    async fn fetch_by_key(&self, table_name: &str, key_column: &str, key_value: &str) -> Result<Option<Value>, AppError> {
+        FileConnector::fetch_by_key(self, table_name, key_column, key_value).await
+    }
+
+    fn simplify_data_type(&self, data_type: &str) -> &'static str {
+        FileConnector::simplify_data_type(data_type)
+    }
+
+    async fn close(&mut self) -> Result<(), AppError> {
+        Ok(())
     }
 }
 
-impl DatabaseConnection for MySQLConnection {
+#[async_trait]
+impl SourceConnector for ApiSourceConnector {
     fn get_type(&self) -> DatabaseType {
-        DatabaseType::MySQL
+        DatabaseType::Api
+    }
+
+    async fn test_connection(&self) -> Result<bool, AppError> {
+        ApiSourceConnector::test_connection(self).await
+    }
+
+    async fn introspect(&self, table_name: &str) -> Result<Vec<Value>, AppError> {
+        self.get_table_columns(table_name).await
     }
 
-    fn is_connected(&self) -> bool {
-        self.connected
+    async fn count(&self, table_name: &str) -> Result<u64, AppError> {
+        self.count_records(table_name).await
     }
 
-    fn get_connection_string(&self) -> &str {
-        &self.config.connection_string
+    async fn fetch_page(&self, table_name: &str, page: u64, page_size: u64) -> Result<Vec<Value>, AppError> {
+        self.fetch_page_data(table_name, page, page_size).await
     }
-}
\ No newline at end of file
+
+    async fn fetch_by_key(&self, table_name: &str, key_column: &str, key_value: &str) -> Result<Option<Value>, AppError> {
+        ApiSourceConnector::fetch_by_key(self, table_name, key_column, key_value).await
+    }
+
+    fn simplify_data_type(&self, data_type: &str) -> &'static str {
+        ApiSourceConnector::simplify_data_type(data_type)
+    }
+
+    async fn close(&mut self) -> Result<(), AppError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_engine_follows_sqlserver_settings() {
+        let sqlserver = SqlServerConnectionSettings::default();
+
+        assert_eq!(ConnectorFactory::resolve_database_type(&ClientAlias::TOTVS, None), DatabaseType::Oracle);
+        assert_eq!(ConnectorFactory::resolve_database_type(&ClientAlias::TOTVS, Some(&sqlserver)), DatabaseType::SqlServer);
+        assert_eq!(ConnectorFactory::resolve_database_type(&ClientAlias::SqlServer, None), DatabaseType::SqlServer);
+        assert_eq!(ConnectorFactory::resolve_database_type(&ClientAlias::Fixture, Some(&sqlserver)), DatabaseType::Fixture);
+    }
+}
@@ -19,6 +19,8 @@ pub struct CreateCompanyDto {
     pub state: Option<String>,
     pub zipcode: Option<String>,
     pub country: Option<String>,
+    pub timezone: Option<String>,
+    pub hl7_facility: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -35,6 +37,8 @@ pub struct UpdateCompanyDto {
     pub zipcode: Option<String>,
     pub country: Option<String>,
     pub status: Option<bool>,
+    pub timezone: Option<String>,
+    pub hl7_facility: Option<String>,
 }
 
 #[derive(Clone)]
@@ -71,6 +75,8 @@ impl CompanyRepository {
             zipcode: company_data.zipcode,
             country: company_data.country,
             status: true,
+            timezone: company_data.timezone,
+            hl7_facility: company_data.hl7_facility,
             created_at: now,
             updated_at: now,
         };
@@ -107,6 +113,8 @@ impl CompanyRepository {
             zipcode: company_data.zipcode,
             country: company_data.country,
             status: true,
+            timezone: company_data.timezone,
+            hl7_facility: company_data.hl7_facility,
             created_at: now,
             updated_at: now,
         };
@@ -151,6 +159,14 @@ impl CompanyRepository {
         Ok(company)
     }
 
+    /// Active company receiving HL7 messages addressed to `facility`
+    pub async fn find_by_hl7_facility(&self, facility: &str) -> Result<Option<Company>, AppError> {
+        let filter = doc! { "hl7_facility": facility, "status": true };
+        let company = self.collection.find_one(filter, None).await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(company)
+    }
+
     pub async fn update(&self, id: &str, company_data: UpdateCompanyDto) -> Result<Company, AppError> {
         let object_id = ObjectId::parse_str(id)
             .map_err(|_| AppError::BadRequest("Invalid ID format".to_string()))?;
@@ -193,6 +209,12 @@ impl CompanyRepository {
         if let Some(status) = company_data.status {
             update_doc.insert("status", status);
         }
+        if let Some(timezone) = company_data.timezone {
+            update_doc.insert("timezone", timezone);
+        }
+        if let Some(hl7_facility) = company_data.hl7_facility {
+            update_doc.insert("hl7_facility", hl7_facility);
+        }
         
         update_doc.insert("updated_at", Utc::now());
         
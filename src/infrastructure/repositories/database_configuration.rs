@@ -2,48 +2,59 @@ use mongodb::{Database, Collection, bson::{doc, oid::ObjectId, Bson, Document}};
 use chrono::Utc;
 use std::sync::Arc;
 
+use crate::core::SecretBox;
+use crate::domain::dtos::{CreateDatabaseConfigurationDto, UpdateDatabaseConfigurationDto};
 use crate::domain::entities::DatabaseConfiguration;
-use crate::utils::AppError;
+use crate::utils::{redact, AppError};
+
+/// Fields sealed with the SecretBox before they reach MongoDB
+const SECRET_FIELDS: [&str; 2] = ["password", "credentials"];
 
 #[derive(Clone)]
 pub struct DatabaseConfigurationRepository {
     collection: Collection<DatabaseConfiguration>,
+    secrets: Arc<SecretBox>,
 }
 
 impl DatabaseConfigurationRepository {
     pub fn new(db: Database) -> Self {
         Self {
             collection: db.collection("database_configurations"),
+            secrets: Arc::new(SecretBox::disabled()),
         }
     }
-    
-    pub fn arc(db: Database) -> Arc<Self> {
-        Arc::new(Self::new(db))
+
+    /// Encrypt password and credentials at rest
+    pub fn with_secrets(mut self, secrets: Arc<SecretBox>) -> Self {
+        self.secrets = secrets;
+        self
+    }
+
+    fn seal(&self, value: Option<String>) -> Result<Option<String>, AppError> {
+        value.map(|v| self.secrets.encrypt(&v)).transpose()
+    }
+
+    /// Configuration as stored, with its secrets sealed
+    fn sealed(&self, config: &DatabaseConfiguration) -> Result<DatabaseConfiguration, AppError> {
+        Ok(DatabaseConfiguration {
+            password: self.seal(config.password.clone())?,
+            credentials: self.seal(config.credentials.clone())?,
+            ..config.clone()
+        })
+    }
+
+    fn reveal(&self, mut config: DatabaseConfiguration) -> Result<DatabaseConfiguration, AppError> {
+        config.password = config.password.map(|v| self.secrets.decrypt(&v)).transpose()?;
+        config.credentials = config.credentials.map(|v| self.secrets.decrypt(&v)).transpose()?;
+        Ok(config)
     }
 }
 
 impl DatabaseConfigurationRepository {
-    pub async fn create(&self, name: String, db_type: String, version: Option<String>, host: String, port: Option<i32>, database: Option<String>, username: Option<String>, password: Option<String>, auth_type: Option<String>, credentials: Option<String>, company_id: String) -> Result<DatabaseConfiguration, AppError> {
-        let now = Utc::now();
-        
-        let config = DatabaseConfiguration {
-            id: None,
-            name,
-            db_type,
-            version,
-            host,
-            port,
-            database,
-            username,
-            password,
-            auth_type,
-            credentials,
-            company_id,
-            created_at: now,
-            updated_at: now,
-        };
+    pub async fn create(&self, data: CreateDatabaseConfigurationDto) -> Result<DatabaseConfiguration, AppError> {
+        let config = Self::new_configuration(None, data);
 
-        let result = self.collection.insert_one(&config, None).await
+        let result = self.collection.insert_one(&self.sealed(&config)?, None).await
             .map_err(|e| AppError::Database(e.to_string()))?;
         
         let mut created_config = config;
@@ -51,45 +62,14 @@ impl DatabaseConfigurationRepository {
         Ok(created_config)
     }
 
-    pub async fn create_with_id(
-        &self,
-        id: &str,
-        name: String,
-        db_type: String,
-        version: Option<String>,
-        host: String,
-        port: Option<i32>,
-        database: Option<String>,
-        username: Option<String>,
-        password: Option<String>,
-        auth_type: Option<String>,
-        credentials: Option<String>,
-        company_id: String,
-    ) -> Result<DatabaseConfiguration, AppError> {
+    pub async fn create_with_id(&self, id: &str, data: CreateDatabaseConfigurationDto) -> Result<DatabaseConfiguration, AppError> {
         let object_id = ObjectId::parse_str(id)
             .map_err(|_| AppError::BadRequest("Invalid ID format".to_string()))?;
         let object_id_bson = object_id.clone();
 
-        let now = Utc::now();
+        let config = Self::new_configuration(Some(object_id), data);
 
-        let config = DatabaseConfiguration {
-            id: Some(object_id),
-            name,
-            db_type,
-            version,
-            host,
-            port,
-            database,
-            username,
-            password,
-            auth_type,
-            credentials,
-            company_id,
-            created_at: now,
-            updated_at: now,
-        };
-
-        let mut doc = mongodb::bson::to_document(&config)
+        let mut doc = mongodb::bson::to_document(&self.sealed(&config)?)
             .map_err(|e| AppError::BadRequest(e.to_string()))?;
 
         doc.remove("id");
@@ -105,11 +85,35 @@ impl DatabaseConfigurationRepository {
         Ok(config)
     }
 
+    fn new_configuration(id: Option<ObjectId>, data: CreateDatabaseConfigurationDto) -> DatabaseConfiguration {
+        let now = Utc::now();
+
+        DatabaseConfiguration {
+            id,
+            name: data.name,
+            db_type: data.db_type,
+            version: data.version,
+            host: data.host,
+            port: data.port,
+            database: data.database,
+            username: data.username,
+            password: data.password,
+            auth_type: data.auth_type,
+            credentials: data.credentials,
+            api_source: data.api_source,
+            oracle: data.oracle,
+            sqlserver: data.sqlserver,
+            company_id: data.company_id.unwrap_or_default(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
     pub async fn find_by_name(&self, name: &str) -> Result<Option<DatabaseConfiguration>, AppError> {
         let filter = doc! { "name": name };
         let config = self.collection.find_one(filter, None).await
             .map_err(|e| AppError::Database(e.to_string()))?;
-        Ok(config)
+        config.map(|c| self.reveal(c)).transpose()
     }
 
     pub async fn find_by_name_and_company_id(
@@ -120,7 +124,7 @@ impl DatabaseConfigurationRepository {
         let filter = doc! { "name": name, "company_id": company_id };
         let config = self.collection.find_one(filter, None).await
             .map_err(|e| AppError::Database(e.to_string()))?;
-        Ok(config)
+        config.map(|c| self.reveal(c)).transpose()
     }
 
     pub async fn find_by_id(&self, id: &str) -> Result<Option<DatabaseConfiguration>, AppError> {
@@ -130,12 +134,28 @@ impl DatabaseConfigurationRepository {
         let filter = doc! { "_id": object_id };
         let config = self.collection.find_one(filter, None).await
             .map_err(|e| AppError::Database(e.to_string()))?;
-        Ok(config)
+        config.map(|c| self.reveal(c)).transpose()
     }
 
-    pub async fn update(&self, id: &str, name: Option<String>, db_type: Option<String>, version: Option<String>, host: Option<String>, port: Option<i32>, database: Option<String>, username: Option<String>, password: Option<String>, auth_type: Option<String>, credentials: Option<String>, company_id: Option<String>) -> Result<DatabaseConfiguration, AppError> {
+    pub async fn update(&self, id: &str, data: UpdateDatabaseConfigurationDto) -> Result<DatabaseConfiguration, AppError> {
         let object_id = ObjectId::parse_str(id)
             .map_err(|_| AppError::BadRequest("Invalid ID format".to_string()))?;
+        let UpdateDatabaseConfigurationDto {
+            name,
+            db_type,
+            version,
+            host,
+            port,
+            database,
+            username,
+            password,
+            auth_type,
+            credentials,
+            api_source,
+            oracle,
+            sqlserver,
+            company_id,
+        } = data;
         
         let mut update_doc = Document::new();
         
@@ -160,14 +180,30 @@ impl DatabaseConfigurationRepository {
         if let Some(username) = username {
             update_doc.insert("username", username);
         }
-        if let Some(password) = password {
-            update_doc.insert("password", password);
+        // The redacted placeholder sent back by clients keeps the stored secret
+        if let Some(password) = password.filter(|v| !redact::is_redacted(v)) {
+            update_doc.insert("password", self.secrets.encrypt(&password)?);
         }
         if let Some(auth_type) = auth_type {
             update_doc.insert("auth_type", auth_type);
         }
-        if let Some(credentials) = credentials {
-            update_doc.insert("credentials", credentials);
+        if let Some(credentials) = credentials.filter(|v| !redact::is_redacted(v)) {
+            update_doc.insert("credentials", self.secrets.encrypt(&credentials)?);
+        }
+        if let Some(api_source) = api_source {
+            let api_source = mongodb::bson::to_bson(&api_source)
+                .map_err(|e| AppError::BadRequest(e.to_string()))?;
+            update_doc.insert("api_source", api_source);
+        }
+        if let Some(oracle) = oracle {
+            let oracle = mongodb::bson::to_bson(&oracle)
+                .map_err(|e| AppError::BadRequest(e.to_string()))?;
+            update_doc.insert("oracle", oracle);
+        }
+        if let Some(sqlserver) = sqlserver {
+            let sqlserver = mongodb::bson::to_bson(&sqlserver)
+                .map_err(|e| AppError::BadRequest(e.to_string()))?;
+            update_doc.insert("sqlserver", sqlserver);
         }
         if let Some(company_id) = company_id {
             update_doc.insert("company_id", company_id);
@@ -181,9 +217,10 @@ impl DatabaseConfigurationRepository {
         self.collection.update_one(filter.clone(), update, None).await
             .map_err(|e| AppError::Database(e.to_string()))?;
         
-        self.collection.find_one(filter, None).await
+        let updated = self.collection.find_one(filter, None).await
             .map_err(|e| AppError::Database(e.to_string()))?
-            .ok_or_else(|| AppError::NotFound("Database configuration not found after update".to_string()))
+            .ok_or_else(|| AppError::NotFound("Database configuration not found after update".to_string()))?;
+        self.reveal(updated)
     }
 
     pub async fn delete(&self, id: &str) -> Result<bool, AppError> {
@@ -230,7 +267,7 @@ impl DatabaseConfigurationRepository {
         let mut configs = Vec::new();
         while let Some(config) = cursor.try_next().await
             .map_err(|e| AppError::Database(e.to_string()))? {
-            configs.push(config);
+            configs.push(self.reveal(config)?);
         }
         
         let total = self.collection.count_documents(filter, None).await
@@ -246,4 +283,35 @@ impl DatabaseConfigurationRepository {
             .map_err(|e| AppError::Database(e.to_string()))?;
         Ok(count)
     }
+
+    /// Seal plaintext secrets and re-wrap those sealed with a previous master key; returns the documents changed
+    pub async fn rotate_secrets(&self) -> Result<u64, AppError> {
+        use futures::stream::TryStreamExt;
+
+        let raw_collection: Collection<Document> = self.collection.clone_with_type();
+        let mut cursor = raw_collection.find(doc! {}, None).await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let mut rotated = 0;
+        while let Some(document) = cursor.try_next().await
+            .map_err(|e| AppError::Database(e.to_string()))? {
+            let mut update_doc = Document::new();
+            for field in SECRET_FIELDS {
+                if let Ok(stored) = document.get_str(field) {
+                    if let Some(value) = self.secrets.rotate(stored)? {
+                        update_doc.insert(field, value);
+                    }
+                }
+            }
+
+            if update_doc.is_empty() {
+                continue;
+            }
+            raw_collection.update_one(doc! { "_id": document.get("_id").cloned().unwrap_or(Bson::Null) }, doc! { "$set": update_doc }, None).await
+                .map_err(|e| AppError::Database(e.to_string()))?;
+            rotated += 1;
+        }
+
+        Ok(rotated)
+    }
 }
@@ -126,6 +126,24 @@ impl DatabaseTableRepository {
         Ok(result.deleted_count > 0)
     }
 
+    /// Tables registered for a source connector (`table_reference` holds the configuration id)
+    pub async fn find_by_table_reference(&self, table_reference: &str) -> Result<Vec<DatabaseTable>, AppError> {
+        use futures::stream::TryStreamExt;
+
+        let filter = doc! { "table_reference": table_reference };
+
+        let mut cursor = self.collection.find(filter, None).await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let mut tables = Vec::new();
+        while let Some(table) = cursor.try_next().await
+            .map_err(|e| AppError::Database(e.to_string()))? {
+            tables.push(table);
+        }
+
+        Ok(tables)
+    }
+
     pub async fn find_all(
         &self,
         page: i64,
@@ -2,7 +2,7 @@ use mongodb::{Database, Collection, bson::{doc, oid::ObjectId, Bson, Document}};
 use chrono::Utc;
 use std::sync::Arc;
 
-use crate::domain::entities::{DatabaseView, ResourceItem};
+use crate::domain::entities::{DatabaseView, DeliveryMode, ReferenceStrategy, ResourceItem};
 use crate::utils::AppError;
 
 #[derive(Clone)]
@@ -50,6 +50,9 @@ impl DatabaseViewRepository {
             database_configuration_id,
             company_id,
             target_integration_id: None,
+            message_types: None,
+            delivery_mode: None,
+            reference_strategy: None,
             status: "pending".to_string(),
             job_id: None,
             resources,
@@ -99,6 +102,9 @@ impl DatabaseViewRepository {
             database_configuration_id,
             company_id,
             target_integration_id: None,
+            message_types: None,
+            delivery_mode: None,
+            reference_strategy: None,
             status: "pending".to_string(),
             job_id: None,
             resources,
@@ -288,6 +294,104 @@ impl DatabaseViewRepository {
         Ok(())
     }
 
+    pub async fn set_message_types(
+        &self,
+        database_view_id: &str,
+        message_types: Option<Vec<String>>,
+    ) -> Result<(), AppError> {
+        let object_id = ObjectId::parse_str(database_view_id)
+            .map_err(|_| AppError::BadRequest("Invalid ID format".to_string()))?;
+
+        let filter = doc! { "_id": object_id };
+
+        let update = match message_types {
+            Some(message_types) if !message_types.is_empty() => {
+                doc! { "$set": { "message_types": message_types, "updatedAt": Utc::now() } }
+            }
+            _ => doc! { "$unset": { "message_types": "" }, "$set": { "updatedAt": Utc::now() } },
+        };
+
+        self.collection
+            .update_one(filter, update, None)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    pub async fn set_delivery_mode(&self, database_view_id: &str, delivery_mode: DeliveryMode) -> Result<(), AppError> {
+        let object_id = ObjectId::parse_str(database_view_id)
+            .map_err(|_| AppError::BadRequest("Invalid ID format".to_string()))?;
+
+        let delivery_mode = mongodb::bson::to_bson(&delivery_mode).map_err(|e| AppError::Database(e.to_string()))?;
+        let update = doc! { "$set": { "delivery_mode": delivery_mode, "updatedAt": Utc::now() } };
+
+        self.collection
+            .update_one(doc! { "_id": object_id }, update, None)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    pub async fn set_reference_strategy(&self, database_view_id: &str, reference_strategy: ReferenceStrategy) -> Result<(), AppError> {
+        let object_id = ObjectId::parse_str(database_view_id)
+            .map_err(|_| AppError::BadRequest("Invalid ID format".to_string()))?;
+
+        let reference_strategy = mongodb::bson::to_bson(&reference_strategy).map_err(|e| AppError::Database(e.to_string()))?;
+        let update = doc! { "$set": { "reference_strategy": reference_strategy, "updatedAt": Utc::now() } };
+
+        self.collection
+            .update_one(doc! { "_id": object_id }, update, None)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Views routed to any of the given HL7 message types
+    pub async fn find_by_message_types(&self, company_id: &str, message_types: &[String]) -> Result<Vec<DatabaseView>, AppError> {
+        use futures::stream::TryStreamExt;
+
+        let filter = doc! { "company_id": company_id, "message_types": { "$in": message_types } };
+        let cursor = self.collection.find(filter, None).await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        cursor.try_collect().await
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// Views of a company, optionally restricted to one source configuration
+    pub async fn find_by_company_id(
+        &self,
+        company_id: &str,
+        database_configuration_id: Option<&str>,
+    ) -> Result<Vec<DatabaseView>, AppError> {
+        use futures::stream::TryStreamExt;
+
+        let mut filter = doc! { "company_id": company_id };
+        if let Some(configuration_id) = database_configuration_id {
+            filter.insert("database_configuration_id", configuration_id);
+        }
+
+        let cursor = self.collection.find(filter, None).await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        cursor.try_collect().await
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// Views delivering to a target integration
+    pub async fn find_by_target_integration_id(&self, target_integration_id: &str) -> Result<Vec<DatabaseView>, AppError> {
+        use futures::stream::TryStreamExt;
+
+        let cursor = self.collection.find(doc! { "target_integration_id": target_integration_id }, None).await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        cursor.try_collect().await
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
     pub async fn find_all(&self, page: i64, limit: i64, database_configuration_id: Option<String>, sort_document: Option<Document>) -> Result<(Vec<DatabaseView>, i64), AppError> {
         use mongodb::options::{FindOptions, Collation, CollationStrength};
         use futures::stream::TryStreamExt;
@@ -0,0 +1,173 @@
+use mongodb::{Database, Collection, bson::{doc, Bson, Document}, options::{FindOneAndUpdateOptions, FindOptions, IndexOptions, ReturnDocument}, IndexModel};
+use chrono::{DateTime, Utc};
+use futures::stream::TryStreamExt;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::domain::entities::DeliveredResource;
+use crate::domain::fhir::FhirVersion;
+use crate::utils::AppError;
+
+/// Criteria of a facade search; empty criteria match every resource of the type
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeliveredResourceQuery {
+    /// `system|value` identifiers, any of which may match
+    pub identifiers: Vec<String>,
+    /// Identifier values without system, any of which may match
+    pub identifier_values: Vec<String>,
+    /// Patient references, any of which may match
+    pub patient_references: Vec<String>,
+    /// `_lastUpdated` bounds as (`$gt`/`$gte`/`$lt`/`$lte`, instant), all of which must match
+    pub last_updated: Vec<(&'static str, DateTime<Utc>)>,
+}
+
+/// Last delivered version of each resource, per company
+#[derive(Clone)]
+pub struct DeliveredResourceRepository {
+    collection: Collection<DeliveredResource>,
+}
+
+impl DeliveredResourceRepository {
+    pub fn new(db: Database) -> Self {
+        Self {
+            collection: db.collection("delivered_resources"),
+        }
+    }
+
+    pub fn arc(db: Database) -> Arc<Self> {
+        Arc::new(Self::new(db))
+    }
+}
+
+impl DeliveredResourceRepository {
+    /// Unique index on what identifies a resource between deliveries, so concurrent
+    /// deliveries of the same resource update one document
+    pub async fn ensure_indexes(&self) -> Result<(), AppError> {
+        let index = IndexModel::builder()
+            .keys(doc! { "company_id": 1, "resource_type": 1, "identifier_key": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+
+        self.collection.create_index(index, None).await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Store a delivered resource over its previous version (same company, type and first identifier),
+    /// keeping its logical id and incrementing the versionId in a single atomic upsert
+    pub async fn record(&self, delivered: DeliveredResource) -> Result<DeliveredResource, AppError> {
+        let filter = doc! {
+            "company_id": &delivered.company_id,
+            "resource_type": &delivered.resource_type,
+            "identifier_key": &delivered.identifier_key,
+        };
+        let update = doc! {
+            "$set": {
+                "database_view_id": &delivered.database_view_id,
+                "identifiers": &delivered.identifiers,
+                "identifier_values": &delivered.identifier_values,
+                "patient_references": &delivered.patient_references,
+                "fhir_version": delivered.fhir_version.as_str(),
+                "resource": bson::to_bson(&delivered.resource).map_err(|e| AppError::Database(e.to_string()))?,
+                "last_updated": bson::DateTime::from_chrono(delivered.last_updated),
+            },
+            "$setOnInsert": { "resource_id": Uuid::new_v4().to_string() },
+            "$inc": { "version_id": 1_i64 },
+        };
+        let options = FindOneAndUpdateOptions::builder()
+            .upsert(true)
+            .return_document(ReturnDocument::After)
+            .build();
+
+        self.collection.find_one_and_update(filter, update, options).await
+            .map_err(|e| AppError::Database(e.to_string()))?
+            .ok_or_else(|| AppError::Database(format!("Recurso {} não registrado", delivered.identifier_key)))
+    }
+
+    pub async fn find_by_id(&self, company_id: &str, resource_type: &str, resource_id: &str) -> Result<Option<DeliveredResource>, AppError> {
+        let filter = doc! {
+            "company_id": company_id,
+            "resource_type": resource_type,
+            "resource_id": resource_id,
+        };
+
+        self.collection.find_one(filter, None).await
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// One page of matching resources, most recently updated first, with the total count
+    pub async fn search(
+        &self,
+        company_id: &str,
+        resource_type: &str,
+        query: &DeliveredResourceQuery,
+        offset: u64,
+        count: i64,
+    ) -> Result<(Vec<DeliveredResource>, u64), AppError> {
+        let filter = Self::filter(company_id, resource_type, query);
+        let options = FindOptions::builder()
+            .sort(doc! { "last_updated": -1, "resource_id": 1 })
+            .skip(offset)
+            .limit(count)
+            .build();
+
+        let cursor = self.collection.find(filter.clone(), options).await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let resources: Vec<DeliveredResource> = cursor.try_collect().await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let total = self.collection.count_documents(filter, None).await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok((resources, total))
+    }
+
+    /// Resource types delivered for the company
+    pub async fn resource_types(&self, company_id: &str) -> Result<Vec<String>, AppError> {
+        let values = self.collection.distinct("resource_type", doc! { "company_id": company_id }, None).await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let mut types: Vec<String> = values.into_iter().filter_map(|value| value.as_str().map(str::to_string)).collect();
+        types.sort();
+        Ok(types)
+    }
+
+    /// FHIR releases of the resources delivered for the company
+    pub async fn fhir_versions(&self, company_id: &str) -> Result<Vec<FhirVersion>, AppError> {
+        let values = self.collection.distinct("fhir_version", doc! { "company_id": company_id }, None).await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let mut versions: Vec<FhirVersion> = values.iter().filter_map(Bson::as_str).filter_map(FhirVersion::parse).collect();
+        versions.sort();
+        Ok(versions)
+    }
+
+    fn filter(company_id: &str, resource_type: &str, query: &DeliveredResourceQuery) -> Document {
+        let mut filter = doc! { "company_id": company_id, "resource_type": resource_type };
+
+        let mut identifier_criteria = Vec::new();
+        if !query.identifiers.is_empty() {
+            identifier_criteria.push(doc! { "identifiers": { "$in": &query.identifiers } });
+        }
+        if !query.identifier_values.is_empty() {
+            identifier_criteria.push(doc! { "identifier_values": { "$in": &query.identifier_values } });
+        }
+        if !identifier_criteria.is_empty() {
+            filter.insert("$or", identifier_criteria);
+        }
+
+        if !query.patient_references.is_empty() {
+            filter.insert("patient_references", doc! { "$in": &query.patient_references });
+        }
+
+        if !query.last_updated.is_empty() {
+            let mut bounds = Document::new();
+            for (operator, instant) in &query.last_updated {
+                bounds.insert(*operator, Bson::DateTime(bson::DateTime::from_chrono(*instant)));
+            }
+            filter.insert("last_updated", bounds);
+        }
+
+        filter
+    }
+}
@@ -0,0 +1,156 @@
+use mongodb::{Database, Collection, bson::{doc, oid::ObjectId, Document}, options::{FindOneOptions, FindOptions}};
+use futures::stream::TryStreamExt;
+use std::sync::Arc;
+
+use crate::domain::entities::{FileDataset, FileDatasetRow};
+use crate::utils::AppError;
+
+/// Rows are inserted in batches to stay well under the MongoDB message size limit
+const ROW_BATCH_SIZE: usize = 1000;
+
+#[derive(Clone)]
+pub struct FileDatasetRepository {
+    database: Database,
+    collection: Collection<FileDataset>,
+    rows: Collection<FileDatasetRow>,
+}
+
+impl FileDatasetRepository {
+    pub fn new(db: Database) -> Self {
+        Self {
+            collection: db.collection("file_datasets"),
+            rows: db.collection("file_dataset_rows"),
+            database: db,
+        }
+    }
+
+    pub fn arc(db: Database) -> Arc<Self> {
+        Arc::new(Self::new(db))
+    }
+}
+
+impl FileDatasetRepository {
+    pub async fn ping(&self) -> Result<bool, AppError> {
+        self.database
+            .run_command(doc! { "ping": 1 }, None)
+            .await
+            .map(|_| true)
+            .map_err(|e| AppError::DatabaseError(format!("Falha ao realizar a conexão: {}", e)))
+    }
+
+    /// Store a dataset and its rows, replacing any previous upload for the same table
+    /// Rows go in first and the dataset document last, so readers keep seeing the previous upload
+    /// until the new one is complete; the previous upload is removed only after that
+    pub async fn replace(&self, mut dataset: FileDataset, rows: Vec<(u64, Document)>) -> Result<FileDataset, AppError> {
+        let previous = self.find_by_table(&dataset.database_configuration_id, &dataset.table_name).await?;
+
+        let dataset_id = ObjectId::new();
+        let rows: Vec<FileDatasetRow> = rows
+            .into_iter()
+            .map(|(row_number, data)| FileDatasetRow {
+                id: None,
+                dataset_id: dataset_id.to_hex(),
+                row_number,
+                data,
+            })
+            .collect();
+
+        for batch in rows.chunks(ROW_BATCH_SIZE) {
+            if let Err(e) = self.rows.insert_many(batch, None).await {
+                // Drop the partial import; the previous upload is untouched
+                self.rows.delete_many(doc! { "dataset_id": dataset_id.to_hex() }, None).await
+                    .map_err(|e| AppError::Database(e.to_string()))?;
+                return Err(AppError::Database(e.to_string()));
+            }
+        }
+
+        let mut document = bson::to_document(&dataset)
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        document.insert("_id", dataset_id);
+        self.collection.clone_with_type::<Document>().insert_one(document, None).await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        dataset.id = Some(dataset_id);
+
+        if let Some(previous_id) = previous.and_then(|previous| previous.id) {
+            self.delete(&previous_id.to_hex()).await?;
+        }
+
+        Ok(dataset)
+    }
+
+    /// Newest dataset of a table (the previous upload stays until its replacement is complete)
+    pub async fn find_by_table(&self, database_configuration_id: &str, table_name: &str) -> Result<Option<FileDataset>, AppError> {
+        let filter = doc! {
+            "database_configuration_id": database_configuration_id,
+            "table_name": table_name,
+        };
+
+        let options = FindOneOptions::builder().sort(doc! { "_id": -1 }).build();
+
+        self.collection.find_one(filter, options).await
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    pub async fn find_by_configuration_id(&self, database_configuration_id: &str) -> Result<Vec<FileDataset>, AppError> {
+        let filter = doc! { "database_configuration_id": database_configuration_id };
+        let options = FindOptions::builder().sort(doc! { "table_name": 1 }).build();
+
+        let cursor = self.collection.find(filter, options).await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        cursor.try_collect().await
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    pub async fn count_rows(&self, dataset_id: &str) -> Result<u64, AppError> {
+        self.rows.count_documents(doc! { "dataset_id": dataset_id }, None).await
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// Rows in file order
+    pub async fn find_rows(&self, dataset_id: &str, skip: u64, limit: u64) -> Result<Vec<FileDatasetRow>, AppError> {
+        let options = FindOptions::builder()
+            .sort(doc! { "row_number": 1 })
+            .skip(skip)
+            .limit(limit as i64)
+            .build();
+
+        let cursor = self.rows.find(doc! { "dataset_id": dataset_id }, options).await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        cursor.try_collect().await
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// First row whose column holds the value (stored either as text or as a number)
+    pub async fn find_row_by_value(&self, dataset_id: &str, column: &str, value: &str) -> Result<Option<FileDatasetRow>, AppError> {
+        let field = format!("data.{}", column);
+        let mut candidates = vec![mongodb::bson::Bson::String(value.to_string())];
+        if let Ok(number) = value.parse::<i64>() {
+            candidates.push(mongodb::bson::Bson::Int64(number));
+        }
+
+        let filter = doc! {
+            "dataset_id": dataset_id,
+            field: { "$in": candidates },
+        };
+        let options = FindOneOptions::builder().sort(doc! { "row_number": 1 }).build();
+
+        self.rows.find_one(filter, options).await
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// Delete a dataset and all of its rows
+    pub async fn delete(&self, id: &str) -> Result<bool, AppError> {
+        let object_id = ObjectId::parse_str(id)
+            .map_err(|_| AppError::BadRequest("Invalid ID format".to_string()))?;
+
+        self.rows.delete_many(doc! { "dataset_id": id }, None).await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let result = self.collection.delete_one(doc! { "_id": object_id }, None).await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(result.deleted_count > 0)
+    }
+}
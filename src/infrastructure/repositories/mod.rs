@@ -13,6 +13,10 @@ pub mod database_model;
 pub mod database_model_values;
 pub mod target_integration;
 pub mod integration_control;
+pub mod file_dataset;
+pub mod schema_drift;
+pub mod terminology;
+pub mod delivered_resource;
 
 pub use company::{CompanyRepository, CreateCompanyDto, UpdateCompanyDto};
 pub use user::{UserRepository, CreateUserDto, UpdateUserDto};
@@ -28,3 +32,7 @@ pub use database_model::DatabaseModelRepository;
 pub use database_model_values::DatabaseModelValueRepository;
 pub use target_integration::TargetIntegrationRepository;
 pub use integration_control::IntegrationControlRepository;
+pub use file_dataset::FileDatasetRepository;
+pub use schema_drift::SchemaDriftRepository;
+pub use terminology::TerminologyRepository;
+pub use delivered_resource::{DeliveredResourceQuery, DeliveredResourceRepository};
@@ -0,0 +1,62 @@
+use mongodb::{Database, Collection, bson::doc, options::ReplaceOptions};
+use futures::stream::TryStreamExt;
+use std::sync::Arc;
+
+use crate::domain::entities::SchemaDriftReport;
+use crate::utils::AppError;
+
+/// Keeps only the latest drift report of each view
+#[derive(Clone)]
+pub struct SchemaDriftRepository {
+    collection: Collection<SchemaDriftReport>,
+}
+
+impl SchemaDriftRepository {
+    pub fn new(db: Database) -> Self {
+        Self {
+            collection: db.collection("schema_drift_reports"),
+        }
+    }
+
+    pub fn arc(db: Database) -> Arc<Self> {
+        Arc::new(Self::new(db))
+    }
+}
+
+impl SchemaDriftRepository {
+    /// Store a report, replacing the previous one of the same view
+    pub async fn save(&self, report: &SchemaDriftReport) -> Result<SchemaDriftReport, AppError> {
+        let filter = doc! { "database_view_id": &report.database_view_id };
+        let options = ReplaceOptions::builder().upsert(true).build();
+
+        self.collection.replace_one(filter.clone(), report, options).await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        self.collection.find_one(filter, None).await
+            .map_err(|e| AppError::Database(e.to_string()))?
+            .ok_or_else(|| AppError::NotFound("Schema drift report not found after save".to_string()))
+    }
+
+    pub async fn find_by_view_id(&self, database_view_id: &str) -> Result<Option<SchemaDriftReport>, AppError> {
+        let filter = doc! { "database_view_id": database_view_id };
+
+        self.collection.find_one(filter, None).await
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// Reports of the given views that are currently drifting
+    pub async fn find_with_drift(&self, database_view_ids: &[String]) -> Result<Vec<SchemaDriftReport>, AppError> {
+        let filter = doc! { "has_drift": true, "database_view_id": { "$in": database_view_ids } };
+
+        let mut cursor = self.collection.find(filter, None).await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let mut reports = Vec::new();
+        while let Some(report) = cursor.try_next().await
+            .map_err(|e| AppError::Database(e.to_string()))? {
+            reports.push(report);
+        }
+
+        Ok(reports)
+    }
+}
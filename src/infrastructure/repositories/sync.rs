@@ -91,8 +91,10 @@ impl SyncJobRepository {
     pub async fn find_by_view_id(&self, database_view_id: &str) -> Result<Option<SyncJobDocument>, AppError> {
         use mongodb::options::FindOptions;
         
+        // Export jobs of the view are not synchronizations
         let filter = doc! {
             "database_view_id": database_view_id,
+            "kind": { "$ne": "export" },
         };
         
         let options = FindOptions::builder()
@@ -2,23 +2,55 @@ use mongodb::{Database, Collection, bson::{doc, oid::ObjectId, Bson, Document}};
 use chrono::Utc;
 use std::sync::Arc;
 
-use crate::domain::entities::TargetIntegration;
-use crate::utils::AppError;
+use crate::core::SecretBox;
+use crate::domain::entities::{ClientCertificate, TargetCapabilities, TargetIntegration};
+use crate::utils::{redact, AppError};
+
+/// Fields sealed by the SecretBox, as stored
+const SECRET_FIELDS: [&str; 4] = [
+    "credentials",
+    "clientCertificate.certificate",
+    "clientCertificate.privateKey",
+    "clientCertificate.password",
+];
 
 #[derive(Clone)]
 pub struct TargetIntegrationRepository {
     collection: Collection<TargetIntegration>,
+    secrets: Arc<SecretBox>,
 }
 
 impl TargetIntegrationRepository {
     pub fn new(db: Database) -> Self {
         Self {
             collection: db.collection("target_integrations"),
+            secrets: Arc::new(SecretBox::disabled()),
         }
     }
 
-    pub fn arc(db: Database) -> Arc<Self> {
-        Arc::new(Self::new(db))
+    /// Encrypt credentials at rest
+    pub fn with_secrets(mut self, secrets: Arc<SecretBox>) -> Self {
+        self.secrets = secrets;
+        self
+    }
+
+    fn reveal(&self, mut target: TargetIntegration) -> Result<TargetIntegration, AppError> {
+        target.credentials = target.credentials.map(|v| self.secrets.decrypt(&v)).transpose()?;
+        if let Some(certificate) = target.client_certificate.as_mut() {
+            certificate.certificate = self.secrets.decrypt(&certificate.certificate)?;
+            certificate.private_key = certificate.private_key.as_deref().map(|v| self.secrets.decrypt(v)).transpose()?;
+            certificate.password = certificate.password.as_deref().map(|v| self.secrets.decrypt(v)).transpose()?;
+        }
+        Ok(target)
+    }
+
+    fn seal_certificate(&self, certificate: &ClientCertificate) -> Result<ClientCertificate, AppError> {
+        Ok(ClientCertificate {
+            certificate: self.secrets.encrypt(&certificate.certificate)?,
+            private_key: certificate.private_key.as_deref().map(|v| self.secrets.encrypt(v)).transpose()?,
+            password: certificate.password.as_deref().map(|v| self.secrets.encrypt(v)).transpose()?,
+            ..certificate.clone()
+        })
     }
 }
 
@@ -41,14 +73,21 @@ impl TargetIntegrationRepository {
             host,
             auth_type,
             credentials,
+            client_certificate: None,
+            capabilities: None,
             company_id,
             created_at: now,
             updated_at: now,
         };
 
+        let stored = TargetIntegration {
+            credentials: target.credentials.as_deref().map(|v| self.secrets.encrypt(v)).transpose()?,
+            ..target.clone()
+        };
+
         let result = self
             .collection
-            .insert_one(&target, None)
+            .insert_one(&stored, None)
             .await
             .map_err(|e| AppError::Database(e.to_string()))?;
 
@@ -67,7 +106,7 @@ impl TargetIntegrationRepository {
             .await
             .map_err(|e| AppError::Database(e.to_string()))?;
 
-        Ok(target)
+        target.map(|t| self.reveal(t)).transpose()
     }
 
     pub async fn find_all(&self) -> Result<Vec<TargetIntegration>, AppError> {
@@ -85,7 +124,7 @@ impl TargetIntegrationRepository {
             .await
             .map_err(|e| AppError::Database(e.to_string()))?
         {
-            targets.push(target);
+            targets.push(self.reveal(target)?);
         }
 
         Ok(targets)
@@ -117,8 +156,9 @@ impl TargetIntegrationRepository {
         if let Some(auth_type) = auth_type {
             update_doc.insert("authType", auth_type);
         }
-        if let Some(credentials) = credentials {
-            update_doc.insert("credentials", credentials);
+        // The redacted placeholder sent back by clients keeps the stored secret
+        if let Some(credentials) = credentials.filter(|v| !redact::is_redacted(v)) {
+            update_doc.insert("credentials", self.secrets.encrypt(&credentials)?);
         }
 
         update_doc.insert("updatedAt", Utc::now());
@@ -131,11 +171,72 @@ impl TargetIntegrationRepository {
             .await
             .map_err(|e| AppError::Database(e.to_string()))?;
 
-        self.collection
+        let updated = self
+            .collection
             .find_one(filter, None)
             .await
             .map_err(|e| AppError::Database(e.to_string()))?
-            .ok_or_else(|| AppError::NotFound("Target integration not found after update".to_string()))
+            .ok_or_else(|| AppError::NotFound("Target integration not found after update".to_string()))?;
+
+        self.reveal(updated)
+    }
+
+    /// Attach (or with None, remove) the mTLS client certificate
+    pub async fn set_client_certificate(
+        &self,
+        id: &str,
+        certificate: Option<ClientCertificate>,
+    ) -> Result<TargetIntegration, AppError> {
+        let object_id = ObjectId::parse_str(id)
+            .map_err(|_| AppError::BadRequest("Invalid ID format".to_string()))?;
+
+        let update = match certificate {
+            Some(certificate) => {
+                let stored = mongodb::bson::to_bson(&self.seal_certificate(&certificate)?)
+                    .map_err(|e| AppError::Database(e.to_string()))?;
+                doc! { "$set": { "clientCertificate": stored, "updatedAt": Utc::now() } }
+            }
+            None => doc! { "$unset": { "clientCertificate": "" }, "$set": { "updatedAt": Utc::now() } },
+        };
+
+        let filter = doc! { "_id": object_id };
+        let result = self
+            .collection
+            .update_one(filter.clone(), update, None)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        if result.matched_count == 0 {
+            return Err(AppError::NotFound("Target integration not found".to_string()));
+        }
+
+        let updated = self
+            .collection
+            .find_one(filter, None)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?
+            .ok_or_else(|| AppError::NotFound("Target integration not found after update".to_string()))?;
+
+        self.reveal(updated)
+    }
+
+    /// Store the outcome of a CapabilityStatement discovery
+    pub async fn set_capabilities(&self, id: &str, capabilities: &TargetCapabilities) -> Result<(), AppError> {
+        let object_id = ObjectId::parse_str(id)
+            .map_err(|_| AppError::BadRequest("Invalid ID format".to_string()))?;
+
+        let capabilities = mongodb::bson::to_bson(capabilities)
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        self.collection
+            .update_one(
+                doc! { "_id": object_id },
+                doc! { "$set": { "capabilities": capabilities, "updatedAt": Utc::now() } },
+                None,
+            )
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(())
     }
 
     pub async fn delete(&self, id: &str) -> Result<bool, AppError> {
@@ -150,4 +251,57 @@ impl TargetIntegrationRepository {
 
         Ok(result.deleted_count > 0)
     }
+
+    /// Seal plaintext credentials and certificates and re-wrap those sealed with a previous master key;
+    /// returns the documents changed
+    pub async fn rotate_secrets(&self) -> Result<u64, AppError> {
+        use futures::stream::TryStreamExt;
+
+        let raw_collection: Collection<Document> = self.collection.clone_with_type();
+        let filter = doc! {
+            "$or": SECRET_FIELDS.iter().map(|field| doc! { *field: { "$type": "string" } }).collect::<Vec<_>>()
+        };
+        let mut cursor = raw_collection
+            .find(filter, None)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let mut rotated = 0;
+        while let Some(document) = cursor
+            .try_next()
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?
+        {
+            let mut changes = Document::new();
+            for field in SECRET_FIELDS {
+                let Some(stored) = stored_str(&document, field) else { continue };
+                if let Some(value) = self.secrets.rotate(stored)? {
+                    changes.insert(field, value);
+                }
+            }
+            if changes.is_empty() {
+                continue;
+            }
+
+            raw_collection
+                .update_one(
+                    doc! { "_id": document.get("_id").cloned().unwrap_or(Bson::Null) },
+                    doc! { "$set": changes },
+                    None,
+                )
+                .await
+                .map_err(|e| AppError::Database(e.to_string()))?;
+            rotated += 1;
+        }
+
+        Ok(rotated)
+    }
+}
+
+/// String at a dotted path, e.g. `clientCertificate.password`
+fn stored_str<'a>(document: &'a Document, path: &str) -> Option<&'a str> {
+    match path.split_once('.') {
+        Some((head, rest)) => stored_str(document.get_document(head).ok()?, rest),
+        None => document.get_str(path).ok(),
+    }
 }
@@ -0,0 +1,57 @@
+use mongodb::{Database, Collection, bson::doc, options::ReplaceOptions};
+use futures::stream::TryStreamExt;
+use std::sync::Arc;
+
+use crate::domain::entities::TerminologyResource;
+use crate::utils::AppError;
+
+/// Uploaded CodeSystems and ValueSets, one per canonical url
+#[derive(Clone)]
+pub struct TerminologyRepository {
+    collection: Collection<TerminologyResource>,
+}
+
+impl TerminologyRepository {
+    pub fn new(db: Database) -> Self {
+        Self {
+            collection: db.collection("terminology_resources"),
+        }
+    }
+
+    pub fn arc(db: Database) -> Arc<Self> {
+        Arc::new(Self::new(db))
+    }
+}
+
+impl TerminologyRepository {
+    /// Store a resource, replacing the previous one with the same url
+    pub async fn save(&self, resource: &TerminologyResource) -> Result<(), AppError> {
+        let filter = doc! { "url": &resource.url };
+        let options = ReplaceOptions::builder().upsert(true).build();
+
+        self.collection.replace_one(filter, resource, options).await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    pub async fn find_all(&self) -> Result<Vec<TerminologyResource>, AppError> {
+        let mut cursor = self.collection.find(doc! {}, None).await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let mut resources = Vec::new();
+        while let Some(resource) = cursor.try_next().await
+            .map_err(|e| AppError::Database(e.to_string()))? {
+            resources.push(resource);
+        }
+
+        Ok(resources)
+    }
+
+    pub async fn delete_by_url(&self, url: &str) -> Result<bool, AppError> {
+        let result = self.collection.delete_one(doc! { "url": url }, None).await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(result.deleted_count > 0)
+    }
+}
@@ -7,6 +7,7 @@ mod utils;
 mod core;
 mod seed;
 mod sync;
+mod hl7;
 
 use axum::{
     http::Method,
@@ -33,10 +34,34 @@ async fn main() -> anyhow::Result<()> {
 
     tracing::info!("[Database] MongoDB connection initialized");
 
-    // Check if we should run seed
+    let secrets = Arc::new(core::SecretBox::new(
+        config.secrets_master_key.as_deref(),
+        &config.secrets_previous_keys,
+    )?);
+    if !secrets.is_enabled() {
+        tracing::warn!("SECRETS_MASTER_KEY não configurada; credenciais serão armazenadas sem criptografia");
+    }
+
+    let connectors = infrastructure::factories::ConnectorFactory::default()
+        .with_fixtures_root(config.fixtures_dir.clone());
+    if let Some(fixtures_dir) = &config.fixtures_dir {
+        tracing::info!("🧪 FIXTURE sources enabled under {}", fixtures_dir.display());
+    }
+
     let args: Vec<String> = std::env::args().collect();
+
+    // Re-encrypt stored credentials with the current master key
+    if args.len() > 1 && args[1] == "rotate-keys" {
+        let app_state = application::AppState::new(db, config.jwt_secret, config.token_exp, config.max_concurrent_jobs, secrets, connectors);
+        let configurations = app_state.database_configuration_repository.rotate_secrets().await?;
+        let targets = app_state.target_integration_repository.rotate_secrets().await?;
+        println!("🔐 Credenciais re-criptografadas: {} configurações de banco, {} integrações de destino", configurations, targets);
+        return Ok(());
+    }
+
+    // Check if we should run seed
     if args.len() > 1 && args[1] == "seed" {
-        let app_state = application::AppState::new(db, config.jwt_secret, config.token_exp, config.max_concurrent_jobs);
+        let app_state = application::AppState::new(db, config.jwt_secret, config.token_exp, config.max_concurrent_jobs, secrets, connectors);
         seed::seed_database(
             app_state.company_repository,
             app_state.user_repository,
@@ -52,7 +77,23 @@ async fn main() -> anyhow::Result<()> {
         return Ok(());
     }
 
-    let mut app_state = application::AppState::new(db, config.jwt_secret, config.token_exp, config.max_concurrent_jobs);
+    let mut app_state = application::AppState::new(db, config.jwt_secret, config.token_exp, config.max_concurrent_jobs, secrets, connectors);
+    app_state.delivered_resource_repository.ensure_indexes().await?;
+
+    // Optional FHIR packages for profile and terminology validation, plus uploaded terminology
+    let mut terminology = match &config.fhir_packages_dir {
+        Some(packages_dir) => {
+            let resources = domain::fhir::profiles::package::load_dir(std::path::Path::new(packages_dir))?;
+            let registry = domain::fhir::ProfileRegistry::from_resources(&resources);
+            tracing::info!("📦 {} FHIR StructureDefinitions loaded from {}", registry.profile_count(), packages_dir);
+            app_state = app_state.with_profile_registry(Arc::new(registry));
+            domain::fhir::Terminology::from_resources(&resources)
+        }
+        None => domain::fhir::Terminology::default(),
+    };
+    let uploaded = application::TerminologyUseCase::load_uploaded(&app_state.terminology_repository, &mut terminology).await?;
+    tracing::info!("📚 {} terminology resources loaded ({} uploaded)", terminology.summaries().len(), uploaded);
+    app_state = app_state.with_terminology(terminology);
 
     // Initialize SyncManager (start background workers)
     tracing::info!("🚀 Initializing SyncManager...");
@@ -64,6 +105,51 @@ async fn main() -> anyhow::Result<()> {
     }
     tracing::info!("✅ SyncManager initialized with background workers running!");
 
+    // Optional HL7 v2 inbound source
+    if let Some(mllp_port) = config.mllp_port {
+        let mut pipeline = hl7::ViewPipeline::new(
+            app_state.database_view_repository.clone(),
+            app_state.database_view_mapping_repository.clone(),
+            app_state.database_transformation_repository.clone(),
+            app_state.target_integration_repository.clone(),
+            app_state.company_repository.clone(),
+        )
+        .with_delivered_resources(app_state.delivered_resource_repository.clone());
+        if let Some(output_dir) = &config.hl7_output_dir {
+            pipeline = pipeline.with_output_dir(output_dir.clone());
+        }
+        let mllp_addr = SocketAddr::from(([0, 0, 0, 0], mllp_port));
+        let mllp_listener = tokio::net::TcpListener::bind(mllp_addr).await?;
+        let mut listener = hl7::MllpListener::new(Arc::new(pipeline));
+        if let Some(secs) = config.mllp_read_timeout_secs.filter(|secs| *secs > 0) {
+            listener = listener.with_read_timeout(std::time::Duration::from_secs(secs));
+        }
+        tokio::spawn(Arc::new(listener).serve(mllp_listener));
+        tracing::info!("📡 HL7 MLLP listener on {}", mllp_addr);
+    }
+
+    // Optional scheduled schema drift check
+    if let Some(interval_secs) = config.schema_drift_interval_secs.filter(|secs| *secs > 0) {
+        let drift_use_case = application::SchemaDriftUseCase::new(
+            app_state.schema_drift_repository.clone(),
+            app_state.database_view_repository.clone(),
+            app_state.database_configuration_repository.clone(),
+            app_state.database_view_mapping_repository.clone(),
+            app_state.database_column_repository.clone(),
+            app_state.connectors.clone(),
+        );
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(interval_secs));
+            loop {
+                interval.tick().await;
+                if let Err(e) = drift_use_case.check_all().await {
+                    tracing::error!("Scheduled schema drift check failed: {}", e);
+                }
+            }
+        });
+        tracing::info!("🔎 Schema drift check every {}s", interval_secs);
+    }
+
     // CORS configuration - specify explicit origins when using credentials
     let cors = CorsLayer::new()
         .allow_origin(Any)
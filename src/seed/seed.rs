@@ -11,6 +11,7 @@ use crate::infrastructure::repositories::{
     DatabaseConfigurationRepository, DatabaseViewRepository,
     CreateCompanyDto,
 };
+use crate::domain::dtos::CreateDatabaseConfigurationDto;
 use crate::domain::entities::{DatabaseModelValue, DatabaseModelValueClient, FieldMapping, ValueMappingItem, ResourceItem};
 use crate::utils::AppResult;
 
@@ -386,6 +387,8 @@ pub async fn seed_database(
             state: Some(company_seed.company.state),
             zipcode: Some(company_seed.company.zipcode),
             country: Some(company_seed.company.country),
+            timezone: None,
+            hl7_facility: None,
         };
 
         let company = if let Some(id) = company_seed.company.id.as_deref() {
@@ -421,39 +424,26 @@ pub async fn seed_database(
                 .ok_or_else(|| AppError::Database("Existing database configuration has no id".to_string()))?
                 .to_string()
         } else {
+            let data = CreateDatabaseConfigurationDto {
+                name: conn.name.clone(),
+                db_type: conn.db_type,
+                version: conn.version,
+                host: conn.host,
+                port: Some(conn.port),
+                database: Some(conn.database),
+                username: Some(conn.username),
+                password: Some(conn.password),
+                auth_type: None,
+                credentials: None,
+                api_source: None,
+                oracle: None,
+                sqlserver: None,
+                company_id: Some(company_id.clone()),
+            };
             let created = if let Some(id) = conn.id.as_deref() {
-                database_configuration_repo
-                    .create_with_id(
-                        id,
-                        conn.name.clone(),
-                        conn.db_type,
-                        conn.version,
-                        conn.host,
-                        Some(conn.port),
-                        Some(conn.database),
-                        Some(conn.username),
-                        Some(conn.password),
-                        None,
-                        None,
-                        company_id.clone(),
-                    )
-                    .await?
+                database_configuration_repo.create_with_id(id, data).await?
             } else {
-                database_configuration_repo
-                    .create(
-                        conn.name.clone(),
-                        conn.db_type,
-                        conn.version,
-                        conn.host,
-                        Some(conn.port),
-                        Some(conn.database),
-                        Some(conn.username),
-                        Some(conn.password),
-                        None,
-                        None,
-                        company_id.clone(),
-                    )
-                    .await?
+                database_configuration_repo.create(data).await?
             };
 
             println!("  ✓ Database connection '{}' created", conn.name);
@@ -643,6 +633,7 @@ pub async fn seed_database(
                 description: fm.description,
                 reference_destiny: fm.reference_destiny,
                 relationship_destiny: fm.relationship_destiny,
+                reference_identifier_system: None,
                 data_type: fm.data_type,
                 is_nullable: fm.is_nullable,
                 min_length: fm.min_length.unwrap_or(0),
@@ -650,6 +641,8 @@ pub async fn seed_database(
                 is_enumerable: fm.is_enumerable.unwrap_or(false),
                 transformation_id: fm.transformation_id,
                 reference: fm.reference,
+                expression: None,
+                time_origin: None,
             }
         }).collect();
 
@@ -1,5 +1,6 @@
 use dotenvy::dotenv;
 use std::env;
+use std::path::PathBuf;
 
 use crate::utils::AppError;
 
@@ -10,6 +11,22 @@ pub struct Config {
     pub jwt_secret: String,
     pub token_exp: u64,
     pub max_concurrent_jobs: usize,
+    /// Port of the HL7 v2 MLLP listener; the listener only starts when set
+    pub mllp_port: Option<u16>,
+    /// Seconds an MLLP connection may stay idle before it is closed; 300 when unset
+    pub mllp_read_timeout_secs: Option<u64>,
+    /// Interval of the scheduled schema drift check; disabled when unset
+    pub schema_drift_interval_secs: Option<u64>,
+    /// Base64 key sealing stored credentials (SECRETS_MASTER_KEY or the file at SECRETS_MASTER_KEY_FILE)
+    pub secrets_master_key: Option<String>,
+    /// Former master keys, still accepted for decryption until `rotate-keys` runs
+    pub secrets_previous_keys: Vec<String>,
+    /// Directory of FHIR packages (.tgz) whose StructureDefinitions validate generated resources
+    pub fhir_packages_dir: Option<String>,
+    /// Folder where resources built from HL7 messages are written (HL7_OUTPUT_DIR)
+    pub hl7_output_dir: Option<String>,
+    /// Canonical directory FIXTURE sources may read (FIXTURES_DIR); FIXTURE sources are disabled without it
+    pub fixtures_dir: Option<PathBuf>,
 }
 
 impl Config {
@@ -37,12 +54,85 @@ impl Config {
             .parse()
             .map_err(|_| AppError::ConfigError("Invalid MAX_CONCURRENT_JOBS".to_string()))?;
 
+        let mllp_port = match env::var("MLLP_PORT") {
+            Ok(port) if !port.trim().is_empty() => Some(
+                port.trim()
+                    .parse()
+                    .map_err(|_| AppError::ConfigError("Invalid MLLP_PORT".to_string()))?,
+            ),
+            _ => None,
+        };
+
+        let mllp_read_timeout_secs = match env::var("MLLP_READ_TIMEOUT_SECS") {
+            Ok(secs) if !secs.trim().is_empty() => Some(
+                secs.trim()
+                    .parse()
+                    .map_err(|_| AppError::ConfigError("Invalid MLLP_READ_TIMEOUT_SECS".to_string()))?,
+            ),
+            _ => None,
+        };
+
+        let schema_drift_interval_secs = match env::var("SCHEMA_DRIFT_INTERVAL_SECS") {
+            Ok(secs) if !secs.trim().is_empty() => Some(
+                secs.trim()
+                    .parse()
+                    .map_err(|_| AppError::ConfigError("Invalid SCHEMA_DRIFT_INTERVAL_SECS".to_string()))?,
+            ),
+            _ => None,
+        };
+
+        let secrets_master_key = match (env::var("SECRETS_MASTER_KEY"), env::var("SECRETS_MASTER_KEY_FILE")) {
+            (Ok(key), _) if !key.trim().is_empty() => Some(key.trim().to_string()),
+            (_, Ok(path)) if !path.trim().is_empty() => Some(
+                std::fs::read_to_string(path.trim())
+                    .map_err(|e| AppError::ConfigError(format!("Cannot read SECRETS_MASTER_KEY_FILE: {}", e)))?
+                    .trim()
+                    .to_string(),
+            ),
+            _ => None,
+        };
+
+        let secrets_previous_keys = env::var("SECRETS_PREVIOUS_MASTER_KEYS")
+            .map(|keys| {
+                keys.split(',')
+                    .map(|key| key.trim().to_string())
+                    .filter(|key| !key.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let fhir_packages_dir = env::var("FHIR_PACKAGES_DIR")
+            .ok()
+            .map(|dir| dir.trim().to_string())
+            .filter(|dir| !dir.is_empty());
+
+        let hl7_output_dir = env::var("HL7_OUTPUT_DIR")
+            .ok()
+            .map(|dir| dir.trim().to_string())
+            .filter(|dir| !dir.is_empty());
+
+        let fixtures_dir = match env::var("FIXTURES_DIR") {
+            Ok(dir) if !dir.trim().is_empty() => Some(
+                std::fs::canonicalize(dir.trim())
+                    .map_err(|e| AppError::ConfigError(format!("Invalid FIXTURES_DIR: {}", e)))?,
+            ),
+            _ => None,
+        };
+
         Ok(Config {
             mongo_url,
             app_port,
             jwt_secret,
             token_exp,
             max_concurrent_jobs,
+            mllp_port,
+            mllp_read_timeout_secs,
+            schema_drift_interval_secs,
+            secrets_master_key,
+            secrets_previous_keys,
+            fhir_packages_dir,
+            hl7_output_dir,
+            fixtures_dir,
         })
     }
 }
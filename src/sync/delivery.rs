@@ -0,0 +1,217 @@
+// Delivery of generated FHIR entries to the target integration of a view
+use serde_json::{json, Value};
+
+use crate::domain::entities::{DatabaseView, DeliveryMode, ReferenceStrategy, TargetIntegration};
+use crate::infrastructure::adapters::{ApiConnector, DeliveryOutcome, ReferenceResolver};
+use crate::utils::AppError;
+
+/// Result of [`TargetDelivery::send`]: one outcome per entry accepted, in order, and the error
+/// that stopped the delivery, if any
+#[derive(Debug)]
+pub struct SendReport {
+    pub outcomes: Vec<DeliveryOutcome>,
+    pub error: Option<AppError>,
+}
+
+impl SendReport {
+    /// Number of leading entries the server accepted
+    pub fn delivered(&self) -> usize {
+        self.outcomes.len()
+    }
+
+    /// The outcomes, or the error when the delivery stopped early
+    pub fn into_result(self) -> Result<Vec<DeliveryOutcome>, AppError> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.outcomes),
+        }
+    }
+}
+
+/// Sends the entries generated for a view to its target FHIR server, following the view's
+/// delivery mode and reference strategy
+pub struct TargetDelivery {
+    connector: ApiConnector,
+    mode: DeliveryMode,
+    strategy: ReferenceStrategy,
+    /// Only built for strategies that look references up on the target
+    resolver: Option<ReferenceResolver>,
+}
+
+impl TargetDelivery {
+    /// Delivery to `target`; `None` when there is no target server, in which case output is written to files
+    pub async fn for_view(view: &DatabaseView, target: Option<&TargetIntegration>) -> Result<Option<Self>, AppError> {
+        let Some(target) = target.filter(|target| !target.host.trim().is_empty()) else {
+            return Ok(None);
+        };
+
+        let strategy = view.reference_strategy();
+        let resolver = if strategy.resolves() {
+            Some(ReferenceResolver::new(ApiConnector::for_target(target).await?))
+        } else {
+            None
+        };
+
+        Ok(Some(Self {
+            connector: ApiConnector::for_target(target).await?,
+            mode: view.delivery_mode(),
+            strategy,
+            resolver,
+        }))
+    }
+
+    /// Mode the entries must be generated with
+    pub fn mode(&self) -> DeliveryMode {
+        self.mode
+    }
+
+    /// Deliver the entries in order, stopping at the first one the server refuses
+    ///
+    /// References are resolved right before each entry is sent, so an entry can point by id to
+    /// one delivered earlier in the same call. The outcomes cover the entries accepted before
+    /// the failure, so callers can still record them.
+    pub async fn send(&self, entries: &[Value]) -> SendReport {
+        let mut outcomes = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let delivered = match self.resolve_references(entry).await {
+                Ok(entry) => self.connector.deliver(&entry, self.mode).await,
+                Err(e) => Err(e),
+            };
+            match delivered {
+                Ok(outcome) => outcomes.push(outcome),
+                Err(error) => return SendReport { outcomes, error: Some(error) },
+            }
+        }
+        SendReport { outcomes, error: None }
+    }
+
+    async fn resolve_references(&self, entry: &Value) -> Result<Value, AppError> {
+        let Some(resolver) = &self.resolver else {
+            return Ok(entry.clone());
+        };
+
+        let mut bundle = json!({ "resourceType": "Bundle", "type": "transaction", "entry": [entry] });
+        resolver.resolve_bundle(&mut bundle, self.strategy).await?;
+        Ok(bundle["entry"][0].take())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, http::StatusCode, routing::{get, post}, Json, Router};
+    use chrono::Utc;
+    use std::sync::{Arc, Mutex};
+
+    /// FHIR server with Patient p1 (identifier `sys|1`) recording the body of every write
+    async fn spawn_server(written: Arc<Mutex<Vec<Value>>>) -> String {
+        async fn search() -> Json<Value> {
+            Json(json!({ "resourceType": "Bundle", "type": "searchset", "entry": [{ "resource": { "resourceType": "Patient", "id": "p1" } }] }))
+        }
+
+        async fn write(State(written): State<Arc<Mutex<Vec<Value>>>>, Json(resource): Json<Value>) -> StatusCode {
+            written.lock().unwrap().push(resource);
+            StatusCode::CREATED
+        }
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let app = Router::new()
+            .route("/Patient", get(search))
+            .route("/Encounter", post(write))
+            .with_state(written);
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        format!("http://{}", address)
+    }
+
+    fn view(strategy: ReferenceStrategy) -> DatabaseView {
+        DatabaseView {
+            id: None,
+            name: "Encounters".to_string(),
+            description: String::new(),
+            resource: None,
+            entity_type: "encounter".to_string(),
+            main_resource: None,
+            is_fhir_destination: Some(true),
+            is_interhealth_destination: None,
+            database_configuration_id: "config-1".to_string(),
+            company_id: "company".to_string(),
+            target_integration_id: Some("target-1".to_string()),
+            message_types: None,
+            delivery_mode: None,
+            reference_strategy: Some(strategy),
+            status: "pending".to_string(),
+            job_id: None,
+            resources: None,
+            started_at: None,
+            cancelled_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn target(host: &str) -> TargetIntegration {
+        TargetIntegration {
+            id: None,
+            name: "FHIR server".to_string(),
+            version: None,
+            host: host.to_string(),
+            auth_type: None,
+            credentials: None,
+            client_certificate: None,
+            capabilities: None,
+            company_id: "company".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn encounter_entry() -> Value {
+        json!({
+            "resource": {
+                "resourceType": "Encounter",
+                "identifier": [{ "system": "sys", "value": "e1" }],
+                "subject": { "reference": "Patient?identifier=sys|1" }
+            },
+            "request": DeliveryMode::ConditionalCreate.request("Encounter", "identifier=sys|e1"),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_references_follow_the_view_strategy() {
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let host = spawn_server(written.clone()).await;
+
+        assert!(TargetDelivery::for_view(&view(ReferenceStrategy::Conditional), Some(&target(" "))).await.unwrap().is_none());
+
+        let conditional = TargetDelivery::for_view(&view(ReferenceStrategy::Conditional), Some(&target(&host))).await.unwrap().unwrap();
+        assert_eq!(conditional.send(&[encounter_entry()]).await.into_result().unwrap(), vec![DeliveryOutcome::Created]);
+
+        let resolving = TargetDelivery::for_view(&view(ReferenceStrategy::ResolveOrFail), Some(&target(&host))).await.unwrap().unwrap();
+        resolving.send(&[encounter_entry()]).await.into_result().unwrap();
+
+        let written = written.lock().unwrap();
+        assert_eq!(written[0]["subject"]["reference"], "Patient?identifier=sys|1");
+        assert_eq!(written[1]["subject"]["reference"], "Patient/p1");
+    }
+
+    #[tokio::test]
+    async fn test_send_reports_entries_accepted_before_a_refusal() {
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let host = spawn_server(written.clone()).await;
+        let delivery = TargetDelivery::for_view(&view(ReferenceStrategy::Conditional), Some(&target(&host))).await.unwrap().unwrap();
+
+        // The server has no Observation endpoint, so the second entry is refused
+        let refused = json!({
+            "resource": { "resourceType": "Observation", "identifier": [{ "system": "sys", "value": "o1" }] },
+            "request": DeliveryMode::ConditionalCreate.request("Observation", "identifier=sys|o1"),
+        });
+        let report = delivery.send(&[encounter_entry(), refused, encounter_entry()]).await;
+
+        assert_eq!(report.delivered(), 1);
+        assert_eq!(report.outcomes, vec![DeliveryOutcome::Created]);
+        assert!(report.error.is_some());
+        assert_eq!(written.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,120 @@
+// Bulk Data export - writes the FHIR resources of an export job as one NDJSON file per resource type
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use serde_json::{json, Value};
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+
+use crate::utils::AppError;
+use super::job::{ExportOutput, SyncJob};
+
+/// NDJSON files of one export job, written under `{output_dir}/{job_id}/`
+pub struct NdjsonExport {
+    job_id: String,
+    dir: PathBuf,
+}
+
+impl NdjsonExport {
+    pub fn new(output_dir: &str, job_id: &str) -> Self {
+        Self {
+            job_id: job_id.to_string(),
+            dir: Path::new(output_dir).join(job_id),
+        }
+    }
+
+    /// Remove the files of a previous run (the job starts again from the first page)
+    pub async fn clear(&self) -> Result<(), AppError> {
+        match fs::remove_dir_all(&self.dir).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(AppError::DatabaseError(format!("Falha ao limpar exportação {}: {}", self.dir.display(), e)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Append resources to the file of their type and count them in `output`
+    /// Returns the names of the files written
+    pub async fn append(&self, resources: &[Value], output: &mut Vec<ExportOutput>) -> Result<Vec<String>, AppError> {
+        let mut lines: BTreeMap<&str, String> = BTreeMap::new();
+        for resource in resources {
+            let resource_type = resource.get("resourceType").and_then(Value::as_str)
+                .filter(|resource_type| is_resource_type(resource_type))
+                .ok_or_else(|| AppError::Validation("Recurso exportado sem 'resourceType' válido".to_string()))?;
+            let line = serde_json::to_string(resource).map_err(|e| AppError::DatabaseError(e.to_string()))?;
+            let file = lines.entry(resource_type).or_default();
+            file.push_str(&line);
+            file.push('\n');
+        }
+
+        fs::create_dir_all(&self.dir).await
+            .map_err(|e| AppError::DatabaseError(format!("Falha ao criar {}: {}", self.dir.display(), e)))?;
+
+        let mut written = Vec::new();
+        for (resource_type, content) in lines {
+            let name = file_name(resource_type);
+            let mut file = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(self.dir.join(&name))
+                .await
+                .map_err(|e| AppError::DatabaseError(format!("Falha ao abrir {}: {}", name, e)))?;
+            file.write_all(content.as_bytes()).await
+                .map_err(|e| AppError::DatabaseError(format!("Falha ao escrever {}: {}", name, e)))?;
+
+            let count = content.lines().count() as u64;
+            match output.iter_mut().find(|entry| entry.resource_type == resource_type) {
+                Some(entry) => entry.count += count,
+                None => output.push(ExportOutput {
+                    resource_type: resource_type.to_string(),
+                    url: download_path(&self.job_id, resource_type),
+                    count,
+                }),
+            }
+            written.push(name);
+        }
+
+        Ok(written)
+    }
+}
+
+fn file_name(resource_type: &str) -> String {
+    format!("{}.ndjson", resource_type)
+}
+
+fn is_resource_type(resource_type: &str) -> bool {
+    !resource_type.is_empty() && resource_type.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// Download path of an exported file (GET /sync/export/:job_id/:file)
+pub fn download_path(job_id: &str, resource_type: &str) -> String {
+    format!("/sync/export/{}/{}", job_id, file_name(resource_type))
+}
+
+/// Location of a downloadable file, refusing names that are not `{ResourceType}.ndjson`
+pub fn file_path(output_dir: &str, job_id: &str, file: &str) -> Option<PathBuf> {
+    let resource_type = file.strip_suffix(".ndjson").filter(|resource_type| is_resource_type(resource_type))?;
+    if job_id.is_empty() || !job_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return None;
+    }
+    Some(Path::new(output_dir).join(job_id).join(file_name(resource_type)))
+}
+
+/// Bulk Data manifest of a finished export; `base_url` turns the download paths into absolute urls
+pub fn manifest(job: &SyncJob, base_url: &str) -> Value {
+    let output: Vec<Value> = job.output
+        .iter()
+        .map(|entry| json!({
+            "type": entry.resource_type,
+            "url": format!("{}{}", base_url.trim_end_matches('/'), entry.url),
+            "count": entry.count,
+        }))
+        .collect();
+
+    json!({
+        "transactionTime": job.started_at.unwrap_or(job.created_at).to_rfc3339(),
+        "request": format!("{}/sync/export", base_url.trim_end_matches('/')),
+        "requiresAccessToken": true,
+        "output": output,
+        "error": [],
+    })
+}
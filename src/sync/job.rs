@@ -17,6 +17,10 @@ pub struct SyncJob {
     
     /// Entity type (e.g., "encounter", "patient")
     pub entity_type: String,
+
+    /// Whether the job synchronizes the records or exports them as NDJSON files
+    #[serde(default)]
+    pub kind: JobKind,
     
     /// Company ID that owns this sync
     pub company_id: String,
@@ -41,6 +45,10 @@ pub struct SyncJob {
     
     /// Códigos dos itens que falharam (patient_code, encounter_code, etc)
     pub failed_item_codes: Vec<String>,
+
+    /// NDJSON files written by an export job, one per resource type
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub output: Vec<ExportOutput>,
     
     /// When the job started processing
     pub started_at: Option<DateTime<Utc>>,
@@ -75,6 +83,32 @@ pub enum JobStatus {
     Cancelled,
 }
 
+/// What a job does with the records it reads
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum JobKind {
+    /// Records are synchronized to the integration's destination
+    #[default]
+    Sync,
+
+    /// Records are transformed to FHIR and written as NDJSON files (Bulk Data export)
+    Export,
+}
+
+/// One NDJSON file of an export job, as listed in the Bulk Data manifest `output[]`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ExportOutput {
+    /// FHIR resource type of every line in the file
+    #[serde(rename = "type")]
+    pub resource_type: String,
+
+    /// Download path of the file, relative to the API host
+    pub url: String,
+
+    /// Number of resources in the file
+    pub count: u64,
+}
+
 /// Configuration to create a new synchronization job
 /// Client only needs to provide the view_id, everything else is fetched automatically
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -86,6 +120,10 @@ pub struct SyncJobConfig {
     /// Optional: number of records per page (default: 100)
     #[serde(rename = "pageSize")]
     pub page_size: Option<u64>,
+
+    /// Optional: export the records instead of synchronizing them (default: sync)
+    #[serde(default)]
+    pub kind: JobKind,
 }
 
 impl SyncJob {
@@ -96,6 +134,7 @@ impl SyncJob {
             database_config_id: String::new(), // Will be filled by manager
             database_view_id: config.database_view_id,
             entity_type,
+            kind: config.kind,
             company_id,
             status: JobStatus::Pending,
             total_records: None,
@@ -104,6 +143,7 @@ impl SyncJob {
             current_page: 0,
             page_size: config.page_size.unwrap_or(100),
             failed_item_codes: Vec::new(),
+            output: Vec::new(),
             started_at: None,
             finished_at: None,
             created_at: Utc::now(),
@@ -4,13 +4,13 @@ use tokio::sync::Semaphore;
 use tracing::{info, error};
 
 use crate::infrastructure::repositories::{
-    DatabaseConfigurationRepository, DatabaseViewRepository,
-    DatabaseViewMappingRepository, DatabaseTransformationRepository,
-    DatabaseTableRepository, SyncJobRepository,
+    DatabaseConfigurationRepository, DatabaseViewRepository, SyncJobRepository,
 };
 use crate::domain::entities::SyncJobDocument;
+use crate::infrastructure::factories::ConnectorFactory;
 use super::job::{SyncJob, SyncJobConfig};
 use super::status::SyncStatus;
+use super::store::{MongoSyncStore, SyncStore};
 use super::worker::SyncWorker;
 
 /// SyncManager orchestrates independent job execution
@@ -30,13 +30,16 @@ pub struct SyncManager {
     /// Public para permitir acesso pelo MetricsAggregator
     pub status: Arc<SyncStatus>,
     
+    /// Persistence handed to every worker
+    store: Arc<dyn SyncStore>,
+
+    /// Source connectors handed to every worker
+    connectors: ConnectorFactory,
+
     /// Repository references
     sync_job_repo: Arc<SyncJobRepository>,
     db_config_repo: Arc<DatabaseConfigurationRepository>,
     db_view_repo: Arc<DatabaseViewRepository>,
-    db_mapping_repo: Arc<DatabaseViewMappingRepository>,
-    db_transformation_repo: Arc<DatabaseTransformationRepository>,
-    db_table_repo: Arc<DatabaseTableRepository>,
 }
 
 impl SyncManager {
@@ -44,30 +47,34 @@ impl SyncManager {
     /// 
     /// # Arguments
     /// * `max_concurrent_jobs` - Maximum number of jobs running in parallel
+    /// * `store` - Persistence handed to every worker
     /// * Repository references for database access
     pub fn new(
         max_concurrent_jobs: usize,
+        store: MongoSyncStore,
         sync_job_repo: Arc<SyncJobRepository>,
         db_config_repo: Arc<DatabaseConfigurationRepository>,
         db_view_repo: Arc<DatabaseViewRepository>,
-        db_mapping_repo: Arc<DatabaseViewMappingRepository>,
-        db_transformation_repo: Arc<DatabaseTransformationRepository>,
-        db_table_repo: Arc<DatabaseTableRepository>,
     ) -> Self {
         info!("🚀 Initializing SyncManager with max {} concurrent jobs", max_concurrent_jobs);
         
         Self {
             semaphore: Arc::new(Semaphore::new(max_concurrent_jobs)),
             status: Arc::new(SyncStatus::new()),
+            store: Arc::new(store),
             sync_job_repo,
             db_config_repo,
             db_view_repo,
-            db_mapping_repo,
-            db_transformation_repo,
-            db_table_repo,
+            connectors: ConnectorFactory::default(),
         }
     }
 
+    /// Open job sources with the application's connector factory
+    pub fn with_connectors(mut self, connectors: ConnectorFactory) -> Self {
+        self.connectors = connectors;
+        self
+    }
+
     /// No initialization needed - jobs spawn on-demand
     /// Kept for backward compatibility but does nothing
     pub async fn start(&mut self) {
@@ -146,12 +153,8 @@ impl SyncManager {
         // Clone everything needed for the independent task
         let semaphore = Arc::clone(&self.semaphore);
         let status = Arc::clone(&self.status);
-        let sync_job_repo = Arc::clone(&self.sync_job_repo);
-        let db_config_repo = Arc::clone(&self.db_config_repo);
-        let db_view_repo = Arc::clone(&self.db_view_repo);
-        let db_mapping_repo = Arc::clone(&self.db_mapping_repo);
-        let db_transformation_repo = Arc::clone(&self.db_transformation_repo);
-        let db_table_repo = Arc::clone(&self.db_table_repo);
+        let store = Arc::clone(&self.store);
+        let connectors = self.connectors.clone();
         let job_clone = job.clone();
 
         // STEP 4: Spawn DEDICATED task for this job
@@ -166,13 +169,9 @@ impl SyncManager {
             let worker = SyncWorker::new(
                 format!("job-{}", job_clone.id),
                 status.clone(),
-                sync_job_repo.clone(),
-                db_config_repo,
-                db_view_repo,
-                db_mapping_repo,
-                db_transformation_repo,
-                db_table_repo,
-            );
+                store,
+            )
+            .with_connectors(connectors);
 
             // Process this ONE job
             let mut job_mut = job_clone;
@@ -203,12 +202,8 @@ impl SyncManager {
         // Clone everything needed for the independent task
         let semaphore = Arc::clone(&self.semaphore);
         let status = Arc::clone(&self.status);
-        let sync_job_repo = Arc::clone(&self.sync_job_repo);
-        let db_config_repo = Arc::clone(&self.db_config_repo);
-        let db_view_repo = Arc::clone(&self.db_view_repo);
-        let db_mapping_repo = Arc::clone(&self.db_mapping_repo);
-        let db_transformation_repo = Arc::clone(&self.db_transformation_repo);
-        let db_table_repo = Arc::clone(&self.db_table_repo);
+        let store = Arc::clone(&self.store);
+        let connectors = self.connectors.clone();
 
         // Spawn DEDICATED task for this job
         tokio::spawn(async move {
@@ -221,13 +216,9 @@ impl SyncManager {
             let worker = SyncWorker::new(
                 format!("job-{}", job.id),
                 status.clone(),
-                sync_job_repo.clone(),
-                db_config_repo,
-                db_view_repo,
-                db_mapping_repo,
-                db_transformation_repo,
-                db_table_repo,
-            );
+                store,
+            )
+            .with_connectors(connectors);
 
             // Process this ONE job
             worker.process_single_job(&mut job).await;
@@ -292,6 +283,7 @@ impl SyncManager {
             let config = SyncJobConfig {
                 database_view_id: job_doc.database_view_id.clone(),
                 page_size: Some(job_doc.page_size),
+                kind: job_doc.kind,
             };
             
             // Resubmit the job
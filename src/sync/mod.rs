@@ -1,10 +1,15 @@
 // Sync module - Handles parallel data synchronization from Oracle to FHIR
+pub mod delivery;
+pub mod export;
 pub mod job;
 pub mod status;
+pub mod store;
 pub mod worker;
 pub mod manager;
 
 pub use job::{SyncJob, SyncJobConfig, JobStatus};
+pub use delivery::TargetDelivery;
 pub use status::SyncStatus;
+pub use store::MongoSyncStore;
 pub use worker::SyncWorker;
 pub use manager::SyncManager;
@@ -0,0 +1,248 @@
+// Sync store - persistence boundary used by workers
+// MongoSyncStore backs production jobs; InMemorySyncStore lets tests run jobs without MongoDB
+use std::collections::HashMap;
+use std::sync::Arc;
+use async_trait::async_trait;
+#[cfg(test)]
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use crate::domain::entities::{
+    Company, DatabaseConfiguration, DatabaseTransformation, DatabaseView, DatabaseViewMapping, DeliveredResource, SyncJobDocument,
+    TargetIntegration,
+};
+use crate::infrastructure::repositories::{
+    CompanyRepository, DatabaseConfigurationRepository, DatabaseTransformationRepository, DatabaseViewMappingRepository, DatabaseViewRepository,
+    DeliveredResourceRepository, SyncJobRepository, TargetIntegrationRepository,
+};
+use crate::utils::AppError;
+use super::job::{JobKind, SyncJob};
+
+/// Everything a worker reads or writes outside of the source database
+#[async_trait]
+pub trait SyncStore: Send + Sync {
+    /// Fetch the database view (integration) a job synchronizes
+    async fn find_view(&self, view_id: &str) -> Result<Option<DatabaseView>, AppError>;
+
+    /// Fetch the source database configuration of a view
+    async fn find_configuration(&self, configuration_id: &str) -> Result<Option<DatabaseConfiguration>, AppError>;
+
+    /// Fetch the mappings of a view (used to refuse jobs whose mapped columns left the source)
+    async fn find_mappings(&self, view_id: &str) -> Result<Vec<DatabaseViewMapping>, AppError>;
+
+    /// Fetch the transformations referenced by the mappings (used when exporting FHIR resources)
+    async fn find_transformations(&self, transformation_ids: &[String]) -> Result<HashMap<String, DatabaseTransformation>, AppError>;
+
+    /// Fetch the company of a job (its timezone is applied to exported dates)
+    async fn find_company(&self, company_id: &str) -> Result<Option<Company>, AppError>;
+
+    /// Fetch the target integration of a view (its FHIR version shapes exported resources)
+    async fn find_target(&self, target_id: &str) -> Result<Option<TargetIntegration>, AppError>;
+
+    /// Persist the current progress and status of a job
+    async fn persist_job(&self, job: &SyncJob) -> Result<(), AppError>;
+
+    /// Record a resource accepted by the target server, served afterwards by the FHIR facade
+    async fn record_delivered(&self, delivered: DeliveredResource) -> Result<(), AppError>;
+}
+
+/// MongoDB-backed store used by the SyncManager
+pub struct MongoSyncStore {
+    sync_job_repo: Arc<SyncJobRepository>,
+    db_config_repo: Arc<DatabaseConfigurationRepository>,
+    db_view_repo: Arc<DatabaseViewRepository>,
+    db_mapping_repo: Arc<DatabaseViewMappingRepository>,
+    db_transformation_repo: Arc<DatabaseTransformationRepository>,
+    company_repo: Arc<CompanyRepository>,
+    target_repo: Arc<TargetIntegrationRepository>,
+    delivered_repo: Option<Arc<DeliveredResourceRepository>>,
+}
+
+impl MongoSyncStore {
+    pub fn new(
+        sync_job_repo: Arc<SyncJobRepository>,
+        db_config_repo: Arc<DatabaseConfigurationRepository>,
+        db_view_repo: Arc<DatabaseViewRepository>,
+        db_mapping_repo: Arc<DatabaseViewMappingRepository>,
+        db_transformation_repo: Arc<DatabaseTransformationRepository>,
+        company_repo: Arc<CompanyRepository>,
+        target_repo: Arc<TargetIntegrationRepository>,
+    ) -> Self {
+        Self {
+            sync_job_repo,
+            db_config_repo,
+            db_view_repo,
+            db_mapping_repo,
+            db_transformation_repo,
+            company_repo,
+            target_repo,
+            delivered_repo: None,
+        }
+    }
+
+    /// Record delivered resources for the FHIR facade
+    pub fn with_delivered_resources(mut self, delivered_repo: Arc<DeliveredResourceRepository>) -> Self {
+        self.delivered_repo = Some(delivered_repo);
+        self
+    }
+}
+
+#[async_trait]
+impl SyncStore for MongoSyncStore {
+    async fn find_view(&self, view_id: &str) -> Result<Option<DatabaseView>, AppError> {
+        self.db_view_repo.find_by_id(view_id).await
+    }
+
+    async fn find_configuration(&self, configuration_id: &str) -> Result<Option<DatabaseConfiguration>, AppError> {
+        self.db_config_repo.find_by_id(configuration_id).await
+    }
+
+    async fn find_mappings(&self, view_id: &str) -> Result<Vec<DatabaseViewMapping>, AppError> {
+        self.db_mapping_repo.find_by_data_view_id(view_id).await
+    }
+
+    async fn find_transformations(&self, transformation_ids: &[String]) -> Result<HashMap<String, DatabaseTransformation>, AppError> {
+        let mut transformations = HashMap::new();
+        for transformation_id in transformation_ids {
+            if let Some(transformation) = self.db_transformation_repo.find_by_id(transformation_id).await? {
+                transformations.insert(transformation_id.clone(), transformation);
+            }
+        }
+        Ok(transformations)
+    }
+
+    async fn find_company(&self, company_id: &str) -> Result<Option<Company>, AppError> {
+        self.company_repo.find_by_id(company_id).await
+    }
+
+    async fn find_target(&self, target_id: &str) -> Result<Option<TargetIntegration>, AppError> {
+        self.target_repo.find_by_id(target_id).await
+    }
+
+    async fn persist_job(&self, job: &SyncJob) -> Result<(), AppError> {
+        // Find existing job document in MongoDB
+        let Some(mut job_doc) = self.sync_job_repo.find_by_job_id(&job.id).await? else {
+            warn!("Job {} not found in MongoDB, cannot update", job.id);
+            return Ok(());
+        };
+
+        // Update document with current job status
+        job_doc.update_from_memory_job(job);
+        self.sync_job_repo.update(&job_doc).await?;
+        info!("💾 Job {} status persisted to MongoDB", job.id);
+
+        // Exports do not change the integration status
+        if job.kind == JobKind::Export {
+            return Ok(());
+        }
+
+        // Atualizar status da integração (DatabaseView) baseado no status do job
+        let job_status = SyncJobDocument::convert_status(&job.status);
+        self.db_view_repo
+            .update_status_from_job(&job.database_view_id, &job.id, &job_status)
+            .await?;
+        info!("🔄 Integration {} status updated to match job status", job.database_view_id);
+
+        Ok(())
+    }
+
+    async fn record_delivered(&self, delivered: DeliveredResource) -> Result<(), AppError> {
+        if let Some(delivered_repo) = &self.delivered_repo {
+            delivered_repo.record(delivered).await?;
+        }
+        Ok(())
+    }
+}
+
+/// In-memory store: views and configurations are registered up front, job snapshots are kept in a map
+#[cfg(test)]
+#[derive(Default)]
+pub struct InMemorySyncStore {
+    views: RwLock<HashMap<String, DatabaseView>>,
+    configurations: RwLock<HashMap<String, DatabaseConfiguration>>,
+    mappings: RwLock<HashMap<String, Vec<DatabaseViewMapping>>>,
+    transformations: RwLock<HashMap<String, DatabaseTransformation>>,
+    companies: RwLock<HashMap<String, Company>>,
+    targets: RwLock<HashMap<String, TargetIntegration>>,
+    jobs: RwLock<HashMap<String, SyncJob>>,
+    delivered: RwLock<Vec<DeliveredResource>>,
+}
+
+#[cfg(test)]
+impl InMemorySyncStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn insert_view(&self, view_id: &str, view: DatabaseView) {
+        self.views.write().await.insert(view_id.to_string(), view);
+    }
+
+    pub async fn insert_configuration(&self, configuration_id: &str, configuration: DatabaseConfiguration) {
+        self.configurations.write().await.insert(configuration_id.to_string(), configuration);
+    }
+
+    pub async fn insert_mappings(&self, view_id: &str, mappings: Vec<DatabaseViewMapping>) {
+        self.mappings.write().await.insert(view_id.to_string(), mappings);
+    }
+
+    pub async fn insert_company(&self, company_id: &str, company: Company) {
+        self.companies.write().await.insert(company_id.to_string(), company);
+    }
+
+    pub async fn insert_target(&self, target_id: &str, target: TargetIntegration) {
+        self.targets.write().await.insert(target_id.to_string(), target);
+    }
+
+    /// Last persisted snapshot of a job
+    pub async fn get_job(&self, job_id: &str) -> Option<SyncJob> {
+        self.jobs.read().await.get(job_id).cloned()
+    }
+
+    /// Resources recorded as delivered, in delivery order
+    pub async fn delivered(&self) -> Vec<DeliveredResource> {
+        self.delivered.read().await.clone()
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl SyncStore for InMemorySyncStore {
+    async fn find_view(&self, view_id: &str) -> Result<Option<DatabaseView>, AppError> {
+        Ok(self.views.read().await.get(view_id).cloned())
+    }
+
+    async fn find_configuration(&self, configuration_id: &str) -> Result<Option<DatabaseConfiguration>, AppError> {
+        Ok(self.configurations.read().await.get(configuration_id).cloned())
+    }
+
+    async fn find_mappings(&self, view_id: &str) -> Result<Vec<DatabaseViewMapping>, AppError> {
+        Ok(self.mappings.read().await.get(view_id).cloned().unwrap_or_default())
+    }
+
+    async fn find_transformations(&self, transformation_ids: &[String]) -> Result<HashMap<String, DatabaseTransformation>, AppError> {
+        let transformations = self.transformations.read().await;
+        Ok(transformation_ids
+            .iter()
+            .filter_map(|id| transformations.get(id).map(|transformation| (id.clone(), transformation.clone())))
+            .collect())
+    }
+
+    async fn find_company(&self, company_id: &str) -> Result<Option<Company>, AppError> {
+        Ok(self.companies.read().await.get(company_id).cloned())
+    }
+
+    async fn find_target(&self, target_id: &str) -> Result<Option<TargetIntegration>, AppError> {
+        Ok(self.targets.read().await.get(target_id).cloned())
+    }
+
+    async fn persist_job(&self, job: &SyncJob) -> Result<(), AppError> {
+        self.jobs.write().await.insert(job.id.clone(), job.clone());
+        Ok(())
+    }
+
+    async fn record_delivered(&self, delivered: DeliveredResource) -> Result<(), AppError> {
+        self.delivered.write().await.push(delivered);
+        Ok(())
+    }
+}
@@ -3,20 +3,45 @@
 use std::sync::Arc;
 use std::collections::HashMap;
 use tracing::{info, error, warn};
+use chrono_tz::Tz;
 use serde_json::Value;
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
+use tokio::time::Duration;
 
-use crate::infrastructure::adapters::oracledb::OracleConnector;
-use crate::infrastructure::repositories::{
-    DatabaseConfigurationRepository, DatabaseViewRepository,
-    DatabaseViewMappingRepository, DatabaseTransformationRepository,
-    DatabaseTableRepository, SyncJobRepository,
+use crate::application::usecases::SyncUseCase;
+use crate::domain::entities::{
+    missing_mapped_columns, DatabaseTransformation, DatabaseView, DatabaseViewMapping, DeliveredResource, TargetIntegration,
 };
-use crate::domain::entities::SyncJobDocument;
-use crate::utils::AppError;
-use super::job::SyncJob;
+use crate::domain::fhir::FhirVersion;
+use crate::infrastructure::factories::{record_to_string_map, ConnectorFactory};
+use crate::utils::{date_format, AppError};
+use super::delivery::TargetDelivery;
+use super::export::NdjsonExport;
+use super::job::{JobKind, SyncJob};
 use super::status::SyncStatus;
+use super::store::SyncStore;
+
+/// Default folder where synchronized records and exported NDJSON files are written
+pub(crate) const DEFAULT_OUTPUT_DIR: &str = "sync_data_fhir_test";
+
+/// What a job needs to turn records into FHIR resources
+struct TransformContext {
+    mappings: Vec<DatabaseViewMapping>,
+    transformations: HashMap<String, DatabaseTransformation>,
+    version: FhirVersion,
+    timezone: Tz,
+}
+
+/// Where the records of a job go
+enum JobOutput {
+    /// Raw records as JSON files (sync jobs of views without a target server)
+    Files,
+    /// FHIR resources appended to NDJSON files (export jobs)
+    Export(TransformContext, NdjsonExport),
+    /// FHIR entries sent to the view's target server (sync jobs of views with one)
+    Target(TransformContext, Box<TargetDelivery>),
+}
 
 /// Worker that processes synchronization jobs
 /// Each worker runs in its own Tokio task (async thread)
@@ -27,15 +52,17 @@ pub struct SyncWorker {
     /// Shared status tracker - all workers write to the same status
     status: Arc<SyncStatus>,
     
-    /// Sync job repository for MongoDB persistence
-    sync_job_repo: Arc<SyncJobRepository>,
-    
-    /// Database repositories to fetch configurations
-    db_config_repo: Arc<DatabaseConfigurationRepository>,
-    db_view_repo: Arc<DatabaseViewRepository>,
-    db_mapping_repo: Arc<DatabaseViewMappingRepository>,
-    db_transformation_repo: Arc<DatabaseTransformationRepository>,
-    db_table_repo: Arc<DatabaseTableRepository>,
+    /// Persistence for views, configurations and job progress
+    store: Arc<dyn SyncStore>,
+
+    /// Folder where synchronized records are written
+    output_dir: String,
+
+    /// Pause between pages to avoid overloading the source
+    page_delay: Duration,
+
+    /// Opens the view's source
+    connectors: ConnectorFactory,
 }
 
 impl SyncWorker {
@@ -43,24 +70,37 @@ impl SyncWorker {
     pub fn new(
         worker_id: String,
         status: Arc<SyncStatus>,
-        sync_job_repo: Arc<SyncJobRepository>,
-        db_config_repo: Arc<DatabaseConfigurationRepository>,
-        db_view_repo: Arc<DatabaseViewRepository>,
-        db_mapping_repo: Arc<DatabaseViewMappingRepository>,
-        db_transformation_repo: Arc<DatabaseTransformationRepository>,
-        db_table_repo: Arc<DatabaseTableRepository>,
+        store: Arc<dyn SyncStore>,
     ) -> Self {
         Self {
             worker_id,
             status,
-            sync_job_repo,
-            db_config_repo,
-            db_view_repo,
-            db_mapping_repo,
-            db_transformation_repo,
-            db_table_repo,
+            store,
+            output_dir: DEFAULT_OUTPUT_DIR.to_string(),
+            page_delay: Duration::from_secs(5),
+            connectors: ConnectorFactory::default(),
         }
     }
+
+    /// Open sources with the application's connector factory
+    pub fn with_connectors(mut self, connectors: ConnectorFactory) -> Self {
+        self.connectors = connectors;
+        self
+    }
+
+    /// Write synchronized records under a different folder
+    #[cfg(test)]
+    pub fn with_output_dir(mut self, output_dir: impl Into<String>) -> Self {
+        self.output_dir = output_dir.into();
+        self
+    }
+
+    /// Change the pause between pages
+    #[cfg(test)]
+    pub fn with_page_delay(mut self, page_delay: Duration) -> Self {
+        self.page_delay = page_delay;
+        self
+    }
     
     /// Verifica se deve simular falha baseado na taxa configurada no .env
     /// 
@@ -189,49 +229,60 @@ impl SyncWorker {
     /// This is the "brain" of the worker!
     async fn process_job(&self, job: &mut SyncJob) -> Result<(), AppError> {
         // STEP 1: Fetch database view configuration
-        let db_view = self.db_view_repo
-            .find_by_id(&job.database_view_id)
+        let db_view = self.store
+            .find_view(&job.database_view_id)
             .await?
             .ok_or_else(|| AppError::NotFound(
                 format!("DatabaseView {} not found", job.database_view_id)
             ))?;
         
         // STEP 2: Fetch database connection configuration
-        let db_config = self.db_config_repo
-            .find_by_id(&db_view.database_configuration_id)
+        let db_config = self.store
+            .find_configuration(&db_view.database_configuration_id)
             .await?
             .ok_or_else(|| AppError::NotFound(
                 format!("DatabaseConfiguration {} not found", db_view.database_configuration_id)
             ))?;
 
-        let username = db_config.username.as_ref().ok_or_else(|| AppError::BadRequest("Database username is required".to_string()))?;
-        let password = db_config.password.as_ref().ok_or_else(|| AppError::BadRequest("Database password is required".to_string()))?;
-        let port = db_config.port.ok_or_else(|| AppError::BadRequest("Database port is required".to_string()))?;
-        let database = db_config.database.as_ref().ok_or_else(|| AppError::BadRequest("Database name is required".to_string()))?;
-
         info!(
-            "[{}] Connecting to Oracle: {}@{}:{}",
-            self.worker_id, username, db_config.host, port
+            "[{}] Connecting to {} source at {}",
+            self.worker_id, db_config.db_type, db_config.host
         );
 
-        // STEP 3: Connect to client's Oracle database
-        let connection_string = format!(
-            "oracle://{}:{}@{}:{}/{}",
-            username,
-            password,
-            db_config.host,
-            port,
-            database
-        );
-        
-        let oracle_connector = OracleConnector::new(&connection_string).await?;
+        // STEP 3: Connect to client's database
+        let mut source = self.connectors.create_source(&db_config).await?;
 
         // STEP 4: Get table name
-        let table_name = format!("{}_INTERHEALTH", db_view.entity_type.to_uppercase());
+        let table_name = db_view.source_table_name();
+
+        // STEP 4.1: Refuse the job when mappings read columns the source no longer has
+        // (otherwise the records silently come out with empty fields)
+        let mappings = self.store.find_mappings(&job.database_view_id).await?;
+        if !mappings.is_empty() {
+            let live_columns: Vec<String> = source
+                .introspect(&table_name)
+                .await?
+                .iter()
+                .filter_map(|column| column.get("name").and_then(|v| v.as_str()).map(str::to_string))
+                .collect();
 
-        // STEP 5: Count total records using connector directly
+            let missing = missing_mapped_columns(&live_columns, &mappings);
+            if !live_columns.is_empty() && !missing.is_empty() {
+                source.close().await?;
+                return Err(AppError::Validation(format!(
+                    "Schema drift em {}: colunas mapeadas não existem mais na origem: {}",
+                    table_name,
+                    missing.join(", ")
+                )));
+            }
+        }
+
+        // STEP 4.2: Export jobs and deliveries to a target server transform each record with the view's mappings
+        let output = self.prepare_output(job, &db_view, mappings).await?;
+
+        // STEP 5: Count total records in the source
         info!("[{}] Counting records in table {}", self.worker_id, table_name);
-        let total_records = oracle_connector.count_records(&table_name).await?;
+        let total_records = source.count(&table_name).await?;
         job.total_records = Some(total_records);
         
         // Update job in memory with total_records (importante para cálculo de progresso)
@@ -247,6 +298,7 @@ impl SyncWorker {
         // If no records, we're done
         if total_records == 0 {
             warn!("[{}] No records to synchronize", self.worker_id);
+            source.close().await?;
             return Ok(());
         }
 
@@ -290,6 +342,7 @@ impl SyncWorker {
                     
                     // Salvar estado atual no MongoDB antes de parar
                     self.persist_job_status(job).await;
+                    source.close().await?;
                     
                     return Ok(()); // Sair do processamento
                 }
@@ -303,8 +356,8 @@ impl SyncWorker {
                 job.id
             );
 
-            // Fetch one page of data from Oracle using connector directly
-            let records = oracle_connector.fetch_page_data(&table_name, page, job.page_size).await?;
+            // Fetch one page of data from the source
+            let records = source.fetch_page(&table_name, page, job.page_size).await?;
             let records_count = records.len();
 
             info!(
@@ -349,7 +402,12 @@ impl SyncWorker {
                     job.failed_records += 1;
                 } else {
                     // Save record to file using GLOBAL index to avoid overwriting
-                    match self.save_record_to_file(&job.id, &job.entity_type, global_record_index as usize, record).await {
+                    let saved = match &output {
+                        JobOutput::Files => self.save_record_to_file(&job.id, &job.entity_type, global_record_index as usize, record).await,
+                        JobOutput::Export(context, files) => self.export_record(context, files, job, record).await,
+                        JobOutput::Target(context, delivery) => self.deliver_record(context, delivery, job, record).await,
+                    };
+                    match saved {
                         Ok(file_path) => {
                             info!(
                                 "[{}] ✅ Record {} (page {}, local {}) saved to: {}",
@@ -401,25 +459,164 @@ impl SyncWorker {
                 j.current_page = job.current_page;
                 j.processed_records = job.processed_records;
                 j.failed_records = job.failed_records;
+                j.output = job.output.clone();
             }).await;
 
             // 📍 Persist progress to MongoDB after each page
             info!("[{}] 📍 Saving progress at page {}", self.worker_id, page + 1);
             self.persist_job_status(job).await;
 
-            // Small delay between pages to avoid overloading Oracle
-            // tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-            tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+            // Small delay between pages to avoid overloading the source
+            tokio::time::sleep(self.page_delay).await;
         }
 
+        source.close().await?;
+
         Ok(())
     }
 
+    /// Decide where the job's records go: export files, the view's target server or raw JSON files
+    async fn prepare_output(
+        &self,
+        job: &mut SyncJob,
+        db_view: &DatabaseView,
+        mappings: Vec<DatabaseViewMapping>,
+    ) -> Result<JobOutput, AppError> {
+        let target = match db_view.target_integration_id.as_deref() {
+            Some(target_id) => self.store.find_target(target_id).await?,
+            None => None,
+        };
+
+        match job.kind {
+            JobKind::Export => {
+                let context = self.prepare_transform(job, mappings, target.as_ref()).await?;
+
+                // A job starting from the first page discards previous files
+                let files = NdjsonExport::new(&self.output_dir, &job.id);
+                if job.current_page == 0 {
+                    files.clear().await?;
+                    job.output.clear();
+                }
+                Ok(JobOutput::Export(context, files))
+            }
+            JobKind::Sync => match TargetDelivery::for_view(db_view, target.as_ref()).await? {
+                Some(delivery) => {
+                    let context = self.prepare_transform(job, mappings, target.as_ref()).await?;
+                    Ok(JobOutput::Target(context, Box::new(delivery)))
+                }
+                None => Ok(JobOutput::Files),
+            },
+        }
+    }
+
+    /// Load the mappings, transformations, FHIR version and timezone used to transform records
+    async fn prepare_transform(
+        &self,
+        job: &SyncJob,
+        mappings: Vec<DatabaseViewMapping>,
+        target: Option<&TargetIntegration>,
+    ) -> Result<TransformContext, AppError> {
+        if mappings.is_empty() {
+            return Err(AppError::Validation(format!(
+                "Integração {} não possui mapeamentos para gerar recursos FHIR",
+                job.database_view_id
+            )));
+        }
+
+        let mut transformation_ids: Vec<String> = mappings
+            .iter()
+            .flat_map(|mapping| &mapping.field_mappings)
+            .filter_map(|field_mapping| field_mapping.transformation_id.clone())
+            .filter(|id| !id.is_empty())
+            .collect();
+        transformation_ids.sort();
+        transformation_ids.dedup();
+        let transformations = self.store.find_transformations(&transformation_ids).await?;
+
+        let timezone = self.store
+            .find_company(&job.company_id)
+            .await?
+            .map(|company| company.timezone())
+            .unwrap_or(date_format::DEFAULT_TIMEZONE);
+
+        // Same version as the HL7 pipeline: the view's target integration, R4 without one
+        let version = FhirVersion::for_target(target.and_then(|target| target.version.as_deref()));
+
+        Ok(TransformContext {
+            mappings: mappings
+                .into_iter()
+                .map(|mapping| SyncUseCase::for_version(mapping, version))
+                .collect(),
+            transformations,
+            version,
+            timezone,
+        })
+    }
+
+    /// Transform a record to FHIR and append the resources to the export files
+    async fn export_record(
+        &self,
+        context: &TransformContext,
+        files: &NdjsonExport,
+        job: &mut SyncJob,
+        record: &Value,
+    ) -> Result<String, AppError> {
+        let resources = SyncUseCase::transform_record(
+            &context.mappings,
+            &context.transformations,
+            &record_to_string_map(record),
+            context.version,
+            context.timezone,
+        )?;
+        let written = files.append(&resources, &mut job.output).await?;
+        Ok(written.join(", "))
+    }
+
+    /// Transform a record to FHIR entries, send them to the view's target server and record
+    /// the delivered resources for the FHIR facade
+    async fn deliver_record(
+        &self,
+        context: &TransformContext,
+        delivery: &TargetDelivery,
+        job: &SyncJob,
+        record: &Value,
+    ) -> Result<String, AppError> {
+        let entries = SyncUseCase::transform_record_entries(
+            &context.mappings,
+            &context.transformations,
+            &record_to_string_map(record),
+            context.version,
+            context.timezone,
+            delivery.mode(),
+        )?;
+        let report = delivery.send(&entries).await;
+
+        // Entries the server accepted before a refusal are recorded all the same
+        for entry in &entries[..report.delivered()] {
+            let resource = SyncUseCase::entry_resource(entry.clone());
+            let Some(delivered) = DeliveredResource::from_resource(&job.company_id, &job.database_view_id, context.version, &resource) else {
+                continue;
+            };
+            if let Err(e) = self.store.record_delivered(delivered).await {
+                warn!("[{}] Falha ao registrar recurso entregue do job {}: {}", self.worker_id, job.id, e);
+            }
+        }
+        let outcomes = report.into_result()?;
+
+        Ok(entries
+            .iter()
+            .zip(outcomes)
+            .map(|(entry, outcome)| {
+                let resource_type = entry.pointer("/resource/resourceType").and_then(Value::as_str).unwrap_or_default();
+                format!("{} {:?}", resource_type, outcome)
+            })
+            .collect::<Vec<_>>()
+            .join(", "))
+    }
+
     /// Saves a record to JSON file in test/ folder
-    /// Persists job status to MongoDB
+    /// Persists job status to the sync store
     async fn persist_job_status(&self, job: &SyncJob) {
-        use chrono::Utc;
-        
         // PASSO 1: Atualizar memória PRIMEIRO (para métricas em tempo real)
         self.status.update_job_progress(
             &job.id,
@@ -428,34 +625,9 @@ impl SyncWorker {
             job.current_page,
         ).await;
         
-        // PASSO 2: Persistir no MongoDB (backup durável)
-        // Find existing job document in MongoDB
-        match self.sync_job_repo.find_by_job_id(&job.id).await {
-            Ok(Some(mut job_doc)) => {
-                // Update document with current job status
-                job_doc.update_from_memory_job(job);
-                
-                // Save to MongoDB
-                if let Err(e) = self.sync_job_repo.update(&job_doc).await {
-                    error!("[{}] Failed to persist job {} to MongoDB: {}", self.worker_id, job.id, e);
-                } else {
-                    info!("[{}] 💾 Job {} status persisted to MongoDB", self.worker_id, job.id);
-                }
-                
-                // PASSO 3: Atualizar status da integração (DatabaseView) baseado no status do job
-                let job_status = SyncJobDocument::convert_status(&job.status);
-                if let Err(e) = self.db_view_repo.update_status_from_job(&job.database_view_id, &job.id, &job_status).await {
-                    error!("[{}] Failed to update integration status for view {}: {}", self.worker_id, job.database_view_id, e);
-                } else {
-                    info!("[{}] 🔄 Integration {} status updated to match job status", self.worker_id, job.database_view_id);
-                }
-            },
-            Ok(None) => {
-                warn!("[{}] Job {} not found in MongoDB, cannot update", self.worker_id, job.id);
-            },
-            Err(e) => {
-                error!("[{}] Failed to fetch job {} from MongoDB: {}", self.worker_id, job.id, e);
-            }
+        // PASSO 2: Persistir no store (backup durável)
+        if let Err(e) = self.store.persist_job(job).await {
+            error!("[{}] Failed to persist job {}: {}", self.worker_id, job.id, e);
         }
     }
     
@@ -469,7 +641,7 @@ impl SyncWorker {
     ) -> Result<String, AppError> {
         
         // Create test directory if it doesn't exist
-        let test_dir = "sync_data_fhir_test";
+        let test_dir = &self.output_dir;
         fs::create_dir_all(test_dir).await
             .map_err(|e| AppError::InternalServerError)?;
 
@@ -500,3 +672,390 @@ impl SyncWorker {
         Ok(filename)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use serde_json::json;
+    use crate::domain::entities::{
+        Company, DatabaseConfiguration, DatabaseView, DatabaseViewMapping, DeliveryMode, FieldMapping, TargetIntegration,
+    };
+    use crate::infrastructure::adapters::FixtureRegistry;
+    use crate::sync::job::{JobKind, JobStatus, SyncJobConfig};
+    use crate::sync::store::InMemorySyncStore;
+
+    fn fixture_configuration(dataset: &str) -> DatabaseConfiguration {
+        DatabaseConfiguration {
+            id: None,
+            name: "Fixture".to_string(),
+            db_type: "FIXTURE".to_string(),
+            version: None,
+            host: String::new(),
+            port: None,
+            database: Some(dataset.to_string()),
+            username: None,
+            password: None,
+            auth_type: None,
+            credentials: None,
+            api_source: None,
+            oracle: None,
+            sqlserver: None,
+            company_id: "company".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn company(timezone: &str) -> Company {
+        Company {
+            id: None,
+            code: "1".to_string(),
+            name: "Company".to_string(),
+            cnpj: String::new(),
+            address: None,
+            number: None,
+            phone: None,
+            email: None,
+            city: None,
+            state: None,
+            zipcode: None,
+            country: None,
+            status: true,
+            timezone: Some(timezone.to_string()),
+            hl7_facility: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn patient_view() -> DatabaseView {
+        DatabaseView {
+            id: None,
+            name: "Patients".to_string(),
+            description: String::new(),
+            resource: None,
+            entity_type: "patient".to_string(),
+            main_resource: None,
+            is_fhir_destination: Some(true),
+            is_interhealth_destination: None,
+            database_configuration_id: "config-1".to_string(),
+            company_id: "company".to_string(),
+            target_integration_id: None,
+            message_types: None,
+            delivery_mode: None,
+            reference_strategy: None,
+            status: "pending".to_string(),
+            job_id: None,
+            resources: None,
+            started_at: None,
+            cancelled_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn patient_mapping(field_origins: &[&str]) -> DatabaseViewMapping {
+        DatabaseViewMapping {
+            id: None,
+            name: "Patient mapping".to_string(),
+            description: String::new(),
+            entity_type: "PATIENT".to_string(),
+            resource: None,
+            database_table_origin_id: String::new(),
+            database_table_destiny_id: String::new(),
+            data_view_id: "view-1".to_string(),
+            field_mappings: field_origins
+                .iter()
+                .map(|origin| FieldMapping {
+                    field_origin: origin.to_string(),
+                    field_destiny: format!("Patient.{}", origin),
+                    description: None,
+                    reference_destiny: None,
+                    relationship_destiny: None,
+                    reference_identifier_system: None,
+                    data_type: "string".to_string(),
+                    is_nullable: true,
+                    min_length: 0,
+                    max_length: 0,
+                    is_enumerable: false,
+                    transformation_id: None,
+                    reference: None,
+                    expression: None,
+                    time_origin: None,
+                })
+                .collect(),
+            status: "active".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn target_integration(host: &str, version: Option<&str>) -> TargetIntegration {
+        TargetIntegration {
+            id: None,
+            name: "FHIR server".to_string(),
+            version: version.map(str::to_string),
+            host: host.to_string(),
+            auth_type: None,
+            credentials: None,
+            client_certificate: None,
+            capabilities: None,
+            company_id: "company".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// FHIR server answering every write with 201 and recording `METHOD /path?query`
+    async fn spawn_fhir_server() -> (String, Arc<std::sync::Mutex<Vec<String>>>) {
+        use axum::{extract::State, http::{Method, StatusCode, Uri}, Router};
+
+        async fn write(State(requests): State<Arc<std::sync::Mutex<Vec<String>>>>, method: Method, uri: Uri) -> StatusCode {
+            requests.lock().unwrap().push(format!("{} {}", method, uri).replace("%7C", "|"));
+            StatusCode::CREATED
+        }
+
+        let requests = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let app = Router::new().fallback(write).with_state(requests.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (format!("http://{}", address), requests)
+    }
+
+    async fn run_export(dataset: &str, page_size: u64, mappings: Vec<DatabaseViewMapping>) -> (SyncJob, std::path::PathBuf) {
+        run_export_with(patient_view(), None, dataset, page_size, mappings).await
+    }
+
+    async fn run_export_with(
+        mut view: DatabaseView,
+        target: Option<TargetIntegration>,
+        dataset: &str,
+        page_size: u64,
+        mappings: Vec<DatabaseViewMapping>,
+    ) -> (SyncJob, std::path::PathBuf) {
+        let store = Arc::new(InMemorySyncStore::new());
+        if let Some(target) = target {
+            view.target_integration_id = Some("target-1".to_string());
+            store.insert_target("target-1", target).await;
+        }
+        let entity_type = view.entity_type.to_uppercase();
+        store.insert_company("company", company("UTC")).await;
+        store.insert_view("view-1", view).await;
+        store.insert_configuration("config-1", fixture_configuration(dataset)).await;
+        store.insert_mappings("view-1", mappings).await;
+
+        let output_dir = std::env::temp_dir().join(format!("interhealth-export-{}", uuid::Uuid::new_v4()));
+        let worker = SyncWorker::new("worker-test".to_string(), Arc::new(SyncStatus::new()), store)
+            .with_output_dir(output_dir.to_string_lossy().to_string())
+            .with_page_delay(Duration::ZERO);
+
+        let mut job = SyncJob::new(
+            SyncJobConfig { database_view_id: "view-1".to_string(), page_size: Some(page_size), kind: JobKind::Export },
+            entity_type,
+            "company".to_string(),
+        );
+        worker.process_single_job(&mut job).await;
+
+        (job, output_dir)
+    }
+
+    async fn run_job(dataset: &str, page_size: u64) -> (SyncJob, Arc<InMemorySyncStore>, std::path::PathBuf) {
+        run_job_with_mappings(dataset, page_size, Vec::new()).await
+    }
+
+    async fn run_job_with_mappings(
+        dataset: &str,
+        page_size: u64,
+        mappings: Vec<DatabaseViewMapping>,
+    ) -> (SyncJob, Arc<InMemorySyncStore>, std::path::PathBuf) {
+        run_sync_with(patient_view(), None, dataset, page_size, mappings).await
+    }
+
+    async fn run_sync_with(
+        mut view: DatabaseView,
+        target: Option<TargetIntegration>,
+        dataset: &str,
+        page_size: u64,
+        mappings: Vec<DatabaseViewMapping>,
+    ) -> (SyncJob, Arc<InMemorySyncStore>, std::path::PathBuf) {
+        let store = Arc::new(InMemorySyncStore::new());
+        if let Some(target) = target {
+            view.target_integration_id = Some("target-1".to_string());
+            store.insert_target("target-1", target).await;
+        }
+        store.insert_view("view-1", view).await;
+        store.insert_configuration("config-1", fixture_configuration(dataset)).await;
+        store.insert_mappings("view-1", mappings).await;
+
+        let output_dir = std::env::temp_dir().join(format!("interhealth-sync-{}", uuid::Uuid::new_v4()));
+        let worker = SyncWorker::new("worker-test".to_string(), Arc::new(SyncStatus::new()), store.clone())
+            .with_output_dir(output_dir.to_string_lossy().to_string())
+            .with_page_delay(Duration::ZERO);
+
+        let mut job = SyncJob::new(
+            SyncJobConfig { database_view_id: "view-1".to_string(), page_size: Some(page_size), kind: JobKind::Sync },
+            "PATIENT".to_string(),
+            "company".to_string(),
+        );
+        worker.process_single_job(&mut job).await;
+
+        (job, store, output_dir)
+    }
+
+    #[tokio::test]
+    async fn test_job_syncs_every_fixture_row() {
+        let rows = (1..=5)
+            .map(|code| json!({ "PATIENT_CODE": code.to_string(), "NAME": format!("Patient {}", code) }))
+            .collect();
+        FixtureRegistry::register("worker-e2e", "PATIENT_INTERHEALTH", rows);
+
+        let (job, store, output_dir) = run_job("worker-e2e", 2).await;
+
+        assert_eq!(job.status, JobStatus::Completed);
+        assert_eq!(job.total_records, Some(5));
+        assert_eq!(job.processed_records, 5);
+        assert_eq!(job.failed_records, 0);
+        assert_eq!(job.current_page, 3);
+
+        let persisted = store.get_job(&job.id).await.expect("job persisted");
+        assert_eq!(persisted.status, JobStatus::Completed);
+
+        let written = std::fs::read_to_string(output_dir.join(&job.id).join("patient_0004.json")).unwrap();
+        let record: Value = serde_json::from_str(&written).unwrap();
+        assert_eq!(record["patient_code"], "5");
+
+        std::fs::remove_dir_all(output_dir).ok();
+        FixtureRegistry::remove("worker-e2e");
+    }
+
+    #[tokio::test]
+    async fn test_export_writes_ndjson_per_resource_type() {
+        let rows = (1..=3)
+            .map(|code| json!({ "PATIENT_CODE": code.to_string(), "NAME": format!("Patient {}", code) }))
+            .collect();
+        FixtureRegistry::register("worker-export", "PATIENT_INTERHEALTH", rows);
+
+        let mut mapping = patient_mapping(&["patient_code", "name"]);
+        mapping.field_mappings[0].field_destiny = "identifier[0].value".to_string();
+        mapping.field_mappings[1].field_destiny = "name[0].text".to_string();
+        let (job, output_dir) = run_export("worker-export", 2, vec![mapping]).await;
+
+        assert_eq!(job.status, JobStatus::Completed);
+        assert_eq!(job.processed_records, 3);
+        assert_eq!(job.output.len(), 1);
+        assert_eq!(job.output[0].resource_type, "Patient");
+        assert_eq!(job.output[0].count, 3);
+        assert_eq!(job.output[0].url, format!("/sync/export/{}/Patient.ndjson", job.id));
+
+        let written = std::fs::read_to_string(output_dir.join(&job.id).join("Patient.ndjson")).unwrap();
+        let resources: Vec<Value> = written.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(resources.len(), 3);
+        assert!(resources.iter().all(|resource| resource["resourceType"] == "Patient"));
+        assert_eq!(resources[2]["identifier"][0]["value"], "3");
+        assert_eq!(resources[2]["name"][0]["text"], "Patient 3");
+
+        let manifest = crate::sync::export::manifest(&job, "http://api");
+        assert_eq!(manifest["output"][0]["url"], format!("http://api/sync/export/{}/Patient.ndjson", job.id));
+        assert_eq!(manifest["output"][0]["count"], 3);
+
+        let (job, _) = run_export("worker-export", 2, Vec::new()).await;
+        assert_eq!(job.status, JobStatus::Failed);
+
+        std::fs::remove_dir_all(output_dir).ok();
+        FixtureRegistry::remove("worker-export");
+    }
+
+    #[tokio::test]
+    async fn test_export_follows_target_fhir_version() {
+        FixtureRegistry::register("worker-export-r5", "ENCOUNTER_INTERHEALTH", vec![json!({ "ADMISSION": "2024-01-15 10:30:00" })]);
+
+        let mut view = patient_view();
+        view.entity_type = "encounter".to_string();
+        let mut mapping = patient_mapping(&["admission"]);
+        mapping.entity_type = "ENCOUNTER".to_string();
+        mapping.field_mappings[0].field_destiny = "period.start".to_string();
+        mapping.field_mappings[0].data_type = "datetime".to_string();
+        let target = target_integration("http://fhir", Some("R5"));
+
+        let (job, output_dir) = run_export_with(view, Some(target), "worker-export-r5", 10, vec![mapping]).await;
+
+        assert_eq!(job.status, JobStatus::Completed);
+        let written = std::fs::read_to_string(output_dir.join(&job.id).join("Encounter.ndjson")).unwrap();
+        let resource: Value = serde_json::from_str(written.lines().next().unwrap()).unwrap();
+        // Read in the company's timezone (UTC)
+        assert_eq!(resource["actualPeriod"]["start"], "2024-01-15T10:30:00+00:00");
+        assert!(resource.get("period").is_none());
+
+        std::fs::remove_dir_all(output_dir).ok();
+        FixtureRegistry::remove("worker-export-r5");
+    }
+
+    #[tokio::test]
+    async fn test_sync_delivers_to_target_with_view_delivery_mode() {
+        let rows = (1..=3)
+            .map(|code| json!({ "PATIENT_CODE": code.to_string(), "NAME": format!("Patient {}", code) }))
+            .collect();
+        FixtureRegistry::register("worker-deliver", "PATIENT_INTERHEALTH", rows);
+
+        let (host, requests) = spawn_fhir_server().await;
+        let mut view = patient_view();
+        view.delivery_mode = Some(DeliveryMode::ConditionalUpdate);
+        let mut mapping = patient_mapping(&["patient_code", "name"]);
+        mapping.field_mappings[0].field_destiny = "identifier[0].value".to_string();
+        mapping.field_mappings[1].field_destiny = "name[0].text".to_string();
+
+        let (job, store, output_dir) = run_sync_with(view, Some(target_integration(&host, None)), "worker-deliver", 2, vec![mapping]).await;
+
+        assert_eq!(job.status, JobStatus::Completed);
+        assert_eq!(job.processed_records, 3);
+        assert!(!output_dir.join(&job.id).exists());
+
+        let requests = requests.lock().unwrap().clone();
+        assert_eq!(requests.len(), 3);
+        assert!(requests.iter().all(|request| request.starts_with("PUT /Patient?identifier=")));
+        assert!(requests[2].ends_with("|3"), "{}", requests[2]);
+
+        let delivered = store.delivered().await;
+        assert_eq!(delivered.len(), 3);
+        assert_eq!(delivered[0].resource_type, "Patient");
+        assert_eq!(delivered[0].database_view_id, "view-1");
+        assert_eq!(delivered[2].identifier_values, vec!["3"]);
+
+        FixtureRegistry::remove("worker-deliver");
+    }
+
+    #[tokio::test]
+    async fn test_job_fails_when_source_table_is_missing() {
+        FixtureRegistry::register("worker-missing", "ENCOUNTER_INTERHEALTH", vec![json!({ "ENCOUNTER_CODE": "1" })]);
+
+        let (job, store, _) = run_job("worker-missing", 10).await;
+
+        assert_eq!(job.status, JobStatus::Failed);
+        assert_eq!(store.get_job(&job.id).await.unwrap().status, JobStatus::Failed);
+
+        FixtureRegistry::remove("worker-missing");
+    }
+
+    #[tokio::test]
+    async fn test_job_blocked_when_mapped_column_was_dropped() {
+        let rows = vec![json!({ "PATIENT_CODE": "1", "NAME": "Patient 1" })];
+        FixtureRegistry::register("worker-drift", "PATIENT_INTERHEALTH", rows);
+
+        let mappings = vec![patient_mapping(&["patient_code", "patient_mother_name"])];
+        let (job, _, output_dir) = run_job_with_mappings("worker-drift", 10, mappings).await;
+
+        assert_eq!(job.status, JobStatus::Failed);
+        assert_eq!(job.processed_records, 0);
+        assert!(!output_dir.join(&job.id).exists());
+
+        let mappings = vec![patient_mapping(&["patient_code", "name"])];
+        let (job, _, output_dir) = run_job_with_mappings("worker-drift", 10, mappings).await;
+        assert_eq!(job.status, JobStatus::Completed);
+
+        std::fs::remove_dir_all(output_dir).ok();
+        FixtureRegistry::remove("worker-drift");
+    }
+}
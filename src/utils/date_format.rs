@@ -1,42 +1,115 @@
-use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Deserializer, Serializer};
 
-/// Format a datetime string to ISO 8601 format (YYYY-MM-DDTHH:MM:SS)
-/// Handles various input formats and ensures consistent output for FHIR resources
-pub fn format_to_iso8601(value: &str) -> String {
-    // If already in ISO 8601 format with 'T', return as is
-    if value.contains('T') {
-        return value.to_string();
+use super::{AppError, AppResult};
+
+/// Timezone of companies that have not configured one
+pub const DEFAULT_TIMEZONE: Tz = chrono_tz::America::Sao_Paulo;
+
+/// IANA timezone name (America/Sao_Paulo, America/Manaus, ...)
+pub fn parse_timezone(name: &str) -> AppResult<Tz> {
+    name.trim()
+        .parse()
+        .map_err(|_| AppError::Validation(format!("Fuso horário inválido: '{}' (use um nome IANA, ex.: America/Sao_Paulo)", name)))
+}
+
+/// FHIR type a source date is written as, from `FieldMapping.data_type`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatePrecision {
+    /// `YYYY-MM-DD`
+    Date,
+    /// `YYYY-MM-DDThh:mm:ss+zz:zz`, or `YYYY-MM-DD` when the source has no time
+    DateTime,
+    /// `YYYY-MM-DDThh:mm:ss+zz:zz`; a source without a time is not an instant
+    Instant,
+}
+
+impl DatePrecision {
+    pub fn from_data_type(data_type: &str) -> Option<Self> {
+        match data_type.to_lowercase().as_str() {
+            "date" => Some(DatePrecision::Date),
+            "datetime" | "timestamp" => Some(DatePrecision::DateTime),
+            "instant" => Some(DatePrecision::Instant),
+            _ => None,
+        }
     }
-    
-    // Try to parse various datetime formats
-    // Format 1: RFC3339 (already handled above)
-    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
-        return dt.format("%Y-%m-%dT%H:%M:%S").to_string();
+
+    /// Precision of a FHIR primitive type (`date`, `dateTime`, `instant`)
+    pub fn from_fhir_type(fhir_type: &str) -> Option<Self> {
+        match fhir_type {
+            "date" => Some(DatePrecision::Date),
+            "dateTime" => Some(DatePrecision::DateTime),
+            "instant" => Some(DatePrecision::Instant),
+            _ => None,
+        }
     }
-    
-    // Format 2: "%d-%m-%Y %H:%M:%S" (e.g., "11-08-2025 16:08:39")
-    if let Ok(ndt) = NaiveDateTime::parse_from_str(value, "%d-%m-%Y %H:%M:%S") {
-        return ndt.format("%Y-%m-%dT%H:%M:%S").to_string();
+}
+
+const DATE_FORMATS: [&str; 4] = ["%Y-%m-%d", "%d/%m/%Y", "%d-%m-%Y", "%Y/%m/%d"];
+const TIME_FORMATS: [&str; 4] = ["%H:%M:%S%.f", "%H:%M", "%H%M%S", "%H%M"];
+
+/// Source date, optionally with its time in a separate column, written as a FHIR date, dateTime or instant
+///
+/// Values without an offset are local times of `timezone`: the offset is the one in force on that day,
+/// historical daylight saving time included. A local time skipped by a DST change (00:30 on the day
+/// Brazilian DST started) moves one hour forward. None when the value cannot be read.
+pub fn to_fhir_date(date: &str, time: Option<&str>, precision: DatePrecision, timezone: Tz) -> Option<String> {
+    let (date, date_time) = match parse_source(date.trim())? {
+        Source::Zoned(datetime) => {
+            let local = datetime.with_timezone(&timezone);
+            (local.date_naive(), Some(local.time()))
+        }
+        Source::Local(date, time) => (date, time),
+    };
+    // A separate time column wins over the (usually midnight) time of a date column
+    let time = match time.map(str::trim).filter(|time| !time.is_empty()) {
+        Some(time) => Some(parse_time(time)?),
+        None => date_time,
+    };
+
+    match (precision, time) {
+        (DatePrecision::Date, _) | (DatePrecision::DateTime, None) => Some(date.format("%Y-%m-%d").to_string()),
+        (DatePrecision::Instant, None) => None,
+        (_, Some(time)) => {
+            let local = date.and_time(time);
+            let zoned = timezone
+                .from_local_datetime(&local)
+                .earliest()
+                .or_else(|| timezone.from_local_datetime(&(local + Duration::hours(1))).earliest())?;
+            Some(zoned.format("%Y-%m-%dT%H:%M:%S%:z").to_string())
+        }
     }
-    
-    // Format 3: "%Y-%m-%d %H:%M:%S" (e.g., "2025-08-11 16:08:39")
-    if let Ok(ndt) = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S") {
-        return ndt.format("%Y-%m-%dT%H:%M:%S").to_string();
+}
+
+enum Source {
+    /// Carries its own offset (RFC 3339)
+    Zoned(DateTime<FixedOffset>),
+    /// Local date, with a time when the value has one
+    Local(NaiveDate, Option<NaiveTime>),
+}
+
+fn parse_source(value: &str) -> Option<Source> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Some(Source::Zoned(datetime));
     }
-    
-    // Format 4: "%d/%m/%Y %H:%M:%S" (e.g., "11/08/2025 16:08:39")
-    if let Ok(ndt) = NaiveDateTime::parse_from_str(value, "%d/%m/%Y %H:%M:%S") {
-        return ndt.format("%Y-%m-%dT%H:%M:%S").to_string();
+    if let Some((date, time)) = value.split_once(['T', ' ']) {
+        let date = parse_date(date)?;
+        return Some(Source::Local(date, Some(parse_time(time.trim())?)));
     }
-    
-    // Format 5: "%Y/%m/%d %H:%M:%S" (e.g., "2025/08/11 16:08:39")
-    if let Ok(ndt) = NaiveDateTime::parse_from_str(value, "%Y/%m/%d %H:%M:%S") {
-        return ndt.format("%Y-%m-%dT%H:%M:%S").to_string();
+    Some(Source::Local(parse_date(value)?, None))
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    DATE_FORMATS.iter().find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+}
+
+/// `14:30`, `14:30:15`, `1430`, or the time of a date and time (`1899-12-30 14:30:00`)
+fn parse_time(value: &str) -> Option<NaiveTime> {
+    if let Some(Source::Local(_, Some(time))) = value.contains(['T', ' ']).then(|| parse_source(value)).flatten() {
+        return Some(time);
     }
-    
-    // If no format matches, return the original value
-    value.to_string()
+    TIME_FORMATS.iter().find_map(|format| NaiveTime::parse_from_str(value, format).ok())
 }
 
 pub fn serialize<S>(date: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
@@ -112,3 +185,33 @@ pub mod option {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merges_date_and_time_in_company_timezone() {
+        let sao_paulo = DEFAULT_TIMEZONE;
+        let at = |date: &str, time: Option<&str>, precision| to_fhir_date(date, time, precision, sao_paulo);
+
+        assert_eq!(at("05/03/2024", Some("14:30"), DatePrecision::DateTime).as_deref(), Some("2024-03-05T14:30:00-03:00"));
+        assert_eq!(at("2024-03-05 00:00:00", Some("1899-12-30 08:15:00"), DatePrecision::Instant).as_deref(), Some("2024-03-05T08:15:00-03:00"));
+        assert_eq!(at("2024-03-05", Some("0930"), DatePrecision::DateTime).as_deref(), Some("2024-03-05T09:30:00-03:00"));
+        assert_eq!(at("2024-03-05T14:30:00Z", None, DatePrecision::DateTime).as_deref(), Some("2024-03-05T11:30:00-03:00"));
+        assert_eq!(at("05/03/2024 14:30", None, DatePrecision::Date).as_deref(), Some("2024-03-05"));
+        assert_eq!(at("05/03/2024", None, DatePrecision::DateTime).as_deref(), Some("2024-03-05"));
+        assert_eq!(at("05/03/2024", None, DatePrecision::Instant), None);
+        assert_eq!(at("05/03/2024", Some("25:00"), DatePrecision::DateTime), None);
+        assert_eq!(at("not a date", None, DatePrecision::Date), None);
+
+        // Brazilian DST (abolished in 2019): -02:00 in January 2018, and 2018-11-04 00:30 did not exist
+        assert_eq!(at("2018-01-15", Some("10:00"), DatePrecision::DateTime).as_deref(), Some("2018-01-15T10:00:00-02:00"));
+        assert_eq!(at("2018-11-04", Some("00:30"), DatePrecision::DateTime).as_deref(), Some("2018-11-04T01:30:00-02:00"));
+        assert_eq!(at("2024-01-15", Some("10:00"), DatePrecision::DateTime).as_deref(), Some("2024-01-15T10:00:00-03:00"));
+
+        let manaus = parse_timezone("America/Manaus").unwrap();
+        assert_eq!(to_fhir_date("2024-03-05", Some("14:30"), DatePrecision::DateTime, manaus).as_deref(), Some("2024-03-05T14:30:00-04:00"));
+        assert!(parse_timezone("Brasil/Porto_Alegre").is_err());
+    }
+}
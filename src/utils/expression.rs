@@ -0,0 +1,668 @@
+// Expressions computing a FieldMapping value from the columns of a row
+//
+//   concat(trim(first_name), ' ', upper(last_name))
+//   lpad(patient_code, 10, '0')
+//   if(gender = 'M', 'male', if(gender = 'F', 'female', 'unknown'))
+//   parse_date(concat(admission_date, ' ', admission_time), '%d/%m/%Y %H:%M')
+//   coalesce(cell_phone, home_phone) & ''    weight_g / 1000
+//
+// Bare names are columns (case-insensitive); empty or missing columns are null. Strings use single
+// or double quotes. Operators: `&` (concatenation, null as ''), + - * / (numbers), = != < <= > >=,
+// `and`, `or`. Any null operand of an arithmetic operator gives null.
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime};
+
+use super::{AppError, AppResult};
+
+/// Longest text `lpad`/`rpad` produce
+const MAX_PAD_LENGTH: usize = 4096;
+
+/// Deepest expression tree the parser builds; parsing and evaluation both recurse over it
+const MAX_DEPTH: usize = 64;
+
+/// Parsed expression, checked for syntax, function names and argument counts
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    root: Node,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Number(f64),
+    Null,
+    Column(String),
+    Negate(Box<Node>),
+    Binary(Operator, Box<Node>, Box<Node>),
+    Call(Function, Vec<Node>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Concat,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Function {
+    Concat,
+    Substring,
+    Trim,
+    Upper,
+    Lower,
+    Lpad,
+    Rpad,
+    Coalesce,
+    If,
+    Empty,
+    ParseDate,
+    FormatDate,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name.to_lowercase().as_str() {
+            "concat" => Function::Concat,
+            "substring" => Function::Substring,
+            "trim" => Function::Trim,
+            "upper" => Function::Upper,
+            "lower" => Function::Lower,
+            "lpad" => Function::Lpad,
+            "rpad" => Function::Rpad,
+            "coalesce" => Function::Coalesce,
+            "if" => Function::If,
+            "empty" => Function::Empty,
+            "parse_date" => Function::ParseDate,
+            "format_date" => Function::FormatDate,
+            _ => return None,
+        })
+    }
+
+    /// Minimum and maximum number of arguments
+    fn arity(&self) -> (usize, usize) {
+        match self {
+            Function::Concat | Function::Coalesce => (1, usize::MAX),
+            Function::Trim | Function::Upper | Function::Lower | Function::Empty => (1, 1),
+            Function::Substring | Function::Lpad | Function::Rpad | Function::If => (2, 3),
+            Function::ParseDate | Function::FormatDate => (2, 2),
+        }
+    }
+}
+
+/// Result of a node: text, number, boolean or null
+#[derive(Debug, Clone, PartialEq)]
+enum Val {
+    Null,
+    Text(String),
+    Number(f64),
+    Bool(bool),
+}
+
+impl Val {
+    fn text(&self) -> Option<String> {
+        match self {
+            Val::Null => None,
+            Val::Text(text) => Some(text.clone()),
+            Val::Number(number) => Some(format_number(*number)),
+            Val::Bool(value) => Some(value.to_string()),
+        }
+    }
+
+    fn number(&self) -> AppResult<Option<f64>> {
+        match self {
+            Val::Null => Ok(None),
+            Val::Number(number) => Ok(Some(*number)),
+            Val::Text(text) => text
+                .trim()
+                .parse()
+                .map(Some)
+                .map_err(|_| AppError::Validation(format!("'{}' não é um número", text))),
+            Val::Bool(_) => Err(AppError::Validation("um booleano não é um número".to_string())),
+        }
+    }
+
+    fn is_true(&self) -> bool {
+        match self {
+            Val::Null => false,
+            Val::Bool(value) => *value,
+            Val::Text(text) => !text.is_empty(),
+            Val::Number(number) => *number != 0.0,
+        }
+    }
+}
+
+/// Integers without decimals; other numbers rounded to 10 places (0.1 + 0.2 = 0.3)
+fn format_number(number: f64) -> String {
+    if number.fract() == 0.0 && number.abs() < 1e15 {
+        format!("{}", number as i64)
+    } else {
+        format!("{}", (number * 1e10).round() / 1e10)
+    }
+}
+
+impl Expression {
+    pub fn parse(source: &str) -> AppResult<Self> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { source, tokens, position: 0, depth: 0 };
+        let root = parser.or()?;
+        if parser.position < parser.tokens.len() {
+            return Err(parser.error("símbolo inesperado"));
+        }
+        Ok(Expression { root })
+    }
+
+    /// Lowercase names of the columns the expression reads
+    pub fn columns(&self) -> Vec<String> {
+        fn collect(node: &Node, columns: &mut Vec<String>) {
+            match node {
+                Node::Column(name) if !columns.contains(name) => columns.push(name.clone()),
+                Node::Negate(operand) => collect(operand, columns),
+                Node::Binary(_, left, right) => {
+                    collect(left, columns);
+                    collect(right, columns);
+                }
+                Node::Call(_, arguments) => arguments.iter().for_each(|argument| collect(argument, columns)),
+                _ => {}
+            }
+        }
+
+        let mut columns = Vec::new();
+        collect(&self.root, &mut columns);
+        columns
+    }
+
+    /// Value over a row whose keys are lowercase column names; None when the result is null
+    pub fn evaluate(&self, row: &HashMap<String, String>) -> AppResult<Option<String>> {
+        Ok(evaluate(&self.root, row)?.text())
+    }
+}
+
+fn evaluate(node: &Node, row: &HashMap<String, String>) -> AppResult<Val> {
+    Ok(match node {
+        Node::Text(text) => Val::Text(text.clone()),
+        Node::Number(number) => Val::Number(*number),
+        Node::Null => Val::Null,
+        Node::Column(name) => match row.get(name) {
+            Some(value) if !value.is_empty() => Val::Text(value.clone()),
+            _ => Val::Null,
+        },
+        Node::Negate(operand) => match evaluate(operand, row)?.number()? {
+            Some(number) => Val::Number(-number),
+            None => Val::Null,
+        },
+        Node::Binary(operator, left, right) => binary(*operator, left, right, row)?,
+        Node::Call(function, arguments) => call(*function, arguments, row)?,
+    })
+}
+
+fn binary(operator: Operator, left: &Node, right: &Node, row: &HashMap<String, String>) -> AppResult<Val> {
+    // `and`/`or` only evaluate the right side when needed
+    match operator {
+        Operator::And => return Ok(Val::Bool(evaluate(left, row)?.is_true() && evaluate(right, row)?.is_true())),
+        Operator::Or => return Ok(Val::Bool(evaluate(left, row)?.is_true() || evaluate(right, row)?.is_true())),
+        _ => {}
+    }
+
+    let left = evaluate(left, row)?;
+    let right = evaluate(right, row)?;
+
+    Ok(match operator {
+        Operator::Concat => Val::Text(format!("{}{}", left.text().unwrap_or_default(), right.text().unwrap_or_default())),
+        Operator::Add | Operator::Subtract | Operator::Multiply | Operator::Divide => {
+            let (Some(a), Some(b)) = (left.number()?, right.number()?) else {
+                return Ok(Val::Null);
+            };
+            Val::Number(match operator {
+                Operator::Add => a + b,
+                Operator::Subtract => a - b,
+                Operator::Multiply => a * b,
+                _ if b == 0.0 => return Err(AppError::Validation("divisão por zero".to_string())),
+                _ => a / b,
+            })
+        }
+        _ => {
+            if left == Val::Null || right == Val::Null {
+                return Ok(Val::Bool(false));
+            }
+            // Numbers compare as numbers ('10' > '9'), anything else as text
+            let ordering = match (left.number(), right.number()) {
+                (Ok(Some(a)), Ok(Some(b))) => a.partial_cmp(&b),
+                _ => left.text().partial_cmp(&right.text()),
+            };
+            let Some(ordering) = ordering else {
+                return Ok(Val::Bool(false));
+            };
+            Val::Bool(match operator {
+                Operator::Equal => ordering.is_eq(),
+                Operator::NotEqual => ordering.is_ne(),
+                Operator::Less => ordering.is_lt(),
+                Operator::LessOrEqual => ordering.is_le(),
+                Operator::Greater => ordering.is_gt(),
+                _ => ordering.is_ge(),
+            })
+        }
+    })
+}
+
+fn call(function: Function, arguments: &[Node], row: &HashMap<String, String>) -> AppResult<Val> {
+    // `if` and `coalesce` stop at the branch or argument they return
+    match function {
+        Function::If => {
+            let branch = if evaluate(&arguments[0], row)?.is_true() { arguments.get(1) } else { arguments.get(2) };
+            return branch.map(|node| evaluate(node, row)).unwrap_or(Ok(Val::Null));
+        }
+        Function::Coalesce => {
+            for argument in arguments {
+                let value = evaluate(argument, row)?;
+                if value != Val::Null {
+                    return Ok(value);
+                }
+            }
+            return Ok(Val::Null);
+        }
+        _ => {}
+    }
+
+    let values = arguments.iter().map(|node| evaluate(node, row)).collect::<AppResult<Vec<Val>>>()?;
+    let integer = |index: usize| -> AppResult<Option<usize>> {
+        match values.get(index).map(Val::number).transpose()?.flatten() {
+            Some(number) if number >= 0.0 && number.fract() == 0.0 => Ok(Some(number as usize)),
+            Some(number) => Err(AppError::Validation(format!("{} não é um inteiro positivo", format_number(number)))),
+            None => Ok(None),
+        }
+    };
+    let Some(text) = values[0].text() else {
+        return Ok(match function {
+            Function::Concat => Val::Text(values.iter().filter_map(Val::text).collect()),
+            Function::Empty => Val::Bool(true),
+            _ => Val::Null,
+        });
+    };
+
+    Ok(match function {
+        Function::Concat => Val::Text(values.iter().filter_map(Val::text).collect()),
+        Function::Empty => Val::Bool(text.is_empty()),
+        Function::Trim => Val::Text(text.trim().to_string()),
+        Function::Upper => Val::Text(text.to_uppercase()),
+        Function::Lower => Val::Text(text.to_lowercase()),
+        Function::Substring => {
+            let start = integer(1)?.unwrap_or(0);
+            let chars = text.chars().skip(start);
+            Val::Text(match integer(2)? {
+                Some(length) => chars.take(length).collect(),
+                None => chars.collect(),
+            })
+        }
+        Function::Lpad | Function::Rpad => {
+            let length = integer(1)?.unwrap_or(0);
+            if length > MAX_PAD_LENGTH {
+                return Err(AppError::Validation(format!("tamanho de preenchimento {} acima do máximo de {}", length, MAX_PAD_LENGTH)));
+            }
+            let fill = values.get(2).and_then(Val::text).and_then(|fill| fill.chars().next()).unwrap_or(' ');
+            let padding: String = std::iter::repeat_n(fill, length.saturating_sub(text.chars().count())).collect();
+            Val::Text(if function == Function::Lpad { padding + &text } else { text + &padding })
+        }
+        Function::ParseDate => parse_date(&text, &values[1].text().unwrap_or_default())?,
+        Function::FormatDate => format_date(&text, &values[1].text().unwrap_or_default())?,
+        Function::If | Function::Coalesce => unreachable!(),
+    })
+}
+
+/// Text read with a chrono pattern, as an ISO 8601 date, date and time, or time
+fn parse_date(text: &str, pattern: &str) -> AppResult<Val> {
+    let text = text.trim();
+    if let Ok(datetime) = NaiveDateTime::parse_from_str(text, pattern) {
+        return Ok(Val::Text(datetime.format("%Y-%m-%dT%H:%M:%S").to_string()));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(text, pattern) {
+        return Ok(Val::Text(date.format("%Y-%m-%d").to_string()));
+    }
+    if let Ok(time) = NaiveTime::parse_from_str(text, pattern) {
+        return Ok(Val::Text(time.format("%H:%M:%S").to_string()));
+    }
+    Err(AppError::Validation(format!("'{}' não corresponde ao formato '{}'", text, pattern)))
+}
+
+/// ISO 8601 date or date and time written with a chrono pattern
+fn format_date(text: &str, pattern: &str) -> AppResult<Val> {
+    check_pattern(pattern)?;
+    let text = text.trim();
+    let mut formatted = String::new();
+    // Writing instead of to_string(): chrono panics on a specifier the value cannot fill (%z without an offset)
+    let written = if let Ok(datetime) = DateTime::parse_from_rfc3339(text) {
+        write!(formatted, "{}", datetime.format(pattern))
+    } else if let Ok(datetime) = NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S%.f") {
+        write!(formatted, "{}", datetime.format(pattern))
+    } else if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        write!(formatted, "{}", date.and_hms_opt(0, 0, 0).unwrap_or_default().format(pattern))
+    } else {
+        return Err(AppError::Validation(format!("'{}' não é uma data ISO 8601", text)));
+    };
+    written.map_err(|_| AppError::Validation(format!("'{}' não pode ser escrito com o formato '{}'", text, pattern)))?;
+    Ok(Val::Text(formatted))
+}
+
+/// Refuse chrono patterns with unknown specifiers (`%Q`)
+fn check_pattern(pattern: &str) -> AppResult<()> {
+    if StrftimeItems::new(pattern).any(|item| matches!(item, Item::Error)) {
+        return Err(AppError::Validation(format!("formato de data inválido '{}'", pattern)));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Text(String),
+    Number(f64),
+    Name(String),
+    Symbol(&'static str),
+}
+
+fn tokenize(source: &str) -> AppResult<Vec<Token>> {
+    let invalid = |reason: String| AppError::Validation(format!("Expressão inválida '{}': {}", source, reason));
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '\'' || c == '"' {
+            let end = chars[i + 1..].iter().position(|&q| q == c).ok_or_else(|| invalid("texto sem aspas de fechamento".to_string()))?;
+            tokens.push(Token::Text(chars[i + 1..i + 1 + end].iter().collect()));
+            i += end + 2;
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let number: String = chars[start..i].iter().collect();
+            tokens.push(Token::Number(number.parse().map_err(|_| invalid(format!("número inválido '{}'", number)))?));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.') {
+                i += 1;
+            }
+            tokens.push(Token::Name(chars[start..i].iter().collect()));
+        } else {
+            let two: String = chars[i..(i + 2).min(chars.len())].iter().collect();
+            let symbol = ["!=", "<=", ">="]
+                .into_iter()
+                .find(|symbol| *symbol == two)
+                .or_else(|| ["(", ")", ",", "&", "+", "-", "*", "/", "=", "<", ">"].into_iter().find(|symbol| symbol.starts_with(c)))
+                .ok_or_else(|| invalid(format!("caractere inesperado '{}'", c)))?;
+            tokens.push(Token::Symbol(symbol));
+            i += symbol.len();
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive descent, from the lowest precedence: or, and, comparison, & + -, * /, unary minus
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<Token>,
+    position: usize,
+    /// Depth of the node being built: one per parenthesis, argument list, unary minus and
+    /// operator of a chain
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, reason: &str) -> AppError {
+        AppError::Validation(format!("Expressão inválida '{}': {} (posição {})", self.source, reason, self.position + 1))
+    }
+
+    fn descend(&mut self) -> AppResult<()> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(self.error(&format!("aninhamento acima do máximo de {} níveis", MAX_DEPTH)));
+        }
+        Ok(())
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn eat_symbol(&mut self, symbol: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Symbol(found)) if *found == symbol) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Name(name)) if name.eq_ignore_ascii_case(keyword) => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn or(&mut self) -> AppResult<Node> {
+        let depth = self.depth;
+        self.descend()?;
+        let mut node = self.and()?;
+        while self.eat_keyword("or") {
+            self.descend()?;
+            node = Node::Binary(Operator::Or, Box::new(node), Box::new(self.and()?));
+        }
+        self.depth = depth;
+        Ok(node)
+    }
+
+    fn and(&mut self) -> AppResult<Node> {
+        let depth = self.depth;
+        let mut node = self.comparison()?;
+        while self.eat_keyword("and") {
+            self.descend()?;
+            node = Node::Binary(Operator::And, Box::new(node), Box::new(self.comparison()?));
+        }
+        self.depth = depth;
+        Ok(node)
+    }
+
+    fn comparison(&mut self) -> AppResult<Node> {
+        let node = self.additive()?;
+        let operators = [
+            ("=", Operator::Equal),
+            ("!=", Operator::NotEqual),
+            ("<=", Operator::LessOrEqual),
+            (">=", Operator::GreaterOrEqual),
+            ("<", Operator::Less),
+            (">", Operator::Greater),
+        ];
+        for (symbol, operator) in operators {
+            if self.eat_symbol(symbol) {
+                return Ok(Node::Binary(operator, Box::new(node), Box::new(self.additive()?)));
+            }
+        }
+        Ok(node)
+    }
+
+    fn additive(&mut self) -> AppResult<Node> {
+        let depth = self.depth;
+        let mut node = self.term()?;
+        loop {
+            let operator = if self.eat_symbol("&") {
+                Operator::Concat
+            } else if self.eat_symbol("+") {
+                Operator::Add
+            } else if self.eat_symbol("-") {
+                Operator::Subtract
+            } else {
+                self.depth = depth;
+                return Ok(node);
+            };
+            self.descend()?;
+            node = Node::Binary(operator, Box::new(node), Box::new(self.term()?));
+        }
+    }
+
+    fn term(&mut self) -> AppResult<Node> {
+        let depth = self.depth;
+        let mut node = self.unary()?;
+        loop {
+            let operator = if self.eat_symbol("*") {
+                Operator::Multiply
+            } else if self.eat_symbol("/") {
+                Operator::Divide
+            } else {
+                self.depth = depth;
+                return Ok(node);
+            };
+            self.descend()?;
+            node = Node::Binary(operator, Box::new(node), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> AppResult<Node> {
+        if self.eat_symbol("-") {
+            let depth = self.depth;
+            self.descend()?;
+            let node = Node::Negate(Box::new(self.unary()?));
+            self.depth = depth;
+            return Ok(node);
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> AppResult<Node> {
+        let token = self.peek().cloned().ok_or_else(|| self.error("expressão incompleta"))?;
+        self.position += 1;
+
+        match token {
+            Token::Text(text) => Ok(Node::Text(text)),
+            Token::Number(number) => Ok(Node::Number(number)),
+            Token::Symbol("(") => {
+                let node = self.or()?;
+                if !self.eat_symbol(")") {
+                    return Err(self.error("falta ')'"));
+                }
+                Ok(node)
+            }
+            Token::Name(name) if name.eq_ignore_ascii_case("null") => Ok(Node::Null),
+            Token::Name(name) if self.eat_symbol("(") => {
+                let function = Function::from_name(&name).ok_or_else(|| self.error(&format!("função desconhecida '{}'", name)))?;
+                let mut arguments = Vec::new();
+                if !self.eat_symbol(")") {
+                    loop {
+                        arguments.push(self.or()?);
+                        if self.eat_symbol(")") {
+                            break;
+                        }
+                        if !self.eat_symbol(",") {
+                            return Err(self.error("falta ',' ou ')'"));
+                        }
+                    }
+                }
+                let (min, max) = function.arity();
+                if arguments.len() < min || arguments.len() > max {
+                    return Err(self.error(&format!("número de argumentos inválido para '{}'", name)));
+                }
+                if let (Function::ParseDate | Function::FormatDate, Some(Node::Text(pattern))) = (function, arguments.get(1)) {
+                    check_pattern(pattern).map_err(|_| self.error(&format!("formato de data inválido '{}'", pattern)))?;
+                }
+                Ok(Node::Call(function, arguments))
+            }
+            Token::Name(name) => Ok(Node::Column(name.to_lowercase())),
+            Token::Symbol(symbol) => Err(self.error(&format!("símbolo inesperado '{}'", symbol))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row() -> HashMap<String, String> {
+        HashMap::from([
+            ("first_name".to_string(), "  maria ".to_string()),
+            ("last_name".to_string(), "Silva".to_string()),
+            ("code".to_string(), "4521".to_string()),
+            ("gender".to_string(), "F".to_string()),
+            ("weight_g".to_string(), "3250".to_string()),
+            ("admission_date".to_string(), "05/03/2024".to_string()),
+            ("admission_time".to_string(), "14:30".to_string()),
+            ("home_phone".to_string(), String::new()),
+            ("cell_phone".to_string(), "51999".to_string()),
+        ])
+    }
+
+    fn eval(source: &str) -> Option<String> {
+        Expression::parse(source).unwrap().evaluate(&row()).unwrap()
+    }
+
+    #[test]
+    fn test_evaluates_functions_and_operators() {
+        assert_eq!(eval("concat(upper(trim(first_name)), ' ', LAST_NAME)").as_deref(), Some("MARIA Silva"));
+        assert_eq!(eval("trim(first_name) & ' ' & middle_name & lower(last_name)").as_deref(), Some("maria silva"));
+        assert_eq!(eval("lpad(code, 8, '0')").as_deref(), Some("00004521"));
+        assert_eq!(eval("rpad(code, 6)").as_deref(), Some("4521  "));
+        assert_eq!(eval("substring(last_name, 1, 3)").as_deref(), Some("ilv"));
+        assert_eq!(eval("coalesce(home_phone, cell_phone)").as_deref(), Some("51999"));
+        assert_eq!(eval("if(gender = 'M', 'male', if(gender = 'F', 'female', 'unknown'))").as_deref(), Some("female"));
+        assert_eq!(eval("if(empty(home_phone) and code > 999, 'sim', 'não')").as_deref(), Some("sim"));
+        assert_eq!(eval("weight_g / 1000").as_deref(), Some("3.25"));
+        assert_eq!(eval("(code + 1) * -2").as_deref(), Some("-9044"));
+        assert_eq!(eval("0.1 + 0.2").as_deref(), Some("0.3"));
+        assert_eq!(eval("missing_column + 1"), None);
+        assert_eq!(eval("upper(missing_column)"), None);
+        assert_eq!(
+            eval("parse_date(admission_date & ' ' & admission_time, '%d/%m/%Y %H:%M')").as_deref(),
+            Some("2024-03-05T14:30:00")
+        );
+        assert_eq!(eval("parse_date(admission_date, '%d/%m/%Y')").as_deref(), Some("2024-03-05"));
+        assert_eq!(eval("format_date('2024-03-05', '%Y%m%d')").as_deref(), Some("20240305"));
+    }
+
+    #[test]
+    fn test_refuses_invalid_expressions() {
+        assert!(Expression::parse("concat(first_name").is_err());
+        assert!(Expression::parse("shout(first_name)").is_err());
+        assert!(Expression::parse("trim(first_name, last_name)").is_err());
+        assert!(Expression::parse("'open").is_err());
+        assert!(Expression::parse("first_name ; drop").is_err());
+        assert!(Expression::parse("format_date(admission_date, '%Q')").is_err());
+
+        let row = row();
+        assert!(Expression::parse("last_name * 2").unwrap().evaluate(&row).is_err());
+        assert!(Expression::parse("code / 0").unwrap().evaluate(&row).is_err());
+        assert!(Expression::parse("parse_date(admission_date, '%Y-%m-%d')").unwrap().evaluate(&row).is_err());
+        assert!(Expression::parse("format_date('2024-03-05', trim(' %Q'))").unwrap().evaluate(&row).is_err());
+        assert!(Expression::parse("format_date('2024-03-05', '%z')").unwrap().evaluate(&row).is_err());
+        assert!(Expression::parse("lpad(code, 1000000000000000)").unwrap().evaluate(&row).is_err());
+
+        let nested = format!("{}code{}", "(".repeat(10_000), ")".repeat(10_000));
+        assert!(Expression::parse(&nested).is_err());
+        assert!(Expression::parse(&format!("{}code", "-".repeat(10_000))).is_err());
+        assert!(Expression::parse(&format!("code{}", " + 1".repeat(10_000))).is_err());
+        assert!(Expression::parse(&format!("{}code{}", "upper(".repeat(10_000), ")".repeat(10_000))).is_err());
+        let shallow = format!("{}code{}", "(".repeat(50), ")".repeat(50));
+        assert_eq!(Expression::parse(&shallow).unwrap().evaluate(&row).unwrap().as_deref(), Some("4521"));
+
+        let columns = Expression::parse("concat(trim(First_Name), ' ', if(empty(last_name), first_name, 'x'))").unwrap().columns();
+        assert_eq!(columns, vec!["first_name", "last_name"]);
+    }
+}
@@ -0,0 +1,166 @@
+// Minimal JSONPath evaluator used to pick records out of API responses
+// Supported: $  .name  ['name']  [n]  [*]  .*  ..name (recursive descent)
+use serde_json::Value;
+
+use super::AppError;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+    Wildcard,
+    Descendant(String),
+}
+
+/// Evaluate a JSONPath expression, returning every matching node in document order
+pub fn select<'a>(value: &'a Value, path: &str) -> Result<Vec<&'a Value>, AppError> {
+    let segments = parse(path)?;
+    let mut current = vec![value];
+
+    for segment in &segments {
+        let mut next = Vec::new();
+        for node in current {
+            match segment {
+                Segment::Key(key) => {
+                    if let Some(child) = node.get(key) {
+                        next.push(child);
+                    }
+                }
+                Segment::Index(index) => {
+                    if let Some(child) = node.get(*index) {
+                        next.push(child);
+                    }
+                }
+                Segment::Wildcard => match node {
+                    Value::Array(items) => next.extend(items.iter()),
+                    Value::Object(map) => next.extend(map.values()),
+                    _ => {}
+                },
+                Segment::Descendant(key) => collect_descendants(node, key, &mut next),
+            }
+        }
+        current = next;
+    }
+
+    Ok(current)
+}
+
+/// First node matched by a JSONPath expression
+pub fn select_first<'a>(value: &'a Value, path: &str) -> Result<Option<&'a Value>, AppError> {
+    Ok(select(value, path)?.into_iter().next())
+}
+
+fn collect_descendants<'a>(node: &'a Value, key: &str, out: &mut Vec<&'a Value>) {
+    match node {
+        Value::Object(map) => {
+            if let Some(child) = map.get(key) {
+                out.push(child);
+            }
+            for child in map.values() {
+                collect_descendants(child, key, out);
+            }
+        }
+        Value::Array(items) => {
+            for child in items {
+                collect_descendants(child, key, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn parse(path: &str) -> Result<Vec<Segment>, AppError> {
+    let invalid = |reason: &str| AppError::BadRequest(format!("JSONPath inválido '{}': {}", path, reason));
+
+    let rest = path.trim().strip_prefix('$').ok_or_else(|| invalid("deve começar com $"))?;
+    let chars: Vec<char> = rest.chars().collect();
+    let mut segments = Vec::new();
+    let mut i = 0;
+
+    let read_name = |start: usize| -> (String, usize) {
+        let mut end = start;
+        while end < chars.len() && chars[end] != '.' && chars[end] != '[' {
+            end += 1;
+        }
+        (chars[start..end].iter().collect(), end)
+    };
+
+    while i < chars.len() {
+        match chars[i] {
+            '.' if chars.get(i + 1) == Some(&'.') => {
+                let (name, end) = read_name(i + 2);
+                if name.is_empty() {
+                    return Err(invalid("nome esperado após .."));
+                }
+                segments.push(Segment::Descendant(name));
+                i = end;
+            }
+            '.' => {
+                let (name, end) = read_name(i + 1);
+                match name.as_str() {
+                    "" => return Err(invalid("nome esperado após .")),
+                    "*" => segments.push(Segment::Wildcard),
+                    _ => segments.push(Segment::Key(name)),
+                }
+                i = end;
+            }
+            '[' => {
+                let close = chars[i..]
+                    .iter()
+                    .position(|c| *c == ']')
+                    .map(|offset| i + offset)
+                    .ok_or_else(|| invalid("[ sem ]"))?;
+                let inner: String = chars[i + 1..close].iter().collect();
+                let inner = inner.trim();
+
+                if inner == "*" {
+                    segments.push(Segment::Wildcard);
+                } else if let Some(quoted) = inner
+                    .strip_prefix('\'')
+                    .and_then(|s| s.strip_suffix('\''))
+                    .or_else(|| inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')))
+                {
+                    segments.push(Segment::Key(quoted.to_string()));
+                } else {
+                    let index = inner.parse::<usize>().map_err(|_| invalid("índice inválido"))?;
+                    segments.push(Segment::Index(index));
+                }
+                i = close + 1;
+            }
+            _ => return Err(invalid("caractere inesperado")),
+        }
+    }
+
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_select_paths() {
+        let body = json!({
+            "data": [{ "id": 1, "name": "Ana" }, { "id": 2, "name": "Bruno" }],
+            "meta": { "next": "abc", "total": 2 },
+            "entry": [{ "resource": { "id": "p1" } }]
+        });
+
+        assert_eq!(select(&body, "$").unwrap(), vec![&body]);
+        assert_eq!(select(&body, "$.data[*].name").unwrap(), vec![&json!("Ana"), &json!("Bruno")]);
+        assert_eq!(select(&body, "$['meta'].next").unwrap(), vec![&json!("abc")]);
+        assert_eq!(select(&body, "$.data[1].id").unwrap(), vec![&json!(2)]);
+        assert_eq!(select(&body, "$.entry[*].resource.id").unwrap(), vec![&json!("p1")]);
+        assert_eq!(select(&body, "$..total").unwrap(), vec![&json!(2)]);
+        assert_eq!(select(&body, "$.meta.*").unwrap().len(), 2);
+        assert!(select(&body, "$.missing[0]").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_paths() {
+        assert!(select(&json!({}), "data").is_err());
+        assert!(select(&json!({}), "$.data[").is_err());
+        assert!(select(&json!({}), "$.data[x]").is_err());
+    }
+}
@@ -7,12 +7,16 @@ pub mod response;
 pub mod replace;
 pub mod validator;
 pub mod sort_helper;
+pub mod json_path;
+pub mod userinfo;
+pub mod redact;
+pub mod expression;
 
 pub use error::{AppError, AppResult};
 pub use pagination::{PaginationResponse, PaginationQuery};
 pub use response::ApiResponse;
 pub use replace::Replacer;
-pub use validator::{Validator, ValidationResult, ValidationRecommendation};
+pub use validator::{Validator, ValidationRecommendation};
 
 // Re-export utils submodule for backward compatibility
 pub mod utils {
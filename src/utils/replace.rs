@@ -2,7 +2,12 @@ use std::collections::HashMap;
 use serde_json::Value;
 use bson::oid::ObjectId;
 use crate::domain::entities::{FieldMapping, DatabaseTransformation, DatabaseModelValue};
-use crate::utils::date_format;
+use crate::domain::fhir::ConditionalReference;
+use chrono_tz::Tz;
+use crate::domain::fhir::r4;
+use crate::utils::date_format::{self, DatePrecision};
+use crate::utils::expression::Expression;
+use crate::utils::{AppError, AppResult};
 
 /// Utility for replacing FHIR placeholder values with real database values
 pub struct Replacer;
@@ -32,6 +37,12 @@ impl Replacer {
                 // First, try direct match
                 if let Some(real_value) = data.get(&lower) {
                     *s = real_value.clone();
+                } else if let Some(mut reference) = ConditionalReference::parse(s) {
+                    // Conditional references like "Patient?identifier=system|encounter_patient_code"
+                    if let Some(real_value) = data.get(&reference.value.to_lowercase()) {
+                        reference.value = real_value.clone();
+                        *s = reference.to_string();
+                    }
                 } else if s.contains('/') {
                     // Handle prefixed references like "Patient/encounter_patient_code"
                     // Split by '/' and check if the second part is a column name
@@ -55,34 +66,6 @@ impl Replacer {
         }
     }
 
-    /// Replace placeholders in a FHIR bundle (multiple entries)
-    pub fn replace_in_bundle(
-        bundle: &mut Value,
-        data: &HashMap<String, String>,
-    ) {
-        if let Some(entries) = bundle.get_mut("entry").and_then(|e| e.as_array_mut()) {
-            for entry in entries.iter_mut() {
-                if let Some(resource) = entry.get_mut("resource") {
-                    Self::replace_in_resource(resource, data);
-                }
-            }
-        }
-    }
-
-    /// Replace placeholders with transformations applied
-    pub fn replace_in_bundle_with_transformations(
-        bundle: &mut Value,
-        data: &HashMap<String, String>,
-        field_mappings: &[FieldMapping],
-        transformations: &HashMap<String, DatabaseTransformation>,
-    ) {
-        // Apply transformations to data
-        let transformed_data = Self::apply_transformations(data, field_mappings, transformations);
-        
-        // Replace with transformed data
-        Self::replace_in_bundle(bundle, &transformed_data);
-    }
-
     /// Replace placeholders in a single FHIR entry (resource + request)
     pub fn replace_in_entry(
         entry: &mut Value,
@@ -94,37 +77,56 @@ impl Replacer {
             Self::remove_empty_references(resource);
         }
         
-        // Also replace in request.ifNoneExist if present
+        // Also replace in request.ifNoneExist, or the query of a conditional update url (Patient?identifier=...)
         if let Some(request) = entry.get_mut("request") {
-            if let Some(if_none_exist) = request.get_mut("ifNoneExist") {
-                if let Value::String(s) = if_none_exist {
-                    // The ifNoneExist should already be in format: "identifier=system|value"
-                    // We just need to replace any placeholder column names that might be in the value part
-                    for (col_name, real_value) in data {
-                        *s = s.replace(col_name, real_value);
+            if let Some(Value::String(s)) = request.get_mut("ifNoneExist") {
+                // The ifNoneExist should already be in format: "identifier=system|value"
+                // We just need to replace any placeholder column names that might be in the value part
+                for (col_name, real_value) in Self::longest_first(data) {
+                    *s = s.replace(col_name, real_value);
+                }
+            }
+            if let Some(Value::String(url)) = request.get_mut("url") {
+                if let Some((resource_type, query)) = url.split_once('?') {
+                    let mut query = query.to_string();
+                    for (col_name, real_value) in Self::longest_first(data) {
+                        query = query.replace(col_name, real_value);
                     }
+                    *url = format!("{}?{}", resource_type, query);
                 }
             }
         }
     }
 
+    /// Placeholders longest first, so a column that is part of a longer placeholder (another column,
+    /// an `=expression`) is not replaced inside it
+    fn longest_first(data: &HashMap<String, String>) -> Vec<(&String, &String)> {
+        let mut placeholders: Vec<_> = data.iter().collect();
+        placeholders.sort_by_key(|(placeholder, _)| std::cmp::Reverse(placeholder.len()));
+        placeholders
+    }
+
     /// Replace placeholders in a single entry with transformations applied
     pub fn replace_in_entry_with_transformations(
         entry: &mut Value,
         data: &HashMap<String, String>,
         field_mappings: &[FieldMapping],
         transformations: &HashMap<String, DatabaseTransformation>,
-    ) {
+        timezone: Tz,
+    ) -> AppResult<()> {
         // Apply transformations to data
-        let transformed_data = Self::apply_transformations(data, field_mappings, transformations);
+        let sources = Self::source_values(data, field_mappings)?;
+        let mut transformed_data = Self::apply_transformations(data, field_mappings, &sources, transformations);
+        Self::format_dates(&mut transformed_data, data, field_mappings, &Self::resource_type(entry), timezone);
         
         // Replace with transformed data
         Self::replace_in_entry(entry, &transformed_data);
         
         // Add display attributes for fields that have transformations and end with .code
         if let Some(resource) = entry.get_mut("resource") {
-            Self::add_display_attributes(resource, data, field_mappings, transformations);
+            Self::add_display_attributes(resource, field_mappings, &sources, transformations);
         }
+        Ok(())
     }
 
     /// Replace placeholders in a single entry with database_model_value transformations applied
@@ -134,17 +136,21 @@ impl Replacer {
         field_mappings: &[FieldMapping],
         model_values: &HashMap<String, DatabaseModelValue>,
         company_id: &str,
-    ) {
+        timezone: Tz,
+    ) -> AppResult<()> {
         // Parse company_id to ObjectId for comparison
         let company_object_id = ObjectId::parse_str(company_id).ok();
         
         // Apply database_model_value transformations to data
-        let transformed_data = Self::apply_model_value_transformations(
+        let sources = Self::source_values(data, field_mappings)?;
+        let mut transformed_data = Self::apply_model_value_transformations(
             data, 
             field_mappings, 
+            &sources,
             model_values, 
             company_object_id.as_ref()
         );
+        Self::format_dates(&mut transformed_data, data, field_mappings, &Self::resource_type(entry), timezone);
         
         // Replace with transformed data
         Self::replace_in_entry(entry, &transformed_data);
@@ -153,28 +159,54 @@ impl Replacer {
         if let Some(resource) = entry.get_mut("resource") {
             Self::add_model_value_display_attributes(
                 resource, 
-                data, 
                 field_mappings, 
+                &sources,
                 model_values, 
                 company_object_id.as_ref()
             );
         }
+        Ok(())
+    }
+
+    /// Row value read by each field mapping: its origin column, or its expression evaluated over the row
+    fn source_values(
+        data: &HashMap<String, String>,
+        field_mappings: &[FieldMapping],
+    ) -> AppResult<Vec<Option<String>>> {
+        field_mappings
+            .iter()
+            .map(|field_mapping| match field_mapping.expression() {
+                Some(expression) => Expression::parse(expression)
+                    .and_then(|expression| expression.evaluate(data))
+                    .map_err(|error| match error {
+                        AppError::Validation(message) => {
+                            AppError::Validation(format!("{}: {}", field_mapping.field_destiny, message))
+                        }
+                        other => other,
+                    }),
+                None => Ok(data.get(&field_mapping.field_origin.to_lowercase()).cloned()),
+            })
+            .collect()
     }
 
     /// Apply transformations to data values based on field mappings
     fn apply_transformations(
         data: &HashMap<String, String>,
         field_mappings: &[FieldMapping],
+        sources: &[Option<String>],
         transformations: &HashMap<String, DatabaseTransformation>,
     ) -> HashMap<String, String> {
         let mut transformed_data = data.clone();
         
-        for field_mapping in field_mappings {
-            let column_name = field_mapping.field_origin.to_lowercase();
+        for (field_mapping, source) in field_mappings.iter().zip(sources) {
+            let column_name = field_mapping.placeholder().to_lowercase();
+            if field_mapping.expression().is_some() {
+                transformed_data.insert(column_name.clone(), source.clone().unwrap_or_default());
+            }
             
             // Apply transformation if exists
             if let Some(transformation_id) = &field_mapping.transformation_id {
-                if let Some(original_value) = data.get(&column_name) {
+                if let Some(original_value) = source {
                     if let Some(transformation) = transformations.get(transformation_id) {
                         if let Some(mapped_value) = transformation.value_mappings.get(original_value) {
                             transformed_data.insert(column_name.clone(), mapped_value.code.clone());
@@ -182,13 +214,6 @@ impl Replacer {
                     }
                 }
             }
-            
-            // Apply datetime formatting
-            if field_mapping.data_type == "datetime" {
-                if let Some(value) = transformed_data.get(&column_name).cloned() {
-                    transformed_data.insert(column_name, date_format::format_to_iso8601(&value));
-                }
-            }
         }
         
         transformed_data
@@ -198,17 +223,21 @@ impl Replacer {
     fn apply_model_value_transformations(
         data: &HashMap<String, String>,
         field_mappings: &[FieldMapping],
+        sources: &[Option<String>],
         model_values: &HashMap<String, DatabaseModelValue>,
         company_object_id: Option<&ObjectId>,
     ) -> HashMap<String, String> {
         let mut transformed_data = data.clone();
         
-        for field_mapping in field_mappings {
-            let column_name = field_mapping.field_origin.to_lowercase();
+        for (field_mapping, source) in field_mappings.iter().zip(sources) {
+            let column_name = field_mapping.placeholder().to_lowercase();
+            if field_mapping.expression().is_some() {
+                transformed_data.insert(column_name.clone(), source.clone().unwrap_or_default());
+            }
             
             // Apply model value transformation if exists
             if let Some(transformation_id) = &field_mapping.transformation_id {
-                if let Some(original_value) = data.get(&column_name) {
+                if let Some(original_value) = source {
                     if let Some(company_oid) = company_object_id {
                         // Find matching model value more efficiently
                         if let Some(model_value) = model_values.values()
@@ -219,30 +248,69 @@ impl Replacer {
                     }
                 }
             }
-            
-            // Apply datetime formatting
-            if field_mapping.data_type == "datetime" {
-                if let Some(value) = transformed_data.get(&column_name).cloned() {
-                    transformed_data.insert(column_name, date_format::format_to_iso8601(&value));
-                }
-            }
         }
         
         transformed_data
     }
 
+    /// Write date fields in their FHIR form: merged with the `timeOrigin` column, placed in the company
+    /// timezone and cut to the precision of the destination element. Only fields typed as dates
+    /// (`dataType` date, datetime, instant) or with a `timeOrigin` are converted; values that cannot
+    /// be read are left for the FHIR model to report
+    fn format_dates(
+        transformed_data: &mut HashMap<String, String>,
+        data: &HashMap<String, String>,
+        field_mappings: &[FieldMapping],
+        resource_type: &str,
+        timezone: Tz,
+    ) {
+        for field_mapping in field_mappings {
+            let declared = DatePrecision::from_data_type(&field_mapping.data_type);
+            if declared.is_none() && field_mapping.time_origin.is_none() {
+                continue;
+            }
+            // The destination element knows better than `dataType`: Oracle DATE columns hold a time too
+            let precision = r4::primitive_at(resource_type, &field_mapping.field_destiny)
+                .ok()
+                .flatten()
+                .and_then(DatePrecision::from_fhir_type)
+                .or(declared)
+                .unwrap_or(DatePrecision::DateTime);
+
+            let time = field_mapping
+                .time_origin
+                .as_ref()
+                .and_then(|column| data.get(&column.to_lowercase()));
+            if let Some(value) = transformed_data.get_mut(&field_mapping.placeholder().to_lowercase()) {
+                if let Some(formatted) = date_format::to_fhir_date(value, time.map(String::as_str), precision, timezone) {
+                    *value = formatted;
+                }
+            }
+        }
+    }
+
+    /// `resourceType` of the resource in an entry
+    fn resource_type(entry: &Value) -> String {
+        entry
+            .get("resource")
+            .and_then(|resource| resource.get("resourceType"))
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    }
+
     /// Add display attributes to code fields that have database_model_value transformations
     fn add_model_value_display_attributes(
         resource: &mut Value,
-        data: &HashMap<String, String>,
         field_mappings: &[FieldMapping],
+        sources: &[Option<String>],
         model_values: &HashMap<String, DatabaseModelValue>,
         company_object_id: Option<&ObjectId>,
     ) {
-        for field_mapping in field_mappings.iter().filter(|fm| fm.field_destiny.ends_with(".code")) {
+        for (field_mapping, source) in field_mappings.iter().zip(sources).filter(|(fm, _)| fm.field_destiny.ends_with(".code")) {
             if let (Some(transformation_id), Some(original_value), Some(company_oid)) = (
                 &field_mapping.transformation_id,
-                data.get(&field_mapping.field_origin.to_lowercase()),
+                source,
                 company_object_id
             ) {
                 if let Some(model_value) = model_values.values()
@@ -258,14 +326,14 @@ impl Replacer {
     /// Add display attributes to code fields that have transformations
     fn add_display_attributes(
         resource: &mut Value,
-        data: &HashMap<String, String>,
         field_mappings: &[FieldMapping],
+        sources: &[Option<String>],
         transformations: &HashMap<String, DatabaseTransformation>,
     ) {
-        for field_mapping in field_mappings.iter().filter(|fm| fm.field_destiny.ends_with(".code")) {
+        for (field_mapping, source) in field_mappings.iter().zip(sources).filter(|(fm, _)| fm.field_destiny.ends_with(".code")) {
             if let (Some(transformation_id), Some(original_value)) = (
                 &field_mapping.transformation_id,
-                data.get(&field_mapping.field_origin.to_lowercase())
+                source
             ) {
                 if let Some(transformation) = transformations.get(transformation_id) {
                     if let Some(mapped_value) = transformation.value_mappings.get(original_value) {
@@ -353,7 +421,7 @@ impl Replacer {
                             } else if obj.len() == 1 && obj.contains_key("reference") {
                                 // Check if reference is empty (ends with / or is empty string)
                                 if let Some(ref_str) = obj.get("reference").and_then(|v| v.as_str()) {
-                                    ref_str.is_empty() || ref_str.ends_with('/') || !ref_str.contains('/')
+                                    Self::is_empty_reference(ref_str)
                                 } else {
                                     false
                                 }
@@ -411,6 +479,14 @@ impl Replacer {
     }
 
     /// Check if an object structure is empty or contains only empty values
+    /// Reference left without its code: "Patient/", "Patient?identifier=system|" or no reference at all
+    fn is_empty_reference(ref_str: &str) -> bool {
+        if ref_str.contains('?') {
+            return ConditionalReference::parse(ref_str).is_none();
+        }
+        ref_str.is_empty() || ref_str.ends_with('/') || !ref_str.contains('/')
+    }
+
     fn is_empty_structure(obj: &serde_json::Map<String, Value>) -> bool {
         if obj.is_empty() {
             return true;
@@ -419,7 +495,7 @@ impl Replacer {
         // Check if this object has a reference field with empty value
         if let Some(reference) = obj.get("reference") {
             if let Some(ref_str) = reference.as_str() {
-                if ref_str.is_empty() || ref_str.ends_with('/') || !ref_str.contains('/') {
+                if Self::is_empty_reference(ref_str) {
                     return true;
                 }
             }
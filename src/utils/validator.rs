@@ -1,6 +1,11 @@
 use serde_json::{json, Value};
 use serde::{Deserialize, Serialize};
 
+use crate::domain::fhir::{ProfileRegistry, Terminology};
+
+/// Conformance resources loaded from FHIR packages or uploaded
+type Conformance<'a> = Option<(&'a ProfileRegistry, &'a Terminology)>;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidationRecommendation {
     pub severity: String,
@@ -20,6 +25,17 @@ pub struct Validator;
 impl Validator {
     /// Validate a FHIR resource and return recommendations
     pub fn validate(resource: &Value) -> ValidationResult {
+        Self::validate_with(resource, None)
+    }
+
+    /// Validate against the StructureDefinitions of loaded FHIR packages; resources whose type
+    /// has no loaded definition keep the built-in checks. Coded elements are checked against
+    /// their bindings and the loaded code systems
+    pub fn validate_with_profiles(resource: &Value, profiles: &ProfileRegistry, terminology: &Terminology) -> ValidationResult {
+        Self::validate_with(resource, Some((profiles, terminology)))
+    }
+
+    fn validate_with(resource: &Value, profiles: Conformance) -> ValidationResult {
         let mut recommendations = Vec::new();
 
         // Check if this is a FHIR entry (has fullUrl, resource, request)
@@ -27,11 +43,11 @@ impl Validator {
         if resource.get("fullUrl").is_some() && resource.get("resource").is_some() {
             // This is a FHIR entry, validate the nested resource
             if let Some(nested_resource) = resource.get("resource") {
-                Self::validate_resource_or_bundle(nested_resource, &mut recommendations);
+                Self::validate_resource_or_bundle(nested_resource, profiles, &mut recommendations);
             }
         } else {
             // This is a direct resource or bundle
-            Self::validate_resource_or_bundle(resource, &mut recommendations);
+            Self::validate_resource_or_bundle(resource, profiles, &mut recommendations);
         }
 
         let is_valid = !recommendations.iter().any(|r| r.severity == "error");
@@ -42,13 +58,13 @@ impl Validator {
         }
     }
 
-    fn validate_resource_or_bundle(resource: &Value, recommendations: &mut Vec<ValidationRecommendation>) {
+    fn validate_resource_or_bundle(resource: &Value, profiles: Conformance, recommendations: &mut Vec<ValidationRecommendation>) {
         // Check if it's a Bundle or a single resource
         if let Some(resource_type) = resource.get("resourceType").and_then(|v| v.as_str()) {
             if resource_type == "Bundle" {
-                Self::validate_bundle(resource, recommendations);
+                Self::validate_bundle(resource, profiles, recommendations);
             } else {
-                Self::validate_resource(resource, recommendations);
+                Self::validate_resource(resource, profiles, recommendations);
             }
         } else {
             recommendations.push(ValidationRecommendation {
@@ -60,7 +76,7 @@ impl Validator {
         }
     }
 
-    fn validate_bundle(bundle: &Value, recommendations: &mut Vec<ValidationRecommendation>) {
+    fn validate_bundle(bundle: &Value, profiles: Conformance, recommendations: &mut Vec<ValidationRecommendation>) {
         // Validate Bundle type
         if let Some(bundle_type) = bundle.get("type").and_then(|v| v.as_str()) {
             let valid_types = ["document", "message", "transaction", "transaction-response", 
@@ -97,7 +113,7 @@ impl Validator {
 
                 // Validate resource in entry
                 if let Some(resource) = entry.get("resource") {
-                    Self::validate_resource(resource, recommendations);
+                    Self::validate_resource(resource, profiles, recommendations);
                 } else {
                     recommendations.push(ValidationRecommendation {
                         severity: "error".to_string(),
@@ -117,7 +133,7 @@ impl Validator {
         }
     }
 
-    fn validate_resource(resource: &Value, recommendations: &mut Vec<ValidationRecommendation>) {
+    fn validate_resource(resource: &Value, profiles: Conformance, recommendations: &mut Vec<ValidationRecommendation>) {
         let resource_type = resource.get("resourceType")
             .and_then(|v| v.as_str())
             .unwrap_or("Unknown");
@@ -139,15 +155,27 @@ impl Validator {
             Self::validate_meta(meta, recommendations);
         }
 
-        // Resource-specific validations
-        match resource_type {
-            "Patient" => Self::validate_patient(resource, recommendations),
-            "Encounter" => Self::validate_encounter(resource, recommendations),
-            "Observation" => Self::validate_observation(resource, recommendations),
-            "Practitioner" => Self::validate_practitioner(resource, recommendations),
-            "Organization" => Self::validate_organization(resource, recommendations),
-            "Location" => Self::validate_location(resource, recommendations),
-            _ => {}
+        // Resource-specific validations, from the loaded profiles when they define this type
+        match profiles.and_then(|(profiles, terminology)| profiles.validate(resource, terminology)) {
+            Some(profile_recommendations) => recommendations.extend(profile_recommendations),
+            None => match resource_type {
+                "Patient" => Self::validate_patient(resource, recommendations),
+                "Encounter" => Self::validate_encounter(resource, recommendations),
+                "Observation" => Self::validate_observation(resource, recommendations),
+                "Practitioner" => Self::validate_practitioner(resource, recommendations),
+                "Organization" => Self::validate_organization(resource, recommendations),
+                "Location" => Self::validate_location(resource, recommendations),
+                _ => {}
+            },
+        }
+
+        // Codes outside their code system, unless a binding already reported them
+        if let Some((_, terminology)) = profiles {
+            for coding in terminology.check_codings(resource) {
+                if !recommendations.iter().any(|r| coding.field.starts_with(&r.field) && r.message.starts_with("Code ")) {
+                    recommendations.push(coding);
+                }
+            }
         }
 
         // Check for references format